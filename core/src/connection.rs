@@ -139,6 +139,17 @@ impl ConnectedPoint {
         }
     }
 
+    /// Returns the local address of the connection, if known.
+    ///
+    /// For `Listener`, this returns `local_addr`. For `Dialer`, the local
+    /// address is not known and `None` is returned.
+    pub fn local_addr(&self) -> Option<&Multiaddr> {
+        match self {
+            ConnectedPoint::Dialer { .. } => None,
+            ConnectedPoint::Listener { local_addr, .. } => Some(local_addr),
+        }
+    }
+
     /// Modifies the address of the remote stored in this struct.
     ///
     /// For `Dialer`, this modifies `address`. For `Listener`, this modifies `send_back_addr`.
@@ -332,3 +343,29 @@ impl fmt::Display for ConnectionLimit {
 
 /// A `ConnectionLimit` can represent an error if it has been exceeded.
 impl Error for ConnectionLimit {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connected_point_accessors() {
+        let local: Multiaddr = "/ip4/127.0.0.1/tcp/1234".parse().unwrap();
+        let remote: Multiaddr = "/ip4/127.0.0.2/tcp/5678".parse().unwrap();
+
+        let dialer = ConnectedPoint::Dialer { address: remote.clone() };
+        assert!(dialer.is_dialer());
+        assert_eq!(dialer.to_endpoint(), Endpoint::Dialer);
+        assert_eq!(dialer.get_remote_address(), &remote);
+        assert_eq!(dialer.local_addr(), None);
+
+        let listener = ConnectedPoint::Listener {
+            local_addr: local.clone(),
+            send_back_addr: remote.clone(),
+        };
+        assert!(listener.is_listener());
+        assert_eq!(listener.to_endpoint(), Endpoint::Listener);
+        assert_eq!(listener.get_remote_address(), &remote);
+        assert_eq!(listener.local_addr(), Some(&local));
+    }
+}
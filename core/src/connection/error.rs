@@ -18,6 +18,7 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
+use crate::PeerId;
 use crate::connection::ConnectionLimit;
 use crate::transport::TransportError;
 use std::{io, fmt};
@@ -67,10 +68,19 @@ pub enum PendingConnectionError<TTransErr> {
     /// An error occurred while negotiating the transport protocol(s).
     Transport(TransportError<TTransErr>),
 
-    /// The peer identity obtained on the connection did not
-    /// match the one that was expected or is otherwise invalid.
+    /// The peer identity obtained on the connection is invalid,
+    /// e.g. because it is the identity of the local node.
     InvalidPeerId,
 
+    /// The peer identity obtained on the connection did not
+    /// match the one that was expected.
+    PeerIdMismatch {
+        /// The peer identity that was expected.
+        expected: PeerId,
+        /// The peer identity obtained on the connection.
+        actual: PeerId,
+    },
+
     /// The connection was dropped because the connection limit
     /// for a peer has been reached.
     ConnectionLimit(ConnectionLimit),
@@ -93,6 +103,8 @@ where
                 write!(f, "Pending connection: Transport error: {}", err),
             PendingConnectionError::InvalidPeerId =>
                 write!(f, "Pending connection: Invalid peer ID."),
+            PendingConnectionError::PeerIdMismatch { expected, actual } =>
+                write!(f, "Pending connection: Peer ID mismatch: expected {}, got {}.", expected, actual),
             PendingConnectionError::ConnectionLimit(l) =>
                 write!(f, "Connection error: Connection limit: {}.", l),
         }
@@ -109,6 +121,7 @@ where
             PendingConnectionError::IO(err) => Some(err),
             PendingConnectionError::Transport(err) => Some(err),
             PendingConnectionError::InvalidPeerId => None,
+            PendingConnectionError::PeerIdMismatch { .. } => None,
             PendingConnectionError::ConnectionLimit(..) => None,
         }
     }
@@ -23,6 +23,8 @@
 use crate::{Multiaddr, Transport, transport::{TransportError, ListenerEvent}};
 use futures::{prelude::*, task::Context, task::Poll};
 use log::debug;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use smallvec::SmallVec;
 use std::{collections::VecDeque, fmt, pin::Pin};
 
@@ -93,9 +95,47 @@ where
 ///
 /// It is part of most [`ListenersEvent`]s and can be used to remove
 /// individual listeners from the [`ListenersStream`].
+///
+/// With the `serde` feature, a `ListenerId` is serialized as the underlying `u64`.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct ListenerId(u64);
 
+impl ListenerId {
+    /// Returns the numeric value of the ID.
+    pub fn as_u64(&self) -> u64 {
+        self.0
+    }
+
+    /// Creates a `ListenerId` from its numeric value, e.g. as previously
+    /// obtained through [`ListenerId::as_u64`] and stored externally.
+    ///
+    /// > **Note**: The ID only refers to the same listener if it originates
+    /// > from the same [`ListenersStream`].
+    pub fn from_u64(id: u64) -> ListenerId {
+        ListenerId(id)
+    }
+}
+
+impl fmt::Display for ListenerId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ListenerId({})", self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for ListenerId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for ListenerId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        u64::deserialize(deserializer).map(ListenerId)
+    }
+}
+
 /// A single active listener.
 #[pin_project::pin_project]
 #[derive(Debug)]
@@ -483,4 +523,24 @@ mod tests {
             }
         });
     }
+
+    #[test]
+    fn listener_id_display() {
+        let id = ListenerId::from_u64(42);
+        assert_eq!(id.as_u64(), 42);
+        assert_eq!(id.to_string(), "ListenerId(42)");
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn listener_id_serde() {
+        let id = ListenerId::from_u64(42);
+
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(json, "42");
+        assert_eq!(serde_json::from_str::<ListenerId>(&json).unwrap(), id);
+
+        let cbor = serde_cbor::to_vec(&id).unwrap();
+        assert_eq!(serde_cbor::from_slice::<ListenerId>(&cbor).unwrap(), id);
+    }
 }
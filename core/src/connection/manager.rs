@@ -287,6 +287,18 @@ impl<I, O, H, TE, HE> Manager<I, O, H, TE, HE> {
         ConnectionId(task_id)
     }
 
+    /// Spawns a background task that is unrelated to any connection.
+    ///
+    /// The task is run on the configured executor, if any, or otherwise
+    /// polled on the current thread when the manager is polled for new events.
+    pub fn spawn(&mut self, task: Pin<Box<dyn Future<Output = ()> + Send>>) {
+        if let Some(executor) = &mut self.executor {
+            executor.exec(task);
+        } else {
+            self.local_spawns.push(task);
+        }
+    }
+
     /// Adds an existing connection to the manager.
     pub fn add<M>(&mut self, conn: Connection<M, H::Handler>, info: Connected) -> ConnectionId
     where
@@ -221,6 +221,12 @@ impl<TInEvent, TOutEvent, THandler, TTransErr, THandlerErr>
         &self.counters
     }
 
+    /// Spawns a background task that is unrelated to any connection,
+    /// using the same executor as for the connection tasks.
+    pub fn spawn(&mut self, task: future::BoxFuture<'static, ()>) {
+        self.manager.spawn(task)
+    }
+
     /// Adds a pending incoming connection to the pool in the form of a
     /// `Future` that establishes and negotiates the connection.
     ///
@@ -330,7 +336,10 @@ impl<TInEvent, TOutEvent, THandler, TTransErr, THandlerErr>
             move |(peer_id, muxer)| {
                 if let Some(peer) = expected_peer {
                     if peer != peer_id {
-                        return future::err(PendingConnectionError::InvalidPeerId)
+                        return future::err(PendingConnectionError::PeerIdMismatch {
+                            expected: peer,
+                            actual: peer_id,
+                        })
                     }
                 }
 
@@ -39,8 +39,6 @@ mod keys_proto {
     include!(concat!(env!("OUT_DIR"), "/keys_proto.rs"));
 }
 
-/// Multi-address re-export.
-pub use multiaddr;
 pub type Negotiated<T> = multistream_select::Negotiated<T>;
 
 mod peer_id;
@@ -49,19 +47,20 @@ mod translation;
 pub mod connection;
 pub mod either;
 pub mod identity;
+pub mod multiaddr;
 pub mod muxing;
 pub mod network;
 pub mod transport;
 pub mod upgrade;
 
-pub use multiaddr::Multiaddr;
+pub use self::multiaddr::Multiaddr;
 pub use multihash;
 pub use muxing::StreamMuxer;
-pub use peer_id::PeerId;
+pub use peer_id::{Distance, PeerId};
 pub use identity::PublicKey;
 pub use transport::Transport;
 pub use translation::address_translation;
-pub use upgrade::{InboundUpgrade, OutboundUpgrade, UpgradeInfo, UpgradeError, ProtocolName};
+pub use upgrade::{InboundUpgrade, OutboundUpgrade, UpgradeInfo, UpgradeError, ProtocolName, StreamProtocol};
 pub use connection::{Connected, Endpoint, ConnectedPoint};
 pub use network::Network;
 
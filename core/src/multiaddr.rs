@@ -0,0 +1,682 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Multi-address re-export, together with utilities for working with
+//! multi-addresses.
+
+mod pattern;
+mod segment;
+
+#[cfg(feature = "serde")]
+pub mod serde_human_readable;
+
+pub use ::multiaddr::*;
+pub use pattern::{MultiaddrPattern, ParseError};
+pub use segment::{ProtocolSegment, Segments};
+
+use crate::PeerId;
+use std::{error, fmt, net::{IpAddr, Ipv4Addr, Ipv6Addr}};
+#[cfg(feature = "url")]
+use url::{Host, Url};
+
+/// Extension trait for [`Multiaddr`].
+pub trait MultiaddrExt: Sized {
+    /// Returns the [`PeerId`] of a trailing `/p2p` component, if any.
+    fn peer_id(&self) -> Option<PeerId>;
+
+    /// Splits off a trailing `/p2p` component, returning the remaining
+    /// address and the [`PeerId`], if any.
+    fn without_peer_id(self) -> (Multiaddr, Option<PeerId>);
+
+    /// Appends a `/p2p` component for the given [`PeerId`].
+    ///
+    /// Returns back the address as an error if it already ends with a `/p2p`
+    /// component. Note that `/p2p` components in other places, e.g. the one
+    /// of the relay in `/p2p/<relay>/p2p-circuit`, are retained.
+    fn with_p2p(self, peer_id: PeerId) -> std::result::Result<Multiaddr, Multiaddr>;
+
+    /// Returns an iterator over the components of the address, grouped into
+    /// [`ProtocolSegment`]s.
+    ///
+    /// Transforming the segments rather than the individual components and
+    /// reassembling them via [`MultiaddrExt::from_segments`] preserves the
+    /// structure of the address, e.g. a transport stack is kept together.
+    fn segments(&self) -> Segments<'_>;
+
+    /// Builds an address from [`ProtocolSegment`]s.
+    fn from_segments(segments: impl IntoIterator<Item = ProtocolSegment>) -> Multiaddr;
+
+    /// Whether the first IP address of the address is private, i.e. within
+    /// `10.0.0.0/8`, `172.16.0.0/12` or `192.168.0.0/16` (RFC 1918) or, for
+    /// IPv6, a unique local address within `fc00::/7` (RFC 4193).
+    ///
+    /// Returns `false` if the address contains no IP address.
+    fn is_private(&self) -> bool;
+
+    /// Whether the first IP address of the address is a loopback address,
+    /// i.e. within `127.0.0.0/8` or `::1`.
+    ///
+    /// Returns `false` if the address contains no IP address.
+    fn is_loopback(&self) -> bool;
+
+    /// Whether the first IP address of the address is a link-local address,
+    /// i.e. within `169.254.0.0/16` (RFC 3927) or `fe80::/10` (RFC 4291).
+    ///
+    /// Returns `false` if the address contains no IP address.
+    fn is_link_local(&self) -> bool;
+
+    /// Whether the first IP address of the address is globally reachable.
+    ///
+    /// Apart from private, loopback and link-local addresses, this excludes
+    /// e.g. unspecified, broadcast, shared (RFC 6598), documentation and
+    /// reserved addresses.
+    ///
+    /// Returns `false` if the address contains no IP address.
+    fn is_global(&self) -> bool;
+
+    /// Converts the address into an equivalent URL, e.g.
+    /// `/dns4/example.com/tcp/443/wss` into `wss://example.com:443/`.
+    ///
+    /// Supported are addresses consisting of an `/ip4`, `/ip6`, `/dns`,
+    /// `/dns4` or `/dns6` host, a `/tcp` port and one of `/ws`, `/wss`,
+    /// `/http` or `/https`. Returns `None` for any other address, e.g. one
+    /// with a trailing `/p2p` component.
+    ///
+    /// > **Note**: The port of the URL is omitted if it is the default port
+    /// > of the scheme, e.g. 443 for `wss`.
+    #[cfg(feature = "url")]
+    fn to_url(&self) -> Option<Url>;
+
+    /// Converts a `ws`, `wss`, `http` or `https` URL into an equivalent
+    /// address, e.g. `http://1.2.3.4/` into `/ip4/1.2.3.4/tcp/80/http`.
+    ///
+    /// Domain names are mapped to `/dns` components. Fails if the URL
+    /// contains information without an equivalent in the address, e.g.
+    /// credentials, a query or, for `http` and `https`, a path.
+    #[cfg(feature = "url")]
+    fn from_url(url: &Url) -> std::result::Result<Multiaddr, ConversionError>;
+}
+
+impl MultiaddrExt for Multiaddr {
+    fn peer_id(&self) -> Option<PeerId> {
+        match self.iter().last() {
+            Some(Protocol::P2p(multihash)) => PeerId::from_multihash(multihash).ok(),
+            _ => None,
+        }
+    }
+
+    fn without_peer_id(mut self) -> (Multiaddr, Option<PeerId>) {
+        match self.peer_id() {
+            Some(peer_id) => {
+                self.pop();
+                (self, Some(peer_id))
+            }
+            None => (self, None),
+        }
+    }
+
+    fn with_p2p(self, peer_id: PeerId) -> std::result::Result<Multiaddr, Multiaddr> {
+        match self.iter().last() {
+            Some(Protocol::P2p(_)) => Err(self),
+            _ => Ok(self.with(Protocol::P2p(peer_id.into()))),
+        }
+    }
+
+    fn segments(&self) -> Segments<'_> {
+        Segments::new(self)
+    }
+
+    fn from_segments(segments: impl IntoIterator<Item = ProtocolSegment>) -> Multiaddr {
+        segment::from_segments(segments)
+    }
+
+    fn is_private(&self) -> bool {
+        match first_ip(self) {
+            Some(IpAddr::V4(ip)) => ip.is_private(),
+            Some(IpAddr::V6(ip)) => is_unique_local(&ip),
+            None => false,
+        }
+    }
+
+    fn is_loopback(&self) -> bool {
+        match first_ip(self) {
+            Some(ip) => ip.is_loopback(),
+            None => false,
+        }
+    }
+
+    fn is_link_local(&self) -> bool {
+        match first_ip(self) {
+            Some(IpAddr::V4(ip)) => ip.is_link_local(),
+            Some(IpAddr::V6(ip)) => is_unicast_link_local(&ip),
+            None => false,
+        }
+    }
+
+    fn is_global(&self) -> bool {
+        match first_ip(self) {
+            Some(IpAddr::V4(ip)) => is_global_v4(&ip),
+            Some(IpAddr::V6(ip)) => is_global_v6(&ip),
+            None => false,
+        }
+    }
+
+    #[cfg(feature = "url")]
+    fn to_url(&self) -> Option<Url> {
+        let mut iter = self.iter();
+
+        let host = match iter.next()? {
+            Protocol::Ip4(ip) => ip.to_string(),
+            Protocol::Ip6(ip) => format!("[{}]", ip),
+            Protocol::Dns(name) | Protocol::Dns4(name) | Protocol::Dns6(name) => name.into_owned(),
+            _ => return None,
+        };
+        let port = match iter.next()? {
+            Protocol::Tcp(port) => port,
+            _ => return None,
+        };
+        let (scheme, path) = match iter.next()? {
+            Protocol::Ws(path) => ("ws", path),
+            Protocol::Wss(path) => ("wss", path),
+            Protocol::Http => ("http", "/".into()),
+            Protocol::Https => ("https", "/".into()),
+            _ => return None,
+        };
+
+        if iter.next().is_some() {
+            return None
+        }
+
+        Url::parse(&format!("{}://{}:{}{}", scheme, host, port, path)).ok()
+    }
+
+    #[cfg(feature = "url")]
+    fn from_url(url: &Url) -> std::result::Result<Multiaddr, ConversionError> {
+        let protocol = match url.scheme() {
+            "ws" => Protocol::Ws(url.path().to_owned().into()),
+            "wss" => Protocol::Wss(url.path().to_owned().into()),
+            "http" | "https" if url.path() != "/" => return Err(ConversionError::InformationLoss),
+            "http" => Protocol::Http,
+            "https" => Protocol::Https,
+            scheme => return Err(ConversionError::UnsupportedScheme(scheme.to_owned())),
+        };
+
+        if !url.username().is_empty() || url.password().is_some()
+            || url.query().is_some() || url.fragment().is_some()
+        {
+            return Err(ConversionError::InformationLoss)
+        }
+
+        let host = match url.host() {
+            Some(Host::Ipv4(ip)) => Protocol::Ip4(ip),
+            Some(Host::Ipv6(ip)) => Protocol::Ip6(ip),
+            Some(Host::Domain(name)) => Protocol::Dns(name.to_owned().into()),
+            None => return Err(ConversionError::MissingHost),
+        };
+        let port = url.port_or_known_default().expect("All supported schemes have a default port.");
+
+        Ok(Multiaddr::empty()
+            .with(host)
+            .with(Protocol::Tcp(port))
+            .with(protocol))
+    }
+}
+
+/// Error returned by [`MultiaddrExt::from_url`] for a URL without an
+/// equivalent [`Multiaddr`].
+#[cfg(feature = "url")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConversionError {
+    /// The scheme of the URL is not supported.
+    UnsupportedScheme(String),
+    /// The URL has no host.
+    MissingHost,
+    /// The URL contains information that cannot be retained in the address.
+    InformationLoss,
+}
+
+#[cfg(feature = "url")]
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConversionError::UnsupportedScheme(scheme) => write!(f, "Unsupported URL scheme: {}", scheme),
+            ConversionError::MissingHost => f.write_str("URL has no host"),
+            ConversionError::InformationLoss => f.write_str("URL cannot be converted without information loss"),
+        }
+    }
+}
+
+#[cfg(feature = "url")]
+impl error::Error for ConversionError {}
+
+/// Returns the first IP address of a [`Multiaddr`], if any.
+fn first_ip(addr: &Multiaddr) -> Option<IpAddr> {
+    addr.iter().find_map(|p| match p {
+        Protocol::Ip4(ip) => Some(IpAddr::V4(ip)),
+        Protocol::Ip6(ip) => Some(IpAddr::V6(ip)),
+        _ => None,
+    })
+}
+
+/// `fc00::/7`, see RFC 4193.
+fn is_unique_local(ip: &Ipv6Addr) -> bool {
+    ip.segments()[0] & 0xfe00 == 0xfc00
+}
+
+/// `fe80::/10`, see RFC 4291.
+fn is_unicast_link_local(ip: &Ipv6Addr) -> bool {
+    ip.segments()[0] & 0xffc0 == 0xfe80
+}
+
+fn is_global_v4(ip: &Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(a == 0 // "This network", RFC 791
+        || ip.is_private()
+        || (a == 100 && b & 0xc0 == 64) // Shared address space, RFC 6598
+        || ip.is_loopback()
+        || ip.is_link_local()
+        // IETF protocol assignments, RFC 6890, except for the globally
+        // reachable PCP and TURN anycast addresses.
+        || (a == 192 && b == 0 && c == 0 && !matches!(ip.octets()[3], 9 | 10))
+        || ip.is_documentation()
+        || (a == 198 && b & 0xfe == 18) // Benchmarking, RFC 2544
+        || (a & 0xf0 == 240) // Reserved, RFC 1112, including broadcast
+    )
+}
+
+fn is_global_v6(ip: &Ipv6Addr) -> bool {
+    let s = ip.segments();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || matches!(s, [0, 0, 0, 0, 0, 0xffff, _, _]) // IPv4-mapped, RFC 4291
+        || matches!(s, [0x100, 0, 0, 0, ..]) // Discard-only, RFC 6666
+        // IETF protocol assignments, RFC 2928, except for the globally
+        // reachable TEREDO-adjacent, ORCHIDv2 and AMT blocks.
+        || (s[0] == 0x2001 && s[1] < 0x200 && !(s[1] == 3 || (0x20..0x40).contains(&s[1])))
+        || (s[0] == 0x2001 && s[1] == 0xdb8) // Documentation, RFC 3849
+        || is_unique_local(ip)
+        || is_unicast_link_local(ip)
+    )
+}
+
+/// Builds a [`Multiaddr`] from its individual components.
+///
+/// The structure of the address is validated by [`MultiaddrBuilder::build`].
+///
+/// # Example
+///
+/// ```
+/// # use libp2p_core::multiaddr::MultiaddrBuilder;
+/// # use std::net::Ipv4Addr;
+/// let addr = MultiaddrBuilder::new()
+///     .ip4(Ipv4Addr::LOCALHOST)
+///     .tcp(4001)
+///     .ws("/")
+///     .build()
+///     .unwrap();
+/// assert_eq!(addr, "/ip4/127.0.0.1/tcp/4001/ws".parse().unwrap());
+/// ```
+#[derive(Debug, Clone)]
+pub struct MultiaddrBuilder {
+    addr: Multiaddr,
+}
+
+impl Default for MultiaddrBuilder {
+    fn default() -> Self {
+        MultiaddrBuilder::new()
+    }
+}
+
+impl MultiaddrBuilder {
+    /// Creates a new builder for an empty [`Multiaddr`].
+    pub fn new() -> Self {
+        MultiaddrBuilder { addr: Multiaddr::empty() }
+    }
+
+    /// Appends an `/ip4` component.
+    pub fn ip4(self, addr: Ipv4Addr) -> Self {
+        self.protocol(Protocol::Ip4(addr))
+    }
+
+    /// Appends an `/ip6` component.
+    pub fn ip6(self, addr: Ipv6Addr) -> Self {
+        self.protocol(Protocol::Ip6(addr))
+    }
+
+    /// Appends a `/dns` component.
+    pub fn dns(self, name: &str) -> Self {
+        self.protocol(Protocol::Dns(name.to_owned().into()))
+    }
+
+    /// Appends a `/dns4` component.
+    pub fn dns4(self, name: &str) -> Self {
+        self.protocol(Protocol::Dns4(name.to_owned().into()))
+    }
+
+    /// Appends a `/dns6` component.
+    pub fn dns6(self, name: &str) -> Self {
+        self.protocol(Protocol::Dns6(name.to_owned().into()))
+    }
+
+    /// Appends a `/dnsaddr` component.
+    pub fn dnsaddr(self, name: &str) -> Self {
+        self.protocol(Protocol::Dnsaddr(name.to_owned().into()))
+    }
+
+    /// Appends an `/onion3` component.
+    pub fn onion3(self, hash: [u8; 35], port: u16) -> Self {
+        self.protocol(Protocol::Onion3((hash, port).into()))
+    }
+
+    /// Appends a `/memory` component.
+    pub fn memory(self, port: u64) -> Self {
+        self.protocol(Protocol::Memory(port))
+    }
+
+    /// Appends a `/tcp` component.
+    pub fn tcp(self, port: u16) -> Self {
+        self.protocol(Protocol::Tcp(port))
+    }
+
+    /// Appends a `/udp` component.
+    pub fn udp(self, port: u16) -> Self {
+        self.protocol(Protocol::Udp(port))
+    }
+
+    /// Appends a `/quic` component.
+    pub fn quic(self) -> Self {
+        self.protocol(Protocol::Quic)
+    }
+
+    /// Appends a `/ws` component with the given HTTP path, e.g. `"/"`.
+    pub fn ws(self, path: &str) -> Self {
+        self.protocol(Protocol::Ws(path.to_owned().into()))
+    }
+
+    /// Appends a `/wss` component with the given HTTP path, e.g. `"/"`.
+    pub fn wss(self, path: &str) -> Self {
+        self.protocol(Protocol::Wss(path.to_owned().into()))
+    }
+
+    /// Appends a `/p2p` component.
+    pub fn p2p(self, peer_id: PeerId) -> Self {
+        self.protocol(Protocol::P2p(peer_id.into()))
+    }
+
+    /// Appends a `/p2p-circuit` component.
+    pub fn p2p_circuit(self) -> Self {
+        self.protocol(Protocol::P2pCircuit)
+    }
+
+    /// Appends an arbitrary component.
+    pub fn protocol(mut self, protocol: Protocol<'_>) -> Self {
+        self.addr.push(protocol);
+        self
+    }
+
+    /// Validates the structure of the address and returns it.
+    ///
+    /// Validation ensures, among others, that transport protocols are
+    /// preceded by a suitable network protocol, e.g. `/tcp` by `/ip4`, and
+    /// that `/dnsaddr` is not followed by a transport protocol.
+    pub fn build(self) -> std::result::Result<Multiaddr, MultiaddrBuilderError> {
+        let mut previous: Option<Protocol<'_>> = None;
+
+        for protocol in self.addr.iter() {
+            if !is_valid_successor(previous.as_ref(), &protocol) {
+                return Err(MultiaddrBuilderError {
+                    protocol: protocol.to_string(),
+                    previous: previous.map(|p| p.to_string()),
+                })
+            }
+            previous = Some(protocol);
+        }
+
+        Ok(self.addr)
+    }
+}
+
+/// Checks whether `protocol` may follow `previous` in a [`Multiaddr`].
+fn is_valid_successor(previous: Option<&Protocol<'_>>, protocol: &Protocol<'_>) -> bool {
+    let previous_is_address = matches!(previous, Some(
+        Protocol::Ip4(_) | Protocol::Ip6(_) |
+        Protocol::Dns(_) | Protocol::Dns4(_) | Protocol::Dns6(_) | Protocol::Dnsaddr(_)
+    ));
+
+    match protocol {
+        Protocol::Ip4(_) | Protocol::Ip6(_) |
+        Protocol::Dns(_) | Protocol::Dns4(_) | Protocol::Dns6(_) | Protocol::Dnsaddr(_) =>
+            !previous_is_address,
+        // `/dnsaddr` resolves to complete addresses, including the transport.
+        Protocol::Tcp(_) | Protocol::Udp(_) =>
+            previous_is_address && !matches!(previous, Some(Protocol::Dnsaddr(_))),
+        Protocol::Quic => matches!(previous, Some(Protocol::Udp(_))),
+        Protocol::Ws(_) | Protocol::Wss(_) => matches!(previous, Some(Protocol::Tcp(_))),
+        Protocol::P2p(_) => !matches!(previous, Some(Protocol::Udp(_))),
+        _ => true,
+    }
+}
+
+/// Error returned by [`MultiaddrBuilder::build`] for a structurally invalid address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiaddrBuilderError {
+    /// The misplaced component.
+    pub protocol: String,
+    /// The component preceding the misplaced one, if any.
+    pub previous: Option<String>,
+}
+
+impl fmt::Display for MultiaddrBuilderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.previous {
+            Some(previous) => write!(f, "Invalid multiaddr: {} may not follow {}", self.protocol, previous),
+            None => write!(f, "Invalid multiaddr: {} may not be the first component", self.protocol),
+        }
+    }
+}
+
+impl error::Error for MultiaddrBuilderError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peer_id_round_trip() {
+        let peer_id = PeerId::random();
+        let other_peer_id = PeerId::random();
+        let addrs: Vec<Multiaddr> = vec![
+            Multiaddr::empty(),
+            "/ip4/127.0.0.1/tcp/4001".parse().unwrap(),
+            format!("/p2p/{}/p2p-circuit", other_peer_id).parse().unwrap(),
+        ];
+
+        for addr in addrs {
+            assert_eq!(addr.peer_id(), None);
+            assert_eq!(addr.clone().without_peer_id(), (addr.clone(), None));
+
+            let with_peer_id = addr.clone().with_p2p(peer_id).unwrap();
+            assert_eq!(with_peer_id, addr.clone().with(Protocol::P2p(peer_id.into())));
+            assert_eq!(with_peer_id.peer_id(), Some(peer_id));
+            assert_eq!(with_peer_id.clone().without_peer_id(), (addr, Some(peer_id)));
+            assert_eq!(with_peer_id.clone().with_p2p(other_peer_id), Err(with_peer_id));
+        }
+    }
+
+    #[test]
+    fn peer_id_of_non_peer_id_multihash() {
+        use multihash::MultihashDigest;
+        let multihash = multihash::Code::Sha2_512.digest(b"not a peer id");
+        let addr = Multiaddr::empty().with(Protocol::P2p(multihash));
+        assert_eq!(addr.peer_id(), None);
+        assert_eq!(addr.clone().with_p2p(PeerId::random()), Err(addr));
+    }
+
+    #[test]
+    fn ip_classification() {
+        // (address, private, loopback, link-local, global)
+        let cases = [
+            ("/ip4/10.1.2.3/tcp/1", true, false, false, false),
+            ("/ip4/172.16.0.1", true, false, false, false),
+            ("/ip4/172.31.255.255", true, false, false, false),
+            ("/ip4/172.32.0.1", false, false, false, true),
+            ("/ip4/192.168.1.1", true, false, false, false),
+            ("/ip4/127.0.0.1/tcp/1", false, true, false, false),
+            ("/ip4/169.254.1.1", false, false, true, false),
+            ("/ip4/100.64.0.1", false, false, false, false),
+            ("/ip4/0.0.0.0", false, false, false, false),
+            ("/ip4/255.255.255.255", false, false, false, false),
+            ("/ip4/192.0.2.1", false, false, false, false),
+            ("/ip4/198.18.0.1", false, false, false, false),
+            ("/ip4/240.0.0.1", false, false, false, false),
+            ("/ip4/192.0.0.9", false, false, false, true),
+            ("/ip4/1.1.1.1/tcp/443", false, false, false, true),
+            ("/ip6/fd00::1", true, false, false, false),
+            ("/ip6/::1/tcp/1", false, true, false, false),
+            ("/ip6/fe80::1", false, false, true, false),
+            ("/ip6/::", false, false, false, false),
+            ("/ip6/::ffff:1.1.1.1", false, false, false, false),
+            ("/ip6/100::1", false, false, false, false),
+            ("/ip6/2001:db8::1", false, false, false, false),
+            ("/ip6/2001::1", false, false, false, false),
+            ("/ip6/2001:20::1", false, false, false, true),
+            ("/ip6/2606:4700::1111", false, false, false, true),
+            ("/dns4/example.com/tcp/1", false, false, false, false),
+        ];
+
+        for (addr, private, loopback, link_local, global) in cases.iter() {
+            let addr: Multiaddr = addr.parse().unwrap();
+            assert_eq!(addr.is_private(), *private, "{}", addr);
+            assert_eq!(addr.is_loopback(), *loopback, "{}", addr);
+            assert_eq!(addr.is_link_local(), *link_local, "{}", addr);
+            assert_eq!(addr.is_global(), *global, "{}", addr);
+        }
+    }
+
+    #[test]
+    fn builder_matches_parsed_address() {
+        let peer_id = PeerId::random();
+
+        let cases = vec![
+            (
+                MultiaddrBuilder::new().ip4(Ipv4Addr::new(1, 2, 3, 4)).tcp(4001),
+                "/ip4/1.2.3.4/tcp/4001".to_string(),
+            ),
+            (
+                MultiaddrBuilder::new().ip6(Ipv6Addr::LOCALHOST).udp(4001).quic().p2p(peer_id),
+                format!("/ip6/::1/udp/4001/quic/p2p/{}", peer_id),
+            ),
+            (
+                MultiaddrBuilder::new().dns("example.com").tcp(443).wss("/"),
+                "/dns/example.com/tcp/443/wss".to_string(),
+            ),
+            (
+                MultiaddrBuilder::new().dns4("example.com").tcp(80).ws("/").p2p(peer_id),
+                format!("/dns4/example.com/tcp/80/ws/p2p/{}", peer_id),
+            ),
+            (
+                MultiaddrBuilder::new().memory(1234).p2p(peer_id).p2p_circuit(),
+                format!("/memory/1234/p2p/{}/p2p-circuit", peer_id),
+            ),
+            (
+                MultiaddrBuilder::new().dnsaddr("bootstrap.libp2p.io").p2p(peer_id),
+                format!("/dnsaddr/bootstrap.libp2p.io/p2p/{}", peer_id),
+            ),
+            (
+                MultiaddrBuilder::new().ip4(Ipv4Addr::new(1, 2, 3, 4)).p2p(peer_id),
+                format!("/ip4/1.2.3.4/p2p/{}", peer_id),
+            ),
+            (
+                MultiaddrBuilder::new().onion3([7; 35], 1234),
+                format!("/onion3/{}:1234", "a4dqobyha4dqobyha4dqobyha4dqobyha4dqobyha4dqobyha4dqobyh"),
+            ),
+        ];
+
+        for (builder, expected) in cases {
+            assert_eq!(builder.build().unwrap(), expected.parse::<Multiaddr>().unwrap());
+        }
+    }
+
+    #[test]
+    fn builder_rejects_invalid_structure() {
+        let err = MultiaddrBuilder::new().dnsaddr("example.com").tcp(1).build().unwrap_err();
+        assert_eq!(err.previous.as_deref(), Some("/dnsaddr/example.com"));
+
+        assert!(MultiaddrBuilder::new().dnsaddr("example.com").udp(1).build().is_err());
+
+        assert!(MultiaddrBuilder::new().tcp(4001).build().is_err());
+        assert!(MultiaddrBuilder::new().ip4(Ipv4Addr::LOCALHOST).udp(1).ws("/").build().is_err());
+        assert!(MultiaddrBuilder::new().ip4(Ipv4Addr::LOCALHOST).tcp(1).quic().build().is_err());
+        assert!(MultiaddrBuilder::new().ip4(Ipv4Addr::LOCALHOST).ip6(Ipv6Addr::LOCALHOST).build().is_err());
+        assert!(MultiaddrBuilder::new().ip4(Ipv4Addr::LOCALHOST).udp(1).p2p(PeerId::random()).build().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "url")]
+    fn url_round_trip() {
+        let cases = [
+            ("/dns/example.com/tcp/443/wss", "wss://example.com/"),
+            ("/dns/example.com/tcp/8443/x-parity-wss/%2Fws", "wss://example.com:8443/ws"),
+            ("/ip4/1.2.3.4/tcp/80/http", "http://1.2.3.4/"),
+            ("/ip4/1.2.3.4/tcp/8080/ws", "ws://1.2.3.4:8080/"),
+            ("/ip6/::1/tcp/443/https", "https://[::1]/"),
+        ];
+
+        for (addr, url) in cases.iter() {
+            let addr: Multiaddr = addr.parse().unwrap();
+            let url = Url::parse(url).unwrap();
+            assert_eq!(addr.to_url().as_ref(), Some(&url), "{}", addr);
+            assert_eq!(Multiaddr::from_url(&url), Ok(addr), "{}", url);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "url")]
+    fn url_conversion_of_dns4_and_dns6() {
+        let addr: Multiaddr = "/dns4/example.com/tcp/443/wss".parse().unwrap();
+        assert_eq!(addr.to_url(), Some(Url::parse("wss://example.com:443/").unwrap()));
+
+        let addr: Multiaddr = "/dns6/example.com/tcp/80/http".parse().unwrap();
+        assert_eq!(addr.to_url(), Some(Url::parse("http://example.com/").unwrap()));
+    }
+
+    #[test]
+    #[cfg(feature = "url")]
+    fn url_conversion_failures() {
+        let addrs = [
+            "/ip4/1.2.3.4/tcp/80",
+            "/ip4/1.2.3.4/udp/80/quic",
+            "/ip4/1.2.3.4/tcp/80/ws/p2p/12D3KooWD3eckifWpRn9wQpMG9R9hX3sD158z7EqHWmweQAJU5SA",
+            "/memory/1234",
+        ];
+        for addr in addrs.iter() {
+            assert_eq!(addr.parse::<Multiaddr>().unwrap().to_url(), None, "{}", addr);
+        }
+
+        let url = |s| Url::parse(s).unwrap();
+        assert_eq!(
+            Multiaddr::from_url(&url("ftp://example.com/")),
+            Err(ConversionError::UnsupportedScheme("ftp".to_string()))
+        );
+        assert_eq!(Multiaddr::from_url(&url("ws://user:pass@example.com/")), Err(ConversionError::InformationLoss));
+        assert_eq!(Multiaddr::from_url(&url("ws://example.com/?query")), Err(ConversionError::InformationLoss));
+        assert_eq!(Multiaddr::from_url(&url("http://example.com/path")), Err(ConversionError::InformationLoss));
+    }
+}
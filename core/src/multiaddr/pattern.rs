@@ -0,0 +1,314 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use super::{Multiaddr, Protocol};
+use std::{error, fmt, net::{Ipv4Addr, Ipv6Addr}, str::FromStr};
+
+/// A pattern that [`Multiaddr`]s can be matched against.
+///
+/// The textual representation of a pattern resembles that of a [`Multiaddr`],
+/// with the following additions:
+///
+///   - `*` matches any value of a component, e.g. `/tcp/*`. This includes
+///     protocols whose value is optional, e.g. `/ws/*` matches `/ws` as well
+///     as `/x-parity-ws/<path>` with any path, whereas `/ws` only matches
+///     the former.
+///   - An `/ip4` or `/ip6` value may be followed by a prefix length to match
+///     a range of addresses in CIDR notation, e.g. `/ip4/10.0.0.0/8`.
+///   - A `?` suffix on a protocol name makes the component optional,
+///     e.g. `/p2p?/*`.
+///
+/// An address matches a pattern if each of its components is matched by the
+/// corresponding component of the pattern, taking optional components into
+/// account. For example, the pattern `/ip4/10.0.0.0/8/tcp/*/p2p?/*` matches
+/// both `/ip4/10.1.2.3/tcp/4001` and `/ip4/10.1.2.3/tcp/4001/p2p/<peer>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiaddrPattern {
+    components: Vec<ComponentPattern>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ComponentPattern {
+    optional: bool,
+    matcher: Matcher,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Matcher {
+    /// Matches any value of the protocol with the given name.
+    Any(String),
+    /// Matches exactly the given component.
+    Exact(Protocol<'static>),
+    /// Matches IPv4 addresses within the given network.
+    Ip4Net(Ipv4Addr, u8),
+    /// Matches IPv6 addresses within the given network.
+    Ip6Net(Ipv6Addr, u8),
+}
+
+impl MultiaddrPattern {
+    /// Parses a pattern from its textual representation.
+    pub fn parse(s: &str) -> Result<MultiaddrPattern, ParseError> {
+        let mut segments = s.split('/').peekable();
+        if segments.next() != Some("") {
+            return Err(ParseError::InvalidFormat);
+        }
+
+        let mut components = Vec::new();
+
+        while let Some(segment) = segments.next() {
+            if segment.is_empty() {
+                // Allow a trailing slash.
+                if segments.peek().is_none() {
+                    break;
+                }
+                return Err(ParseError::InvalidFormat);
+            }
+
+            let (name, optional) = match segment.strip_suffix('?') {
+                Some(name) => (name, true),
+                None => (segment, false),
+            };
+
+            if segments.peek() == Some(&"*") {
+                segments.next();
+                components.push(ComponentPattern {
+                    optional,
+                    matcher: Matcher::Any(canonical_name(name).to_owned()),
+                });
+                continue;
+            }
+
+            // Protocols without a value, e.g. `/quic`.
+            if let Ok(addr) = Multiaddr::from_str(&format!("/{}", name)) {
+                if let Some(protocol) = addr.iter().next() {
+                    components.push(ComponentPattern {
+                        optional,
+                        matcher: Matcher::Exact(protocol.acquire()),
+                    });
+                    continue;
+                }
+            }
+
+            let value = segments.next()
+                .ok_or_else(|| ParseError::InvalidComponent(name.to_owned()))?;
+
+            let protocol = Multiaddr::from_str(&format!("/{}/{}", name, value)).ok()
+                .and_then(|addr| addr.iter().next().map(Protocol::acquire))
+                .ok_or_else(|| ParseError::InvalidComponent(format!("/{}/{}", name, value)))?;
+
+            // An IP address may be followed by a prefix length.
+            let prefix_len = segments.peek().and_then(|s| s.parse::<u8>().ok());
+            let matcher = match (protocol, prefix_len) {
+                (Protocol::Ip4(ip), Some(len)) => {
+                    segments.next();
+                    if len > 32 {
+                        return Err(ParseError::InvalidPrefixLength(len));
+                    }
+                    Matcher::Ip4Net(ip, len)
+                }
+                (Protocol::Ip6(ip), Some(len)) => {
+                    segments.next();
+                    if len > 128 {
+                        return Err(ParseError::InvalidPrefixLength(len));
+                    }
+                    Matcher::Ip6Net(ip, len)
+                }
+                (protocol, _) => Matcher::Exact(protocol),
+            };
+
+            components.push(ComponentPattern { optional, matcher });
+        }
+
+        Ok(MultiaddrPattern { components })
+    }
+
+    /// Checks whether the given address matches the pattern.
+    pub fn matches(&self, addr: &Multiaddr) -> bool {
+        let protocols = addr.iter().collect::<Vec<_>>();
+        matches_from(&self.components, &protocols)
+    }
+}
+
+impl FromStr for MultiaddrPattern {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        MultiaddrPattern::parse(s)
+    }
+}
+
+fn matches_from(patterns: &[ComponentPattern], protocols: &[Protocol<'_>]) -> bool {
+    let (pattern, patterns) = match patterns.split_first() {
+        Some(split) => split,
+        None => return protocols.is_empty(),
+    };
+
+    let matches_here = match protocols.split_first() {
+        Some((protocol, protocols)) =>
+            pattern.matcher.matches(protocol) && matches_from(patterns, protocols),
+        None => false,
+    };
+
+    matches_here || (pattern.optional && matches_from(patterns, protocols))
+}
+
+impl Matcher {
+    fn matches(&self, protocol: &Protocol<'_>) -> bool {
+        match (self, protocol) {
+            (Matcher::Any(name), protocol) => protocol_name(protocol) == *name,
+            (Matcher::Exact(expected), protocol) => expected == protocol,
+            (Matcher::Ip4Net(net, len), Protocol::Ip4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - u32::from(*len)).unwrap_or(0);
+                u32::from(*net) & mask == u32::from(*ip) & mask
+            }
+            (Matcher::Ip6Net(net, len), Protocol::Ip6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - u32::from(*len)).unwrap_or(0);
+                u128::from(*net) & mask == u128::from(*ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Returns the name of a protocol as used in the textual representation
+/// of a [`Multiaddr`], e.g. `tcp`.
+///
+/// `/ws` and `/wss` are named as such regardless of their path.
+fn protocol_name(protocol: &Protocol<'_>) -> String {
+    match protocol {
+        Protocol::Ws(_) => return "ws".to_owned(),
+        Protocol::Wss(_) => return "wss".to_owned(),
+        _ => {}
+    }
+    protocol.to_string()
+        .split('/')
+        .nth(1)
+        .unwrap_or_default()
+        .to_owned()
+}
+
+/// Maps the alternative names of a protocol to the one used by [`protocol_name`].
+fn canonical_name(name: &str) -> &str {
+    match name {
+        "x-parity-ws" => "ws",
+        "x-parity-wss" => "wss",
+        name => name,
+    }
+}
+
+/// Error when parsing a [`MultiaddrPattern`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The pattern does not start with a `/` or contains an empty component.
+    InvalidFormat,
+    /// A component is not a valid protocol name and value.
+    InvalidComponent(String),
+    /// The prefix length of an IP network exceeds the length of the address.
+    InvalidPrefixLength(u8),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidFormat =>
+                write!(f, "Invalid multiaddr pattern format"),
+            ParseError::InvalidComponent(c) =>
+                write!(f, "Invalid multiaddr pattern component: {}", c),
+            ParseError::InvalidPrefixLength(l) =>
+                write!(f, "Invalid prefix length: {}", l),
+        }
+    }
+}
+
+impl error::Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PeerId;
+
+    fn matches(pattern: &str, addr: &str) -> bool {
+        MultiaddrPattern::parse(pattern).unwrap().matches(&addr.parse().unwrap())
+    }
+
+    #[test]
+    fn cidr() {
+        assert!(matches("/ip4/10.0.0.0/8/tcp/4001", "/ip4/10.1.2.3/tcp/4001"));
+        assert!(matches("/ip4/10.0.0.0/8/tcp/4001", "/ip4/10.255.255.255/tcp/4001"));
+        assert!(!matches("/ip4/10.0.0.0/8/tcp/4001", "/ip4/11.0.0.1/tcp/4001"));
+        assert!(matches("/ip4/0.0.0.0/0", "/ip4/1.2.3.4"));
+        assert!(matches("/ip6/fe80::/10", "/ip6/fe80::1"));
+        assert!(!matches("/ip6/fe80::/10", "/ip6/::1"));
+        assert!(!matches("/ip6/::/0", "/ip4/1.2.3.4"));
+    }
+
+    #[test]
+    fn wildcard() {
+        assert!(matches("/ip4/10.0.0.0/8/tcp/*", "/ip4/10.0.0.1/tcp/4001"));
+        assert!(matches("/ip4/*/tcp/*", "/ip4/1.2.3.4/tcp/1"));
+        assert!(!matches("/ip4/*/tcp/*", "/ip4/1.2.3.4/udp/1"));
+        assert!(!matches("/ip4/*/tcp/*", "/ip4/1.2.3.4/tcp/1/ws"));
+        assert!(matches("/dns/*/tcp/443/wss", "/dns/example.com/tcp/443/wss"));
+    }
+
+    #[test]
+    fn websocket_path() {
+        assert!(matches("/ip4/*/tcp/*/ws", "/ip4/1.2.3.4/tcp/1/ws"));
+        assert!(!matches("/ip4/*/tcp/*/ws", "/ip4/1.2.3.4/tcp/1/x-parity-ws/%2Ffoo"));
+
+        assert!(matches("/ip4/*/tcp/*/ws/*", "/ip4/1.2.3.4/tcp/1/ws"));
+        assert!(matches("/ip4/*/tcp/*/ws/*", "/ip4/1.2.3.4/tcp/1/x-parity-ws/%2Ffoo"));
+        assert!(matches("/ip4/*/tcp/*/x-parity-ws/*", "/ip4/1.2.3.4/tcp/1/x-parity-ws/%2Ffoo"));
+        assert!(!matches("/ip4/*/tcp/*/ws/*", "/ip4/1.2.3.4/tcp/1/wss"));
+        assert!(matches("/ip4/*/tcp/*/wss?/*", "/ip4/1.2.3.4/tcp/1/x-parity-wss/%2Ffoo"));
+        assert!(matches("/ip4/*/tcp/*/wss?/*", "/ip4/1.2.3.4/tcp/1"));
+
+        assert!(matches("/ip4/*/tcp/*/x-parity-ws/%2Ffoo", "/ip4/1.2.3.4/tcp/1/x-parity-ws/%2Ffoo"));
+        assert!(!matches("/ip4/*/tcp/*/x-parity-ws/%2Ffoo", "/ip4/1.2.3.4/tcp/1/ws"));
+    }
+
+    #[test]
+    fn peer_id() {
+        let peer_id = PeerId::random();
+        let other_peer_id = PeerId::random();
+        let pattern = format!("/ip4/*/tcp/*/p2p/{}", peer_id);
+
+        assert!(matches(&pattern, &format!("/ip4/1.2.3.4/tcp/1/p2p/{}", peer_id)));
+        assert!(!matches(&pattern, &format!("/ip4/1.2.3.4/tcp/1/p2p/{}", other_peer_id)));
+        assert!(!matches(&pattern, "/ip4/1.2.3.4/tcp/1"));
+    }
+
+    #[test]
+    fn optional() {
+        let peer_id = PeerId::random();
+        assert!(matches("/ip4/*/tcp/*/p2p?/*", "/ip4/1.2.3.4/tcp/1"));
+        assert!(matches("/ip4/*/tcp/*/p2p?/*", &format!("/ip4/1.2.3.4/tcp/1/p2p/{}", peer_id)));
+        assert!(matches("/ip4/*/udp/*/quic?", "/ip4/1.2.3.4/udp/1/quic"));
+        assert!(matches("/ip4/*/udp/*/quic?", "/ip4/1.2.3.4/udp/1"));
+    }
+
+    #[test]
+    fn invalid_patterns() {
+        assert_eq!(MultiaddrPattern::parse("ip4/*"), Err(ParseError::InvalidFormat));
+        assert_eq!(MultiaddrPattern::parse("/ip4/10.0.0.0/33"), Err(ParseError::InvalidPrefixLength(33)));
+        assert!(matches!(MultiaddrPattern::parse("/tcp"), Err(ParseError::InvalidComponent(_))));
+        assert!(matches!(MultiaddrPattern::parse("/tcp/foo"), Err(ParseError::InvalidComponent(_))));
+    }
+}
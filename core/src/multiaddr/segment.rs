@@ -0,0 +1,161 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use super::{Iter, Multiaddr, Protocol};
+use multihash::Multihash;
+use std::iter::Peekable;
+
+/// A group of semantically related components of a [`Multiaddr`].
+///
+/// Obtained via [`MultiaddrExt::segments`](super::MultiaddrExt::segments).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProtocolSegment {
+    /// A transport stack, i.e. a network address followed by the
+    /// protocols layered on top of it, e.g. `/ip4/1.2.3.4/tcp/4001/ws`.
+    Transport(Vec<Protocol<'static>>),
+    /// A `/p2p` component identifying a peer.
+    Peer(Multihash),
+    /// A `/p2p-circuit` component.
+    Circuit,
+    /// Any component that is not part of a transport stack.
+    Other(Protocol<'static>),
+}
+
+impl ProtocolSegment {
+    /// Returns the components of the segment.
+    pub fn into_protocols(self) -> Vec<Protocol<'static>> {
+        match self {
+            ProtocolSegment::Transport(protocols) => protocols,
+            ProtocolSegment::Peer(multihash) => vec![Protocol::P2p(multihash)],
+            ProtocolSegment::Circuit => vec![Protocol::P2pCircuit],
+            ProtocolSegment::Other(protocol) => vec![protocol],
+        }
+    }
+}
+
+/// Iterator over the [`ProtocolSegment`]s of a [`Multiaddr`].
+pub struct Segments<'a> {
+    iter: Peekable<Iter<'a>>,
+}
+
+impl<'a> Segments<'a> {
+    pub(super) fn new(addr: &'a Multiaddr) -> Self {
+        Segments { iter: addr.iter().peekable() }
+    }
+}
+
+impl<'a> Iterator for Segments<'a> {
+    type Item = ProtocolSegment;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let protocol = self.iter.next()?.acquire();
+
+        let segment = match protocol {
+            Protocol::P2p(multihash) => ProtocolSegment::Peer(multihash),
+            Protocol::P2pCircuit => ProtocolSegment::Circuit,
+            protocol if is_address(&protocol) => {
+                let mut protocols = vec![protocol];
+                while let Some(next) = self.iter.peek() {
+                    if is_address(next) || matches!(next, Protocol::P2p(_) | Protocol::P2pCircuit) {
+                        break;
+                    }
+                    protocols.push(self.iter.next().expect("peeked").acquire());
+                }
+                ProtocolSegment::Transport(protocols)
+            }
+            protocol => ProtocolSegment::Other(protocol),
+        };
+
+        Some(segment)
+    }
+}
+
+/// Builds a [`Multiaddr`] from [`ProtocolSegment`]s.
+pub(super) fn from_segments(segments: impl IntoIterator<Item = ProtocolSegment>) -> Multiaddr {
+    segments.into_iter().flat_map(ProtocolSegment::into_protocols).collect()
+}
+
+/// Whether the protocol is a network address that starts a transport stack.
+fn is_address(protocol: &Protocol<'_>) -> bool {
+    matches!(protocol,
+        Protocol::Ip4(_) | Protocol::Ip6(_) |
+        Protocol::Dns(_) | Protocol::Dns4(_) | Protocol::Dns6(_) | Protocol::Dnsaddr(_) |
+        Protocol::Memory(_) | Protocol::Onion(..) | Protocol::Onion3(_) | Protocol::Unix(_)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PeerId;
+    use crate::multiaddr::MultiaddrExt;
+
+    #[test]
+    fn round_trip() {
+        let relay = PeerId::random();
+        let dst = PeerId::random();
+        let addrs = vec![
+            String::new(),
+            "/ip4/1.2.3.4/tcp/4001".to_string(),
+            "/ip6/::1/udp/4001/quic".to_string(),
+            format!("/dns4/example.com/tcp/443/wss/p2p/{}", relay),
+            format!("/ip4/1.2.3.4/tcp/4001/ws/p2p/{}/p2p-circuit/p2p/{}", relay, dst),
+            format!("/p2p/{}/p2p-circuit/ip4/1.2.3.4/tcp/1/p2p/{}", relay, dst),
+            "/tcp/1/http".to_string(),
+        ];
+
+        for addr in addrs {
+            let addr: Multiaddr = addr.parse().unwrap();
+            assert_eq!(Multiaddr::from_segments(addr.segments()), addr);
+        }
+    }
+
+    #[test]
+    fn grouping() {
+        let relay = PeerId::random();
+        let addr: Multiaddr = format!("/ip4/1.2.3.4/tcp/4001/ws/p2p/{}/p2p-circuit", relay)
+            .parse()
+            .unwrap();
+
+        assert_eq!(addr.segments().collect::<Vec<_>>(), vec![
+            ProtocolSegment::Transport(vec![
+                Protocol::Ip4([1, 2, 3, 4].into()),
+                Protocol::Tcp(4001),
+                Protocol::Ws("/".into()),
+            ]),
+            ProtocolSegment::Peer(relay.into()),
+            ProtocolSegment::Circuit,
+        ]);
+    }
+
+    #[test]
+    fn replace_transport() {
+        let peer_id = PeerId::random();
+        let addr: Multiaddr = format!("/ip4/1.2.3.4/tcp/4001/p2p/{}", peer_id).parse().unwrap();
+
+        let replaced = Multiaddr::from_segments(addr.segments().map(|segment| match segment {
+            ProtocolSegment::Transport(_) =>
+                ProtocolSegment::Transport(vec![Protocol::Memory(1)]),
+            other => other,
+        }));
+
+        assert_eq!(replaced, format!("/memory/1/p2p/{}", peer_id).parse().unwrap());
+    }
+}
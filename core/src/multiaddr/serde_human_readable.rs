@@ -0,0 +1,128 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Serialization of a [`Multiaddr`] that is readable by humans where possible.
+//!
+//! With a human-readable format, e.g. JSON, the address is serialized in its
+//! textual representation, e.g. `"/ip4/1.2.3.4/tcp/4001"`. With any other
+//! format, e.g. CBOR, the address is serialized in its compact binary
+//! representation.
+//!
+//! To be used with `#[serde(with = "...")]`:
+//!
+//! ```
+//! # use libp2p_core::Multiaddr;
+//! # use serde::{Deserialize, Serialize};
+//! #[derive(Serialize, Deserialize)]
+//! struct Config {
+//!     #[serde(with = "libp2p_core::multiaddr::serde_human_readable")]
+//!     listen_addr: Multiaddr,
+//! }
+//! ```
+
+use super::Multiaddr;
+use serde::{de, Deserializer, Serializer};
+use std::{convert::TryFrom, fmt};
+
+/// Serializes a [`Multiaddr`], see the [module documentation](self).
+pub fn serialize<S>(addr: &Multiaddr, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    if serializer.is_human_readable() {
+        serializer.collect_str(addr)
+    } else {
+        serializer.serialize_bytes(addr.as_ref())
+    }
+}
+
+/// Deserializes a [`Multiaddr`], see the [module documentation](self).
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Multiaddr, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_str(Visitor)
+    } else {
+        deserializer.deserialize_bytes(Visitor)
+    }
+}
+
+struct Visitor;
+
+impl<'de> de::Visitor<'de> for Visitor {
+    type Value = Multiaddr;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a multiaddress")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Multiaddr::try_from(v.to_vec()).map_err(E::custom)
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Multiaddr::try_from(v).map_err(E::custom)
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        self.visit_byte_buf(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Config {
+        #[serde(with = "super")]
+        addr: Multiaddr,
+    }
+
+    fn config() -> Config {
+        Config { addr: "/ip4/1.2.3.4/tcp/4001".parse().unwrap() }
+    }
+
+    #[test]
+    fn json_is_readable() {
+        let json = serde_json::to_string(&config()).unwrap();
+        assert_eq!(json, r#"{"addr":"/ip4/1.2.3.4/tcp/4001"}"#);
+        assert_eq!(serde_json::from_str::<Config>(&json).unwrap(), config());
+    }
+
+    #[test]
+    fn cbor_is_compact() {
+        let cbor = serde_cbor::to_vec(&config()).unwrap();
+        let addr_bytes = config().addr.to_vec();
+        assert!(cbor.windows(addr_bytes.len()).any(|w| w == addr_bytes.as_slice()));
+        assert!(cbor.len() < "/ip4/1.2.3.4/tcp/4001".len());
+        assert_eq!(serde_cbor::from_slice::<Config>(&cbor).unwrap(), config());
+    }
+}
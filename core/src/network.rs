@@ -209,6 +209,15 @@ where
         &self.local_peer_id
     }
 
+    /// Spawns a background task that is unrelated to any connection.
+    ///
+    /// The task is run on the configured executor, like the connection
+    /// tasks, or otherwise polled on the current thread whenever the
+    /// `Network` is polled.
+    pub fn spawn(&mut self, task: future::BoxFuture<'static, ()>) {
+        self.pool.spawn(task)
+    }
+
     /// Dials a [`Multiaddr`] that may or may not encapsulate a
     /// specific expected remote peer ID.
     ///
@@ -594,6 +603,7 @@ where
         match endpoint {
             ConnectedPoint::Dialer { address } =>
                 (None, NetworkEvent::UnknownPeerDialError {
+                    id,
                     multiaddr: address,
                     error,
                 }),
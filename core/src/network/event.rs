@@ -151,6 +151,9 @@ where
 
     /// Failed to reach a peer that we were trying to dial.
     UnknownPeerDialError {
+        /// The ID of the failed connection attempt.
+        id: ConnectionId,
+
         /// The multiaddr we failed to reach.
         multiaddr: Multiaddr,
 
@@ -247,8 +250,9 @@ where
                     .field("error", error)
                     .finish()
             }
-            NetworkEvent::UnknownPeerDialError { multiaddr, error, .. } => {
+            NetworkEvent::UnknownPeerDialError { id, multiaddr, error } => {
                 f.debug_struct("UnknownPeerDialError")
+                    .field("id", id)
                     .field("multiaddr", multiaddr)
                     .field("error", error)
                     .finish()
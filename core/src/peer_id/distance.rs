@@ -0,0 +1,304 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! The XOR distance between peer IDs in the Kademlia keyspace.
+
+use super::PeerId;
+use sha2::{Digest, Sha256};
+use std::ops::{Add, Shl, Shr};
+
+/// The XOR distance between two peers, as a 256-bit unsigned integer.
+///
+/// Like in Kademlia, the distance is computed between the SHA-256 hashes
+/// of the byte representations of the peer IDs, such that it is uniformly
+/// distributed regardless of the kind of peer ID.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Default, PartialOrd, Ord, Debug)]
+pub struct Distance([u8; 32]);
+
+impl Distance {
+    /// Returns the big-endian byte representation of the distance.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Returns the integer part of the base 2 logarithm of the distance.
+    ///
+    /// Returns `None` if the distance is zero.
+    pub fn ilog2(&self) -> Option<u32> {
+        (256 - self.leading_zeros()).checked_sub(1)
+    }
+
+    /// Adds two distances, returning `None` on overflow.
+    pub fn checked_add(self, other: Distance) -> Option<Distance> {
+        let mut sum = [0; 32];
+        let mut carry = 0;
+        for i in (0 .. 32).rev() {
+            let s = u16::from(self.0[i]) + u16::from(other.0[i]) + carry;
+            sum[i] = s as u8;
+            carry = s >> 8;
+        }
+        if carry == 0 { Some(Distance(sum)) } else { None }
+    }
+
+    fn leading_zeros(&self) -> u32 {
+        let mut zeros = 0;
+        for byte in self.0.iter() {
+            zeros += byte.leading_zeros();
+            if *byte != 0 {
+                break
+            }
+        }
+        zeros
+    }
+}
+
+impl From<[u8; 32]> for Distance {
+    fn from(bytes: [u8; 32]) -> Distance {
+        Distance(bytes)
+    }
+}
+
+impl Add for Distance {
+    type Output = Distance;
+
+    /// # Panics
+    ///
+    /// Panics if the sum does not fit into 256 bits.
+    fn add(self, other: Distance) -> Distance {
+        self.checked_add(other).expect("Distance addition overflowed")
+    }
+}
+
+impl Shl<u32> for Distance {
+    type Output = Distance;
+
+    fn shl(self, shift: u32) -> Distance {
+        let mut shifted = [0; 32];
+        let (bytes, bits) = ((shift / 8) as usize, shift % 8);
+        for (i, byte) in shifted.iter_mut().enumerate().take(32usize.saturating_sub(bytes)) {
+            let hi = self.0[i + bytes].checked_shl(bits).unwrap_or(0);
+            let lo = self.0.get(i + bytes + 1)
+                .and_then(|b| b.checked_shr(8 - bits))
+                .unwrap_or(0);
+            *byte = hi | lo;
+        }
+        Distance(shifted)
+    }
+}
+
+impl Shr<u32> for Distance {
+    type Output = Distance;
+
+    fn shr(self, shift: u32) -> Distance {
+        let mut shifted = [0; 32];
+        let (bytes, bits) = ((shift / 8) as usize, shift % 8);
+        for (i, byte) in shifted.iter_mut().enumerate().skip(bytes) {
+            let lo = self.0[i - bytes].checked_shr(bits).unwrap_or(0);
+            let hi = (i - bytes).checked_sub(1)
+                .and_then(|j| self.0[j].checked_shl(8 - bits))
+                .unwrap_or(0);
+            *byte = hi | lo;
+        }
+        Distance(shifted)
+    }
+}
+
+impl PeerId {
+    /// Returns the XOR distance between this peer and the given peer.
+    pub fn xor_distance(&self, other: &PeerId) -> Distance {
+        distance(&self.key(), &other.key())
+    }
+
+    /// Returns up to `n` of the given peers that are closest to this peer,
+    /// ordered by increasing XOR distance.
+    pub fn closest_n(&self, peers: &[PeerId], n: usize) -> Vec<PeerId> {
+        let local = self.key();
+        let mut by_distance = peers.iter()
+            .map(|peer| (distance(&local, &peer.key()), *peer))
+            .collect::<Vec<_>>();
+        by_distance.sort_unstable();
+        by_distance.into_iter().take(n).map(|(_, peer)| peer).collect()
+    }
+
+    /// Returns the index of the Kademlia k-bucket of this peer into which
+    /// the given peer falls, i.e. the integer part of the base 2 logarithm
+    /// of their XOR distance.
+    ///
+    /// Returns `None` if both peers are the same.
+    pub fn bucket_index(&self, other: &PeerId) -> Option<u8> {
+        self.xor_distance(other).ilog2().map(|i| i as u8)
+    }
+
+    /// Generates the peer ID of a freshly generated Ed25519 public key
+    /// whose position in the Kademlia keyspace, i.e. the SHA-256 hash of its
+    /// byte representation, starts with the first `num_bits` bits of
+    /// `prefix_bits`.
+    ///
+    /// Keys are generated until one matches, i.e. `2^num_bits` times on
+    /// average, which makes this only suitable for tests, e.g. to place a
+    /// peer into a specific k-bucket.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `prefix_bits` has fewer than `num_bits` bits.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn random_with_prefix(prefix_bits: &[u8], num_bits: usize) -> PeerId {
+        assert!(num_bits <= prefix_bits.len() * 8, "The prefix has fewer than `num_bits` bits.");
+        let bit = |bytes: &[u8], i: usize| bytes[i / 8] & (0x80 >> (i % 8)) != 0;
+        loop {
+            let peer_id = crate::identity::Keypair::generate_ed25519().public().into_peer_id();
+            let key = peer_id.key();
+            if (0 .. num_bits).all(|i| bit(&key, i) == bit(prefix_bits, i)) {
+                return peer_id
+            }
+        }
+    }
+
+    /// Returns the position of this peer in the Kademlia keyspace.
+    fn key(&self) -> [u8; 32] {
+        Sha256::digest(&self.to_bytes()).into()
+    }
+}
+
+fn distance(a: &[u8; 32], b: &[u8; 32]) -> Distance {
+    let mut d = [0; 32];
+    for (d, (a, b)) in d.iter_mut().zip(a.iter().zip(b.iter())) {
+        *d = a ^ b;
+    }
+    Distance(d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck::*;
+
+    #[derive(Clone, Debug)]
+    struct ArbDistance(Distance);
+
+    impl Arbitrary for ArbDistance {
+        fn arbitrary<G: Gen>(g: &mut G) -> ArbDistance {
+            let mut bytes = [0; 32];
+            g.fill_bytes(&mut bytes);
+            ArbDistance(Distance(bytes))
+        }
+    }
+
+    fn to_u128(d: Distance) -> Option<u128> {
+        if d.0[.. 16].iter().any(|b| *b != 0) {
+            return None
+        }
+        let mut bytes = [0; 16];
+        bytes.copy_from_slice(&d.0[16 ..]);
+        Some(u128::from_be_bytes(bytes))
+    }
+
+    fn from_u128(n: u128) -> Distance {
+        let mut bytes = [0; 32];
+        bytes[16 ..].copy_from_slice(&n.to_be_bytes());
+        Distance(bytes)
+    }
+
+    #[test]
+    fn identity_and_symmetry() {
+        let a = PeerId::random();
+        let b = PeerId::random();
+        assert_eq!(a.xor_distance(&a), Distance::default());
+        assert_eq!(a.xor_distance(&b), b.xor_distance(&a));
+        assert_eq!(a.bucket_index(&a), None);
+        assert_eq!(a.bucket_index(&b), b.bucket_index(&a));
+    }
+
+    #[test]
+    fn closest_n_orders_by_distance() {
+        let local = PeerId::random();
+        let peers = (0 .. 20).map(|_| PeerId::random()).collect::<Vec<_>>();
+        let closest = local.closest_n(&peers, 5);
+        assert_eq!(closest.len(), 5);
+        assert!(closest.windows(2).all(|w| local.xor_distance(&w[0]) <= local.xor_distance(&w[1])));
+        let fifth = local.xor_distance(&closest[4]);
+        assert!(peers.iter()
+            .filter(|p| !closest.contains(p))
+            .all(|p| local.xor_distance(p) >= fifth));
+        assert_eq!(local.closest_n(&peers, 100).len(), peers.len());
+    }
+
+    #[test]
+    fn random_with_prefix() {
+        let reference = PeerId::random();
+        // Flipping the 4th bit of the reference's key places all peers
+        // into the same bucket of the reference.
+        let prefix = [reference.key()[0] ^ 0x10];
+        for _ in 0 .. 10 {
+            let peer = PeerId::random_with_prefix(&prefix, 4);
+            assert_eq!(peer.key()[0] >> 4, prefix[0] >> 4);
+            assert_eq!(reference.bucket_index(&peer), Some(252));
+        }
+    }
+
+    #[test]
+    fn add_matches_u128() {
+        fn prop(a: u64, b: u64) -> bool {
+            let (a, b) = (u128::from(a) << 32, u128::from(b) << 32);
+            to_u128(from_u128(a) + from_u128(b)) == Some(a + b)
+        }
+        quickcheck(prop as fn(_, _) -> _)
+    }
+
+    #[test]
+    fn add_overflow() {
+        let max = Distance([0xff; 32]);
+        assert_eq!(max.checked_add(Distance::default()), Some(max));
+        assert_eq!(max.checked_add(from_u128(1)), None);
+    }
+
+    #[test]
+    fn shift_roundtrip() {
+        fn prop(d: ArbDistance, shift: u8) -> bool {
+            let shift = u32::from(shift);
+            let d = d.0;
+            // Shifting left and right again clears the high bits only.
+            let cleared = (d << shift) >> shift;
+            cleared.leading_zeros() >= shift.min(256)
+                && (cleared << shift) == (d << shift)
+        }
+        quickcheck(prop as fn(_, _) -> _)
+    }
+
+    #[test]
+    fn shift_matches_u128() {
+        fn prop(n: u64, shift: u8) -> bool {
+            let shift = u32::from(shift % 64);
+            let n = u128::from(n);
+            to_u128(from_u128(n) << shift) == Some(n << shift)
+                && to_u128(from_u128(n) >> shift) == Some(n >> shift)
+        }
+        quickcheck(prop as fn(_, _) -> _)
+    }
+
+    #[test]
+    fn ilog2() {
+        assert_eq!(Distance::default().ilog2(), None);
+        assert_eq!(from_u128(1).ilog2(), Some(0));
+        assert_eq!((from_u128(1) << 255).ilog2(), Some(255));
+        assert_eq!((from_u128(1) << 256).ilog2(), None);
+        assert_eq!(from_u128(0x80).ilog2(), Some(7));
+    }
+}
@@ -26,13 +26,16 @@
 //! modifying a transport through composition with other transports or protocol upgrades.
 
 use crate::ConnectedPoint;
+use crate::multiaddr::MultiaddrPattern;
 use futures::prelude::*;
 use multiaddr::Multiaddr;
 use std::{error::Error, fmt};
 
 pub mod and_then;
+pub mod and_then_map;
 pub mod choice;
 pub mod dummy;
+pub mod filter;
 pub mod map;
 pub mod map_err;
 pub mod memory;
@@ -163,6 +166,18 @@ pub trait Transport {
         map_err::MapErr::new(self, f)
     }
 
+    /// Restricts the transport to addresses matching the given pattern.
+    ///
+    /// `listen_on` and `dial` return [`TransportError::MultiaddrNotSupported`]
+    /// for addresses not matching the pattern, allowing e.g. another transport
+    /// combined via [`Transport::or_transport`] to handle them.
+    fn filter(self, pattern: MultiaddrPattern) -> filter::Filter<Self>
+    where
+        Self: Sized,
+    {
+        filter::Filter::new(self, pattern)
+    }
+
     /// Adds a fallback transport that is used when encountering errors
     /// while establishing inbound or outbound connections.
     ///
@@ -194,6 +209,24 @@ pub trait Transport {
         and_then::AndThen::new(self, f)
     }
 
+    /// Applies a function producing an asynchronous result to every connection
+    /// created by this transport, like [`Transport::and_then`], except that
+    /// the errors of the transport are converted into the error type of the
+    /// function rather than combined in an [`EitherError`](crate::either::EitherError).
+    ///
+    /// This is the transport analogue of [`TryFutureExt::and_then`], e.g. for
+    /// performing a handshake on every raw connection that reports failures
+    /// of the handshake and the transport alike.
+    fn and_then_map<C, F, O, E>(self, f: C) -> and_then_map::AndThenMap<Self, C>
+    where
+        Self: Sized,
+        C: FnOnce(Self::Output, ConnectedPoint) -> F + Clone,
+        F: TryFuture<Ok = O, Error = E>,
+        E: Error + From<Self::Error>
+    {
+        and_then_map::AndThenMap::new(self, f)
+    }
+
     /// Begins a series of protocol upgrades via an
     /// [`upgrade::Builder`](upgrade::Builder).
     fn upgrade(self, version: upgrade::Version) -> upgrade::Builder<Self>
@@ -0,0 +1,248 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use crate::{
+    ConnectedPoint,
+    either::EitherError,
+    transport::{Transport, TransportError, ListenerEvent},
+    transport::and_then::{AndThen, AndThenFuture, AndThenStream},
+};
+use futures::prelude::*;
+use multiaddr::Multiaddr;
+use std::{error, pin::Pin, task::Context, task::Poll};
+
+/// See the `Transport::and_then_map` method.
+#[derive(Debug, Clone)]
+pub struct AndThenMap<T, C> { inner: AndThen<T, C> }
+
+impl<T, C> AndThenMap<T, C> {
+    pub(crate) fn new(transport: T, fun: C) -> Self {
+        AndThenMap { inner: AndThen::new(transport, fun) }
+    }
+}
+
+impl<T, C, F, O, E> Transport for AndThenMap<T, C>
+where
+    T: Transport,
+    C: FnOnce(T::Output, ConnectedPoint) -> F + Clone,
+    F: TryFuture<Ok = O, Error = E>,
+    E: error::Error + From<T::Error>,
+{
+    type Output = O;
+    type Error = E;
+    type Listener = AndThenMapStream<AndThenStream<T::Listener, C>>;
+    type ListenerUpgrade = AndThenMapFuture<AndThenFuture<T::ListenerUpgrade, C, F>>;
+    type Dial = AndThenMapFuture<AndThenFuture<T::Dial, C, F>>;
+
+    fn listen_on(self, addr: Multiaddr) -> Result<Self::Listener, TransportError<Self::Error>> {
+        let stream = self.inner.listen_on(addr).map_err(|err| err.map(unify))?;
+        Ok(AndThenMapStream { stream })
+    }
+
+    fn dial(self, addr: Multiaddr) -> Result<Self::Dial, TransportError<Self::Error>> {
+        let future = self.inner.dial(addr).map_err(|err| err.map(unify))?;
+        Ok(AndThenMapFuture { future })
+    }
+
+    fn address_translation(&self, server: &Multiaddr, observed: &Multiaddr) -> Option<Multiaddr> {
+        self.inner.address_translation(server, observed)
+    }
+}
+
+/// Converts an error of the underlying transport into the error type of the
+/// applied function.
+fn unify<A, B: From<A>>(err: EitherError<A, B>) -> B {
+    match err {
+        EitherError::A(a) => B::from(a),
+        EitherError::B(b) => b,
+    }
+}
+
+/// Listening stream for `AndThenMap`.
+#[pin_project::pin_project]
+#[derive(Debug, Clone)]
+pub struct AndThenMapStream<TStream> {
+    #[pin]
+    stream: TStream,
+}
+
+impl<TStream, TUpgr, A, B> Stream for AndThenMapStream<TStream>
+where
+    TStream: TryStream<Ok = ListenerEvent<TUpgr, EitherError<A, B>>, Error = EitherError<A, B>>,
+    B: From<A>,
+{
+    type Item = Result<ListenerEvent<AndThenMapFuture<TUpgr>, B>, B>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        match TryStream::try_poll_next(this.stream, cx) {
+            Poll::Ready(Some(Ok(event))) => {
+                let event = event
+                    .map(|future| AndThenMapFuture { future })
+                    .map_err(unify);
+                Poll::Ready(Some(Ok(event)))
+            }
+            Poll::Ready(Some(Err(err))) => Poll::Ready(Some(Err(unify(err)))),
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// Connection upgrade future for `AndThenMap`.
+#[pin_project::pin_project]
+#[derive(Debug)]
+pub struct AndThenMapFuture<TFut> {
+    #[pin]
+    future: TFut,
+}
+
+impl<TFut, A, B> Future for AndThenMapFuture<TFut>
+where
+    TFut: TryFuture<Error = EitherError<A, B>>,
+    B: From<A>,
+{
+    type Output = Result<TFut::Ok, B>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        TryFuture::try_poll(this.future, cx).map_err(unify)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::memory::{Channel, MemoryTransport, MemoryTransportError};
+    use std::{fmt, io};
+
+    const HELLO: &[u8] = b"hello";
+
+    /// Error of the handshake, including errors of the underlying transport.
+    #[derive(Debug)]
+    enum HandshakeError {
+        Transport(MemoryTransportError),
+        Io(io::Error),
+        Mismatch,
+    }
+
+    impl fmt::Display for HandshakeError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{:?}", self)
+        }
+    }
+
+    impl error::Error for HandshakeError {}
+
+    impl From<MemoryTransportError> for HandshakeError {
+        fn from(err: MemoryTransportError) -> Self {
+            HandshakeError::Transport(err)
+        }
+    }
+
+    /// A connection on which the handshake has been performed.
+    struct Handshaken(Channel<Vec<u8>>);
+
+    /// Sends a greeting and expects the same greeting back.
+    async fn handshake(mut socket: Channel<Vec<u8>>, greeting: &'static [u8])
+        -> Result<Handshaken, HandshakeError>
+    {
+        socket.write_all(greeting).await.map_err(HandshakeError::Io)?;
+        let mut buf = vec![0; HELLO.len()];
+        socket.read_exact(&mut buf).await.map_err(HandshakeError::Io)?;
+        if buf != HELLO {
+            return Err(HandshakeError::Mismatch)
+        }
+        Ok(Handshaken(socket))
+    }
+
+    fn transport(greeting: &'static [u8]) -> impl Transport<Output = Handshaken, Error = HandshakeError> + Clone {
+        MemoryTransport::default().and_then_map(move |socket, _| handshake(socket, greeting))
+    }
+
+    #[test]
+    fn performs_handshake() {
+        let addr: Multiaddr = format!("/memory/{}", rand::random::<u64>().saturating_add(1)).parse().unwrap();
+
+        let listener = Box::pin(transport(HELLO).listen_on(addr.clone()).unwrap());
+        let listener = async move {
+            let (upgrade, _) = listener.try_filter_map(|ev| future::ok(ev.into_upgrade()))
+                .next().await.unwrap().unwrap();
+            let Handshaken(mut socket) = upgrade.await.unwrap();
+            socket.write_all(&[42]).await.unwrap();
+        };
+
+        let dialer = async move {
+            let Handshaken(mut socket) = transport(HELLO).dial(addr).unwrap().await.unwrap();
+            let mut buf = [0];
+            socket.read_exact(&mut buf).await.unwrap();
+            assert_eq!(buf, [42]);
+        };
+
+        futures::executor::block_on(future::join(listener, dialer));
+    }
+
+    #[test]
+    fn reports_handshake_and_transport_errors() {
+        let addr: Multiaddr = format!("/memory/{}", rand::random::<u64>().saturating_add(1)).parse().unwrap();
+
+        // Nobody is listening on the address.
+        match transport(HELLO).dial(addr.clone()) {
+            Err(TransportError::Other(HandshakeError::Transport(MemoryTransportError::Unreachable))) => {}
+            _ => panic!("expected dialing to fail"),
+        }
+
+        let listener = Box::pin(transport(b"howdy").listen_on(addr.clone()).unwrap());
+        let listener = async move {
+            let (upgrade, _) = listener.try_filter_map(|ev| future::ok(ev.into_upgrade()))
+                .next().await.unwrap().unwrap();
+            let _ = upgrade.await;
+        };
+
+        let dialer = async move {
+            match transport(HELLO).dial(addr).unwrap().await {
+                Err(HandshakeError::Mismatch) => {}
+                _ => panic!("expected the handshake to fail"),
+            }
+        };
+
+        futures::executor::block_on(future::join(listener, dialer));
+
+        // The remote closes the connection after reading the greeting.
+        let addr: Multiaddr = format!("/memory/{}", rand::random::<u64>().saturating_add(1)).parse().unwrap();
+        let listener = Box::pin(MemoryTransport::default().listen_on(addr.clone()).unwrap());
+        let listener = async move {
+            let (upgrade, _) = listener.try_filter_map(|ev| future::ok(ev.into_upgrade()))
+                .next().await.unwrap().unwrap();
+            let mut socket = upgrade.await.unwrap();
+            let mut buf = vec![0; HELLO.len()];
+            socket.read_exact(&mut buf).await.unwrap();
+        };
+
+        let dialer = async move {
+            match transport(HELLO).dial(addr).unwrap().await {
+                Err(HandshakeError::Io(err)) => assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof),
+                _ => panic!("expected the handshake to fail"),
+            }
+        };
+
+        futures::executor::block_on(future::join(listener, dialer));
+    }
+}
@@ -0,0 +1,86 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use crate::multiaddr::{Multiaddr, MultiaddrPattern};
+use crate::transport::{Transport, TransportError};
+
+/// See `Transport::filter`.
+#[derive(Debug, Clone)]
+pub struct Filter<T> {
+    transport: T,
+    pattern: MultiaddrPattern,
+}
+
+impl<T> Filter<T> {
+    /// Internal function that builds a `Filter`.
+    pub(crate) fn new(transport: T, pattern: MultiaddrPattern) -> Filter<T> {
+        Filter { transport, pattern }
+    }
+}
+
+impl<T> Transport for Filter<T>
+where
+    T: Transport,
+{
+    type Output = T::Output;
+    type Error = T::Error;
+    type Listener = T::Listener;
+    type ListenerUpgrade = T::ListenerUpgrade;
+    type Dial = T::Dial;
+
+    fn listen_on(self, addr: Multiaddr) -> Result<Self::Listener, TransportError<Self::Error>> {
+        if !self.pattern.matches(&addr) {
+            return Err(TransportError::MultiaddrNotSupported(addr));
+        }
+        self.transport.listen_on(addr)
+    }
+
+    fn dial(self, addr: Multiaddr) -> Result<Self::Dial, TransportError<Self::Error>> {
+        if !self.pattern.matches(&addr) {
+            return Err(TransportError::MultiaddrNotSupported(addr));
+        }
+        self.transport.dial(addr)
+    }
+
+    fn address_translation(&self, server: &Multiaddr, observed: &Multiaddr) -> Option<Multiaddr> {
+        self.transport.address_translation(server, observed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::MemoryTransport;
+
+    #[test]
+    fn rejects_non_matching_addresses() {
+        let pattern = "/memory/*".parse().unwrap();
+        let transport = MemoryTransport::default().filter(pattern);
+
+        let addr: Multiaddr = "/ip4/127.0.0.1/tcp/1234".parse().unwrap();
+        match transport.clone().dial(addr.clone()) {
+            Err(TransportError::MultiaddrNotSupported(a)) => assert_eq!(a, addr),
+            _ => panic!("expected address to be rejected"),
+        }
+
+        let addr: Multiaddr = format!("/memory/{}", rand::random::<u64>()).parse().unwrap();
+        assert!(transport.listen_on(addr).is_ok());
+    }
+}
@@ -162,3 +162,70 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt;
+
+    #[derive(Debug, PartialEq)]
+    struct CustomError(u8);
+
+    impl fmt::Display for CustomError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "custom error {}", self.0)
+        }
+    }
+
+    impl error::Error for CustomError {}
+
+    #[derive(Debug, PartialEq)]
+    struct WrappedError(CustomError);
+
+    impl fmt::Display for WrappedError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "wrapped {}", self.0)
+        }
+    }
+
+    impl error::Error for WrappedError {}
+
+    /// A transport that fails to listen and whose dials fail.
+    #[derive(Debug, Clone)]
+    struct FailingTransport;
+
+    impl Transport for FailingTransport {
+        type Output = ();
+        type Error = CustomError;
+        type Listener = stream::Pending<Result<ListenerEvent<Self::ListenerUpgrade, Self::Error>, Self::Error>>;
+        type ListenerUpgrade = future::Ready<Result<(), CustomError>>;
+        type Dial = future::Ready<Result<(), CustomError>>;
+
+        fn listen_on(self, _: Multiaddr) -> Result<Self::Listener, TransportError<Self::Error>> {
+            Err(TransportError::Other(CustomError(1)))
+        }
+
+        fn dial(self, _: Multiaddr) -> Result<Self::Dial, TransportError<Self::Error>> {
+            Ok(future::err(CustomError(2)))
+        }
+
+        fn address_translation(&self, _: &Multiaddr, _: &Multiaddr) -> Option<Multiaddr> {
+            None
+        }
+    }
+
+    #[test]
+    fn maps_errors() {
+        let transport = FailingTransport.map_err(WrappedError);
+        let addr: Multiaddr = "/memory/1234".parse().unwrap();
+
+        match transport.clone().listen_on(addr.clone()) {
+            Err(TransportError::Other(e)) => assert_eq!(e, WrappedError(CustomError(1))),
+            _ => panic!("expected listening to fail"),
+        }
+
+        let dial = transport.dial(addr).expect("dial to start");
+        let err = futures::executor::block_on(dial).expect_err("dial to fail");
+        assert_eq!(err, WrappedError(CustomError(2)));
+    }
+}
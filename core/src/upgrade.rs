@@ -64,7 +64,10 @@ mod error;
 mod from_fn;
 mod map;
 mod optional;
+mod priority;
 mod select;
+mod stream_protocol;
+mod timeout;
 mod transfer;
 
 use futures::future::Future;
@@ -72,14 +75,20 @@ use futures::future::Future;
 pub use crate::Negotiated;
 pub use multistream_select::{Version, NegotiatedComplete, NegotiationError, ProtocolError};
 pub use self::{
-    apply::{apply, apply_inbound, apply_outbound, InboundUpgradeApply, OutboundUpgradeApply},
+    apply::{
+        apply, apply_inbound, apply_outbound, apply_inbound_with_protocol, apply_outbound_with_protocol,
+        InboundUpgradeApply, OutboundUpgradeApply, InboundUpgradeApplyWithProtocol, OutboundUpgradeApplyWithProtocol,
+    },
     denied::DeniedUpgrade,
     either::EitherUpgrade,
     error::UpgradeError,
     from_fn::{from_fn, FromFnUpgrade},
     map::{MapInboundUpgrade, MapOutboundUpgrade, MapInboundUpgradeErr, MapOutboundUpgradeErr},
     optional::OptionalUpgrade,
+    priority::PriorityUpgrade,
     select::SelectUpgrade,
+    stream_protocol::{InvalidProtocol, StreamProtocol},
+    timeout::{apply_inbound_with_timeout, apply_outbound_with_timeout, Timeout, TimedUpgrade, TimedUpgradeError},
     transfer::{write_length_prefixed, write_varint, read_length_prefixed, read_varint},
 };
 #[allow(deprecated)]
@@ -19,8 +19,8 @@
 // DEALINGS IN THE SOFTWARE.
 
 use crate::{ConnectedPoint, Negotiated};
-use crate::upgrade::{InboundUpgrade, OutboundUpgrade, UpgradeError, ProtocolName};
-use futures::{future::Either, prelude::*};
+use crate::upgrade::{InboundUpgrade, OutboundUpgrade, UpgradeError, UpgradeInfo, ProtocolName};
+use futures::{future::Either, prelude::*, ready};
 use log::debug;
 use multistream_select::{self, DialerSelectFuture, ListenerSelectFuture};
 use std::{iter, mem, pin::Pin, task::Context, task::Poll};
@@ -54,6 +54,16 @@ where
     }
 }
 
+/// Tries to perform an upgrade on an inbound connection or substream, yielding the
+/// negotiated protocol together with the output of the upgrade.
+pub fn apply_inbound_with_protocol<C, U>(conn: C, up: U) -> InboundUpgradeApplyWithProtocol<C, U>
+where
+    C: AsyncRead + AsyncWrite + Unpin,
+    U: InboundUpgrade<Negotiated<C>>,
+{
+    InboundUpgradeApplyWithProtocol { inner: apply_inbound(conn, WithProtocol(up)) }
+}
+
 /// Tries to perform an upgrade on an outbound connection or substream.
 pub fn apply_outbound<C, U>(conn: C, up: U, v: Version) -> OutboundUpgradeApply<C, U>
 where
@@ -67,6 +77,16 @@ where
     }
 }
 
+/// Tries to perform an upgrade on an outbound connection or substream, yielding the
+/// negotiated protocol together with the output of the upgrade.
+pub fn apply_outbound_with_protocol<C, U>(conn: C, up: U, v: Version) -> OutboundUpgradeApplyWithProtocol<C, U>
+where
+    C: AsyncRead + AsyncWrite + Unpin,
+    U: OutboundUpgrade<Negotiated<C>>
+{
+    OutboundUpgradeApplyWithProtocol { inner: apply_outbound(conn, WithProtocol(up), v) }
+}
+
 /// Future returned by `apply_inbound`. Drives the upgrade process.
 pub struct InboundUpgradeApply<C, U>
 where
@@ -219,6 +239,116 @@ where
     }
 }
 
+/// Future returned by `apply_inbound_with_protocol`. Drives the upgrade process.
+pub struct InboundUpgradeApplyWithProtocol<C, U>
+where
+    C: AsyncRead + AsyncWrite + Unpin,
+    U: InboundUpgrade<Negotiated<C>>
+{
+    inner: InboundUpgradeApply<C, WithProtocol<U>>
+}
+
+impl<C, U> Unpin for InboundUpgradeApplyWithProtocol<C, U>
+where
+    C: AsyncRead + AsyncWrite + Unpin,
+    U: InboundUpgrade<Negotiated<C>>,
+{
+}
+
+impl<C, U> Future for InboundUpgradeApplyWithProtocol<C, U>
+where
+    C: AsyncRead + AsyncWrite + Unpin,
+    U: InboundUpgrade<Negotiated<C>>,
+{
+    type Output = Result<(U::Info, U::Output), UpgradeError<U::Error>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.inner.poll_unpin(cx)
+    }
+}
+
+/// Future returned by `apply_outbound_with_protocol`. Drives the upgrade process.
+pub struct OutboundUpgradeApplyWithProtocol<C, U>
+where
+    C: AsyncRead + AsyncWrite + Unpin,
+    U: OutboundUpgrade<Negotiated<C>>
+{
+    inner: OutboundUpgradeApply<C, WithProtocol<U>>
+}
+
+impl<C, U> Unpin for OutboundUpgradeApplyWithProtocol<C, U>
+where
+    C: AsyncRead + AsyncWrite + Unpin,
+    U: OutboundUpgrade<Negotiated<C>>,
+{
+}
+
+impl<C, U> Future for OutboundUpgradeApplyWithProtocol<C, U>
+where
+    C: AsyncRead + AsyncWrite + Unpin,
+    U: OutboundUpgrade<Negotiated<C>>,
+{
+    type Output = Result<(U::Info, U::Output), UpgradeError<U::Error>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.inner.poll_unpin(cx)
+    }
+}
+
+/// Wraps an upgrade such that its output is paired with the negotiated protocol.
+struct WithProtocol<U>(U);
+
+impl<U: UpgradeInfo> UpgradeInfo for WithProtocol<U> {
+    type Info = U::Info;
+    type InfoIter = U::InfoIter;
+
+    fn protocol_info(&self) -> Self::InfoIter {
+        self.0.protocol_info()
+    }
+}
+
+impl<C, U: InboundUpgrade<C>> InboundUpgrade<C> for WithProtocol<U> {
+    type Output = (U::Info, U::Output);
+    type Error = U::Error;
+    type Future = WithProtocolFuture<U::Future, U::Info>;
+
+    fn upgrade_inbound(self, socket: C, info: Self::Info) -> Self::Future {
+        WithProtocolFuture { protocol: Some(info.clone()), future: self.0.upgrade_inbound(socket, info) }
+    }
+}
+
+impl<C, U: OutboundUpgrade<C>> OutboundUpgrade<C> for WithProtocol<U> {
+    type Output = (U::Info, U::Output);
+    type Error = U::Error;
+    type Future = WithProtocolFuture<U::Future, U::Info>;
+
+    fn upgrade_outbound(self, socket: C, info: Self::Info) -> Self::Future {
+        WithProtocolFuture { protocol: Some(info.clone()), future: self.0.upgrade_outbound(socket, info) }
+    }
+}
+
+/// Future of a [`WithProtocol`] upgrade.
+#[pin_project::pin_project]
+struct WithProtocolFuture<F, I> {
+    #[pin]
+    future: F,
+    protocol: Option<I>,
+}
+
+impl<F, I, O, E> Future for WithProtocolFuture<F, I>
+where
+    F: TryFuture<Ok = O, Error = E>,
+{
+    type Output = Result<(I, O), E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let output = ready!(this.future.try_poll(cx))?;
+        let protocol = this.protocol.take().expect("Future polled after completion.");
+        Poll::Ready(Ok((protocol, output)))
+    }
+}
+
 type NameWrapIter<I> = iter::Map<I, fn(<I as Iterator>::Item) -> NameWrap<<I as Iterator>::Item>>;
 
 /// Wrapper type to expose an `AsRef<[u8]>` impl for all types implementing `ProtocolName`.
@@ -231,3 +361,77 @@ impl<N: ProtocolName> AsRef<[u8]> for NameWrap<N> {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::{ListenerEvent, MemoryTransport, Transport};
+    use crate::upgrade::{StreamProtocol, UpgradeInfo};
+    use crate::Multiaddr;
+    use std::{convert::Infallible, vec};
+
+    /// An upgrade supporting the given versions of a protocol, in order of preference.
+    #[derive(Clone)]
+    struct Versioned(Vec<StreamProtocol>);
+
+    impl UpgradeInfo for Versioned {
+        type Info = StreamProtocol;
+        type InfoIter = vec::IntoIter<StreamProtocol>;
+
+        fn protocol_info(&self) -> Self::InfoIter {
+            self.0.clone().into_iter()
+        }
+    }
+
+    impl<C: Send + 'static> InboundUpgrade<C> for Versioned {
+        type Output = C;
+        type Error = Infallible;
+        type Future = future::Ready<Result<C, Infallible>>;
+
+        fn upgrade_inbound(self, socket: C, _: Self::Info) -> Self::Future {
+            future::ok(socket)
+        }
+    }
+
+    impl<C: Send + 'static> OutboundUpgrade<C> for Versioned {
+        type Output = C;
+        type Error = Infallible;
+        type Future = future::Ready<Result<C, Infallible>>;
+
+        fn upgrade_outbound(self, socket: C, _: Self::Info) -> Self::Future {
+            future::ok(socket)
+        }
+    }
+
+    #[test]
+    fn negotiated_protocol_is_returned() {
+        let v1 = StreamProtocol::new("/versioned/1.0.0");
+        let v2 = StreamProtocol::new("/versioned/2.0.0");
+
+        let addr: Multiaddr = format!("/memory/{}", rand::random::<u64>().saturating_add(1))
+            .parse().unwrap();
+        let listener_addr = addr.clone();
+
+        // The listener only supports the first version of the protocol.
+        let listener_upgrade = Versioned(vec![v1.clone()]);
+        let listener = async move {
+            let listener = MemoryTransport::default().listen_on(listener_addr).unwrap();
+            let upgrade = listener.filter_map(|ev| future::ready(
+                ListenerEvent::into_upgrade(ev.unwrap())
+            )).next().await.unwrap();
+            let socket = upgrade.0.await.unwrap();
+            apply_inbound_with_protocol(socket, listener_upgrade).await.unwrap()
+        };
+
+        // The dialer prefers the second version of the protocol.
+        let dialer_upgrade = Versioned(vec![v2, v1.clone()]);
+        let dialer = async move {
+            let socket = MemoryTransport::default().dial(addr).unwrap().await.unwrap();
+            apply_outbound_with_protocol(socket, dialer_upgrade, Version::V1).await.unwrap()
+        };
+
+        let ((listener_protocol, _), (dialer_protocol, _)) =
+            futures::executor::block_on(future::join(listener, dialer));
+        assert_eq!(listener_protocol, v1);
+        assert_eq!(dialer_protocol, v1);
+    }
+}
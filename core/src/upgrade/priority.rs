@@ -0,0 +1,208 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use crate::upgrade::{InboundUpgrade, OutboundUpgrade, StreamProtocol, UpgradeInfo};
+use std::vec;
+
+/// Upgrade that supports a list of protocols in order of preference, each
+/// with its own upgrade.
+///
+/// When dialing, the protocols are proposed in order, so the most preferred
+/// protocol supported by the remote is selected. When listening, any of the
+/// protocols is accepted. This permits preferring newer versions of a
+/// protocol while remaining compatible with peers only supporting older ones.
+#[derive(Debug, Clone)]
+pub struct PriorityUpgrade<U> {
+    upgrades: Vec<(StreamProtocol, U)>,
+    fallback: Option<U>,
+}
+
+impl<U> PriorityUpgrade<U> {
+    /// Creates a `PriorityUpgrade` from the given protocols and their
+    /// upgrades, in decreasing order of preference.
+    ///
+    /// The [`UpgradeInfo::protocol_info`] of these upgrades is not consulted.
+    /// Each upgrade is only negotiated under the protocol it is paired with,
+    /// which lets the same upgrade type serve several protocol versions, e.g.
+    /// one upgrade per version of a protocol that is configured at runtime.
+    /// The upgrade is applied with the paired protocol as its `info`.
+    pub fn new(upgrades: Vec<(StreamProtocol, U)>) -> Self {
+        PriorityUpgrade { upgrades, fallback: None }
+    }
+
+    /// Adds an upgrade supporting the protocols of its own
+    /// [`UpgradeInfo::protocol_info`] with the lowest preference, i.e.
+    /// after all other protocols.
+    pub fn with_fallback(mut self, upgrade: U) -> Self {
+        self.fallback = Some(upgrade);
+        self
+    }
+
+    /// Returns the upgrade for the given negotiated protocol.
+    fn into_upgrade(self, info: &StreamProtocol) -> U {
+        self.upgrades.into_iter()
+            .find(|(p, _)| p == info)
+            .map(|(_, u)| u)
+            .or(self.fallback)
+            .expect("The negotiated protocol is one of `protocol_info`; qed")
+    }
+}
+
+impl<U> UpgradeInfo for PriorityUpgrade<U>
+where
+    U: UpgradeInfo<Info = StreamProtocol>,
+{
+    type Info = StreamProtocol;
+    type InfoIter = vec::IntoIter<StreamProtocol>;
+
+    fn protocol_info(&self) -> Self::InfoIter {
+        let mut protocols = self.upgrades.iter()
+            .map(|(p, _)| p.clone())
+            .collect::<Vec<_>>();
+        if let Some(fallback) = &self.fallback {
+            protocols.extend(fallback.protocol_info());
+        }
+        protocols.into_iter()
+    }
+}
+
+impl<C, U> InboundUpgrade<C> for PriorityUpgrade<U>
+where
+    U: InboundUpgrade<C, Info = StreamProtocol>,
+{
+    type Output = U::Output;
+    type Error = U::Error;
+    type Future = U::Future;
+
+    fn upgrade_inbound(self, sock: C, info: Self::Info) -> Self::Future {
+        self.into_upgrade(&info).upgrade_inbound(sock, info)
+    }
+}
+
+impl<C, U> OutboundUpgrade<C> for PriorityUpgrade<U>
+where
+    U: OutboundUpgrade<C, Info = StreamProtocol>,
+{
+    type Output = U::Output;
+    type Error = U::Error;
+    type Future = U::Future;
+
+    fn upgrade_outbound(self, sock: C, info: Self::Info) -> Self::Future {
+        self.into_upgrade(&info).upgrade_outbound(sock, info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::{ListenerEvent, MemoryTransport, Transport};
+    use crate::upgrade::{apply_inbound, apply_outbound, Version};
+    use crate::Multiaddr;
+    use futures::prelude::*;
+    use std::{convert::Infallible, iter};
+
+    /// An upgrade for a single version of a protocol, yielding that version.
+    #[derive(Debug, Clone)]
+    struct Versioned(u8);
+
+    impl UpgradeInfo for Versioned {
+        type Info = StreamProtocol;
+        type InfoIter = iter::Once<StreamProtocol>;
+
+        fn protocol_info(&self) -> Self::InfoIter {
+            iter::once(protocol(self.0))
+        }
+    }
+
+    impl<C> InboundUpgrade<C> for Versioned {
+        type Output = u8;
+        type Error = Infallible;
+        type Future = future::Ready<Result<u8, Infallible>>;
+
+        fn upgrade_inbound(self, _: C, _: Self::Info) -> Self::Future {
+            future::ok(self.0)
+        }
+    }
+
+    impl<C> OutboundUpgrade<C> for Versioned {
+        type Output = u8;
+        type Error = Infallible;
+        type Future = future::Ready<Result<u8, Infallible>>;
+
+        fn upgrade_outbound(self, _: C, _: Self::Info) -> Self::Future {
+            future::ok(self.0)
+        }
+    }
+
+    fn protocol(version: u8) -> StreamProtocol {
+        StreamProtocol::try_from_owned(format!("/versioned/{}.0.0", version)).unwrap()
+    }
+
+    fn upgrade(versions: &[u8]) -> PriorityUpgrade<Versioned> {
+        PriorityUpgrade::new(versions.iter().map(|v| (protocol(*v), Versioned(*v))).collect())
+    }
+
+    /// Negotiates between the given upgrades, returning the outputs of
+    /// the listener and the dialer, respectively.
+    fn negotiate(
+        listener_upgrade: PriorityUpgrade<Versioned>,
+        dialer_upgrade: PriorityUpgrade<Versioned>
+    ) -> (u8, u8) {
+        let addr: Multiaddr = format!("/memory/{}", rand::random::<u64>().saturating_add(1))
+            .parse().unwrap();
+        let listener_addr = addr.clone();
+
+        let listener = async move {
+            let listener = MemoryTransport.listen_on(listener_addr).unwrap();
+            let upgrade = listener.filter_map(|ev| future::ready(
+                ListenerEvent::into_upgrade(ev.unwrap())
+            )).next().await.unwrap();
+            let socket = upgrade.0.await.unwrap();
+            apply_inbound(socket, listener_upgrade).await.unwrap()
+        };
+
+        let dialer = async move {
+            let socket = MemoryTransport.dial(addr).unwrap().await.unwrap();
+            apply_outbound(socket, dialer_upgrade, Version::V1).await.unwrap()
+        };
+
+        futures::executor::block_on(future::join(listener, dialer))
+    }
+
+    #[test]
+    fn highest_mutual_version_is_selected() {
+        // Both peers support both versions, the dialer's preference wins.
+        assert_eq!(negotiate(upgrade(&[1, 2]), upgrade(&[2, 1])), (2, 2));
+        // The listener only supports the older version.
+        assert_eq!(negotiate(upgrade(&[1]), upgrade(&[2, 1])), (1, 1));
+        // The dialer only supports the older version.
+        assert_eq!(negotiate(upgrade(&[2, 1]), upgrade(&[1])), (1, 1));
+    }
+
+    #[test]
+    fn fallback_is_selected_last() {
+        let dialer_upgrade = upgrade(&[3, 2]).with_fallback(Versioned(1));
+        assert_eq!(
+            dialer_upgrade.protocol_info().collect::<Vec<_>>(),
+            vec![protocol(3), protocol(2), protocol(1)]
+        );
+        assert_eq!(negotiate(upgrade(&[1]), dialer_upgrade), (1, 1));
+    }
+}
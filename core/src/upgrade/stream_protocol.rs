@@ -0,0 +1,275 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    convert::TryFrom,
+    error,
+    fmt,
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+
+/// The maximum length of a protocol name in bytes.
+const MAX_LEN: usize = 255;
+
+/// Identifies a protocol for a stream, e.g. `/ipfs/ping/1.0.0`.
+///
+/// A valid protocol name is non-empty, starts with a `/`, contains no
+/// whitespace and does not exceed 255 bytes.
+///
+/// Implements [`ProtocolName`](super::ProtocolName) and can thus serve as
+/// [`UpgradeInfo::Info`](super::UpgradeInfo::Info).
+#[derive(Debug, Clone)]
+pub struct StreamProtocol {
+    inner: Inner,
+}
+
+#[derive(Debug, Clone)]
+enum Inner {
+    Static(&'static str),
+    Owned(Arc<str>),
+}
+
+impl StreamProtocol {
+    /// Creates a `StreamProtocol` from a static string.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the protocol name is invalid, see [`StreamProtocol::is_valid_str`].
+    pub fn new(s: &'static str) -> Self {
+        if let Err(e) = validate(s) {
+            panic!("Invalid protocol name {:?}: {}", s, e);
+        }
+        StreamProtocol { inner: Inner::Static(s) }
+    }
+
+    /// Attempts to create a `StreamProtocol` from an owned string.
+    pub fn try_from_owned(s: String) -> Result<Self, InvalidProtocol> {
+        validate(&s)?;
+        Ok(StreamProtocol { inner: Inner::Owned(Arc::from(s)) })
+    }
+
+    /// Checks whether the given string is a valid protocol name.
+    pub fn is_valid_str(s: &str) -> bool {
+        validate(s).is_ok()
+    }
+
+    /// Returns the protocol name.
+    pub fn as_str(&self) -> &str {
+        match &self.inner {
+            Inner::Static(s) => s,
+            Inner::Owned(s) => s,
+        }
+    }
+}
+
+fn validate(s: &str) -> Result<(), InvalidProtocol> {
+    if s.is_empty() {
+        return Err(InvalidProtocol::Empty);
+    }
+    if !s.starts_with('/') {
+        return Err(InvalidProtocol::MissingLeadingSlash);
+    }
+    if s.len() > MAX_LEN {
+        return Err(InvalidProtocol::TooLong(s.len()));
+    }
+    if s.chars().any(char::is_whitespace) {
+        return Err(InvalidProtocol::ContainsWhitespace);
+    }
+    Ok(())
+}
+
+impl AsRef<str> for StreamProtocol {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<[u8]> for StreamProtocol {
+    fn as_ref(&self) -> &[u8] {
+        self.as_str().as_bytes()
+    }
+}
+
+impl Borrow<str> for StreamProtocol {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Display for StreamProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl PartialEq for StreamProtocol {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for StreamProtocol {}
+
+impl Hash for StreamProtocol {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl PartialOrd for StreamProtocol {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for StreamProtocol {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl PartialEq<&str> for StreamProtocol {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl TryFrom<String> for StreamProtocol {
+    type Error = InvalidProtocol;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        StreamProtocol::try_from_owned(s)
+    }
+}
+
+impl TryFrom<&str> for StreamProtocol {
+    type Error = InvalidProtocol;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        validate(s)?;
+        Ok(StreamProtocol { inner: Inner::Owned(Arc::from(s)) })
+    }
+}
+
+/// Error when creating a [`StreamProtocol`] from an invalid protocol name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvalidProtocol {
+    /// The protocol name is empty.
+    Empty,
+    /// The protocol name does not start with a `/`.
+    MissingLeadingSlash,
+    /// The protocol name contains whitespace.
+    ContainsWhitespace,
+    /// The protocol name exceeds 255 bytes. Contains the actual length.
+    TooLong(usize),
+}
+
+impl fmt::Display for InvalidProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidProtocol::Empty =>
+                write!(f, "Protocol name is empty"),
+            InvalidProtocol::MissingLeadingSlash =>
+                write!(f, "Protocol name does not start with '/'"),
+            InvalidProtocol::ContainsWhitespace =>
+                write!(f, "Protocol name contains whitespace"),
+            InvalidProtocol::TooLong(len) =>
+                write!(f, "Protocol name exceeds {} bytes: {}", MAX_LEN, len),
+        }
+    }
+}
+
+impl error::Error for InvalidProtocol {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck::*;
+
+    #[test]
+    fn valid_protocols() {
+        for s in &["/", "/ipfs/ping/1.0.0", "/meshsub/1.1.0", "/ü/✓"] {
+            assert!(StreamProtocol::is_valid_str(s));
+            assert_eq!(StreamProtocol::try_from(*s).unwrap(), *s);
+            assert_eq!(StreamProtocol::try_from(s.to_string()).unwrap().as_str(), *s);
+        }
+        let max = format!("/{}", "a".repeat(MAX_LEN - 1));
+        assert!(StreamProtocol::is_valid_str(&max));
+        assert_eq!(StreamProtocol::new("/ipfs/id/1.0.0"), StreamProtocol::try_from("/ipfs/id/1.0.0").unwrap());
+    }
+
+    #[test]
+    fn invalid_protocols() {
+        let too_long = format!("/{}", "a".repeat(MAX_LEN));
+        let cases = vec![
+            ("", InvalidProtocol::Empty),
+            ("ipfs/ping/1.0.0", InvalidProtocol::MissingLeadingSlash),
+            (" /ipfs/ping", InvalidProtocol::MissingLeadingSlash),
+            ("/ipfs ping", InvalidProtocol::ContainsWhitespace),
+            ("/ipfs/ping\n", InvalidProtocol::ContainsWhitespace),
+            ("/ipfs\t/ping", InvalidProtocol::ContainsWhitespace),
+            ("/ipfs\u{a0}ping", InvalidProtocol::ContainsWhitespace),
+            (too_long.as_str(), InvalidProtocol::TooLong(MAX_LEN + 1)),
+        ];
+
+        for (s, error) in cases {
+            assert!(!StreamProtocol::is_valid_str(s));
+            assert_eq!(StreamProtocol::try_from(s), Err(error.clone()));
+            assert_eq!(StreamProtocol::try_from_owned(s.to_owned()), Err(error));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn new_panics_on_invalid_protocol() {
+        StreamProtocol::new("no-slash");
+    }
+
+    #[test]
+    fn arbitrary_strings() {
+        fn prop(s: String) -> bool {
+            let expected = if s.is_empty() {
+                Err(InvalidProtocol::Empty)
+            } else if !s.starts_with('/') {
+                Err(InvalidProtocol::MissingLeadingSlash)
+            } else if s.len() > MAX_LEN {
+                Err(InvalidProtocol::TooLong(s.len()))
+            } else if s.chars().any(char::is_whitespace) {
+                Err(InvalidProtocol::ContainsWhitespace)
+            } else {
+                Ok(())
+            };
+
+            let result = StreamProtocol::try_from(s.as_str());
+            result.map(|p| assert_eq!(p.as_str(), s)) == expected
+                && StreamProtocol::is_valid_str(&s) == expected.is_ok()
+        }
+
+        fn prop_slash(s: String) -> bool {
+            prop(format!("/{}", s))
+        }
+
+        QuickCheck::new().quickcheck(prop as fn(_) -> _);
+        QuickCheck::new().quickcheck(prop_slash as fn(_) -> _);
+    }
+}
@@ -0,0 +1,236 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use crate::Negotiated;
+use crate::upgrade::{
+    InboundUpgrade,
+    InboundUpgradeApply,
+    OutboundUpgrade,
+    OutboundUpgradeApply,
+    UpgradeInfo,
+    Version,
+    apply_inbound,
+    apply_outbound,
+};
+use futures::prelude::*;
+use futures_timer::Delay;
+use std::{error, fmt, pin::Pin, task::Context, task::Poll, time::Duration};
+
+/// Wraps around an upgrade and fails it with [`TimedUpgradeError::Timeout`]
+/// if the upgrade does not complete within the given duration.
+///
+/// The timeout only covers the upgrade itself, i.e. the future returned by
+/// `upgrade_inbound` or `upgrade_outbound`. Use [`apply_inbound_with_timeout`]
+/// or [`apply_outbound_with_timeout`] to include the protocol negotiation.
+#[derive(Debug, Clone)]
+pub struct TimedUpgrade<U> { upgrade: U, timeout: Duration }
+
+impl<U> TimedUpgrade<U> {
+    pub fn new(upgrade: U, timeout: Duration) -> Self {
+        TimedUpgrade { upgrade, timeout }
+    }
+}
+
+impl<U> UpgradeInfo for TimedUpgrade<U>
+where
+    U: UpgradeInfo
+{
+    type Info = U::Info;
+    type InfoIter = U::InfoIter;
+
+    fn protocol_info(&self) -> Self::InfoIter {
+        self.upgrade.protocol_info()
+    }
+}
+
+impl<C, U> InboundUpgrade<C> for TimedUpgrade<U>
+where
+    U: InboundUpgrade<C>,
+{
+    type Output = U::Output;
+    type Error = TimedUpgradeError<U::Error>;
+    type Future = Timeout<U::Future>;
+
+    fn upgrade_inbound(self, sock: C, info: Self::Info) -> Self::Future {
+        Timeout::new(self.upgrade.upgrade_inbound(sock, info), self.timeout)
+    }
+}
+
+impl<C, U> OutboundUpgrade<C> for TimedUpgrade<U>
+where
+    U: OutboundUpgrade<C>,
+{
+    type Output = U::Output;
+    type Error = TimedUpgradeError<U::Error>;
+    type Future = Timeout<U::Future>;
+
+    fn upgrade_outbound(self, sock: C, info: Self::Info) -> Self::Future {
+        Timeout::new(self.upgrade.upgrade_outbound(sock, info), self.timeout)
+    }
+}
+
+/// Tries to perform an upgrade on an inbound connection or substream,
+/// failing with [`TimedUpgradeError::Timeout`] if the protocol negotiation
+/// and the upgrade do not complete within the given duration.
+pub fn apply_inbound_with_timeout<C, U>(conn: C, up: U, timeout: Duration)
+    -> Timeout<InboundUpgradeApply<C, U>>
+where
+    C: AsyncRead + AsyncWrite + Unpin,
+    U: InboundUpgrade<Negotiated<C>>,
+{
+    Timeout::new(apply_inbound(conn, up), timeout)
+}
+
+/// Tries to perform an upgrade on an outbound connection or substream,
+/// failing with [`TimedUpgradeError::Timeout`] if the protocol negotiation
+/// and the upgrade do not complete within the given duration.
+pub fn apply_outbound_with_timeout<C, U>(conn: C, up: U, v: Version, timeout: Duration)
+    -> Timeout<OutboundUpgradeApply<C, U>>
+where
+    C: AsyncRead + AsyncWrite + Unpin,
+    U: OutboundUpgrade<Negotiated<C>>,
+{
+    Timeout::new(apply_outbound(conn, up, v), timeout)
+}
+
+/// Future returned by [`TimedUpgrade`] and the `apply_*_with_timeout`
+/// functions. Races the inner future against a timer.
+#[pin_project::pin_project]
+#[must_use = "futures do nothing unless polled"]
+pub struct Timeout<F> {
+    #[pin]
+    inner: F,
+    timer: Delay,
+}
+
+impl<F> Timeout<F> {
+    fn new(inner: F, timeout: Duration) -> Self {
+        Timeout { inner, timer: Delay::new(timeout) }
+    }
+}
+
+impl<F, T, E> Future for Timeout<F>
+where
+    F: Future<Output = Result<T, E>>,
+{
+    type Output = Result<T, TimedUpgradeError<E>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        match this.inner.poll(cx) {
+            Poll::Pending => {},
+            Poll::Ready(Ok(v)) => return Poll::Ready(Ok(v)),
+            Poll::Ready(Err(err)) => return Poll::Ready(Err(TimedUpgradeError::Upgrade(err))),
+        }
+
+        match Pin::new(this.timer).poll(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(()) => Poll::Ready(Err(TimedUpgradeError::Timeout)),
+        }
+    }
+}
+
+/// Error produced by a [`TimedUpgrade`] or the `apply_*_with_timeout` functions.
+#[derive(Debug)]
+pub enum TimedUpgradeError<E> {
+    /// The upgrade did not complete in time.
+    Timeout,
+    /// The upgrade failed.
+    Upgrade(E),
+}
+
+impl<E> fmt::Display for TimedUpgradeError<E>
+where
+    E: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimedUpgradeError::Timeout => write!(f, "Upgrade timed out"),
+            TimedUpgradeError::Upgrade(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl<E> error::Error for TimedUpgradeError<E>
+where
+    E: error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            TimedUpgradeError::Timeout => None,
+            TimedUpgradeError::Upgrade(err) => Some(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::upgrade::from_fn;
+    use std::io;
+
+    /// A connection that accepts all writes but never receives any data.
+    struct Silent;
+
+    impl AsyncRead for Silent {
+        fn poll_read(self: Pin<&mut Self>, _: &mut Context<'_>, _: &mut [u8]) -> Poll<io::Result<usize>> {
+            Poll::Pending
+        }
+    }
+
+    impl AsyncWrite for Silent {
+        fn poll_write(self: Pin<&mut Self>, _: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[test]
+    fn pending_upgrade_times_out() {
+        let upgrade = from_fn("/pending/1.0.0", |_: (), _| future::pending::<Result<(), io::Error>>());
+        let timed = TimedUpgrade::new(upgrade, Duration::from_millis(10));
+        let result = futures::executor::block_on(timed.upgrade_inbound((), "/pending/1.0.0"));
+        assert!(matches!(result, Err(TimedUpgradeError::Timeout)));
+    }
+
+    #[test]
+    fn upgrade_error_passes_through() {
+        let upgrade = from_fn("/failing/1.0.0", |_: (), _| future::err::<(), _>(io::ErrorKind::Other));
+        let timed = TimedUpgrade::new(upgrade, Duration::from_secs(10));
+        let result = futures::executor::block_on(timed.upgrade_outbound((), "/failing/1.0.0"));
+        assert!(matches!(result, Err(TimedUpgradeError::Upgrade(io::ErrorKind::Other))));
+    }
+
+    #[test]
+    fn negotiation_times_out() {
+        let upgrade = from_fn("/silent/1.0.0", |_, _| future::ok::<_, io::Error>(()));
+        let apply = apply_outbound_with_timeout(Silent, upgrade, Version::V1, Duration::from_millis(10));
+        let result = futures::executor::block_on(apply);
+        assert!(matches!(result, Err(TimedUpgradeError::Timeout)));
+    }
+}
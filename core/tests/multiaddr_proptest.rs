@@ -0,0 +1,121 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Property-based tests for the encoding of `Multiaddr`s.
+
+use libp2p_core::{Multiaddr, PeerId, multiaddr::Protocol};
+use quickcheck::*;
+use std::{borrow::Cow, convert::TryFrom, iter::FromIterator, net::{Ipv4Addr, Ipv6Addr}};
+
+/// An arbitrary `Multiaddr`, composed of arbitrary valid protocols.
+#[derive(Clone, Debug)]
+struct ArbMultiaddr(Multiaddr);
+
+impl Arbitrary for ArbMultiaddr {
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        let len = g.next_u32() % 16;
+        ArbMultiaddr(Multiaddr::from_iter((0 .. len).map(|_| arb_protocol(g))))
+    }
+}
+
+fn arb_protocol<G: Gen>(g: &mut G) -> Protocol<'static> {
+    use Protocol::*;
+    match u8::arbitrary(g) % 26 {
+         0 => Dccp(Arbitrary::arbitrary(g)),
+         1 => Dns(Cow::Owned(arb_substring(g))),
+         2 => Dns4(Cow::Owned(arb_substring(g))),
+         3 => Dns6(Cow::Owned(arb_substring(g))),
+         4 => Dnsaddr(Cow::Owned(arb_substring(g))),
+         5 => Http,
+         6 => Https,
+         7 => Ip4(Ipv4Addr::from(u32::arbitrary(g))),
+         8 => Ip6(Ipv6Addr::from(u128::arbitrary(g))),
+         9 => P2pWebRtcDirect,
+        10 => P2pWebRtcStar,
+        11 => P2pWebSocketStar,
+        12 => Memory(Arbitrary::arbitrary(g)),
+        13 => P2p(PeerId::random().into()),
+        14 => P2pCircuit,
+        15 => Quic,
+        16 => Sctp(Arbitrary::arbitrary(g)),
+        17 => Tcp(Arbitrary::arbitrary(g)),
+        18 => Udp(Arbitrary::arbitrary(g)),
+        19 => Udt,
+        20 => Unix(Cow::Owned(arb_substring(g))),
+        21 => Utp,
+        22 => Ws("/".into()),
+        23 => Wss("/".into()),
+        24 => {
+            let mut a = [0; 10];
+            a.iter_mut().for_each(|b| *b = u8::arbitrary(g));
+            Onion(Cow::Owned(a), std::cmp::max(1, u16::arbitrary(g)))
+        }
+        25 => {
+            let mut a = [0; 35];
+            a.iter_mut().for_each(|b| *b = u8::arbitrary(g));
+            Onion3((a, std::cmp::max(1, u16::arbitrary(g))).into())
+        }
+        _ => unreachable!()
+    }
+}
+
+/// An arbitrary ASCII string without `/`, as used in textual protocol values.
+fn arb_substring<G: Gen>(g: &mut G) -> String {
+    let mut s = String::arbitrary(g);
+    s.retain(|c| c.is_ascii() && c != '/');
+    s
+}
+
+#[test]
+fn string_roundtrip() {
+    fn prop(ma: ArbMultiaddr) -> bool {
+        ma.0.to_string().parse::<Multiaddr>().ok() == Some(ma.0)
+    }
+    QuickCheck::new().quickcheck(prop as fn(_) -> _)
+}
+
+#[test]
+fn bytes_roundtrip() {
+    fn prop(ma: ArbMultiaddr) -> bool {
+        Multiaddr::try_from(ma.0.to_vec()).ok() == Some(ma.0)
+    }
+    QuickCheck::new().quickcheck(prop as fn(_) -> _)
+}
+
+#[test]
+fn protocols_roundtrip() {
+    fn prop(ma: ArbMultiaddr) -> bool {
+        ma.0.iter().all(|p| p.clone() == p)
+            && Multiaddr::from_iter(ma.0.iter().map(|p| p.clone().acquire())) == ma.0
+    }
+    QuickCheck::new().quickcheck(prop as fn(_) -> _)
+}
+
+#[test]
+fn encoded_len() {
+    fn prop(ma: ArbMultiaddr) -> bool {
+        let mut encoded = Vec::new();
+        for p in ma.0.iter() {
+            p.write_bytes(&mut encoded).unwrap();
+        }
+        ma.0.len() == ma.0.to_vec().len() && encoded == ma.0.to_vec()
+    }
+    QuickCheck::new().quickcheck(prop as fn(_) -> _)
+}
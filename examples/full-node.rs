@@ -0,0 +1,373 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Full node example
+//!
+//! Demonstrates how to compose the behaviours provided by this crate into a
+//! single [`NetworkBehaviour`] via `#[derive(NetworkBehaviour)]`, handling all
+//! of their events in one `loop` over [`Swarm::select_next_some`]. The node
+//! combines ping, identify, Kademlia, gossipsub, circuit relay and a simple
+//! echo protocol built on request-response.
+//!
+//! Each behaviour is wrapped in a [`Toggle`] and can be disabled on the command
+//! line via `--no-ping`, `--no-identify`, `--no-kad`, `--no-gossipsub`,
+//! `--no-relay` and `--no-request-response`. All other arguments are treated as
+//! addresses to dial.
+//!
+//! In the first terminal window, run:
+//!
+//! ```sh
+//! cargo run --example full-node
+//! ```
+//!
+//! It will print the PeerId and the listening addresses, e.g. `Listening on
+//! "/ip4/127.0.0.1/tcp/24915"`.
+//!
+//! In the second terminal window, start a new instance of the example with:
+//!
+//! ```sh
+//! cargo run --example full-node -- /ip4/127.0.0.1/tcp/24915
+//! ```
+//!
+//! The two nodes connect, identify each other, add each other to their
+//! Kademlia routing tables, exchange echo requests and greet each other via
+//! gossipsub.
+//!
+//! Note that the circuit relay behaviour serves as both relay client and relay
+//! server. Peers can thus be dialed and reached via `/p2p-circuit` addresses.
+
+use async_std::task;
+use async_trait::async_trait;
+use futures::prelude::*;
+use libp2p::core::upgrade::{self, read_length_prefixed, write_length_prefixed, ProtocolName};
+use libp2p::gossipsub::{self, Gossipsub, GossipsubConfig, GossipsubEvent, MessageAuthenticity};
+use libp2p::identify::{Identify, IdentifyConfig, IdentifyEvent};
+use libp2p::kad::{record::store::MemoryStore, Kademlia, KademliaEvent};
+use libp2p::ping::{Ping, PingConfig, PingEvent};
+use libp2p::relay::{self, Relay, RelayConfig};
+use libp2p::request_response::{
+    ProtocolSupport, RequestResponse, RequestResponseCodec, RequestResponseConfig,
+    RequestResponseEvent, RequestResponseMessage,
+};
+use libp2p::swarm::{toggle::Toggle, ListenPorts, SwarmBuilder, SwarmEvent};
+use libp2p::tcp::TcpConfig;
+use libp2p::{identity, noise, yamux, Multiaddr, NetworkBehaviour, PeerId, Transport};
+use std::{env, error::Error, io, iter};
+
+/// The behaviours of the node, each of which can be disabled.
+#[derive(NetworkBehaviour)]
+#[behaviour(out_event = "FullNodeEvent", event_process = false)]
+struct FullNode {
+    ping: Toggle<Ping>,
+    identify: Toggle<Identify>,
+    kademlia: Toggle<Kademlia<MemoryStore>>,
+    gossipsub: Toggle<Gossipsub>,
+    relay: Toggle<Relay>,
+    request_response: Toggle<RequestResponse<EchoCodec>>,
+}
+
+/// The events emitted by the [`FullNode`] behaviour, one variant per behaviour.
+#[derive(Debug)]
+enum FullNodeEvent {
+    Ping(PingEvent),
+    Identify(IdentifyEvent),
+    Kademlia(KademliaEvent),
+    Gossipsub(GossipsubEvent),
+    /// The relay behaviour does not emit any events.
+    Relay,
+    RequestResponse(RequestResponseEvent<EchoRequest, EchoResponse>),
+}
+
+impl From<PingEvent> for FullNodeEvent {
+    fn from(event: PingEvent) -> Self {
+        FullNodeEvent::Ping(event)
+    }
+}
+
+impl From<IdentifyEvent> for FullNodeEvent {
+    fn from(event: IdentifyEvent) -> Self {
+        FullNodeEvent::Identify(event)
+    }
+}
+
+impl From<KademliaEvent> for FullNodeEvent {
+    fn from(event: KademliaEvent) -> Self {
+        FullNodeEvent::Kademlia(event)
+    }
+}
+
+impl From<GossipsubEvent> for FullNodeEvent {
+    fn from(event: GossipsubEvent) -> Self {
+        FullNodeEvent::Gossipsub(event)
+    }
+}
+
+impl From<()> for FullNodeEvent {
+    fn from(_: ()) -> Self {
+        FullNodeEvent::Relay
+    }
+}
+
+impl From<RequestResponseEvent<EchoRequest, EchoResponse>> for FullNodeEvent {
+    fn from(event: RequestResponseEvent<EchoRequest, EchoResponse>) -> Self {
+        FullNodeEvent::RequestResponse(event)
+    }
+}
+
+/// The command-line options of the example.
+struct Opts {
+    ping: bool,
+    identify: bool,
+    kademlia: bool,
+    gossipsub: bool,
+    relay: bool,
+    request_response: bool,
+    dial: Vec<Multiaddr>,
+}
+
+impl Opts {
+    fn from_args() -> Result<Self, Box<dyn Error>> {
+        let mut opts = Opts {
+            ping: true,
+            identify: true,
+            kademlia: true,
+            gossipsub: true,
+            relay: true,
+            request_response: true,
+            dial: Vec::new(),
+        };
+        for arg in env::args().skip(1) {
+            match arg.as_str() {
+                "--no-ping" => opts.ping = false,
+                "--no-identify" => opts.identify = false,
+                "--no-kad" => opts.kademlia = false,
+                "--no-gossipsub" => opts.gossipsub = false,
+                "--no-relay" => opts.relay = false,
+                "--no-request-response" => opts.request_response = false,
+                addr => opts.dial.push(addr.parse()?),
+            }
+        }
+        Ok(opts)
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    env_logger::init();
+
+    let opts = Opts::from_args()?;
+
+    let local_key = identity::Keypair::generate_ed25519();
+    let local_peer_id = PeerId::from(local_key.public());
+    println!("Local peer id: {:?}", local_peer_id);
+
+    // The relay behaviour is tied to the transport, which needs to be wrapped
+    // before being upgraded.
+    let (relay_transport, relay) = relay::new_transport_and_behaviour(
+        RelayConfig::default(),
+        TcpConfig::new().nodelay(true),
+    );
+    let noise_keys = noise::Keypair::<noise::X25519Spec>::new().into_authentic(&local_key)?;
+    let transport = relay_transport
+        .upgrade(upgrade::Version::V1)
+        .authenticate(noise::NoiseConfig::xx(noise_keys).into_authenticated())
+        .multiplex(yamux::YamuxConfig::default())
+        .boxed();
+
+    let topic = gossipsub::IdentTopic::new("full-node");
+
+    let behaviour = FullNode {
+        ping: Toggle::from(if opts.ping {
+            Some(Ping::new(PingConfig::new().with_keep_alive(true)))
+        } else {
+            None
+        }),
+        identify: Toggle::from(if opts.identify {
+            Some(Identify::new(IdentifyConfig::new("/full-node/1.0.0".into(), local_key.public())))
+        } else {
+            None
+        }),
+        kademlia: Toggle::from(if opts.kademlia {
+            Some(Kademlia::new(local_peer_id, MemoryStore::new(local_peer_id)))
+        } else {
+            None
+        }),
+        gossipsub: Toggle::from(if opts.gossipsub {
+            let mut gossipsub = Gossipsub::new(
+                MessageAuthenticity::Signed(local_key.clone()),
+                GossipsubConfig::default(),
+            )?;
+            gossipsub.subscribe(&topic).unwrap();
+            Some(gossipsub)
+        } else {
+            None
+        }),
+        relay: Toggle::from(if opts.relay { Some(relay) } else { None }),
+        request_response: Toggle::from(if opts.request_response {
+            Some(RequestResponse::new(
+                EchoCodec,
+                iter::once((EchoProtocol, ProtocolSupport::Full)),
+                RequestResponseConfig::default(),
+            ))
+        } else {
+            None
+        }),
+    };
+
+    let mut swarm = SwarmBuilder::new(transport, behaviour, local_peer_id)
+        .executor(Box::new(|fut| {
+            task::spawn(fut);
+        }))
+        .build();
+
+    swarm.listen_on_all_interfaces(ListenPorts::default())?;
+
+    for addr in opts.dial {
+        swarm.dial_addr(addr.clone())?;
+        println!("Dialed {}", addr);
+    }
+
+    task::block_on(async move {
+        loop {
+            match swarm.select_next_some().await {
+                SwarmEvent::NewListenAddr { address, .. } => {
+                    println!("Listening on {:?}", address);
+                }
+                SwarmEvent::ConnectionEstablished { peer_id, endpoint, .. } => {
+                    println!("Connected to {} via {:?}", peer_id, endpoint);
+                    if let Some(request_response) = swarm.behaviour_mut().request_response.as_mut() {
+                        request_response.send_request(&peer_id, EchoRequest(b"hello".to_vec()));
+                    }
+                }
+                SwarmEvent::ConnectionClosed { peer_id, cause, .. } => {
+                    println!("Disconnected from {}: {:?}", peer_id, cause);
+                }
+                SwarmEvent::Behaviour(FullNodeEvent::Ping(event)) => {
+                    println!("Ping: {:?}", event);
+                }
+                SwarmEvent::Behaviour(FullNodeEvent::Identify(IdentifyEvent::Received { peer_id, info })) => {
+                    println!("Identified {} running {}", peer_id, info.agent_version);
+                    // Make the listen addresses of identified peers known to Kademlia.
+                    if let Some(kademlia) = swarm.behaviour_mut().kademlia.as_mut() {
+                        for addr in info.listen_addrs {
+                            kademlia.add_address(&peer_id, addr);
+                        }
+                    }
+                }
+                SwarmEvent::Behaviour(FullNodeEvent::Identify(event)) => {
+                    println!("Identify: {:?}", event);
+                }
+                SwarmEvent::Behaviour(FullNodeEvent::Kademlia(event)) => {
+                    println!("Kademlia: {:?}", event);
+                }
+                SwarmEvent::Behaviour(FullNodeEvent::Gossipsub(GossipsubEvent::Subscribed { peer_id, topic: subscribed })) => {
+                    println!("{} subscribed to {}", peer_id, subscribed);
+                    if let Some(gossipsub) = swarm.behaviour_mut().gossipsub.as_mut() {
+                        let greeting = format!("hello from {}", local_peer_id);
+                        if let Err(e) = gossipsub.publish(topic.clone(), greeting) {
+                            println!("Failed to publish greeting: {:?}", e);
+                        }
+                    }
+                }
+                SwarmEvent::Behaviour(FullNodeEvent::Gossipsub(GossipsubEvent::Message { message, .. })) => {
+                    println!("Gossipsub message: {}", String::from_utf8_lossy(&message.data));
+                }
+                SwarmEvent::Behaviour(FullNodeEvent::Gossipsub(event)) => {
+                    println!("Gossipsub: {:?}", event);
+                }
+                SwarmEvent::Behaviour(FullNodeEvent::Relay) => {}
+                SwarmEvent::Behaviour(FullNodeEvent::RequestResponse(RequestResponseEvent::Message {
+                    peer,
+                    message: RequestResponseMessage::Request { request, channel, .. },
+                })) => {
+                    println!("Echoing request from {}", peer);
+                    if let Some(request_response) = swarm.behaviour_mut().request_response.as_mut() {
+                        let _ = request_response.send_response(channel, EchoResponse(request.0));
+                    }
+                }
+                SwarmEvent::Behaviour(FullNodeEvent::RequestResponse(RequestResponseEvent::Message {
+                    peer,
+                    message: RequestResponseMessage::Response { response, .. },
+                })) => {
+                    println!("Echo from {}: {}", peer, String::from_utf8_lossy(&response.0));
+                }
+                SwarmEvent::Behaviour(FullNodeEvent::RequestResponse(event)) => {
+                    println!("Request-response: {:?}", event);
+                }
+                _ => {}
+            }
+        }
+    })
+}
+
+/// The echo protocol, answering every request with its payload.
+#[derive(Debug, Clone)]
+struct EchoProtocol;
+
+#[derive(Clone)]
+struct EchoCodec;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct EchoRequest(Vec<u8>);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct EchoResponse(Vec<u8>);
+
+impl ProtocolName for EchoProtocol {
+    fn protocol_name(&self) -> &[u8] {
+        b"/echo/1.0.0"
+    }
+}
+
+#[async_trait]
+impl RequestResponseCodec for EchoCodec {
+    type Protocol = EchoProtocol;
+    type Request = EchoRequest;
+    type Response = EchoResponse;
+
+    async fn read_request<T>(&mut self, _: &EchoProtocol, io: &mut T) -> io::Result<EchoRequest>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        Ok(EchoRequest(read_length_prefixed(io, 1024).await?))
+    }
+
+    async fn read_response<T>(&mut self, _: &EchoProtocol, io: &mut T) -> io::Result<EchoResponse>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        Ok(EchoResponse(read_length_prefixed(io, 1024).await?))
+    }
+
+    async fn write_request<T>(&mut self, _: &EchoProtocol, io: &mut T, EchoRequest(data): EchoRequest) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        write_length_prefixed(io, data).await?;
+        io.close().await
+    }
+
+    async fn write_response<T>(&mut self, _: &EchoProtocol, io: &mut T, EchoResponse(data): EchoResponse) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        write_length_prefixed(io, data).await?;
+        io.close().await
+    }
+}
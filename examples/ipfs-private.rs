@@ -44,7 +44,7 @@ use libp2p::{
     noise,
     ping::{self, Ping, PingConfig, PingEvent},
     pnet::{PnetConfig, PreSharedKey},
-    swarm::{NetworkBehaviourEventProcess, SwarmEvent},
+    swarm::{ListenPorts, NetworkBehaviourEventProcess, SwarmEvent},
     tcp::TcpConfig,
     yamux::YamuxConfig,
     Multiaddr, NetworkBehaviour, PeerId, Swarm, Transport,
@@ -217,6 +217,17 @@ fn main() -> Result<(), Box<dyn Error>> {
                 } => {
                     println!("ping: pong from {}", peer.to_base58());
                 }
+                PingEvent {
+                    peer,
+                    result: Result::Ok(PingSuccess::RttExceeded { rtt, threshold }),
+                } => {
+                    println!(
+                        "ping: rtt to {} of {} ms exceeds {} ms",
+                        peer.to_base58(),
+                        rtt.as_millis(),
+                        threshold.as_millis()
+                    );
+                }
                 PingEvent {
                     peer,
                     result: Result::Err(PingFailure::Timeout),
@@ -268,7 +279,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     let mut stdin = io::BufReader::new(io::stdin()).lines();
 
     // Listen on all interfaces and whatever port the OS assigns
-    swarm.listen_on("/ip4/0.0.0.0/tcp/0".parse()?)?;
+    swarm.listen_on_all_interfaces(ListenPorts::default())?;
 
     // Kick it off
     task::block_on(future::poll_fn(move |cx: &mut Context<'_>| {
@@ -22,7 +22,7 @@ use futures::StreamExt;
 use libp2p::{
     identity, 
     mdns::{Mdns, MdnsConfig, MdnsEvent}, 
-    swarm::{Swarm, SwarmEvent},
+    swarm::{ListenPorts, Swarm, SwarmEvent},
     PeerId
 };
 use std::error::Error;
@@ -46,7 +46,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
     // Note that the MDNS behaviour itself will not actually inititiate any connections,
     // as it only uses UDP.
     let mut swarm = Swarm::new(transport, behaviour, peer_id);
-    swarm.listen_on("/ip4/0.0.0.0/tcp/0".parse()?)?;
+    swarm.listen_on_all_interfaces(ListenPorts::default())?;
 
     loop {
         match swarm.select_next_some().await {
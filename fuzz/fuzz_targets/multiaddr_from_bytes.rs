@@ -0,0 +1,14 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use libp2p_core::Multiaddr;
+use std::convert::TryFrom;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(addr) = Multiaddr::try_from(data.to_vec()) {
+        let _ = addr.to_string();
+        for protocol in addr.iter() {
+            let _ = protocol.to_string();
+        }
+    }
+});
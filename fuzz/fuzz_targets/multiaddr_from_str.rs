@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use libp2p_core::Multiaddr;
+use std::convert::TryFrom;
+
+fuzz_target!(|data: &str| {
+    if let Ok(addr) = Multiaddr::try_from(data) {
+        let _ = addr.to_string();
+        assert_eq!(Multiaddr::try_from(addr.to_vec()).unwrap(), addr);
+    }
+});
@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use libp2p_core::Multiaddr;
+use std::convert::TryFrom;
+
+// `dnsaddr` TXT records are untrusted input received from DNS resolvers.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(addr) = libp2p_dns::parse_dnsaddr_txt(data) {
+        assert_eq!(Multiaddr::try_from(addr.to_vec()).unwrap(), addr);
+    }
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use libp2p_core::multiaddr::Protocol;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok((protocol, rest)) = Protocol::from_bytes(data) {
+        let _ = protocol.to_string();
+        assert!(rest.len() < data.len());
+    }
+});
@@ -0,0 +1,405 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Peer discovery combining multiple discovery mechanisms.
+//!
+//! The [`DiscoveryBehaviour`] wraps the [`Mdns`] and [`Kademlia`] network
+//! behaviours, each of which is optional, and reports the peers discovered by
+//! any of them as a single [`DiscoveryEvent::Discovered`], annotated with the
+//! [`DiscoverySource`] of the discovery. Addresses that a source no longer
+//! knows about, e.g. because their mDNS record expired, are reported as
+//! [`DiscoveryEvent::Expired`]. Addresses of peers known by other means can be
+//! added via [`DiscoveryBehaviour::add_address`].
+//!
+//! All events of the [`Kademlia`] behaviour that are not related to the
+//! discovery of peers, e.g. the results of queries, are passed on as
+//! [`DiscoveryEvent::Kademlia`].
+
+use libp2p_core::{
+    ConnectedPoint,
+    Multiaddr,
+    PeerId,
+    connection::{ConnectionId, ListenerId},
+};
+use libp2p_kad::{Kademlia, KademliaEvent, record::store::MemoryStore};
+use libp2p_mdns::{Mdns, MdnsEvent};
+use libp2p_swarm::{
+    AddressMeta,
+    AddressSource,
+    IntoProtocolsHandler,
+    NetworkBehaviour,
+    NetworkBehaviourAction,
+    PollParameters,
+    ProtocolsHandler,
+    TimerId,
+    toggle::Toggle,
+};
+use smallvec::SmallVec;
+use std::{collections::{HashMap, VecDeque}, error, task::{Context, Poll}};
+
+/// The mechanism by which a peer was discovered.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DiscoverySource {
+    /// The peer was discovered via mDNS in the local network.
+    Mdns,
+    /// The peer was added to the Kademlia routing table.
+    Kademlia,
+    /// The address of the peer was added via [`DiscoveryBehaviour::add_address`].
+    UserProvided,
+}
+
+/// Event produced by the [`DiscoveryBehaviour`].
+#[derive(Debug)]
+pub enum DiscoveryEvent {
+    /// A peer has been discovered.
+    ///
+    /// The same peer may be discovered repeatedly, by the same or by
+    /// different sources, e.g. whenever new addresses become known.
+    Discovered {
+        /// The discovered peer.
+        peer_id: PeerId,
+        /// The addresses of the peer known to the source.
+        addresses: Vec<Multiaddr>,
+        /// How the peer was discovered.
+        source: DiscoverySource,
+    },
+    /// Addresses of a peer are no longer known to a source.
+    ///
+    /// This is the case if the mDNS records of the addresses expired, if the
+    /// peer was removed from the Kademlia routing table or if the addresses
+    /// were removed via [`DiscoveryBehaviour::remove_address`].
+    Expired {
+        /// The peer whose addresses expired.
+        peer_id: PeerId,
+        /// The expired addresses.
+        addresses: Vec<Multiaddr>,
+        /// The source that no longer knows the addresses.
+        source: DiscoverySource,
+    },
+    /// Any other event produced by the [`Kademlia`] behaviour.
+    Kademlia(KademliaEvent),
+}
+
+/// A [`NetworkBehaviour`] that combines the discovery of peers via mDNS,
+/// Kademlia and user-provided addresses.
+///
+/// See the crate root documentation for more information.
+pub struct DiscoveryBehaviour {
+    /// Discovery of peers in the local network, if enabled.
+    mdns: Option<Mdns>,
+    /// Discovery of peers via the Kademlia DHT, if enabled.
+    kademlia: Toggle<Kademlia<MemoryStore>>,
+    /// Addresses added via [`DiscoveryBehaviour::add_address`].
+    user_provided: HashMap<PeerId, SmallVec<[Multiaddr; 4]>>,
+    /// Queue of events to report.
+    events: VecDeque<DiscoveryEvent>,
+}
+
+impl DiscoveryBehaviour {
+    /// Creates a new `DiscoveryBehaviour` without any discovery mechanism
+    /// enabled, i.e. only knowing user-provided addresses.
+    pub fn new() -> Self {
+        DiscoveryBehaviour {
+            mdns: None,
+            kademlia: Toggle::from(None),
+            user_provided: HashMap::new(),
+            events: VecDeque::new(),
+        }
+    }
+
+    /// Enables the discovery of peers via mDNS.
+    pub fn with_mdns(mut self, mdns: Mdns) -> Self {
+        self.mdns = Some(mdns);
+        self
+    }
+
+    /// Enables the discovery of peers via Kademlia.
+    pub fn with_kademlia(mut self, kademlia: Kademlia<MemoryStore>) -> Self {
+        self.kademlia = Toggle::from(Some(kademlia));
+        self
+    }
+
+    /// Returns the [`Mdns`] behaviour, if enabled.
+    pub fn mdns(&self) -> Option<&Mdns> {
+        self.mdns.as_ref()
+    }
+
+    /// Returns the [`Kademlia`] behaviour, if enabled.
+    pub fn kademlia(&self) -> Option<&Kademlia<MemoryStore>> {
+        self.kademlia.as_ref()
+    }
+
+    /// Returns the [`Kademlia`] behaviour mutably, if enabled, e.g. for
+    /// bootstrapping or adding addresses to the routing table.
+    pub fn kademlia_mut(&mut self) -> Option<&mut Kademlia<MemoryStore>> {
+        self.kademlia.as_mut()
+    }
+
+    /// Adds a known address of a peer.
+    ///
+    /// The address is used for dialing the peer and reported as a
+    /// [`DiscoveryEvent::Discovered`] with source [`DiscoverySource::UserProvided`]
+    /// unless it has been added before.
+    pub fn add_address(&mut self, peer_id: &PeerId, address: Multiaddr) {
+        let addresses = self.user_provided.entry(*peer_id).or_default();
+        if addresses.contains(&address) {
+            return
+        }
+        addresses.push(address.clone());
+        self.events.push_back(DiscoveryEvent::Discovered {
+            peer_id: *peer_id,
+            addresses: vec![address],
+            source: DiscoverySource::UserProvided,
+        });
+    }
+
+    /// Removes an address of a peer previously added via
+    /// [`DiscoveryBehaviour::add_address`].
+    ///
+    /// The address is reported as a [`DiscoveryEvent::Expired`] with source
+    /// [`DiscoverySource::UserProvided`] if it was known.
+    pub fn remove_address(&mut self, peer_id: &PeerId, address: &Multiaddr) {
+        if let Some(addresses) = self.user_provided.get_mut(peer_id) {
+            let len = addresses.len();
+            addresses.retain(|a| a != address);
+            if addresses.len() != len {
+                self.events.push_back(DiscoveryEvent::Expired {
+                    peer_id: *peer_id,
+                    addresses: vec![address.clone()],
+                    source: DiscoverySource::UserProvided,
+                });
+            }
+            if addresses.is_empty() {
+                self.user_provided.remove(peer_id);
+            }
+        }
+    }
+}
+
+impl Default for DiscoveryBehaviour {
+    fn default() -> Self {
+        DiscoveryBehaviour::new()
+    }
+}
+
+impl NetworkBehaviour for DiscoveryBehaviour {
+    type ProtocolsHandler = <Toggle<Kademlia<MemoryStore>> as NetworkBehaviour>::ProtocolsHandler;
+    type OutEvent = DiscoveryEvent;
+
+    fn new_handler(&mut self) -> Self::ProtocolsHandler {
+        self.kademlia.new_handler()
+    }
+
+    fn addresses_of_peer(&mut self, peer_id: &PeerId) -> Vec<Multiaddr> {
+        self.addresses_of_peer_with_metadata(peer_id)
+            .into_iter()
+            .map(|(addr, _)| addr)
+            .collect()
+    }
+
+    fn addresses_of_peer_with_metadata(&mut self, peer_id: &PeerId) -> Vec<(Multiaddr, AddressMeta)> {
+        let mut addresses = self.user_provided.get(peer_id)
+            .map(|a| a.iter()
+                .map(|addr| (addr.clone(), AddressMeta::new(AddressSource::UserProvided).with_score(1.0)))
+                .collect::<Vec<_>>())
+            .unwrap_or_default();
+        if let Some(mdns) = self.mdns.as_mut() {
+            addresses.extend(mdns.addresses_of_peer_with_metadata(peer_id));
+        }
+        addresses.extend(self.kademlia.addresses_of_peer_with_metadata(peer_id));
+
+        // An address reported by several mechanisms is kept once, with the
+        // metadata of the mechanism that is most confident about it.
+        let mut unique: Vec<(Multiaddr, AddressMeta)> = Vec::with_capacity(addresses.len());
+        for (address, meta) in addresses {
+            match unique.iter_mut().find(|(a, _)| *a == address) {
+                Some((_, m)) => if meta.score > m.score { *m = meta },
+                None => unique.push((address, meta)),
+            }
+        }
+        unique
+    }
+
+    fn inject_connected(&mut self, peer_id: &PeerId) {
+        self.kademlia.inject_connected(peer_id)
+    }
+
+    fn inject_disconnected(&mut self, peer_id: &PeerId) {
+        self.kademlia.inject_disconnected(peer_id)
+    }
+
+    fn inject_connection_established(&mut self, peer_id: &PeerId, id: &ConnectionId, endpoint: &ConnectedPoint) {
+        self.kademlia.inject_connection_established(peer_id, id, endpoint)
+    }
+
+    fn inject_connection_closed(&mut self, peer_id: &PeerId, id: &ConnectionId, endpoint: &ConnectedPoint) {
+        self.kademlia.inject_connection_closed(peer_id, id, endpoint)
+    }
+
+    fn inject_address_change(
+        &mut self,
+        peer_id: &PeerId,
+        id: &ConnectionId,
+        old: &ConnectedPoint,
+        new: &ConnectedPoint
+    ) {
+        self.kademlia.inject_address_change(peer_id, id, old, new)
+    }
+
+    fn inject_event(
+        &mut self,
+        peer_id: PeerId,
+        connection: ConnectionId,
+        event: <<Self::ProtocolsHandler as IntoProtocolsHandler>::Handler as ProtocolsHandler>::OutEvent
+    ) {
+        self.kademlia.inject_event(peer_id, connection, event)
+    }
+
+    fn inject_addr_reach_failure(&mut self, peer_id: Option<&PeerId>, addr: &Multiaddr, error: &dyn error::Error) {
+        self.kademlia.inject_addr_reach_failure(peer_id, addr, error)
+    }
+
+    fn inject_dial_failure(&mut self, peer_id: &PeerId) {
+        self.kademlia.inject_dial_failure(peer_id)
+    }
+
+    fn inject_new_listener(&mut self, id: ListenerId) {
+        if let Some(mdns) = self.mdns.as_mut() {
+            mdns.inject_new_listener(id);
+        }
+        self.kademlia.inject_new_listener(id)
+    }
+
+    fn inject_new_listen_addr(&mut self, id: ListenerId, addr: &Multiaddr) {
+        if let Some(mdns) = self.mdns.as_mut() {
+            mdns.inject_new_listen_addr(id, addr);
+        }
+        self.kademlia.inject_new_listen_addr(id, addr)
+    }
+
+    fn inject_expired_listen_addr(&mut self, id: ListenerId, addr: &Multiaddr) {
+        if let Some(mdns) = self.mdns.as_mut() {
+            mdns.inject_expired_listen_addr(id, addr);
+        }
+        self.kademlia.inject_expired_listen_addr(id, addr)
+    }
+
+    fn inject_listener_error(&mut self, id: ListenerId, err: &(dyn std::error::Error + 'static)) {
+        if let Some(mdns) = self.mdns.as_mut() {
+            mdns.inject_listener_error(id, err);
+        }
+        self.kademlia.inject_listener_error(id, err)
+    }
+
+    fn inject_listener_closed(&mut self, id: ListenerId, reason: Result<(), &std::io::Error>) {
+        if let Some(mdns) = self.mdns.as_mut() {
+            mdns.inject_listener_closed(id, reason);
+        }
+        self.kademlia.inject_listener_closed(id, reason)
+    }
+
+    fn inject_new_external_addr(&mut self, addr: &Multiaddr) {
+        if let Some(mdns) = self.mdns.as_mut() {
+            mdns.inject_new_external_addr(addr);
+        }
+        self.kademlia.inject_new_external_addr(addr)
+    }
+
+    fn inject_expired_external_addr(&mut self, addr: &Multiaddr) {
+        if let Some(mdns) = self.mdns.as_mut() {
+            mdns.inject_expired_external_addr(addr);
+        }
+        self.kademlia.inject_expired_external_addr(addr)
+    }
+
+    fn inject_timeout(&mut self, timer_id: TimerId) {
+        if let Some(mdns) = self.mdns.as_mut() {
+            mdns.inject_timeout(timer_id);
+        }
+        self.kademlia.inject_timeout(timer_id)
+    }
+
+    fn poll(&mut self, cx: &mut Context<'_>, params: &mut impl PollParameters)
+        -> Poll<NetworkBehaviourAction<<<Self::ProtocolsHandler as IntoProtocolsHandler>::Handler as ProtocolsHandler>::InEvent, Self::OutEvent>>
+    {
+        loop {
+            if let Some(event) = self.events.pop_front() {
+                return Poll::Ready(NetworkBehaviourAction::GenerateEvent(event))
+            }
+
+            if let Some(mdns) = self.mdns.as_mut() {
+                match mdns.poll(cx, params) {
+                    Poll::Ready(NetworkBehaviourAction::GenerateEvent(MdnsEvent::Discovered(list))) => {
+                        self.events.extend(group_by_peer(list).map(|(peer_id, addresses)| {
+                            DiscoveryEvent::Discovered { peer_id, addresses, source: DiscoverySource::Mdns }
+                        }));
+                        continue
+                    }
+                    Poll::Ready(NetworkBehaviourAction::GenerateEvent(MdnsEvent::Expired(list))) => {
+                        self.events.extend(group_by_peer(list).map(|(peer_id, addresses)| {
+                            DiscoveryEvent::Expired { peer_id, addresses, source: DiscoverySource::Mdns }
+                        }));
+                        continue
+                    }
+                    Poll::Ready(action) => {
+                        return Poll::Ready(action
+                            .map_in(|v| void::unreachable(v))
+                            .map_out(|_| unreachable!("`GenerateEvent` is handled above.")))
+                    }
+                    Poll::Pending => {}
+                }
+            }
+
+            return match self.kademlia.poll(cx, params) {
+                // Only emitted for new peers and new addresses of known peers.
+                Poll::Ready(NetworkBehaviourAction::GenerateEvent(KademliaEvent::RoutingUpdated { peer, addresses, .. })) => {
+                    Poll::Ready(NetworkBehaviourAction::GenerateEvent(DiscoveryEvent::Discovered {
+                        peer_id: peer,
+                        addresses: addresses.into_vec(),
+                        source: DiscoverySource::Kademlia,
+                    }))
+                }
+                Poll::Ready(NetworkBehaviourAction::GenerateEvent(KademliaEvent::RoutingRemoved { peer, addresses, .. })) => {
+                    Poll::Ready(NetworkBehaviourAction::GenerateEvent(DiscoveryEvent::Expired {
+                        peer_id: peer,
+                        addresses: addresses.into_vec(),
+                        source: DiscoverySource::Kademlia,
+                    }))
+                }
+                Poll::Ready(action) => Poll::Ready(action.map_out(DiscoveryEvent::Kademlia)),
+                Poll::Pending => Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Groups a list of addresses by peer, such that all addresses of a peer
+/// are reported in a single event.
+fn group_by_peer(list: impl Iterator<Item = (PeerId, Multiaddr)>) -> impl Iterator<Item = (PeerId, Vec<Multiaddr>)> {
+    let mut grouped: Vec<(PeerId, Vec<Multiaddr>)> = Vec::new();
+    for (peer_id, address) in list {
+        match grouped.iter_mut().find(|(p, _)| *p == peer_id) {
+            Some((_, addresses)) => addresses.push(address),
+            None => grouped.push((peer_id, vec![address])),
+        }
+    }
+    grouped.into_iter()
+}
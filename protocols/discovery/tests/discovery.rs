@@ -0,0 +1,246 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Integration tests for the `DiscoveryBehaviour`.
+
+use futures::prelude::*;
+use libp2p_core::{
+    Multiaddr,
+    PeerId,
+    identity,
+    multiaddr::Protocol,
+    transport::{MemoryTransport, Transport},
+    upgrade,
+};
+use libp2p_discovery::{DiscoveryBehaviour, DiscoveryEvent, DiscoverySource};
+use libp2p_kad::{Kademlia, record::store::MemoryStore};
+use libp2p_mdns::{Mdns, MdnsConfig};
+use libp2p_mplex::MplexConfig;
+use libp2p_plaintext::PlainText2Config;
+use libp2p_swarm::{AddressSource, NetworkBehaviour, Swarm, SwarmEvent};
+use std::time::Duration;
+
+fn build_swarm() -> Swarm<DiscoveryBehaviour> {
+    build_swarm_with_mdns(None)
+}
+
+fn build_swarm_with_mdns(mdns: Option<Mdns>) -> Swarm<DiscoveryBehaviour> {
+    let local_public_key = identity::Keypair::generate_ed25519().public();
+    let local_peer_id = local_public_key.clone().into_peer_id();
+    let transport = MemoryTransport::default()
+        .upgrade(upgrade::Version::V1)
+        .authenticate(PlainText2Config { local_public_key })
+        .multiplex(MplexConfig::new())
+        .boxed();
+
+    let kademlia = Kademlia::new(local_peer_id, MemoryStore::new(local_peer_id));
+    let mut behaviour = DiscoveryBehaviour::new().with_kademlia(kademlia);
+    if let Some(mdns) = mdns {
+        behaviour = behaviour.with_mdns(mdns);
+    }
+
+    Swarm::new(transport, behaviour, local_peer_id)
+}
+
+/// Polls the swarm until the next discovery.
+async fn next_discovery(swarm: &mut Swarm<DiscoveryBehaviour>) -> (PeerId, Vec<Multiaddr>, DiscoverySource) {
+    loop {
+        if let SwarmEvent::Behaviour(DiscoveryEvent::Discovered { peer_id, addresses, source }) =
+            swarm.select_next_some().await
+        {
+            return (peer_id, addresses, source)
+        }
+    }
+}
+
+#[test]
+fn discovery_sources() {
+    let mut swarm = build_swarm();
+
+    let user_peer = PeerId::random();
+    let user_addr: Multiaddr = Protocol::Memory(1).into();
+    swarm.behaviour_mut().add_address(&user_peer, user_addr.clone());
+    // Adding the same address again is not reported.
+    swarm.behaviour_mut().add_address(&user_peer, user_addr.clone());
+
+    let kad_peer = PeerId::random();
+    let kad_addr: Multiaddr = Protocol::Memory(2).into();
+    swarm.behaviour_mut().kademlia_mut().unwrap().add_address(&kad_peer, kad_addr.clone());
+
+    async_std::task::block_on(async move {
+        assert_eq!(
+            next_discovery(&mut swarm).await,
+            (user_peer, vec![user_addr.clone()], DiscoverySource::UserProvided)
+        );
+        assert_eq!(
+            next_discovery(&mut swarm).await,
+            (kad_peer, vec![kad_addr.clone()], DiscoverySource::Kademlia)
+        );
+
+        assert_eq!(swarm.behaviour_mut().addresses_of_peer(&user_peer), vec![user_addr]);
+        assert_eq!(swarm.behaviour_mut().addresses_of_peer(&kad_peer), vec![kad_addr]);
+    });
+}
+
+/// Polls the swarm until the next expiry.
+async fn next_expiry(swarm: &mut Swarm<DiscoveryBehaviour>) -> (PeerId, Vec<Multiaddr>, DiscoverySource) {
+    loop {
+        if let SwarmEvent::Behaviour(DiscoveryEvent::Expired { peer_id, addresses, source }) =
+            swarm.select_next_some().await
+        {
+            return (peer_id, addresses, source)
+        }
+    }
+}
+
+#[test]
+fn expiry_sources() {
+    let mut swarm = build_swarm();
+
+    let user_peer = PeerId::random();
+    let user_addr: Multiaddr = Protocol::Memory(1).into();
+    swarm.behaviour_mut().add_address(&user_peer, user_addr.clone());
+    swarm.behaviour_mut().remove_address(&user_peer, &user_addr);
+    // Removing an unknown address is not reported.
+    swarm.behaviour_mut().remove_address(&user_peer, &user_addr);
+
+    let kad_peer = PeerId::random();
+    let kad_addr1: Multiaddr = Protocol::Memory(2).into();
+    let kad_addr2: Multiaddr = Protocol::Memory(3).into();
+    let kademlia = swarm.behaviour_mut().kademlia_mut().unwrap();
+    kademlia.add_address(&kad_peer, kad_addr1.clone());
+    kademlia.add_address(&kad_peer, kad_addr2.clone());
+    // Removing an address of a peer that remains in the routing table is
+    // neither a discovery nor an expiry.
+    kademlia.remove_address(&kad_peer, &kad_addr1);
+    kademlia.remove_address(&kad_peer, &kad_addr2);
+
+    async_std::task::block_on(async move {
+        let mut events = Vec::new();
+        while events.len() < 5 {
+            match swarm.select_next_some().await {
+                SwarmEvent::Behaviour(e @ DiscoveryEvent::Discovered { .. }) |
+                SwarmEvent::Behaviour(e @ DiscoveryEvent::Expired { .. }) => events.push(e),
+                _ => {}
+            }
+        }
+        let events = events.into_iter()
+            .map(|e| match e {
+                DiscoveryEvent::Discovered { peer_id, addresses, source } => (true, peer_id, addresses, source),
+                DiscoveryEvent::Expired { peer_id, addresses, source } => (false, peer_id, addresses, source),
+                DiscoveryEvent::Kademlia(_) => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(events, vec![
+            (true, user_peer, vec![user_addr.clone()], DiscoverySource::UserProvided),
+            (false, user_peer, vec![user_addr], DiscoverySource::UserProvided),
+            (true, kad_peer, vec![kad_addr1.clone()], DiscoverySource::Kademlia),
+            (true, kad_peer, vec![kad_addr1, kad_addr2.clone()], DiscoverySource::Kademlia),
+            (false, kad_peer, vec![kad_addr2], DiscoverySource::Kademlia),
+        ]);
+    });
+}
+
+#[test]
+fn mdns_discovery_and_expiry() {
+    let config = MdnsConfig {
+        ttl: Duration::from_secs(1),
+        query_interval: Duration::from_millis(200),
+    };
+    let (mdns1, mdns2) = async_std::task::block_on(async {
+        (Mdns::new(config.clone()).await.unwrap(), Mdns::new(config).await.unwrap())
+    });
+    let mut swarm1 = build_swarm_with_mdns(Some(mdns1));
+    let mut swarm2 = build_swarm_with_mdns(Some(mdns2));
+    let peer2 = *swarm2.local_peer_id();
+
+    let addr2: Multiaddr = Protocol::Memory(rand::random::<u64>()).into();
+    swarm2.listen_on(addr2.clone()).unwrap();
+
+    let handle = async_std::task::spawn(async move {
+        loop {
+            swarm2.next().await;
+        }
+    });
+
+    async_std::task::block_on(async move {
+        loop {
+            let (peer_id, addresses, source) = next_discovery(&mut swarm1).await;
+            if peer_id == peer2 {
+                assert_eq!(addresses, vec![addr2.clone()]);
+                assert_eq!(source, DiscoverySource::Mdns);
+                break
+            }
+        }
+
+        // Without the responses of the second peer, its records expire.
+        handle.cancel().await;
+        loop {
+            let (peer_id, addresses, source) = next_expiry(&mut swarm1).await;
+            if peer_id == peer2 {
+                assert_eq!(addresses, vec![addr2]);
+                assert_eq!(source, DiscoverySource::Mdns);
+                break
+            }
+        }
+    });
+}
+
+#[test]
+fn user_provided_addresses_are_dialable() {
+    let mut swarm1 = build_swarm();
+    let mut swarm2 = build_swarm();
+    let peer2 = *swarm2.local_peer_id();
+
+    let addr2: Multiaddr = Protocol::Memory(rand::random::<u64>()).into();
+    swarm2.listen_on(addr2.clone()).unwrap();
+    swarm1.behaviour_mut().add_address(&peer2, addr2);
+    swarm1.dial(&peer2).unwrap();
+
+    async_std::task::spawn(async move {
+        loop {
+            swarm2.next().await;
+        }
+    });
+
+    async_std::task::block_on(async move {
+        loop {
+            if let SwarmEvent::ConnectionEstablished { peer_id, .. } = swarm1.select_next_some().await {
+                assert_eq!(peer_id, peer2);
+                break
+            }
+        }
+    });
+}
+
+#[test]
+fn address_metadata_is_deduplicated() {
+    let mut swarm = build_swarm();
+    let peer = PeerId::random();
+    let addr: Multiaddr = Protocol::Memory(rand::random::<u64>()).into();
+
+    swarm.behaviour_mut().add_address(&peer, addr.clone());
+    swarm.behaviour_mut().kademlia_mut().unwrap().add_address(&peer, addr.clone());
+
+    let addresses = swarm.behaviour_mut().addresses_of_peer_with_metadata(&peer);
+    assert_eq!(addresses.len(), 1);
+    assert_eq!(addresses[0].0, addr);
+    assert_eq!(addresses[0].1.source, AddressSource::UserProvided);
+}
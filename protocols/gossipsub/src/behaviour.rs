@@ -51,7 +51,9 @@ use crate::error::{PublishError, SubscriptionError, ValidationError};
 use crate::gossip_promises::GossipPromises;
 use crate::handler::{GossipsubHandler, GossipsubHandlerIn, HandlerEvent};
 use crate::mcache::MessageCache;
-use crate::peer_score::{PeerScore, PeerScoreParams, PeerScoreThresholds, RejectReason};
+use crate::peer_score::{
+    PeerScore, PeerScoreDetails, PeerScoreParams, PeerScoreThresholds, RejectReason,
+};
 use crate::protocol::SIGNING_PREFIX;
 use crate::subscription_filter::{AllowAllSubscriptionFilter, TopicSubscriptionFilter};
 use crate::time_cache::{DuplicateCache, TimeCache};
@@ -139,6 +141,25 @@ pub enum GossipsubEvent {
         /// The topic it has subscribed from.
         topic: TopicHash,
     },
+    /// The delivery of messages for a topic has been paused via [`Gossipsub::pause_topic`].
+    TopicPaused {
+        /// The paused topic.
+        topic: TopicHash,
+    },
+    /// The delivery of messages for a topic has been resumed via [`Gossipsub::resume_topic`].
+    TopicResumed {
+        /// The resumed topic.
+        topic: TopicHash,
+    },
+    /// The number of received messages of a topic awaiting delivery to the application exceeded
+    /// the configured [`GossipsubConfig::message_queue_warn_threshold`], see
+    /// [`Gossipsub::message_queue_depth`].
+    QueueDepthWarning {
+        /// The topic of the queued messages.
+        topic: TopicHash,
+        /// The number of queued messages.
+        depth: usize,
+    },
 }
 
 /// A data structure for storing configuration for publishing messages. See [`MessageAuthenticity`]
@@ -216,6 +237,9 @@ pub struct Gossipsub<
     /// Events that need to be yielded to the outside when polling.
     events: VecDeque<GossipsubNetworkBehaviourAction>,
 
+    /// The number of [`GossipsubEvent::Message`]s per topic in `events`.
+    message_queue_depths: HashMap<TopicHash, usize>,
+
     /// Pools non-urgent control messages between heartbeats.
     control_pool: HashMap<PeerId, Vec<GossipsubControlAction>>,
 
@@ -247,6 +271,15 @@ pub struct Gossipsub<
     /// Overlay network of connected peers - Maps topics to connected gossipsub peers.
     mesh: HashMap<TopicHash, BTreeSet<PeerId>>,
 
+    /// The names of the topics we are subscribed to.
+    topic_names: HashMap<TopicHash, String>,
+
+    /// Subscribed topics for which received messages are not delivered to the application.
+    paused_topics: HashSet<TopicHash>,
+
+    /// Subscribed topics for which we don't join the mesh, see [`Gossipsub::subscribe_lazy`].
+    lazy_topics: HashSet<TopicHash>,
+
     /// Map of topics to list of peers that we publish to, but don't subscribe to.
     fanout: HashMap<TopicHash, BTreeSet<PeerId>>,
 
@@ -387,6 +420,7 @@ where
 
         Ok(Gossipsub {
             events: VecDeque::new(),
+            message_queue_depths: HashMap::new(),
             control_pool: HashMap::new(),
             publish_config: privacy.into(),
             duplicate_cache: DuplicateCache::new(config.duplicate_cache_time()),
@@ -396,6 +430,9 @@ where
             explicit_peers: HashSet::new(),
             blacklisted_peers: HashSet::new(),
             mesh: HashMap::new(),
+            topic_names: HashMap::new(),
+            paused_topics: HashSet::new(),
+            lazy_topics: HashSet::new(),
             fanout: HashMap::new(),
             fanout_last_pub: HashMap::new(),
             backoffs: BackoffStorage::new(
@@ -433,6 +470,20 @@ where
         self.mesh.keys()
     }
 
+    /// Returns the name of a topic we are subscribed to, e.g. to display the topic of a received
+    /// [`GossipsubMessage`], whose [`TopicHash`] may be a hash of the name.
+    pub fn topic_hash_to_str(&self, topic_hash: &TopicHash) -> Option<&str> {
+        self.topic_names.get(topic_hash).map(String::as_str)
+    }
+
+    /// Returns the number of received messages of the given topic that are awaiting delivery to
+    /// the application via [`GossipsubEvent::Message`].
+    ///
+    /// Applications may use this to shed load or to apply back-pressure upstream.
+    pub fn message_queue_depth(&self, topic: &TopicHash) -> usize {
+        self.message_queue_depths.get(topic).copied().unwrap_or(0)
+    }
+
     /// Lists all mesh peers for a certain topic hash.
     pub fn mesh_peers(&self, topic_hash: &TopicHash) -> impl Iterator<Item = &PeerId> {
         self.mesh
@@ -442,6 +493,20 @@ where
             .flatten()
     }
 
+    /// Returns the number of mesh peers for a certain topic hash.
+    pub fn mesh_size(&self, topic_hash: &TopicHash) -> usize {
+        self.mesh.get(topic_hash).map_or(0, |peers| peers.len())
+    }
+
+    /// Lists all fanout peers for a certain topic hash, i.e. the peers we publish to on a topic we
+    /// are not subscribed to.
+    pub fn fanout_peers(&self, topic_hash: &TopicHash) -> impl Iterator<Item = &PeerId> {
+        self.fanout
+            .get(topic_hash)
+            .into_iter()
+            .flat_map(|x| x.iter())
+    }
+
     /// Lists all mesh peers for all topics.
     pub fn all_mesh_peers(&self) -> impl Iterator<Item = &PeerId> {
         let mut res = BTreeSet::new();
@@ -470,11 +535,47 @@ where
             .map(|(score, ..)| score.score(peer_id))
     }
 
+    /// Returns the gossipsub score for a given peer broken down into its
+    /// individual components, if scoring is enabled.
+    pub fn peer_score_details(&self, peer_id: &PeerId) -> Option<PeerScoreDetails> {
+        self.peer_score
+            .as_ref()
+            .map(|(score, ..)| score.score_details(peer_id))
+    }
+
     /// Subscribe to a topic.
     ///
+    /// Subscribing to a topic that was subscribed to via [`Gossipsub::subscribe_lazy`] upgrades
+    /// the subscription, joining the mesh of the topic.
+    ///
     /// Returns [`Ok(true)`] if the subscription worked. Returns [`Ok(false)`] if we were already
     /// subscribed.
     pub fn subscribe<H: Hasher>(&mut self, topic: &Topic<H>) -> Result<bool, SubscriptionError> {
+        self.subscribe_with_mode(topic, false)
+    }
+
+    /// Subscribe to a topic without joining its mesh.
+    ///
+    /// The subscription is announced to all peers as usual, but we never graft peers into the
+    /// mesh of the topic and prune peers trying to graft us. Messages of the topic are only
+    /// received via gossip, i.e. by requesting messages advertised in `IHAVE`s with `IWANT`, and
+    /// are not forwarded to other peers. This is useful for nodes observing a topic without
+    /// contributing forwarding bandwidth.
+    ///
+    /// Returns [`Ok(true)`] if the subscription worked. Returns [`Ok(false)`] if we were already
+    /// subscribed, lazily or not. A regular subscription is not downgraded.
+    pub fn subscribe_lazy<H: Hasher>(
+        &mut self,
+        topic: &Topic<H>,
+    ) -> Result<bool, SubscriptionError> {
+        self.subscribe_with_mode(topic, true)
+    }
+
+    fn subscribe_with_mode<H: Hasher>(
+        &mut self,
+        topic: &Topic<H>,
+        lazy: bool,
+    ) -> Result<bool, SubscriptionError> {
         debug!("Subscribing to topic: {}", topic);
         let topic_hash = topic.hash();
         if !self.subscription_filter.can_subscribe(&topic_hash) {
@@ -482,6 +583,13 @@ where
         }
 
         if self.mesh.get(&topic_hash).is_some() {
+            if !lazy && self.lazy_topics.remove(&topic_hash) {
+                // upgrade the lazy subscription, the SUBSCRIBE has already been sent
+                debug!("Joining the mesh of lazily subscribed topic: {}", topic);
+                self.mesh.remove(&topic_hash);
+                self.join(&topic_hash);
+                return Ok(true);
+            }
             debug!("Topic: {} is already in the mesh.", topic);
             return Ok(false);
         }
@@ -506,9 +614,16 @@ where
             }
         }
 
-        // call JOIN(topic)
-        // this will add new peers to the mesh for the topic
-        self.join(&topic_hash);
+        if lazy {
+            // we only gossip on the topic, so don't add any peers to the mesh
+            self.lazy_topics.insert(topic_hash.clone());
+            self.mesh.insert(topic_hash, BTreeSet::new());
+        } else {
+            // call JOIN(topic)
+            // this will add new peers to the mesh for the topic
+            self.join(&topic_hash);
+        }
+        self.topic_names.insert(topic.hash(), topic.to_string());
         debug!("Subscribed to topic: {}", topic);
         Ok(true)
     }
@@ -548,11 +663,49 @@ where
         // call LEAVE(topic)
         // this will remove the topic from the mesh
         self.leave(&topic_hash);
+        self.topic_names.remove(&topic_hash);
+        self.paused_topics.remove(&topic_hash);
+        self.lazy_topics.remove(&topic_hash);
 
         debug!("Unsubscribed from topic: {:?}", topic_hash);
         Ok(true)
     }
 
+    /// Stops delivering received messages for a subscribed topic to the application, without
+    /// leaving the mesh of the topic. Messages received while the topic is paused are dropped.
+    ///
+    /// If message validation is enabled (see [`GossipsubConfig::validate_messages`]), messages
+    /// received while the topic is paused are not forwarded either, as they can not be validated
+    /// by the application.
+    ///
+    /// Returns `true` and emits [`GossipsubEvent::TopicPaused`] if we are subscribed to the topic
+    /// and it was not paused already.
+    pub fn pause_topic(&mut self, topic: TopicHash) -> bool {
+        if !self.mesh.contains_key(&topic) || !self.paused_topics.insert(topic.clone()) {
+            return false;
+        }
+        debug!("Paused topic: {:?}", topic);
+        self.events.push_back(NetworkBehaviourAction::GenerateEvent(
+            GossipsubEvent::TopicPaused { topic },
+        ));
+        true
+    }
+
+    /// Resumes the delivery of received messages for a topic paused via
+    /// [`Gossipsub::pause_topic`].
+    ///
+    /// Returns `true` and emits [`GossipsubEvent::TopicResumed`] if the topic was paused.
+    pub fn resume_topic(&mut self, topic: TopicHash) -> bool {
+        if !self.paused_topics.remove(&topic) {
+            return false;
+        }
+        debug!("Resumed topic: {:?}", topic);
+        self.events.push_back(NetworkBehaviourAction::GenerateEvent(
+            GossipsubEvent::TopicResumed { topic },
+        ));
+        true
+    }
+
     /// Publishes a message with multiple topics to the network.
     pub fn publish<H: Hasher>(
         &mut self,
@@ -560,21 +713,8 @@ where
         data: impl Into<Vec<u8>>,
     ) -> Result<MessageId, PublishError> {
         let data = data.into();
-
-        // Transform the data before building a raw_message.
-        let transformed_data = self
-            .data_transform
-            .outbound_transform(&topic.hash(), data.clone())?;
-
-        let raw_message = self.build_raw_message(topic.into(), transformed_data)?;
-
-        // calculate the message id from the un-transformed data
-        let msg_id = self.config.message_id(&GossipsubMessage {
-            source: raw_message.source,
-            data, // the uncompressed form
-            sequence_number: raw_message.sequence_number,
-            topic: raw_message.topic.clone(),
-        });
+        self.check_message_size(&data)?;
+        let (msg_id, raw_message) = self.build_published_message(topic.into(), data)?;
 
         let event = GossipsubRpc {
             subscriptions: Vec::new(),
@@ -584,8 +724,12 @@ where
         .into_protobuf();
 
         // check that the size doesn't exceed the max transmission size
-        if event.encoded_len() > self.config.max_transmit_size() {
-            return Err(PublishError::MessageTooLarge);
+        let encoded_len = event.encoded_len();
+        if encoded_len > self.config.max_transmit_size() {
+            return Err(PublishError::MessageTooLarge {
+                actual: encoded_len,
+                max: self.config.max_transmit_size(),
+            });
         }
 
         // Check the if the message has been published before
@@ -607,8 +751,167 @@ where
         let mesh_peers_sent =
             !self.config.flood_publish() && self.forward_msg(&msg_id, raw_message.clone(), None)?;
 
+        let recipient_peers = self.publish_recipients(&topic_hash);
+
+        if recipient_peers.is_empty() && !mesh_peers_sent {
+            return Err(PublishError::InsufficientPeers);
+        }
+
+        // If the message isn't a duplicate and we have sent it to some peers add it to the
+        // duplicate cache and memcache.
+        self.duplicate_cache.insert(msg_id.clone());
+        self.mcache.put(&msg_id, raw_message);
+
+        // If the message is anonymous or has a random author add it to the published message ids
+        // cache.
+        if let PublishConfig::RandomAuthor | PublishConfig::Anonymous = self.publish_config {
+            if !self.config.allow_self_origin() {
+                self.published_message_ids.insert(msg_id.clone());
+            }
+        }
+
+        // Send to peers we know are subscribed to the topic.
+        for peer_id in recipient_peers.iter() {
+            debug!("Sending message to peer: {:?}", peer_id);
+            self.send_message(*peer_id, event.clone())?;
+        }
+
+        debug!("Published message: {:?}", &msg_id);
+        Ok(msg_id)
+    }
+
+    /// Publishes multiple messages on a topic to the network, sending all of them to each peer
+    /// in a single RPC.
+    ///
+    /// The batch is published atomically: if any of the messages has been published before or
+    /// the RPC would exceed the maximum transmit size, none of them is published. Each message is
+    /// added to the duplicate cache and the message cache individually.
+    pub fn publish_batch<H: Hasher, M: Into<Vec<u8>>>(
+        &mut self,
+        topic: Topic<H>,
+        messages: impl IntoIterator<Item = M>,
+    ) -> Result<Vec<MessageId>, PublishError> {
+        let topic_hash = topic.hash();
+
+        let mut msg_ids = Vec::new();
+        let mut raw_messages = Vec::new();
+        for data in messages {
+            let data = data.into();
+            self.check_message_size(&data)?;
+            let (msg_id, raw_message) = self.build_published_message(topic_hash.clone(), data)?;
+            // Check if the message has been published before, possibly in the same batch.
+            if self.duplicate_cache.contains(&msg_id) || msg_ids.contains(&msg_id) {
+                warn!(
+                    "Not publishing a batch with an already published message. Msg-id {}",
+                    msg_id
+                );
+                return Err(PublishError::Duplicate);
+            }
+            msg_ids.push(msg_id);
+            raw_messages.push(raw_message);
+        }
+
+        if raw_messages.is_empty() {
+            return Ok(msg_ids);
+        }
+
+        let event = GossipsubRpc {
+            subscriptions: Vec::new(),
+            messages: raw_messages.clone(),
+            control_msgs: Vec::new(),
+        }
+        .into_protobuf();
+
+        // check that the size doesn't exceed the max transmission size
+        let encoded_len = event.encoded_len();
+        if encoded_len > self.config.max_transmit_size() {
+            return Err(PublishError::MessageTooLarge {
+                actual: encoded_len,
+                max: self.config.max_transmit_size(),
+            });
+        }
+
+        debug!("Publishing batch of {} messages", msg_ids.len());
+
+        let mut recipient_peers = self.publish_recipients(&topic_hash);
+        // If we are not flood publishing send the messages to mesh peers as well.
+        if !self.config.flood_publish() {
+            if let Some(mesh_peers) = self.mesh.get(&topic_hash) {
+                recipient_peers.extend(mesh_peers.iter().cloned());
+            }
+        }
+
+        if recipient_peers.is_empty() {
+            return Err(PublishError::InsufficientPeers);
+        }
+
+        for (msg_id, raw_message) in msg_ids.iter().zip(raw_messages) {
+            self.duplicate_cache.insert(msg_id.clone());
+            self.mcache.put(msg_id, raw_message);
+
+            // If the message is anonymous or has a random author add it to the published message
+            // ids cache.
+            if let PublishConfig::RandomAuthor | PublishConfig::Anonymous = self.publish_config {
+                if !self.config.allow_self_origin() {
+                    self.published_message_ids.insert(msg_id.clone());
+                }
+            }
+        }
+
+        for peer_id in recipient_peers.iter() {
+            debug!("Sending batch to peer: {:?}", peer_id);
+            self.send_message(*peer_id, event.clone())?;
+        }
+
+        debug!("Published batch: {:?}", msg_ids);
+        Ok(msg_ids)
+    }
+
+    /// Checks that the given un-transformed data of a message to publish does not exceed the
+    /// configured maximum message size.
+    fn check_message_size(&self, data: &[u8]) -> Result<(), PublishError> {
+        let max = self.config.max_message_size();
+        if data.len() > max {
+            return Err(PublishError::MessageTooLarge {
+                actual: data.len(),
+                max,
+            });
+        }
+        Ok(())
+    }
+
+    /// Builds a message published by us from the given un-transformed data, returning it together
+    /// with its [`MessageId`].
+    fn build_published_message(
+        &self,
+        topic_hash: TopicHash,
+        data: Vec<u8>,
+    ) -> Result<(MessageId, RawGossipsubMessage), PublishError> {
+        // Transform the data before building a raw_message.
+        let transformed_data = self
+            .data_transform
+            .outbound_transform(&topic_hash, data.clone())?;
+
+        let raw_message = self.build_raw_message(topic_hash, transformed_data)?;
+
+        // calculate the message id from the un-transformed data
+        let msg_id = self.config.message_id(&GossipsubMessage {
+            source: raw_message.source,
+            data, // the uncompressed form
+            sequence_number: raw_message.sequence_number,
+            topic: raw_message.topic.clone(),
+        });
+
+        Ok((msg_id, raw_message))
+    }
+
+    /// Returns the peers a message published on the given topic is sent to, apart from the mesh
+    /// peers of the topic if we are not flood publishing.
+    ///
+    /// If we are not subscribed to the topic, this selects the fanout peers of the topic.
+    fn publish_recipients(&mut self, topic_hash: &TopicHash) -> HashSet<PeerId> {
         let mut recipient_peers = HashSet::new();
-        if let Some(set) = self.topic_peers.get(&topic_hash) {
+        if let Some(set) = self.topic_peers.get(topic_hash) {
             if self.config.flood_publish() {
                 // Forward to all peers above score and all explicit peers
                 recipient_peers.extend(
@@ -639,11 +942,11 @@ where
                 }
 
                 // Gossipsub peers
-                if self.mesh.get(&topic_hash).is_none() {
+                if !self.mesh.contains_key(topic_hash) {
                     debug!("Topic: {:?} not in the mesh", topic_hash);
                     // If we have fanout peers add them to the map.
-                    if self.fanout.contains_key(&topic_hash) {
-                        for peer in self.fanout.get(&topic_hash).expect("Topic must exist") {
+                    if self.fanout.contains_key(topic_hash) {
+                        for peer in self.fanout.get(topic_hash).expect("Topic must exist") {
                             recipient_peers.insert(*peer);
                         }
                     } else {
@@ -652,7 +955,7 @@ where
                         let new_peers = get_random_peers(
                             &self.topic_peers,
                             &self.connected_peers,
-                            &topic_hash,
+                            topic_hash,
                             mesh_n,
                             {
                                 |p| {
@@ -677,31 +980,7 @@ where
             }
         }
 
-        if recipient_peers.is_empty() && !mesh_peers_sent {
-            return Err(PublishError::InsufficientPeers);
-        }
-
-        // If the message isn't a duplicate and we have sent it to some peers add it to the
-        // duplicate cache and memcache.
-        self.duplicate_cache.insert(msg_id.clone());
-        self.mcache.put(&msg_id, raw_message);
-
-        // If the message is anonymous or has a random author add it to the published message ids
-        // cache.
-        if let PublishConfig::RandomAuthor | PublishConfig::Anonymous = self.publish_config {
-            if !self.config.allow_self_origin() {
-                self.published_message_ids.insert(msg_id.clone());
-            }
-        }
-
-        // Send to peers we know are subscribed to the topic.
-        for peer_id in recipient_peers.iter() {
-            debug!("Sending message to peer: {:?}", peer_id);
-            self.send_message(*peer_id, event.clone())?;
-        }
-
-        debug!("Published message: {:?}", &msg_id);
-        Ok(msg_id)
+        recipient_peers
     }
 
     /// This function should be called when [`GossipsubConfig::validate_messages()`] is `true` after
@@ -1252,6 +1531,16 @@ where
             let (below_zero, score) = self.score_below_threshold(peer_id, |_| 0.0);
             let now = Instant::now();
             for topic_hash in topics {
+                // we don't GRAFT peers into the mesh of topics we lazily subscribed to
+                if self.lazy_topics.contains(&topic_hash) {
+                    debug!(
+                        "GRAFT: Pruning peer {:?} for lazily subscribed topic {:?}",
+                        peer_id, &topic_hash
+                    );
+                    to_prune_topics.insert(topic_hash);
+                    continue;
+                }
+
                 if let Some(peers) = self.mesh.get_mut(&topic_hash) {
                     // if the peer is already in the mesh ignore the graft
                     if peers.contains(peer_id) {
@@ -1351,7 +1640,8 @@ where
             // build the prune messages to send
             let prune_messages = to_prune_topics
                 .iter()
-                .map(|t| self.make_prune(t, peer_id, do_px))
+                // no PX for lazily subscribed topics, as we are not part of their mesh
+                .map(|t| self.make_prune(t, peer_id, do_px && !self.lazy_topics.contains(t)))
                 .collect();
             // Send the prune messages to the peer
             debug!(
@@ -1655,8 +1945,11 @@ where
         self.mcache.put(&msg_id, raw_message.clone());
 
         // Dispatch the message to the user if we are subscribed to any of the topics
-        if self.mesh.contains_key(&message.topic) {
+        if self.paused_topics.contains(&message.topic) {
+            debug!("Dropping message on paused topic: {:?}", message.topic);
+        } else if self.mesh.contains_key(&message.topic) {
             debug!("Sending received message to user");
+            let topic = message.topic.clone();
             self.events.push_back(NetworkBehaviourAction::GenerateEvent(
                 GossipsubEvent::Message {
                     propagation_source: *propagation_source,
@@ -1664,6 +1957,19 @@ where
                     message,
                 },
             ));
+            let depth = {
+                let depth = self.message_queue_depths.entry(topic.clone()).or_insert(0);
+                *depth += 1;
+                *depth
+            };
+            // Warn once whenever the queue of the topic grows beyond the threshold.
+            if let Some(threshold) = self.config.message_queue_warn_threshold() {
+                if depth == threshold + 1 {
+                    self.events.push_back(NetworkBehaviourAction::GenerateEvent(
+                        GossipsubEvent::QueueDepthWarning { topic, depth },
+                    ));
+                }
+            }
         } else {
             debug!(
                 "Received message on a topic we are not subscribed to: {:?}",
@@ -1793,6 +2099,7 @@ where
                         && !self
                             .backoffs
                             .is_backoff_with_slack(&subscription.topic_hash, propagation_source)
+                        && !self.lazy_topics.contains(&subscription.topic_hash)
                     {
                         if let Some(peers) = self.mesh.get_mut(&subscription.topic_hash) {
                             if peers.len() < self.config.mesh_n_low()
@@ -1943,6 +2250,11 @@ where
 
         // maintain the mesh for each topic
         for (topic_hash, peers) in self.mesh.iter_mut() {
+            // the mesh of lazily subscribed topics stays empty
+            if self.lazy_topics.contains(topic_hash) {
+                continue;
+            }
+
             let explicit_peers = &self.explicit_peers;
             let backoffs = &self.backoffs;
             let topic_peers = &self.topic_peers;
@@ -2697,7 +3009,10 @@ where
                     // This should not be possible. All received and published messages have already
                     // been vetted to fit within the size.
                     error!("Individual message too large to fragment");
-                    return Err(PublishError::MessageTooLarge);
+                    return Err(PublishError::MessageTooLarge {
+                        actual: object_size + 2,
+                        max: self.config.max_transmit_size(),
+                    });
                 }
 
                 create_or_add_rpc!(object_size);
@@ -2801,6 +3116,8 @@ where
             self.config.validation_mode().clone(),
             self.config.idle_timeout(),
             self.config.support_floodsub(),
+            Arc::clone(self.config.topic_priorities()),
+            self.config.max_send_queue_size(),
         )
     }
 
@@ -3191,6 +3508,14 @@ where
                     }
                 }
                 NetworkBehaviourAction::GenerateEvent(e) => {
+                    if let GossipsubEvent::Message { message, .. } = &e {
+                        if let Some(depth) = self.message_queue_depths.get_mut(&message.topic) {
+                            *depth -= 1;
+                            if *depth == 0 {
+                                self.message_queue_depths.remove(&message.topic);
+                            }
+                        }
+                    }
                     NetworkBehaviourAction::GenerateEvent(e)
                 }
                 NetworkBehaviourAction::DialAddress { address } => {
@@ -3199,12 +3524,27 @@ where
                 NetworkBehaviourAction::DialPeer { peer_id, condition } => {
                     NetworkBehaviourAction::DialPeer { peer_id, condition }
                 }
-                NetworkBehaviourAction::ReportObservedAddr { address, score } => {
-                    NetworkBehaviourAction::ReportObservedAddr { address, score }
-                }
+                NetworkBehaviourAction::ReportObservedAddr {
+                    address,
+                    score,
+                    observer,
+                } => NetworkBehaviourAction::ReportObservedAddr {
+                    address,
+                    score,
+                    observer,
+                },
                 NetworkBehaviourAction::CloseConnection { peer_id, connection } => {
                     NetworkBehaviourAction::CloseConnection { peer_id, connection }
                 }
+                NetworkBehaviourAction::TagConnection { connection, tag } => {
+                    NetworkBehaviourAction::TagConnection { connection, tag }
+                }
+                NetworkBehaviourAction::SpawnTask(task) => {
+                    NetworkBehaviourAction::SpawnTask(task)
+                }
+                NetworkBehaviourAction::SetTimer { timer_id, duration } => {
+                    NetworkBehaviourAction::SetTimer { timer_id, duration }
+                }
             });
         }
 
@@ -647,6 +647,120 @@ mod tests {
         );
     }
 
+    /// Test that messages exceeding the maximum message size are rejected.
+    #[test]
+    fn test_publish_message_too_large() {
+        let config = GossipsubConfig::default();
+        let max = config.max_message_size();
+        let publish_topic = String::from("test_publish_too_large");
+        let (mut gs, _, _) = inject_nodes1()
+            .peer_no(20)
+            .topics(vec![publish_topic.clone()])
+            .to_subscribe(true)
+            .gs_config(config)
+            .create_network();
+        gs.events.clear();
+
+        match gs.publish(Topic::new(publish_topic.clone()), vec![0; max + 1]) {
+            Err(PublishError::MessageTooLarge { actual, max: m }) => {
+                assert_eq!(actual, max + 1);
+                assert_eq!(m, max);
+            }
+            other => panic!("Expected MessageTooLarge, got {:?}", other),
+        }
+        assert!(gs.events.is_empty(), "No message should be sent");
+
+        // Smaller limits are enforced on the un-encoded data.
+        let config = GossipsubConfigBuilder::default()
+            .max_message_size(100)
+            .build()
+            .unwrap();
+        let (mut gs, _, _) = inject_nodes1()
+            .peer_no(20)
+            .topics(vec![publish_topic.clone()])
+            .to_subscribe(true)
+            .gs_config(config)
+            .create_network();
+        assert!(matches!(
+            gs.publish(Topic::new(publish_topic.clone()), vec![0; 101]),
+            Err(PublishError::MessageTooLarge {
+                actual: 101,
+                max: 100
+            })
+        ));
+        assert!(matches!(
+            gs.publish_batch(
+                Topic::new(publish_topic.clone()),
+                vec![vec![0; 1], vec![1; 101]]
+            ),
+            Err(PublishError::MessageTooLarge {
+                actual: 101,
+                max: 100
+            })
+        ));
+        assert!(gs.publish(Topic::new(publish_topic), vec![0; 100]).is_ok());
+    }
+
+    /// Test publishing a batch of messages in a single RPC per peer.
+    #[test]
+    fn test_publish_batch() {
+        let config = GossipsubConfigBuilder::default()
+            .flood_publish(false)
+            .message_id_fn(|m| MessageId::new(&m.data))
+            .build()
+            .unwrap();
+
+        let publish_topic = String::from("test_publish_batch");
+        let (mut gs, _, topic_hashes) = inject_nodes1()
+            .peer_no(20)
+            .topics(vec![publish_topic.clone()])
+            .to_subscribe(true)
+            .gs_config(config)
+            .create_network();
+        let mesh_peers = gs.mesh.get(&topic_hashes[0]).unwrap().clone();
+        gs.events.clear();
+
+        let batch = (0..10u8).map(|i| vec![i; 42]).collect::<Vec<_>>();
+        let msg_ids = gs
+            .publish_batch(Topic::new(publish_topic.clone()), batch.clone())
+            .unwrap();
+        assert_eq!(msg_ids.len(), 10);
+
+        // Collect all RPCs containing messages, by recipient.
+        let rpcs = gs
+            .events
+            .iter()
+            .filter_map(|e| match e {
+                NetworkBehaviourAction::NotifyHandler { peer_id, event, .. } => match **event {
+                    GossipsubHandlerIn::Message(ref message) => {
+                        Some((*peer_id, proto_to_message(message)))
+                    }
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(rpcs.len(), mesh_peers.len(), "Should send a single RPC to each mesh peer");
+        for (peer_id, rpc) in rpcs {
+            assert!(mesh_peers.contains(&peer_id));
+            assert_eq!(rpc.messages.len(), 10, "RPC should contain the whole batch");
+        }
+
+        for msg_id in &msg_ids {
+            assert!(
+                gs.mcache.get(msg_id).is_some(),
+                "Message cache should contain each published message"
+            );
+        }
+
+        // Publishing any of the messages again fails for the whole batch.
+        assert!(matches!(
+            gs.publish_batch(Topic::new(publish_topic), vec![vec![42; 42], batch[0].clone()]),
+            Err(PublishError::Duplicate)
+        ));
+    }
+
     /// Test local node publish to unsubscribed topic
     #[test]
     fn test_fanout() {
@@ -2096,6 +2210,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_topic_hash_to_str() {
+        let (mut gs, _, _) = inject_nodes1()
+            .peer_no(5)
+            .topics(vec![])
+            .to_subscribe(true)
+            .create_network();
+
+        let topic = crate::Sha256Topic::new("named topic");
+        assert!(gs.subscribe(&topic).unwrap());
+        assert_eq!(gs.topic_hash_to_str(&topic.hash()), Some("named topic"));
+
+        let raw_message = RawGossipsubMessage {
+            source: Some(PeerId::random()),
+            data: vec![1, 2, 3],
+            sequence_number: Some(0),
+            topic: topic.hash(),
+            signature: None,
+            key: None,
+            validated: true,
+        };
+        gs.handle_received_message(raw_message, &PeerId::random());
+
+        let message = gs
+            .events
+            .iter()
+            .find_map(|e| match e {
+                NetworkBehaviourAction::GenerateEvent(GossipsubEvent::Message {
+                    message, ..
+                }) => Some(message.clone()),
+                _ => None,
+            })
+            .expect("The message to be delivered");
+        assert_ne!(message.topic.as_str(), "named topic");
+        assert_eq!(gs.topic_hash_to_str(&message.topic), Some("named topic"));
+
+        assert!(gs.unsubscribe(&topic).unwrap());
+        assert_eq!(gs.topic_hash_to_str(&topic.hash()), None);
+    }
+
     #[test]
     fn test_gossip_to_at_least_gossip_lazy_peers() {
         let config: GossipsubConfig = GossipsubConfig::default();
@@ -4217,6 +4371,54 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_peer_score_details() {
+        let config = GossipsubConfigBuilder::default()
+            .prune_backoff(Duration::from_millis(200))
+            .graft_flood_threshold(Duration::from_millis(100))
+            .build()
+            .unwrap();
+        let peer_score_params = PeerScoreParams {
+            behaviour_penalty_weight: -2.0,
+            ..Default::default()
+        };
+
+        let (mut gs, peers, topics) = inject_nodes1()
+            .peer_no(2)
+            .topics(vec!["test".into()])
+            .to_subscribe(true)
+            .gs_config(config)
+            .explicit(0)
+            .outbound(0)
+            .scoring(Some((peer_score_params, PeerScoreThresholds::default())))
+            .create_network();
+
+        assert_eq!(gs.peer_score(&peers[0]), Some(0.0));
+        assert_eq!(
+            gs.peer_score_details(&peers[0]),
+            Some(PeerScoreDetails::default())
+        );
+
+        // prune the first peer and let it graft during its backoff
+        gs.mesh.get_mut(&topics[0]).unwrap().remove(&peers[0]);
+        gs.send_graft_prune(
+            HashMap::new(),
+            vec![(peers[0], vec![topics[0].clone()])]
+                .into_iter()
+                .collect(),
+            HashSet::new(),
+        );
+        gs.handle_graft(&peers[0], vec![topics[0].clone()]);
+
+        let details = gs.peer_score_details(&peers[0]).unwrap();
+        assert_eq!(details.behaviour_penalty, 4.0 * -2.0);
+        assert_eq!(details.score, 4.0 * -2.0);
+        assert_eq!(gs.peer_score(&peers[0]), Some(details.score));
+
+        // the well-behaved peer is not penalized
+        assert_eq!(gs.peer_score(&peers[1]), Some(0.0));
+    }
+
     #[test]
     fn test_opportunistic_grafting() {
         let config = GossipsubConfigBuilder::default()
@@ -5228,4 +5430,293 @@ mod tests {
         //nobody got penalized
         assert!(gs1.peer_score.as_ref().unwrap().0.score(&p2) >= original_score);
     }
+
+    #[test]
+    fn test_pause_and_resume_topic() {
+        let (mut gs, peers, topic_hashes) = inject_nodes1()
+            .peer_no(20)
+            .topics(vec!["topic".into()])
+            .to_subscribe(true)
+            .create_network();
+        let topic = topic_hashes[0].clone();
+        let mesh = gs.mesh.get(&topic).unwrap().clone();
+
+        let count_messages = |gs: &mut Gossipsub| {
+            gs.events
+                .drain(..)
+                .filter(|e| matches!(e, NetworkBehaviourAction::GenerateEvent(GossipsubEvent::Message { .. })))
+                .count()
+        };
+
+        assert!(gs.pause_topic(topic.clone()));
+        assert!(!gs.pause_topic(topic.clone()), "Topic is already paused");
+        assert!(
+            gs.events.iter().any(|e| matches!(
+                e,
+                NetworkBehaviourAction::GenerateEvent(GossipsubEvent::TopicPaused { topic: t }) if t == &topic
+            )),
+            "Expected a TopicPaused event"
+        );
+        count_messages(&mut gs);
+
+        let mut seq = 0;
+        gs.handle_received_message(random_message(&mut seq, &topic_hashes), &peers[0]);
+        assert_eq!(count_messages(&mut gs), 0, "Messages on paused topics are dropped");
+        assert_eq!(gs.mesh.get(&topic), Some(&mesh), "Pausing keeps the mesh intact");
+
+        assert!(gs.resume_topic(topic.clone()));
+        assert!(!gs.resume_topic(topic.clone()), "Topic is not paused anymore");
+        assert!(
+            gs.events.iter().any(|e| matches!(
+                e,
+                NetworkBehaviourAction::GenerateEvent(GossipsubEvent::TopicResumed { topic: t }) if t == &topic
+            )),
+            "Expected a TopicResumed event"
+        );
+
+        gs.handle_received_message(random_message(&mut seq, &topic_hashes), &peers[0]);
+        assert_eq!(count_messages(&mut gs), 1, "Messages are delivered after resuming");
+
+        // Only subscribed topics can be paused.
+        assert!(!gs.pause_topic(TopicHash::from_raw("unsubscribed")));
+    }
+
+    #[test]
+    fn test_message_queue_depth() {
+        let config = GossipsubConfigBuilder::default()
+            .message_queue_warn_threshold(Some(5))
+            .build()
+            .unwrap();
+        let (mut gs, peers, topic_hashes) = inject_nodes1()
+            .peer_no(20)
+            .topics(vec!["topic".into()])
+            .to_subscribe(true)
+            .gs_config(config)
+            .create_network();
+        let topic = topic_hashes[0].clone();
+        gs.events.clear();
+
+        // The application does not poll the behaviour in the meantime.
+        let mut seq = 0;
+        for _ in 0..10 {
+            gs.handle_received_message(random_message(&mut seq, &topic_hashes), &peers[0]);
+        }
+        assert_eq!(gs.message_queue_depth(&topic), 10);
+        assert_eq!(gs.message_queue_depth(&TopicHash::from_raw("other")), 0);
+
+        let warnings = gs
+            .events
+            .iter()
+            .filter_map(|e| match e {
+                NetworkBehaviourAction::GenerateEvent(GossipsubEvent::QueueDepthWarning {
+                    topic: t,
+                    depth,
+                }) if t == &topic => Some(*depth),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            warnings,
+            vec![6],
+            "Expected a single warning when exceeding the threshold"
+        );
+
+        // Delivering messages to the application reduces the depth.
+        let mut params = DummyPollParameters(PeerId::random());
+        let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+        let mut delivered = 0;
+        while let Poll::Ready(event) = gs.poll(&mut cx, &mut params) {
+            if let NetworkBehaviourAction::GenerateEvent(GossipsubEvent::Message { .. }) = event {
+                delivered += 1;
+                assert_eq!(gs.message_queue_depth(&topic), 10 - delivered);
+            }
+        }
+        assert_eq!(delivered, 10);
+        assert_eq!(gs.message_queue_depth(&topic), 0);
+    }
+
+    struct DummyPollParameters(PeerId);
+
+    impl PollParameters for DummyPollParameters {
+        type SupportedProtocolsIter = std::vec::IntoIter<Vec<u8>>;
+        type ListenedAddressesIter = std::vec::IntoIter<Multiaddr>;
+        type ExternalAddressesIter = std::vec::IntoIter<libp2p_swarm::AddressRecord>;
+
+        fn supported_protocols(&self) -> Self::SupportedProtocolsIter {
+            Vec::new().into_iter()
+        }
+
+        fn listened_addresses(&self) -> Self::ListenedAddressesIter {
+            Vec::new().into_iter()
+        }
+
+        fn external_addresses(&self) -> Self::ExternalAddressesIter {
+            Vec::new().into_iter()
+        }
+
+        fn local_peer_id(&self) -> &PeerId {
+            &self.0
+        }
+    }
+
+    #[test]
+    fn test_subscribe_lazy() {
+        let (mut gs, _, _) = inject_nodes1().create_network();
+        let topic = Topic::new("lazy");
+        let topic_hashes = vec![topic.hash()];
+
+        assert!(gs.subscribe_lazy(&topic).unwrap());
+        assert!(!gs.subscribe_lazy(&topic).unwrap(), "Already subscribed");
+
+        // Subscribing peers are not added to the mesh, neither on subscription nor on heartbeat.
+        let peers = (0..20)
+            .map(|_| add_peer(&mut gs, &topic_hashes, false, false))
+            .collect::<Vec<_>>();
+        gs.heartbeat();
+        assert_eq!(gs.mesh.get(&topic_hashes[0]), Some(&BTreeSet::new()));
+        assert_eq!(
+            count_control_msgs(&gs, |_, m| matches!(m, GossipsubControlAction::Graft { .. })),
+            0,
+            "Lazy subscribers never graft"
+        );
+
+        // Peers trying to graft us get pruned.
+        gs.handle_graft(&peers[0], topic_hashes.clone());
+        assert!(gs.mesh.get(&topic_hashes[0]).unwrap().is_empty());
+        assert_eq!(
+            count_control_msgs(&gs, |p, m| p == &peers[0]
+                && matches!(m, GossipsubControlAction::Prune { .. })),
+            1
+        );
+        flush_events(&mut gs);
+
+        // Messages advertised via IHAVE are requested.
+        let mut seq = 0;
+        let message = random_message(&mut seq, &topic_hashes);
+        let msg_id = gs.config.message_id(
+            &gs.data_transform
+                .inbound_transform(message.clone())
+                .unwrap(),
+        );
+        gs.handle_ihave(&peers[1], vec![(topic_hashes[0].clone(), vec![msg_id.clone()])]);
+        assert_eq!(
+            count_control_msgs(&gs, |p, m| p == &peers[1]
+                && matches!(m, GossipsubControlAction::IWant { message_ids } if message_ids == &vec![msg_id.clone()])),
+            1,
+            "Expected an IWANT for the advertised message"
+        );
+        flush_events(&mut gs);
+
+        // The message is delivered to the application but not forwarded.
+        gs.handle_received_message(message, &peers[1]);
+        assert!(gs.events.iter().any(|e| matches!(
+            e,
+            NetworkBehaviourAction::GenerateEvent(GossipsubEvent::Message { message_id, .. })
+                if message_id == &msg_id
+        )));
+        assert!(!gs.events.iter().any(|e| matches!(
+            e,
+            NetworkBehaviourAction::NotifyHandler { event, .. }
+                if matches!(**event, GossipsubHandlerIn::Message(ref m) if !m.publish.is_empty())
+        )));
+    }
+
+    #[test]
+    fn test_subscribe_upgrades_lazy_subscription() {
+        let (mut gs, _, _) = inject_nodes1().create_network();
+        let topic = Topic::new("lazy");
+        let topic_hashes = vec![topic.hash()];
+
+        assert!(gs.subscribe_lazy(&topic).unwrap());
+        for _ in 0..gs.config.mesh_n() {
+            add_peer(&mut gs, &topic_hashes, false, false);
+        }
+        assert!(gs.mesh.get(&topic_hashes[0]).unwrap().is_empty());
+        flush_events(&mut gs);
+
+        // A regular subscription joins the mesh without announcing the subscription again.
+        assert!(gs.subscribe(&topic).unwrap());
+        assert!(!gs.lazy_topics.contains(&topic_hashes[0]));
+        assert_eq!(gs.mesh.get(&topic_hashes[0]).unwrap().len(), gs.config.mesh_n());
+        assert_eq!(
+            count_control_msgs(&gs, |_, m| matches!(m, GossipsubControlAction::Graft { .. })),
+            gs.config.mesh_n()
+        );
+        assert!(!gs.events.iter().any(|e| matches!(
+            e,
+            NetworkBehaviourAction::NotifyHandler { event, .. }
+                if matches!(**event, GossipsubHandlerIn::Message(ref m) if !m.subscriptions.is_empty())
+        )));
+
+        // A regular subscription is not downgraded.
+        assert!(!gs.subscribe_lazy(&topic).unwrap());
+        assert!(!gs.lazy_topics.contains(&topic_hashes[0]));
+    }
+
+    #[test]
+    fn test_no_px_on_graft_for_lazy_topic_only() {
+        let config = GossipsubConfigBuilder::default()
+            .do_px()
+            .prune_peers(16)
+            .build()
+            .unwrap();
+        let (mut gs, peers, topics) = inject_nodes1()
+            .peer_no(config.mesh_n_high())
+            .topics(vec!["full".into()])
+            .to_subscribe(true)
+            .gs_config(config)
+            .create_network();
+        let lazy = Topic::new("lazy");
+        gs.subscribe_lazy(&lazy).unwrap();
+
+        // The mesh of the regular topic is full, so a GRAFT for it is answered with PX.
+        gs.mesh.insert(topics[0].clone(), peers.iter().cloned().collect());
+        let peer = add_peer(&mut gs, &vec![topics[0].clone(), lazy.hash()], false, false);
+        flush_events(&mut gs);
+        gs.handle_graft(&peer, vec![topics[0].clone(), lazy.hash()]);
+
+        let prune_peers = |topic: &TopicHash| {
+            let mut px = None;
+            count_control_msgs(&gs, |p, m| {
+                if let GossipsubControlAction::Prune { topic_hash, peers, .. } = m {
+                    if p == &peer && topic_hash == topic {
+                        px = Some(peers.len());
+                    }
+                }
+                false
+            });
+            px.expect("Expected a PRUNE for the topic")
+        };
+        assert!(prune_peers(&topics[0]) > 0);
+        assert_eq!(prune_peers(&lazy.hash()), 0);
+    }
+
+    #[test]
+    fn test_mesh_and_fanout_peers() {
+        let config = GossipsubConfigBuilder::default()
+            .flood_publish(false)
+            .build()
+            .unwrap();
+        let (mut gs, _, topic_hashes) = inject_nodes1()
+            .peer_no(20)
+            .topics(vec!["mesh".into()])
+            .to_subscribe(true)
+            .gs_config(config.clone())
+            .create_network();
+        gs.heartbeat();
+
+        let mesh_size = gs.mesh_size(&topic_hashes[0]);
+        assert!(mesh_size >= config.mesh_n_low() && mesh_size <= config.mesh_n_high());
+        assert_eq!(gs.mesh_peers(&topic_hashes[0]).count(), mesh_size);
+        assert_eq!(gs.fanout_peers(&topic_hashes[0]).count(), 0);
+
+        // Publishing on a topic we are not subscribed to selects fanout peers.
+        let fanout_topic = Topic::new("fanout");
+        for _ in 0..20 {
+            add_peer(&mut gs, &vec![fanout_topic.hash()], false, false);
+        }
+        gs.publish(fanout_topic.clone(), vec![0; 42]).unwrap();
+        assert_eq!(gs.mesh_size(&fanout_topic.hash()), 0);
+        assert_eq!(gs.fanout_peers(&fanout_topic.hash()).count(), config.mesh_n());
+    }
 }
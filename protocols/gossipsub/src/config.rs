@@ -19,11 +19,16 @@
 // DEALINGS IN THE SOFTWARE.
 
 use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Duration;
 
 use libp2p_core::PeerId;
 
-use crate::types::{FastMessageId, GossipsubMessage, MessageId, RawGossipsubMessage};
+use crate::types::{
+    FastMessageId, GossipsubMessage, MessageId, MessagePriority, RawGossipsubMessage,
+};
+use crate::TopicHash;
 
 /// The types of message validation that can be employed by gossipsub.
 #[derive(Debug, Clone)]
@@ -65,6 +70,7 @@ pub struct GossipsubConfig {
     fanout_ttl: Duration,
     check_explicit_peers_ticks: u64,
     max_transmit_size: usize,
+    max_message_size: Option<usize>,
     idle_timeout: Duration,
     duplicate_cache_time: Duration,
     validate_messages: bool,
@@ -88,6 +94,9 @@ pub struct GossipsubConfig {
     iwant_followup_time: Duration,
     support_floodsub: bool,
     published_message_ids_cache_time: Duration,
+    topic_priorities: Arc<HashMap<TopicHash, MessagePriority>>,
+    max_send_queue_size: Option<usize>,
+    message_queue_warn_threshold: Option<usize>,
 }
 
 impl GossipsubConfig {
@@ -182,6 +191,14 @@ impl GossipsubConfig {
         self.max_transmit_size
     }
 
+    /// The maximum size of the data of a published message, checked before the message is
+    /// transformed, signed and encoded. Publishing larger messages fails with
+    /// [`PublishError::MessageTooLarge`](crate::error::PublishError::MessageTooLarge). Defaults to
+    /// the [`max_transmit_size`](Self::max_transmit_size).
+    pub fn max_message_size(&self) -> usize {
+        self.max_message_size.unwrap_or(self.max_transmit_size)
+    }
+
     /// The time a connection is maintained to a peer without being in the mesh and without
     /// send/receiving a message from. Connections that idle beyond this timeout are disconnected.
     /// Default is 120 seconds.
@@ -327,6 +344,35 @@ impl GossipsubConfig {
         self.max_messages_per_rpc
     }
 
+    /// The priority with which messages published on the given topic are sent to peers. The
+    /// default is [`MessagePriority::Normal`].
+    pub fn topic_priority(&self, topic: &TopicHash) -> MessagePriority {
+        self.topic_priorities
+            .get(topic)
+            .copied()
+            .unwrap_or(MessagePriority::Normal)
+    }
+
+    /// The priorities of all topics with a priority other than the default, shared with the
+    /// connection handlers.
+    pub fn topic_priorities(&self) -> &Arc<HashMap<TopicHash, MessagePriority>> {
+        &self.topic_priorities
+    }
+
+    /// The maximum number of RPCs queued for sending to a peer per [`MessagePriority`]. RPCs
+    /// exceeding this limit are dropped, unless they carry subscriptions or control messages. If
+    /// this is unset, there is no limit. The default is None.
+    pub fn max_send_queue_size(&self) -> Option<usize> {
+        self.max_send_queue_size
+    }
+
+    /// The number of received messages of a topic awaiting delivery to the application, above
+    /// which a [`GossipsubEvent::QueueDepthWarning`](crate::GossipsubEvent::QueueDepthWarning) is
+    /// emitted. If this is unset, no warnings are emitted. The default is None.
+    pub fn message_queue_warn_threshold(&self) -> Option<usize> {
+        self.message_queue_warn_threshold
+    }
+
     /// The maximum number of messages to include in an IHAVE message.
     /// Also controls the maximum number of IHAVE ids we will accept and request with IWANT from a
     /// peer within a heartbeat, to protect from IHAVE floods. You should adjust this value from the
@@ -392,6 +438,7 @@ impl Default for GossipsubConfigBuilder {
                 fanout_ttl: Duration::from_secs(60),
                 check_explicit_peers_ticks: 300,
                 max_transmit_size: 65536,
+                max_message_size: None,
                 idle_timeout: Duration::from_secs(120),
                 duplicate_cache_time: Duration::from_secs(60),
                 validate_messages: false,
@@ -428,6 +475,9 @@ impl Default for GossipsubConfigBuilder {
                 iwant_followup_time: Duration::from_secs(3),
                 support_floodsub: false,
                 published_message_ids_cache_time: Duration::from_secs(10),
+                topic_priorities: Arc::new(HashMap::new()),
+                max_send_queue_size: None,
+                message_queue_warn_threshold: None,
             },
         }
     }
@@ -533,6 +583,14 @@ impl GossipsubConfigBuilder {
         self
     }
 
+    /// The maximum size of the data of a published message, checked before the message is
+    /// transformed, signed and encoded. Must not exceed the maximum transmission size, which is
+    /// also the default.
+    pub fn max_message_size(&mut self, max_message_size: usize) -> &mut Self {
+        self.config.max_message_size = Some(max_message_size);
+        self
+    }
+
     /// The time a connection is maintained to a peer without being in the mesh and without
     /// send/receiving a message from. Connections that idle beyond this timeout are disconnected.
     /// Default is 120 seconds.
@@ -689,6 +747,36 @@ impl GossipsubConfigBuilder {
         self
     }
 
+    /// Sets the priority with which messages published on the given topic are sent to peers.
+    ///
+    /// RPCs are sent with the highest priority of the topics of the messages they carry, or with
+    /// [`MessagePriority::Normal`] if they carry no messages. The default for all topics is
+    /// [`MessagePriority::Normal`].
+    pub fn topic_priority(&mut self, topic: TopicHash, priority: MessagePriority) -> &mut Self {
+        if priority == MessagePriority::Normal {
+            Arc::make_mut(&mut self.config.topic_priorities).remove(&topic);
+        } else {
+            Arc::make_mut(&mut self.config.topic_priorities).insert(topic, priority);
+        }
+        self
+    }
+
+    /// The maximum number of RPCs queued for sending to a peer per [`MessagePriority`]. RPCs
+    /// exceeding this limit are dropped, unless they carry subscriptions or control messages. If
+    /// this is unset, there is no limit. The default is None.
+    pub fn max_send_queue_size(&mut self, max: Option<usize>) -> &mut Self {
+        self.config.max_send_queue_size = max;
+        self
+    }
+
+    /// The number of received messages of a topic awaiting delivery to the application, above
+    /// which a [`GossipsubEvent::QueueDepthWarning`](crate::GossipsubEvent::QueueDepthWarning) is
+    /// emitted. If this is unset, no warnings are emitted. The default is None.
+    pub fn message_queue_warn_threshold(&mut self, threshold: Option<usize>) -> &mut Self {
+        self.config.message_queue_warn_threshold = threshold;
+        self
+    }
+
     /// The maximum number of messages to include in an IHAVE message.
     /// Also controls the maximum number of IHAVE ids we will accept and request with IWANT from a
     /// peer within a heartbeat, to protect from IHAVE floods. You should adjust this value from the
@@ -745,6 +833,10 @@ impl GossipsubConfigBuilder {
             return Err("The maximum transmission size must be greater than 100 to permit basic control messages");
         }
 
+        if self.config.max_message_size() > self.config.max_transmit_size {
+            return Err("The maximum message size must not exceed the maximum transmission size");
+        }
+
         if self.config.history_length < self.config.history_gossip {
             return Err(
                 "The history_length must be greater than or equal to the history_gossip \
@@ -785,6 +877,7 @@ impl std::fmt::Debug for GossipsubConfig {
         let _ = builder.field("heartbeat_interval", &self.heartbeat_interval);
         let _ = builder.field("fanout_ttl", &self.fanout_ttl);
         let _ = builder.field("max_transmit_size", &self.max_transmit_size);
+        let _ = builder.field("max_message_size", &self.max_message_size());
         let _ = builder.field("idle_timeout", &self.idle_timeout);
         let _ = builder.field("duplicate_cache_time", &self.duplicate_cache_time);
         let _ = builder.field("validate_messages", &self.validate_messages);
@@ -800,6 +893,12 @@ impl std::fmt::Debug for GossipsubConfig {
         let _ = builder.field("opportunistic_graft_ticks", &self.opportunistic_graft_ticks);
         let _ = builder.field("opportunistic_graft_peers", &self.opportunistic_graft_peers);
         let _ = builder.field("max_messages_per_rpc", &self.max_messages_per_rpc);
+        let _ = builder.field("topic_priorities", &self.topic_priorities);
+        let _ = builder.field("max_send_queue_size", &self.max_send_queue_size);
+        let _ = builder.field(
+            "message_queue_warn_threshold",
+            &self.message_queue_warn_threshold,
+        );
         let _ = builder.field("max_ihave_length", &self.max_ihave_length);
         let _ = builder.field("max_ihave_messages", &self.max_ihave_messages);
         let _ = builder.field("iwant_followup_time", &self.iwant_followup_time);
@@ -35,7 +35,14 @@ pub enum PublishError {
     InsufficientPeers,
     /// The overall message was too large. This could be due to excessive topics or an excessive
     /// message size.
-    MessageTooLarge,
+    MessageTooLarge {
+        /// The size of the message data or of the encoded RPC in bytes.
+        actual: usize,
+        /// The maximum permitted size, i.e. the configured
+        /// [`max_message_size`](crate::GossipsubConfig::max_message_size) for message data and
+        /// [`max_transmit_size`](crate::GossipsubConfig::max_transmit_size) for encoded RPCs.
+        max: usize,
+    },
     /// The compression algorithm failed.
     TransformFailed(std::io::Error),
 }
@@ -21,7 +21,8 @@
 use crate::config::ValidationMode;
 use crate::error::{GossipsubHandlerError, ValidationError};
 use crate::protocol::{GossipsubCodec, ProtocolConfig};
-use crate::types::{GossipsubRpc, PeerKind, RawGossipsubMessage};
+use crate::topic::TopicHash;
+use crate::types::{GossipsubRpc, MessagePriority, PeerKind, RawGossipsubMessage};
 use asynchronous_codec::Framed;
 use futures::prelude::*;
 use futures::StreamExt;
@@ -31,11 +32,11 @@ use libp2p_swarm::protocols_handler::{
 };
 use libp2p_swarm::NegotiatedSubstream;
 use log::{error, trace, warn};
-use smallvec::SmallVec;
 use std::{
-    collections::VecDeque,
+    collections::{HashMap, VecDeque},
     io,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
     time::Duration,
 };
@@ -80,6 +81,78 @@ pub enum GossipsubHandlerIn {
 /// connection faulty and disconnect. This also prevents against potential substream creation loops.
 const MAX_SUBSTREAM_CREATION: usize = 5;
 
+/// Queue of RPCs to send to the remote, drained in order of [`MessagePriority`].
+struct SendQueue {
+    /// The priorities of topics with a priority other than [`MessagePriority::Normal`], shared
+    /// by all connection handlers.
+    topic_priorities: Arc<HashMap<TopicHash, MessagePriority>>,
+    /// The maximum number of RPCs per priority.
+    max_size: Option<usize>,
+    high: VecDeque<crate::rpc_proto::Rpc>,
+    normal: VecDeque<crate::rpc_proto::Rpc>,
+    low: VecDeque<crate::rpc_proto::Rpc>,
+}
+
+impl SendQueue {
+    fn new(
+        topic_priorities: Arc<HashMap<TopicHash, MessagePriority>>,
+        max_size: Option<usize>,
+    ) -> Self {
+        SendQueue {
+            topic_priorities,
+            max_size,
+            high: VecDeque::new(),
+            normal: VecDeque::new(),
+            low: VecDeque::new(),
+        }
+    }
+
+    /// The priority of an RPC is the highest priority of the topics of the messages it carries,
+    /// or [`MessagePriority::Normal`] if it carries none.
+    fn priority(&self, rpc: &crate::rpc_proto::Rpc) -> MessagePriority {
+        rpc.publish
+            .iter()
+            .map(|m| {
+                self.topic_priorities
+                    .get(&TopicHash::from_raw(m.topic.clone()))
+                    .copied()
+                    .unwrap_or(MessagePriority::Normal)
+            })
+            .max()
+            .unwrap_or(MessagePriority::Normal)
+    }
+
+    /// Queues an RPC, dropping it if the queue of its priority is full. RPCs carrying
+    /// subscriptions or control messages are never dropped, since losing them would leave the
+    /// remote with an inconsistent view of our subscriptions and mesh.
+    fn push(&mut self, rpc: crate::rpc_proto::Rpc) {
+        let priority = self.priority(&rpc);
+        let queue = match priority {
+            MessagePriority::High => &mut self.high,
+            MessagePriority::Normal => &mut self.normal,
+            MessagePriority::Low => &mut self.low,
+        };
+        let is_control = rpc.control.is_some() || !rpc.subscriptions.is_empty();
+        if !is_control && matches!(self.max_size, Some(max) if queue.len() >= max) {
+            warn!("Send queue for {:?} priority full, dropping RPC", priority);
+            return;
+        }
+        queue.push_back(rpc);
+    }
+
+    /// Removes the next RPC to send, i.e. the oldest RPC of the highest priority.
+    fn pop(&mut self) -> Option<crate::rpc_proto::Rpc> {
+        self.high
+            .pop_front()
+            .or_else(|| self.normal.pop_front())
+            .or_else(|| self.low.pop_front())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.high.is_empty() && self.normal.is_empty() && self.low.is_empty()
+    }
+}
+
 /// Protocol Handler that manages a single long-lived substream with a peer.
 pub struct GossipsubHandler {
     /// Upgrade configuration for the gossipsub protocol.
@@ -92,7 +165,7 @@ pub struct GossipsubHandler {
     inbound_substream: Option<InboundSubstreamState>,
 
     /// Queue of values that we want to send to the remote.
-    send_queue: SmallVec<[crate::rpc_proto::Rpc; 16]>,
+    send_queue: SendQueue,
 
     /// Flag indicating that an outbound substream is being established to prevent duplicate
     /// requests.
@@ -168,6 +241,8 @@ impl GossipsubHandler {
         validation_mode: ValidationMode,
         idle_timeout: Duration,
         support_floodsub: bool,
+        topic_priorities: Arc<HashMap<TopicHash, MessagePriority>>,
+        max_send_queue_size: Option<usize>,
     ) -> Self {
         GossipsubHandler {
             listen_protocol: SubstreamProtocol::new(
@@ -184,7 +259,7 @@ impl GossipsubHandler {
             outbound_substream_establishing: false,
             outbound_substreams_created: 0,
             inbound_substreams_created: 0,
-            send_queue: SmallVec::new(),
+            send_queue: SendQueue::new(topic_priorities, max_send_queue_size),
             peer_kind: None,
             peer_kind_sent: false,
             protocol_unsupported: false,
@@ -371,8 +446,7 @@ impl ProtocolsHandler for GossipsubHandler {
                     GossipsubHandlerError::MaxOutboundSubstreams,
                 ));
             }
-            let message = self.send_queue.remove(0);
-            self.send_queue.shrink_to_fit();
+            let message = self.send_queue.pop().expect("send queue not empty");
             self.outbound_substream_establishing = true;
             return Poll::Ready(ProtocolsHandlerEvent::OutboundSubstreamRequest {
                 protocol: self.listen_protocol.clone().map_info(|()| message),
@@ -466,9 +540,7 @@ impl ProtocolsHandler for GossipsubHandler {
             ) {
                 // outbound idle state
                 Some(OutboundSubstreamState::WaitingOutput(substream)) => {
-                    if !self.send_queue.is_empty() {
-                        let message = self.send_queue.remove(0);
-                        self.send_queue.shrink_to_fit();
+                    if let Some(message) = self.send_queue.pop() {
                         self.outbound_substream =
                             Some(OutboundSubstreamState::PendingSend(substream, message));
                     } else {
@@ -569,3 +641,80 @@ impl ProtocolsHandler for GossipsubHandler {
         Poll::Pending
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rpc(topic: &str, data: u8) -> crate::rpc_proto::Rpc {
+        crate::rpc_proto::Rpc {
+            subscriptions: Vec::new(),
+            publish: vec![crate::rpc_proto::Message {
+                from: None,
+                data: Some(vec![data]),
+                seqno: None,
+                topic: topic.into(),
+                signature: None,
+                key: None,
+            }],
+            control: None,
+        }
+    }
+
+    fn priorities() -> Arc<HashMap<TopicHash, MessagePriority>> {
+        let mut priorities = HashMap::new();
+        priorities.insert(TopicHash::from_raw("consensus"), MessagePriority::High);
+        priorities.insert(TopicHash::from_raw("ambient"), MessagePriority::Low);
+        Arc::new(priorities)
+    }
+
+    #[test]
+    fn send_queue_drains_high_priority_first() {
+        let mut queue = SendQueue::new(priorities(), None);
+        queue.push(rpc("ambient", 1));
+        queue.push(rpc("ambient", 2));
+        queue.push(rpc("other", 3));
+        queue.push(rpc("consensus", 4));
+
+        let sent = std::iter::from_fn(|| queue.pop())
+            .map(|rpc| rpc.publish[0].data.clone().unwrap()[0])
+            .collect::<Vec<_>>();
+        assert_eq!(sent, vec![4, 3, 1, 2]);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn send_queue_drops_when_full() {
+        let mut queue = SendQueue::new(priorities(), Some(1));
+        queue.push(rpc("ambient", 1));
+        queue.push(rpc("ambient", 2));
+        queue.push(rpc("consensus", 3));
+
+        let sent = std::iter::from_fn(|| queue.pop())
+            .map(|rpc| rpc.publish[0].data.clone().unwrap()[0])
+            .collect::<Vec<_>>();
+        assert_eq!(sent, vec![3, 1]);
+    }
+
+    #[test]
+    fn send_queue_never_drops_control() {
+        let mut queue = SendQueue::new(priorities(), Some(1));
+        queue.push(rpc("other", 1));
+        let mut control = rpc("other", 2);
+        control.publish.clear();
+        control.control = Some(crate::rpc_proto::ControlMessage {
+            ihave: Vec::new(),
+            iwant: Vec::new(),
+            graft: vec![crate::rpc_proto::ControlGraft {
+                topic_id: Some("other".into()),
+            }],
+            prune: Vec::new(),
+        });
+        queue.push(control);
+        queue.push(rpc("other", 3));
+
+        let sent = std::iter::from_fn(|| queue.pop()).collect::<Vec<_>>();
+        assert_eq!(sent.len(), 2);
+        assert!(sent[1].control.is_some());
+    }
+}
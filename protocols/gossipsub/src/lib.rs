@@ -131,6 +131,8 @@ mod config;
 mod gossip_promises;
 mod handler;
 mod mcache;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 mod peer_score;
 pub mod subscription_filter;
 pub mod time_cache;
@@ -149,13 +151,13 @@ pub use self::transform::{DataTransform, IdentityTransform};
 
 pub use self::config::{GossipsubConfig, GossipsubConfigBuilder, ValidationMode};
 pub use self::peer_score::{
-    score_parameter_decay, score_parameter_decay_with_base, PeerScoreParams, PeerScoreThresholds,
-    TopicScoreParams,
+    score_parameter_decay, score_parameter_decay_with_base, PeerScoreDetails, PeerScoreParams,
+    PeerScoreThresholds, TopicScoreDetails, TopicScoreParams,
 };
 pub use self::topic::{Hasher, Topic, TopicHash};
 pub use self::types::{
     FastMessageId, GossipsubMessage, GossipsubRpc, MessageAcceptance, MessageId,
-    RawGossipsubMessage,
+    MessagePriority, RawGossipsubMessage,
 };
 pub type IdentTopic = Topic<self::topic::IdentityHash>;
 pub type Sha256Topic = Topic<self::topic::Sha256Hash>;
@@ -0,0 +1,85 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Prometheus metrics of the [`Gossipsub`](crate::Gossipsub) behaviour.
+
+use crate::GossipsubEvent;
+use prometheus::{exponential_buckets, Histogram, HistogramOpts, IntCounter, Registry};
+
+/// The metrics recorded from [`GossipsubEvent`]s.
+#[derive(Clone)]
+pub struct Metrics {
+    messages: IntCounter,
+    message_size: Histogram,
+    subscriptions: IntCounter,
+    unsubscriptions: IntCounter,
+    queue_depth_warnings: IntCounter,
+}
+
+impl Metrics {
+    /// Creates the metrics and registers them with the given registry.
+    pub fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let metrics = Metrics {
+            messages: IntCounter::new(
+                "libp2p_gossipsub_messages_total",
+                "Number of messages received.",
+            )?,
+            message_size: Histogram::with_opts(
+                HistogramOpts::new(
+                    "libp2p_gossipsub_message_size_bytes",
+                    "Size of the data of received messages in bytes.",
+                )
+                .buckets(exponential_buckets(64.0, 4.0, 8)?),
+            )?,
+            subscriptions: IntCounter::new(
+                "libp2p_gossipsub_subscriptions_total",
+                "Number of topic subscriptions of remote peers.",
+            )?,
+            unsubscriptions: IntCounter::new(
+                "libp2p_gossipsub_unsubscriptions_total",
+                "Number of topic unsubscriptions of remote peers.",
+            )?,
+            queue_depth_warnings: IntCounter::new(
+                "libp2p_gossipsub_queue_depth_warnings_total",
+                "Number of times the received messages of a topic exceeded the queue warning threshold.",
+            )?,
+        };
+        registry.register(Box::new(metrics.messages.clone()))?;
+        registry.register(Box::new(metrics.message_size.clone()))?;
+        registry.register(Box::new(metrics.subscriptions.clone()))?;
+        registry.register(Box::new(metrics.unsubscriptions.clone()))?;
+        registry.register(Box::new(metrics.queue_depth_warnings.clone()))?;
+        Ok(metrics)
+    }
+
+    /// Records an event emitted by the [`Gossipsub`](crate::Gossipsub) behaviour.
+    pub fn record(&self, event: &GossipsubEvent) {
+        match event {
+            GossipsubEvent::Message { message, .. } => {
+                self.messages.inc();
+                self.message_size.observe(message.data.len() as f64);
+            }
+            GossipsubEvent::Subscribed { .. } => self.subscriptions.inc(),
+            GossipsubEvent::Unsubscribed { .. } => self.unsubscriptions.inc(),
+            GossipsubEvent::QueueDepthWarning { .. } => self.queue_depth_warnings.inc(),
+            GossipsubEvent::TopicPaused { .. } | GossipsubEvent::TopicResumed { .. } => {}
+        }
+    }
+}
@@ -54,6 +54,55 @@ pub(crate) struct PeerScore {
     message_delivery_time_callback: Option<fn(&PeerId, &TopicHash, f64)>,
 }
 
+/// The score of a peer broken down into its individual components, see
+/// [`Gossipsub::peer_score_details`](crate::Gossipsub::peer_score_details).
+///
+/// Each component is already multiplied by its weight as configured in the
+/// [`PeerScoreParams`] and [`TopicScoreParams`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PeerScoreDetails {
+    /// The overall score of the peer.
+    pub score: f64,
+    /// The score components per scored topic.
+    pub topics: HashMap<TopicHash, TopicScoreDetails>,
+    /// The application-specific score (P5).
+    pub application_score: f64,
+    /// The IP colocation penalty (P6).
+    pub ip_colocation_penalty: f64,
+    /// The behaviour penalty (P7).
+    pub behaviour_penalty: f64,
+}
+
+/// The score components of a peer for a single topic.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TopicScoreDetails {
+    /// The topic score, i.e. the sum of all components multiplied by the
+    /// topic weight.
+    pub score: f64,
+    /// The time spent in the mesh (P1).
+    pub time_in_mesh: f64,
+    /// The first message deliveries (P2).
+    pub first_message_deliveries: f64,
+    /// The mesh message delivery deficit (P3).
+    pub mesh_message_deliveries: f64,
+    /// The mesh failure penalty (P3b).
+    pub mesh_failure_penalty: f64,
+    /// The invalid message deliveries (P4).
+    pub invalid_message_deliveries: f64,
+}
+
+/// A single score component, as reported by `PeerScore::compute_score`.
+enum ScoreComponent<'a> {
+    /// The score components for one scored topic.
+    Topic(&'a TopicHash, TopicScoreDetails),
+    /// The application-specific score (P5).
+    Application(f64),
+    /// The IP colocation penalty (P6).
+    IpColocation(f64),
+    /// The behaviour penalty (P7).
+    Behaviour(f64),
+}
+
 /// General statistics for a given gossipsub peer.
 struct PeerStats {
     /// Connection status of the peer.
@@ -213,6 +262,29 @@ impl PeerScore {
 
     /// Returns the score for a peer.
     pub fn score(&self, peer_id: &PeerId) -> f64 {
+        self.compute_score(peer_id, |_| {})
+    }
+
+    /// Returns the score for a peer together with its individual components.
+    pub fn score_details(&self, peer_id: &PeerId) -> PeerScoreDetails {
+        let mut details = PeerScoreDetails::default();
+        details.score = self.compute_score(peer_id, |component| match component {
+            ScoreComponent::Topic(topic, topic_details) => {
+                details.topics.insert(topic.clone(), topic_details);
+            }
+            ScoreComponent::Application(v) => details.application_score = v,
+            ScoreComponent::IpColocation(v) => details.ip_colocation_penalty = v,
+            ScoreComponent::Behaviour(v) => details.behaviour_penalty = v,
+        });
+        details
+    }
+
+    /// Computes the score for a peer, reporting each of its components to
+    /// `on_component`. Does not allocate, as it is called on every heartbeat.
+    fn compute_score<F>(&self, peer_id: &PeerId, mut on_component: F) -> f64
+    where
+        F: FnMut(ScoreComponent<'_>),
+    {
         let peer_stats = match self.peer_stats.get(peer_id) {
             Some(v) => v,
             None => return 0.0,
@@ -225,9 +297,7 @@ impl PeerScore {
             // topic parameters
             if let Some(topic_params) = self.params.topics.get(topic) {
                 // we are tracking the topic
-
-                // the topic score
-                let mut topic_score = 0.0;
+                let mut topic_details = TopicScoreDetails::default();
 
                 // P1: time in mesh
                 if let MeshStatus::Active { mesh_time, .. } = topic_stats.mesh_status {
@@ -240,7 +310,7 @@ impl PeerScore {
                             topic_params.time_in_mesh_cap
                         }
                     };
-                    topic_score += p1 * topic_params.time_in_mesh_weight;
+                    topic_details.time_in_mesh = p1 * topic_params.time_in_mesh_weight;
                 }
 
                 // P2: first message deliveries
@@ -252,7 +322,8 @@ impl PeerScore {
                         topic_params.first_message_deliveries_cap
                     }
                 };
-                topic_score += p2 * topic_params.first_message_deliveries_weight;
+                topic_details.first_message_deliveries =
+                    p2 * topic_params.first_message_deliveries_weight;
 
                 // P3: mesh message deliveries
                 if topic_stats.mesh_message_deliveries_active
@@ -262,7 +333,8 @@ impl PeerScore {
                     let deficit = topic_params.mesh_message_deliveries_threshold
                         - topic_stats.mesh_message_deliveries;
                     let p3 = deficit * deficit;
-                    topic_score += p3 * topic_params.mesh_message_deliveries_weight;
+                    topic_details.mesh_message_deliveries =
+                        p3 * topic_params.mesh_message_deliveries_weight;
                     debug!(
                         "The peer {} has a mesh message deliveries deficit of {} in topic\
                          {} and will get penalized by {}",
@@ -276,16 +348,26 @@ impl PeerScore {
                 // P3b:
                 // NOTE: the weight of P3b is negative (validated in TopicScoreParams.validate), so this detracts.
                 let p3b = topic_stats.mesh_failure_penalty;
-                topic_score += p3b * topic_params.mesh_failure_penalty_weight;
+                topic_details.mesh_failure_penalty = p3b * topic_params.mesh_failure_penalty_weight;
 
                 // P4: invalid messages
                 // NOTE: the weight of P4 is negative (validated in TopicScoreParams.validate), so this detracts.
                 let p4 =
                     topic_stats.invalid_message_deliveries * topic_stats.invalid_message_deliveries;
-                topic_score += p4 * topic_params.invalid_message_deliveries_weight;
-
-                // update score, mixing with topic weight
-                score += topic_score * topic_params.topic_weight;
+                topic_details.invalid_message_deliveries =
+                    p4 * topic_params.invalid_message_deliveries_weight;
+
+                // the topic score, mixed with the topic weight
+                topic_details.score = (topic_details.time_in_mesh
+                    + topic_details.first_message_deliveries
+                    + topic_details.mesh_message_deliveries
+                    + topic_details.mesh_failure_penalty
+                    + topic_details.invalid_message_deliveries)
+                    * topic_params.topic_weight;
+
+                // update score
+                score += topic_details.score;
+                on_component(ScoreComponent::Topic(topic, topic_details));
             }
         }
 
@@ -296,7 +378,11 @@ impl PeerScore {
 
         // P5: application-specific score
         let p5 = peer_stats.application_score;
-        score += p5 * self.params.app_specific_weight;
+        let application_score = p5 * self.params.app_specific_weight;
+        on_component(ScoreComponent::Application(application_score));
+        score += application_score;
+
+        let mut ip_colocation_penalty = 0.0;
 
         // P6: IP collocation factor
         for ip in peer_stats.known_ips.iter() {
@@ -317,17 +403,23 @@ impl PeerScore {
                         The surplus is {}. ",
                         peer_id, ip, surplus
                     );
-                    score += p6 * self.params.ip_colocation_factor_weight;
+                    ip_colocation_penalty += p6 * self.params.ip_colocation_factor_weight;
                 }
             }
         }
 
         // P7: behavioural pattern penalty
+        let mut behaviour_penalty = 0.0;
         if peer_stats.behaviour_penalty > self.params.behaviour_penalty_threshold {
             let excess = peer_stats.behaviour_penalty - self.params.behaviour_penalty_threshold;
             let p7 = excess * excess;
-            score += p7 * self.params.behaviour_penalty_weight;
+            behaviour_penalty = p7 * self.params.behaviour_penalty_weight;
         }
+        on_component(ScoreComponent::IpColocation(ip_colocation_penalty));
+        on_component(ScoreComponent::Behaviour(behaviour_penalty));
+        score += ip_colocation_penalty;
+        score += behaviour_penalty;
+
         score
     }
 
@@ -101,6 +101,20 @@ pub enum PeerKind {
     NotSupported,
 }
 
+/// The priority with which RPCs carrying messages of a topic are sent to peers.
+///
+/// Each connection drains queued RPCs of higher priority before those of lower
+/// priority. See [`crate::GossipsubConfigBuilder::topic_priority`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum MessagePriority {
+    /// Bulk data whose propagation may be delayed, e.g. ambient data.
+    Low,
+    /// The default priority.
+    Normal,
+    /// Time-sensitive messages, e.g. consensus messages.
+    High,
+}
+
 /// A message received by the gossipsub system and stored locally in caches..
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct RawGossipsubMessage {
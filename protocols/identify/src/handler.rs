@@ -87,9 +87,15 @@ pub enum IdentifyHandlerEvent {
     IdentificationError(ProtocolsHandlerUpgrErr<io::Error>),
 }
 
-/// Identifying information of the local node that is pushed to a remote.
+/// Event sent to the `IdentifyHandler`.
 #[derive(Debug)]
-pub struct IdentifyPush(pub IdentifyInfo);
+pub enum IdentifyHandlerIn {
+    /// Actively push the given identifying information of the local node to
+    /// the remote.
+    Push(Box<IdentifyInfo>),
+    /// Request identifying information from the remote right away.
+    Identify,
+}
 
 impl IdentifyHandler {
     /// Creates a new `IdentifyHandler`.
@@ -104,7 +110,7 @@ impl IdentifyHandler {
 }
 
 impl ProtocolsHandler for IdentifyHandler {
-    type InEvent = IdentifyPush;
+    type InEvent = IdentifyHandlerIn;
     type OutEvent = IdentifyHandlerEvent;
     type Error = io::Error;
     type InboundProtocol = SelectUpgrade<IdentifyProtocol, IdentifyPushProtocol<InboundPush>>;
@@ -156,12 +162,22 @@ impl ProtocolsHandler for IdentifyHandler {
         }
     }
 
-    fn inject_event(&mut self, IdentifyPush(push): Self::InEvent) {
-        self.events.push(ProtocolsHandlerEvent::OutboundSubstreamRequest {
-            protocol: SubstreamProtocol::new(
-                EitherUpgrade::B(
-                    IdentifyPushProtocol::outbound(push)), ())
-        });
+    fn inject_event(&mut self, event: Self::InEvent) {
+        match event {
+            IdentifyHandlerIn::Push(push) => {
+                self.events.push(ProtocolsHandlerEvent::OutboundSubstreamRequest {
+                    protocol: SubstreamProtocol::new(
+                        EitherUpgrade::B(
+                            IdentifyPushProtocol::outbound(*push)), ())
+                });
+            }
+            IdentifyHandlerIn::Identify => {
+                self.next_id.reset(self.interval);
+                self.events.push(ProtocolsHandlerEvent::OutboundSubstreamRequest {
+                    protocol: SubstreamProtocol::new(EitherUpgrade::A(IdentifyProtocol), ())
+                });
+            }
+        }
     }
 
     fn inject_dial_upgrade_error(
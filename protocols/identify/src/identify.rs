@@ -18,7 +18,7 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
-use crate::handler::{IdentifyHandler, IdentifyHandlerEvent, IdentifyPush};
+use crate::handler::{IdentifyHandler, IdentifyHandlerEvent, IdentifyHandlerIn};
 use crate::protocol::{IdentifyInfo, ReplySubstream};
 use futures::prelude::*;
 use libp2p_core::{
@@ -62,7 +62,7 @@ pub struct Identify {
     /// Pending replies to send.
     pending_replies: VecDeque<Reply>,
     /// Pending events to be emitted when polled.
-    events: VecDeque<NetworkBehaviourAction<IdentifyPush, IdentifyEvent>>,
+    events: VecDeque<NetworkBehaviourAction<IdentifyHandlerIn, IdentifyEvent>>,
     /// Peers to which an active push with current information about
     /// the local peer should be sent.
     pending_push: HashSet<PeerId>,
@@ -111,6 +111,7 @@ pub struct IdentifyConfig {
 
     /// Whether new or expired listen addresses of the local node should
     /// trigger an active push of an identify message to all connected peers.
+    /// This includes the external addresses of the local node.
     ///
     /// Enabling this option can result in connected peers being informed
     /// earlier about new or expired listen addresses of the local node,
@@ -191,6 +192,24 @@ impl Identify {
             }
         }
     }
+
+    /// Requests identifying information from the given peer right away on
+    /// all established connections, regardless of the configured interval.
+    ///
+    /// This is useful to learn about changes to the information of a remote,
+    /// e.g. its listen addresses, without waiting for it to push them. Does
+    /// nothing if the peer is not connected.
+    pub fn identify_peer(&mut self, peer_id: PeerId) {
+        if let Some(connections) = self.connected.get(&peer_id) {
+            for conn in connections.keys() {
+                self.events.push_back(NetworkBehaviourAction::NotifyHandler {
+                    peer_id,
+                    handler: NotifyHandler::One(*conn),
+                    event: IdentifyHandlerIn::Identify,
+                });
+            }
+        }
+    }
 }
 
 impl NetworkBehaviour for Identify {
@@ -246,6 +265,18 @@ impl NetworkBehaviour for Identify {
         }
     }
 
+    fn inject_new_external_addr(&mut self, _addr: &Multiaddr) {
+        if self.config.push_listen_addr_updates {
+            self.pending_push.extend(self.connected.keys());
+        }
+    }
+
+    fn inject_expired_external_addr(&mut self, _addr: &Multiaddr) {
+        if self.config.push_listen_addr_updates {
+            self.pending_push.extend(self.connected.keys());
+        }
+    }
+
     fn inject_event(
         &mut self,
         peer_id: PeerId,
@@ -265,6 +296,7 @@ impl NetworkBehaviour for Identify {
                     NetworkBehaviourAction::ReportObservedAddr {
                         address: observed,
                         score: AddressScore::Finite(1),
+                        observer: Some(peer_id),
                     });
             }
             IdentifyHandlerEvent::IdentificationPushed => {
@@ -329,7 +361,7 @@ impl NetworkBehaviour for Identify {
                     observed_addr,
                 };
 
-                (*peer, IdentifyPush(info))
+                (*peer, IdentifyHandlerIn::Push(Box::new(info)))
             })
         });
 
@@ -616,4 +648,193 @@ mod tests {
             }
         })
     }
+
+    #[test]
+    fn identify_push_external_addr_updates() {
+        let _ = env_logger::try_init();
+
+        let mut swarm1 = {
+            let (pubkey, transport) = transport();
+            let protocol = Identify::new(
+                IdentifyConfig::new("a".to_string(), pubkey.clone())
+                    // Delay identification requests so we can test the push protocol.
+                    .with_initial_delay(Duration::from_secs(u32::MAX as u64)));
+            Swarm::new(transport, protocol, pubkey.into_peer_id())
+        };
+
+        let mut swarm2 = {
+            let (pubkey, transport) = transport();
+            let protocol = Identify::new(
+                IdentifyConfig::new("a".to_string(), pubkey.clone())
+                    // Delay identification requests so we can test the push protocol.
+                    .with_initial_delay(Duration::from_secs(u32::MAX as u64))
+                    .with_push_listen_addr_updates(true));
+            Swarm::new(transport, protocol, pubkey.into_peer_id())
+        };
+
+        Swarm::listen_on(&mut swarm1, "/ip4/127.0.0.1/tcp/0".parse().unwrap()).unwrap();
+
+        let listen_addr = async_std::task::block_on(async {
+            loop {
+                let swarm1_fut = swarm1.select_next_some();
+                pin_mut!(swarm1_fut);
+                if let SwarmEvent::NewListenAddr { address, .. } = swarm1_fut.await {
+                    return address
+                }
+            }
+        });
+
+        Swarm::dial_addr(&mut swarm2, listen_addr).unwrap();
+
+        let external_addr: Multiaddr = "/ip4/1.2.3.4/tcp/1234".parse().unwrap();
+
+        async_std::task::block_on(async move {
+            let mut added = false;
+            loop {
+                let swarm1_fut = swarm1.select_next_some();
+                let swarm2_fut = swarm2.select_next_some();
+
+                let event = {
+                    pin_mut!(swarm1_fut);
+                    pin_mut!(swarm2_fut);
+                    future::select(swarm1_fut, swarm2_fut).await.factor_second().0
+                };
+
+                match event {
+                    future::Either::Left(SwarmEvent::Behaviour(
+                        IdentifyEvent::Received { info, .. }
+                    )) => {
+                        if !added {
+                            assert_eq!(info.listen_addrs, vec![external_addr.clone()]);
+                            added = true;
+                            assert!(swarm2.remove_external_address(&external_addr));
+                        } else {
+                            assert!(info.listen_addrs.is_empty());
+                            return;
+                        }
+                    }
+                    future::Either::Right(SwarmEvent::ConnectionEstablished { .. }) => {
+                        swarm2.add_external_address(external_addr.clone(), AddressScore::Infinite);
+                    }
+                    _ => {}
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn identify_peer() {
+        let _ = env_logger::try_init();
+
+        let mut swarm1 = {
+            let (pubkey, transport) = transport();
+            let protocol = Identify::new(
+                IdentifyConfig::new("a".to_string(), pubkey.clone())
+                    // Delay identification requests so we can test forced identification.
+                    .with_initial_delay(Duration::from_secs(u32::MAX as u64)));
+            Swarm::new(transport, protocol, pubkey.into_peer_id())
+        };
+
+        let mut swarm2 = {
+            let (pubkey, transport) = transport();
+            let protocol = Identify::new(
+                IdentifyConfig::new("a".to_string(), pubkey.clone())
+                    // Delay identification requests so we can test forced identification.
+                    .with_initial_delay(Duration::from_secs(u32::MAX as u64)));
+            Swarm::new(transport, protocol, pubkey.into_peer_id())
+        };
+
+        Swarm::listen_on(&mut swarm1, "/ip4/127.0.0.1/tcp/0".parse().unwrap()).unwrap();
+
+        let listen_addr = async_std::task::block_on(async {
+            loop {
+                let swarm1_fut = swarm1.select_next_some();
+                pin_mut!(swarm1_fut);
+                if let SwarmEvent::NewListenAddr { address, .. } = swarm1_fut.await {
+                    return address
+                }
+            }
+        });
+
+        Swarm::dial_addr(&mut swarm2, listen_addr.clone()).unwrap();
+
+        let external_addr: Multiaddr = "/ip4/1.2.3.4/tcp/1234".parse().unwrap();
+
+        async_std::task::block_on(async move {
+            let mut sent = false;
+            let mut received = false;
+            while !(sent && received) {
+                let swarm1_fut = swarm1.select_next_some();
+                let swarm2_fut = swarm2.select_next_some();
+
+                let event = {
+                    pin_mut!(swarm1_fut);
+                    pin_mut!(swarm2_fut);
+                    future::select(swarm1_fut, swarm2_fut).await.factor_second().0
+                };
+
+                match event {
+                    future::Either::Left(SwarmEvent::Behaviour(IdentifyEvent::Sent { peer_id })) => {
+                        assert_eq!(&peer_id, swarm2.local_peer_id());
+                        sent = true;
+                    }
+                    future::Either::Right(SwarmEvent::Behaviour(
+                        IdentifyEvent::Received { peer_id, info }
+                    )) => {
+                        assert_eq!(&peer_id, swarm1.local_peer_id());
+                        assert_eq!(info.listen_addrs, vec![external_addr.clone(), listen_addr.clone()]);
+                        received = true;
+                    }
+                    future::Either::Right(SwarmEvent::ConnectionEstablished { peer_id, .. }) => {
+                        swarm1.add_external_address(external_addr.clone(), AddressScore::Infinite);
+                        swarm2.behaviour_mut().identify_peer(peer_id);
+                    }
+                    _ => {}
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn observed_addr_confirmed_by_distinct_peers() {
+        let _ = env_logger::try_init();
+
+        let mut swarms = (0..3).map(|_| {
+            let (pubkey, transport) = transport();
+            let protocol = Identify::new(IdentifyConfig::new("a".to_string(), pubkey.clone()));
+            Swarm::new(transport, protocol, pubkey.into_peer_id())
+        }).collect::<Vec<_>>();
+
+        let listen_addrs = async_std::task::block_on(async {
+            let mut addrs = Vec::new();
+            for swarm in swarms.iter_mut() {
+                swarm.listen_on("/ip4/127.0.0.1/tcp/0".parse().unwrap()).unwrap();
+                loop {
+                    if let SwarmEvent::NewListenAddr { address, .. } = swarm.select_next_some().await {
+                        addrs.push(address);
+                        break
+                    }
+                }
+            }
+            addrs
+        });
+
+        // The two remotes observe the first swarm on different ephemeral
+        // ports, which both translate to its single listen address.
+        swarms[0].dial_addr(listen_addrs[1].clone()).unwrap();
+        swarms[0].dial_addr(listen_addrs[2].clone()).unwrap();
+
+        async_std::task::block_on(future::poll_fn(|cx| {
+            for swarm in swarms.iter_mut() {
+                while let Poll::Ready(Some(_)) = swarm.poll_next_unpin(cx) {}
+            }
+            match swarms[0].external_addresses_with_scores().as_slice() {
+                [(addr, score)] if score.confirmed_by == 2 => {
+                    assert_eq!(addr, &listen_addrs[0]);
+                    Poll::Ready(())
+                }
+                _ => Poll::Pending,
+            }
+        }))
+    }
 }
@@ -42,6 +42,8 @@ pub use self::protocol::IdentifyInfo;
 
 mod handler;
 mod identify;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 mod protocol;
 
 mod structs_proto {
@@ -0,0 +1,83 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Prometheus metrics of the [`Identify`](crate::Identify) behaviour.
+
+use crate::IdentifyEvent;
+use prometheus::{Histogram, HistogramOpts, IntCounter, Registry};
+
+/// The metrics recorded from [`IdentifyEvent`]s.
+#[derive(Clone)]
+pub struct Metrics {
+    received: IntCounter,
+    sent: IntCounter,
+    pushed: IntCounter,
+    errors: IntCounter,
+    listen_addrs: Histogram,
+}
+
+impl Metrics {
+    /// Creates the metrics and registers them with the given registry.
+    pub fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let metrics = Metrics {
+            received: IntCounter::new(
+                "libp2p_identify_received_total",
+                "Number of identification information received from peers.",
+            )?,
+            sent: IntCounter::new(
+                "libp2p_identify_sent_total",
+                "Number of identification requests answered.",
+            )?,
+            pushed: IntCounter::new(
+                "libp2p_identify_pushed_total",
+                "Number of identification information pushed to peers.",
+            )?,
+            errors: IntCounter::new(
+                "libp2p_identify_errors_total",
+                "Number of failed identifications.",
+            )?,
+            listen_addrs: Histogram::with_opts(
+                HistogramOpts::new(
+                    "libp2p_identify_listen_addrs",
+                    "Number of listen addresses reported by identified peers.",
+                ).buckets(vec![0.0, 1.0, 2.0, 4.0, 8.0, 16.0, 32.0]),
+            )?,
+        };
+        registry.register(Box::new(metrics.received.clone()))?;
+        registry.register(Box::new(metrics.sent.clone()))?;
+        registry.register(Box::new(metrics.pushed.clone()))?;
+        registry.register(Box::new(metrics.errors.clone()))?;
+        registry.register(Box::new(metrics.listen_addrs.clone()))?;
+        Ok(metrics)
+    }
+
+    /// Records an event emitted by the [`Identify`](crate::Identify) behaviour.
+    pub fn record(&self, event: &IdentifyEvent) {
+        match event {
+            IdentifyEvent::Received { info, .. } => {
+                self.received.inc();
+                self.listen_addrs.observe(info.listen_addrs.len() as f64);
+            }
+            IdentifyEvent::Sent { .. } => self.sent.inc(),
+            IdentifyEvent::Pushed { .. } => self.pushed.inc(),
+            IdentifyEvent::Error { .. } => self.errors.inc(),
+        }
+    }
+}
@@ -24,6 +24,7 @@ mod test;
 
 use crate::K_VALUE;
 use crate::addresses::Addresses;
+use crate::bootstrap::BootstrapPeers;
 use crate::handler::{
     KademliaHandlerProto,
     KademliaHandlerConfig,
@@ -35,10 +36,19 @@ use crate::jobs::*;
 use crate::kbucket::{self, Distance, KBucketsTable, NodeStatus};
 use crate::protocol::{KademliaProtocolConfig, KadConnectionType, KadPeer};
 use crate::query::{Query, QueryId, QueryPool, QueryConfig, QueryPoolState};
-use crate::record::{self, store::{self, RecordStore}, Record, ProviderRecord};
+use crate::record::{
+    self,
+    store::{self, RecordStore},
+    ExpiryPolicy,
+    FixedExpiryPolicy,
+    Record,
+    ProviderRecord,
+};
 use fnv::{FnvHashMap, FnvHashSet};
 use libp2p_core::{ConnectedPoint, Multiaddr, PeerId, connection::{ConnectionId, ListenerId}};
 use libp2p_swarm::{
+    AddressMeta,
+    AddressSource,
     DialPeerCondition,
     NetworkBehaviour,
     NetworkBehaviourAction,
@@ -47,7 +57,7 @@ use libp2p_swarm::{
 };
 use log::{info, debug, warn};
 use smallvec::SmallVec;
-use std::{borrow::Cow, error, time::Duration};
+use std::{borrow::Cow, error, mem, sync::Arc, time::Duration};
 use std::collections::{HashSet, VecDeque, BTreeMap};
 use std::fmt;
 use std::num::NonZeroUsize;
@@ -85,11 +95,8 @@ pub struct Kademlia<TStore> {
     /// regular (value-)records.
     put_record_job: Option<PutRecordJob>,
 
-    /// The TTL of regular (value-)records.
-    record_ttl: Option<Duration>,
-
-    /// The TTL of provider records.
-    provider_record_ttl: Option<Duration>,
+    /// The TTLs of records and provider records.
+    expiry_policy: Arc<dyn ExpiryPolicy>,
 
     /// How long to keep connections alive when they're idle.
     connection_idle_timeout: Duration,
@@ -103,6 +110,9 @@ pub struct Kademlia<TStore> {
     /// See [`KademliaConfig::caching`].
     caching: KademliaCaching,
 
+    /// The configured bootstrap peers, see [`KademliaConfig::set_bootstrap_peers`].
+    bootstrap_peers: BootstrapPeers,
+
     /// The record storage.
     store: TStore,
 }
@@ -147,6 +157,9 @@ pub struct KademliaConfig {
     connection_idle_timeout: Duration,
     kbucket_inserts: KademliaBucketInserts,
     caching: KademliaCaching,
+    bootstrap_peers: Vec<(PeerId, Vec<Multiaddr>)>,
+    bootstrap_recheck_interval: Duration,
+    expiry_policy: Option<Arc<dyn ExpiryPolicy>>,
 }
 
 /// The configuration for Kademlia "write-back" caching after successful
@@ -180,6 +193,9 @@ impl Default for KademliaConfig {
             connection_idle_timeout: Duration::from_secs(10),
             kbucket_inserts: KademliaBucketInserts::OnConnected,
             caching: KademliaCaching::Enabled { max_peers: 1 },
+            bootstrap_peers: Vec::new(),
+            bootstrap_recheck_interval: Duration::from_secs(5 * 60),
+            expiry_policy: None,
         }
     }
 }
@@ -190,11 +206,25 @@ impl KademliaConfig {
     /// Kademlia nodes only communicate with other nodes using the same protocol
     /// name. Using a custom name therefore allows to segregate the DHT from
     /// others, if that is desired.
+    ///
+    /// The default is [`DEFAULT_PROTO_NAME`](crate::protocol::DEFAULT_PROTO_NAME),
+    /// i.e. that of the IPFS DHT.
     pub fn set_protocol_name(&mut self, name: impl Into<Cow<'static, [u8]>>) -> &mut Self {
         self.protocol_config.set_protocol_name(name);
         self
     }
 
+    /// Sets a protocol name derived from the genesis hash of a chain.
+    ///
+    /// The resulting protocol name is `/<genesis-hash>/kad/1.0.0`, with the
+    /// hash hex-encoded, so that nodes of different chains each form their
+    /// own DHT. Overrides any name set via [`KademliaConfig::set_protocol_name`]
+    /// and vice versa.
+    pub fn set_genesis_hash(&mut self, hash: [u8; 32]) -> &mut Self {
+        let hex = hash.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+        self.set_protocol_name(format!("/{}/kad/1.0.0", hex).into_bytes())
+    }
+
     /// Sets the timeout for a single query.
     ///
     /// > **Note**: A single query usually comprises at least as many requests
@@ -259,6 +289,16 @@ impl KademliaConfig {
         self
     }
 
+    /// Sets the policy determining the TTLs of records and provider records,
+    /// e.g. depending on the record key.
+    ///
+    /// Overrides the TTLs configured via [`KademliaConfig::set_record_ttl`]
+    /// and [`KademliaConfig::set_provider_record_ttl`].
+    pub fn set_expiry_policy(&mut self, policy: impl ExpiryPolicy) -> &mut Self {
+        self.expiry_policy = Some(Arc::new(policy));
+        self
+    }
+
     /// Sets the (re-)replication interval for stored records.
     ///
     /// Periodic replication of stored records ensures that the records
@@ -336,6 +376,16 @@ impl KademliaConfig {
         self
     }
 
+    /// Sets the timeout after which a peer pending insertion into a full
+    /// k-bucket replaces the least-recently connected peer of that bucket,
+    /// if the latter is still disconnected.
+    ///
+    /// The default is 60 seconds.
+    pub fn set_kbucket_pending_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.kbucket_pending_timeout = timeout;
+        self
+    }
+
     /// Sets the k-bucket insertion strategy for the Kademlia routing table.
     pub fn set_kbucket_inserts(&mut self, inserts: KademliaBucketInserts) -> &mut Self {
         self.kbucket_inserts = inserts;
@@ -352,6 +402,32 @@ impl KademliaConfig {
         self.caching = c;
         self
     }
+
+    /// Sets the bootstrap peers together with their addresses.
+    ///
+    /// Bootstrap peers are added to the routing table when the [`Kademlia`]
+    /// behaviour is created. Every bootstrap peer that is not connected is
+    /// dialed periodically, see [`KademliaConfig::set_bootstrap_recheck_interval`].
+    /// A bootstrap peer that cannot be dialed is removed from the routing table,
+    /// but retained as a failed bootstrap peer, see [`Kademlia::failed_bootstrap_peers`],
+    /// and added back to the routing table once a later dialing attempt succeeds.
+    /// Changes in the reachability of bootstrap peers are reported as
+    /// [`KademliaEvent::BootstrapPeerStateChanged`].
+    ///
+    /// The default is to have no bootstrap peers.
+    pub fn set_bootstrap_peers(&mut self, peers: Vec<(PeerId, Vec<Multiaddr>)>) -> &mut Self {
+        self.bootstrap_peers = peers;
+        self
+    }
+
+    /// Sets the interval at which the reachability of bootstrap peers
+    /// that are not connected is checked.
+    ///
+    /// The default is 5 minutes.
+    pub fn set_bootstrap_recheck_interval(&mut self, interval: Duration) -> &mut Self {
+        self.bootstrap_recheck_interval = interval;
+        self
+    }
 }
 
 impl<TStore> Kademlia<TStore>
@@ -372,6 +448,10 @@ where
     pub fn with_config(id: PeerId, store: TStore, config: KademliaConfig) -> Self {
         let local_key = kbucket::Key::from(id);
 
+        let expiry_policy = config.expiry_policy.clone().unwrap_or_else(|| Arc::new(
+            FixedExpiryPolicy::new(config.record_ttl, config.provider_record_ttl)
+        ));
+
         let put_record_job = config
             .record_replication_interval
             .or(config.record_publication_interval)
@@ -379,14 +459,19 @@ where
                 id,
                 interval,
                 config.record_publication_interval,
-                config.record_ttl,
+                expiry_policy.clone(),
             ));
 
         let add_provider_job = config
             .provider_publication_interval
             .map(AddProviderJob::new);
 
-        Kademlia {
+        let bootstrap_peers = BootstrapPeers::new(
+            config.bootstrap_peers,
+            config.bootstrap_recheck_interval,
+        );
+
+        let mut kademlia = Kademlia {
             store,
             kbuckets: KBucketsTable::new(local_key, config.kbucket_pending_timeout),
             kbucket_inserts: config.kbucket_inserts,
@@ -396,12 +481,29 @@ where
             connected_peers: Default::default(),
             add_provider_job,
             put_record_job,
-            record_ttl: config.record_ttl,
-            provider_record_ttl: config.provider_record_ttl,
+            expiry_policy,
             connection_idle_timeout: config.connection_idle_timeout,
             local_addrs: HashSet::new(),
             caching: config.caching,
+            bootstrap_peers,
+        };
+
+        let bootstrap_addrs = kademlia.bootstrap_peers.iter()
+            .flat_map(|(peer, addrs)| addrs.iter().map(move |a| (*peer, a.clone())))
+            .collect::<Vec<_>>();
+        for (peer, addr) in bootstrap_addrs {
+            kademlia.add_address(&peer, addr);
         }
+
+        kademlia
+    }
+
+    /// Returns the configured bootstrap peers that are currently considered
+    /// unreachable and hence not part of the routing table.
+    ///
+    /// See [`KademliaConfig::set_bootstrap_peers`].
+    pub fn failed_bootstrap_peers(&self) -> impl Iterator<Item = &PeerId> {
+        self.bootstrap_peers.failed()
     }
 
     /// Gets an iterator over immutable references to all running queries.
@@ -535,7 +637,8 @@ where
     /// into the routing table.
     ///
     /// If the given peer or address is not in the routing table,
-    /// this is a no-op.
+    /// this is a no-op. If the peer is removed from the routing table,
+    /// a [`KademliaEvent::RoutingRemoved`] event is emitted.
     pub fn remove_address(&mut self, peer: &PeerId, address: &Multiaddr)
         -> Option<kbucket::EntryView<kbucket::Key<PeerId>, Addresses>>
     {
@@ -543,7 +646,10 @@ where
         match self.kbuckets.entry(&key) {
             kbucket::Entry::Present(mut entry, _) => {
                 if entry.value().remove(address).is_err() {
-                    Some(entry.remove()) // it is the last address, thus remove the peer.
+                    // it is the last address, thus remove the peer.
+                    let removed = entry.remove();
+                    self.queue_routing_removed(&removed);
+                    Some(removed)
                 } else {
                     None
                 }
@@ -564,14 +670,17 @@ where
     /// Removes a peer from the routing table.
     ///
     /// Returns `None` if the peer was not in the routing table,
-    /// not even pending insertion.
+    /// not even pending insertion. If the peer is removed from the routing
+    /// table, a [`KademliaEvent::RoutingRemoved`] event is emitted.
     pub fn remove_peer(&mut self, peer: &PeerId)
         -> Option<kbucket::EntryView<kbucket::Key<PeerId>, Addresses>>
     {
         let key = kbucket::Key::from(*peer);
         match self.kbuckets.entry(&key) {
             kbucket::Entry::Present(entry, _) => {
-                Some(entry.remove())
+                let removed = entry.remove();
+                self.queue_routing_removed(&removed);
+                Some(removed)
             }
             kbucket::Entry::Pending(entry, _) => {
                 Some(entry.remove())
@@ -620,6 +729,38 @@ where
     /// The result of this operation is delivered in a
     /// [`KademliaEvent::OutboundQueryCompleted{QueryResult::GetRecord}`].
     pub fn get_record(&mut self, key: &record::Key, quorum: Quorum) -> QueryId {
+        self.get_record_inner(key, quorum, false, false)
+    }
+
+    /// Performs a lookup for a record in the DHT, reporting every record
+    /// as soon as it is found.
+    ///
+    /// Each record found, including one found in the local store, is
+    /// immediately reported via a [`KademliaEvent::RecordFound`], without
+    /// waiting for the lookup to complete. Apart from that, the lookup
+    /// behaves like [`Kademlia::get_record`] and its final result is
+    /// delivered in a [`KademliaEvent::OutboundQueryCompleted{QueryResult::GetRecord}`].
+    pub fn get_record_streaming(&mut self, key: &record::Key, quorum: Quorum) -> QueryId {
+        self.get_record_inner(key, quorum, false, true)
+    }
+
+    /// Performs a lookup for a record in the DHT that only succeeds once
+    /// `quorum` peers returned records with identical values.
+    ///
+    /// Whereas [`Kademlia::get_record`] succeeds with any `quorum` records,
+    /// regardless of their values, the lookup continues until enough records
+    /// agree or all reachable peers have been queried.
+    ///
+    /// The result of this operation is delivered in a
+    /// [`KademliaEvent::OutboundQueryCompleted{QueryResult::GetRecord}`].
+    /// On success, [`GetRecordOk::records`] contains only the confirming
+    /// records. If the quorum is not reached and the records found disagree,
+    /// the lookup fails with [`GetRecordError::Conflict`].
+    pub fn get_record_with_quorum(&mut self, key: &record::Key, quorum: Quorum) -> QueryId {
+        self.get_record_inner(key, quorum, true, false)
+    }
+
+    fn get_record_inner(&mut self, key: &record::Key, quorum: Quorum, confirm: bool, stream: bool) -> QueryId {
         let quorum = quorum.eval(self.queries.config().replication_factor);
         let mut records = Vec::with_capacity(quorum.get());
 
@@ -633,16 +774,25 @@ where
 
         let done = records.len() >= quorum.get();
         let target = kbucket::Key::new(key.clone());
+        let local_record = if stream { records.first().cloned() } else { None };
         let info = QueryInfo::GetRecord {
             key: key.clone(),
             records,
             quorum,
+            confirm,
+            stream,
             cache_candidates: BTreeMap::new(),
         };
         let peers = self.kbuckets.closest_keys(&target);
         let inner = QueryInner::new(info);
         let id = self.queries.add_iter_closest(target.clone(), peers, inner); // (*)
 
+        if let Some(record) = local_record {
+            self.queued_events.push_back(NetworkBehaviourAction::GenerateEvent(
+                KademliaEvent::RecordFound { id, record }
+            ));
+        }
+
         // Instantly finish the query if we already have enough records.
         if done {
             self.queries.get_mut(&id).expect("by (*)").finish();
@@ -673,7 +823,7 @@ where
         record.publisher = Some(*self.kbuckets.local_key().preimage());
         self.store.put(record.clone())?;
         record.expires = record.expires.or_else(||
-            self.record_ttl.map(|ttl| Instant::now() + ttl));
+            self.expiry_policy.record_ttl(&record).map(|ttl| Instant::now() + ttl));
         let quorum = quorum.eval(self.queries.config().replication_factor);
         let target = kbucket::Key::new(record.key.clone());
         let peers = self.kbuckets.closest_keys(&target);
@@ -688,6 +838,22 @@ where
         Ok(self.queries.add_iter_closest(target.clone(), peers, inner))
     }
 
+    /// Stores a record locally, without publishing it in the DHT.
+    ///
+    /// The record is stored as given and thereby becomes available to
+    /// remote peers that query the local node for it, e.g. as part of a
+    /// [`Kademlia::get_record`] lookup.
+    ///
+    /// > **Note**: Like any other record in local storage, the record is
+    /// > subject to periodic (re-)replication as per the configured
+    /// > replication interval, as well as to (re-)publication if the local
+    /// > node is set as its publisher. See
+    /// > [`KademliaConfig::set_replication_interval`] and
+    /// > [`KademliaConfig::set_publication_interval`].
+    pub fn put_record_local(&mut self, record: Record) -> Result<(), store::Error> {
+        self.store.put(record)
+    }
+
     /// Stores a record at specific peers, without storing it locally.
     ///
     /// The given [`Quorum`] is understood in the context of the total
@@ -721,7 +887,7 @@ where
             NonZeroUsize::new(1).expect("1 > 0")
         };
         record.expires = record.expires.or_else(||
-            self.record_ttl.map(|ttl| Instant::now() + ttl));
+            self.expiry_policy.record_ttl(&record).map(|ttl| Instant::now() + ttl));
         let context = PutRecordContext::Custom;
         let info = QueryInfo::PutRecord {
             context,
@@ -837,6 +1003,20 @@ where
         Ok(id)
     }
 
+    /// Establishes the local node as a provider of a value for the given key,
+    /// without announcing it in the DHT.
+    ///
+    /// The provider record is only stored locally and thereby returned to
+    /// remote peers that query the local node for providers of the key.
+    ///
+    /// > **Note**: Like provider records added via [`Kademlia::start_providing`],
+    /// > the provider record is subject to periodic (re-)publication as per
+    /// > [`KademliaConfig::set_provider_publication_interval`].
+    pub fn start_providing_local(&mut self, key: record::Key) -> Result<(), store::Error> {
+        let record = ProviderRecord::new(key, *self.kbuckets.local_key().preimage(), Vec::new());
+        self.store.add_provider(record)
+    }
+
     /// Stops the local node from announcing that it is a provider for the given key.
     ///
     /// This is a local operation. The local node will still be considered as a
@@ -850,9 +1030,34 @@ where
     /// The result of this operation is delivered in a
     /// reported via [`KademliaEvent::OutboundQueryCompleted{QueryResult::GetProviders}`].
     pub fn get_providers(&mut self, key: record::Key) -> QueryId {
+        self.get_providers_inner(key, false)
+    }
+
+    /// Performs a lookup for providers of a value to the given key, reporting
+    /// every provider as soon as it is found.
+    ///
+    /// Each distinct provider is immediately reported via a
+    /// [`KademliaEvent::ProviderFound`], without waiting for the lookup to
+    /// complete. Apart from that, the lookup behaves like
+    /// [`Kademlia::get_providers`] and its final result is delivered in a
+    /// [`KademliaEvent::OutboundQueryCompleted{QueryResult::GetProviders}`].
+    ///
+    /// The providers are reported as events rather than through a channel
+    /// returned by this method, like the records of
+    /// [`Kademlia::get_record_streaming`]. Thereby they are delivered in order
+    /// with all other events of the lookup by polling the `Swarm`, and the
+    /// behaviour does not have to hold a sender for a lookup whose receiver
+    /// may have been dropped. The lookup is identified by the returned
+    /// [`QueryId`], e.g. to cancel it via [`Kademlia::query_mut`].
+    pub fn get_providers_streaming(&mut self, key: record::Key) -> QueryId {
+        self.get_providers_inner(key, true)
+    }
+
+    fn get_providers_inner(&mut self, key: record::Key, stream: bool) -> QueryId {
         let info = QueryInfo::GetProviders {
             key: key.clone(),
             providers: HashSet::new(),
+            stream,
         };
         let target = kbucket::Key::new(key);
         let peers = self.kbuckets.closest_keys(&target);
@@ -966,6 +1171,22 @@ where
         self.queries.add_iter_closest(target.clone(), peers, inner);
     }
 
+    /// Queues a [`KademliaEvent::RoutingRemoved`] for an entry that has just
+    /// been removed from the routing table.
+    fn queue_routing_removed(&mut self, removed: &kbucket::EntryView<kbucket::Key<PeerId>, Addresses>) {
+        let bucket_range = self.kbuckets
+            .bucket(&removed.node.key)
+            .map(|b| b.range())
+            .expect("Not kbucket::Entry::SelfEntry.");
+        self.queued_events.push_back(NetworkBehaviourAction::GenerateEvent(
+            KademliaEvent::RoutingRemoved {
+                peer: *removed.node.key.preimage(),
+                addresses: removed.node.value.clone(),
+                bucket_range,
+            }
+        ));
+    }
+
     /// Updates the routing table with a new connection status and address of a peer.
     fn connection_updated(&mut self, peer: PeerId, address: Option<Multiaddr>, new_status: NodeStatus) {
         let key = kbucket::Key::from(peer);
@@ -1142,7 +1363,7 @@ where
                 })
             }
 
-            QueryInfo::GetProviders { key, providers } => {
+            QueryInfo::GetProviders { key, providers, .. } => {
                 Some(KademliaEvent::OutboundQueryCompleted {
                     id: query_id,
                     stats: result.stats,
@@ -1199,8 +1420,15 @@ where
                 }
             }
 
-            QueryInfo::GetRecord { key, records, quorum, cache_candidates } => {
-                let results = if records.len() >= quorum.get() { // [not empty]
+            QueryInfo::GetRecord { key, mut records, quorum, confirm, cache_candidates, .. } => {
+                let confirmed = if confirm {
+                    confirming_records(&records, quorum)
+                } else if records.len() >= quorum.get() {
+                    Some(mem::take(&mut records))
+                } else {
+                    None
+                };
+                let results = if let Some(records) = confirmed { // [not empty]
                     if quorum.get() == 1 && !cache_candidates.is_empty() {
                         // Cache the record at the closest node(s) to the key that
                         // did not return the record.
@@ -1225,6 +1453,8 @@ where
                         key,
                         closest_peers: result.peers.collect()
                     })
+                } else if confirm && records.iter().any(|r| r.record.value != records[0].record.value) {
+                    Err(GetRecordError::Conflict { key, records, quorum })
                 } else {
                     Err(GetRecordError::QuorumFailed { key, records, quorum })
                 };
@@ -1414,7 +1644,7 @@ where
                     ))
                 }),
 
-            QueryInfo::GetProviders { key, providers } =>
+            QueryInfo::GetProviders { key, providers, .. } =>
                 Some(KademliaEvent::OutboundQueryCompleted {
                     id: query_id,
                     stats: result.stats,
@@ -1463,7 +1693,8 @@ where
         let num_between = self.kbuckets.count_nodes_between(&target);
         let k = self.queries.config().replication_factor.get();
         let num_beyond_k = (usize::max(k, num_between) - k) as u32;
-        let expiration = self.record_ttl.map(|ttl| now + exp_decrease(ttl, num_beyond_k));
+        let expiration = self.expiry_policy.record_ttl(&record)
+            .map(|ttl| now + exp_decrease(ttl, num_beyond_k));
         // The smaller TTL prevails. Only if neither TTL is set is the record
         // stored "forever".
         record.expires = record.expires.or(expiration).min(expiration);
@@ -1526,10 +1757,11 @@ where
     /// Processes a provider record received from a peer.
     fn provider_received(&mut self, key: record::Key, provider: KadPeer) {
         if &provider.node_id != self.kbuckets.local_key().preimage() {
+            let expires = self.expiry_policy.provider_ttl(&key).map(|ttl| Instant::now() + ttl);
             let record = ProviderRecord {
                 key,
                 provider: provider.node_id,
-                expires: self.provider_record_ttl.map(|ttl| Instant::now() + ttl),
+                expires,
                 addresses: provider.multiaddrs,
             };
             if let Err(e) = self.store.add_provider(record) {
@@ -1561,12 +1793,24 @@ where
     }
 
     fn addresses_of_peer(&mut self, peer_id: &PeerId) -> Vec<Multiaddr> {
+        self.addresses_of_peer_with_metadata(peer_id)
+            .into_iter()
+            .map(|(addr, _)| addr)
+            .collect()
+    }
+
+    fn addresses_of_peer_with_metadata(&mut self, peer_id: &PeerId) -> Vec<(Multiaddr, AddressMeta)> {
         // We should order addresses from decreasing likelyhood of connectivity, so start with
-        // the addresses of that peer in the k-buckets.
+        // the addresses of that peer in the k-buckets. Addresses of connected peers are the
+        // most likely to be reachable.
         let key = kbucket::Key::from(*peer_id);
         let mut peer_addrs =
-            if let kbucket::Entry::Present(mut entry, _) = self.kbuckets.entry(&key) {
-                let addrs = entry.value().iter().cloned().collect::<Vec<_>>();
+            if let kbucket::Entry::Present(mut entry, status) = self.kbuckets.entry(&key) {
+                let score = if status == NodeStatus::Connected { 1.0 } else { 0.75 };
+                let meta = AddressMeta::new(AddressSource::Kademlia).with_score(score);
+                let addrs = entry.value().iter()
+                    .map(|a| (a.clone(), meta.clone()))
+                    .collect::<Vec<_>>();
                 debug_assert!(!addrs.is_empty(), "Empty peer addresses in routing table.");
                 addrs
             } else {
@@ -1574,9 +1818,21 @@ where
             };
 
         // We add to that a temporary list of addresses from the ongoing queries.
+        // These have been reported by other peers and are not yet confirmed.
+        let meta = AddressMeta::new(AddressSource::Kademlia).with_score(0.25);
         for query in self.queries.iter() {
             if let Some(addrs) = query.inner.addresses.get(peer_id) {
-                peer_addrs.extend(addrs.iter().cloned())
+                peer_addrs.extend(addrs.iter().map(|a| (a.clone(), meta.clone())))
+            }
+        }
+
+        // Bootstrap peers are dialed on their configured addresses, even while
+        // they are not part of the routing table.
+        if let Some(addrs) = self.bootstrap_peers.addresses(peer_id) {
+            for addr in addrs {
+                if !peer_addrs.iter().any(|(a, _)| a == addr) {
+                    peer_addrs.push((addr.clone(), AddressMeta::new(AddressSource::UserProvided)));
+                }
             }
         }
 
@@ -1604,6 +1860,17 @@ where
         }
 
         self.connected_peers.insert(*peer);
+
+        if self.bootstrap_peers.set_reachable(peer, true) {
+            debug!("Bootstrap peer {} is reachable again.", peer);
+            let addrs = self.bootstrap_peers.addresses(peer).map(|a| a.to_vec()).unwrap_or_default();
+            for addr in addrs {
+                self.add_address(peer, addr);
+            }
+            self.queued_events.push_back(NetworkBehaviourAction::GenerateEvent(
+                KademliaEvent::BootstrapPeerStateChanged { peer_id: *peer, reachable: true }
+            ));
+        }
     }
 
     fn inject_address_change(
@@ -1704,6 +1971,14 @@ where
         for query in self.queries.iter_mut() {
             query.on_failure(peer_id);
         }
+
+        if self.bootstrap_peers.set_reachable(peer_id, false) {
+            debug!("Bootstrap peer {} is unreachable.", peer_id);
+            self.remove_peer(peer_id);
+            self.queued_events.push_back(NetworkBehaviourAction::GenerateEvent(
+                KademliaEvent::BootstrapPeerStateChanged { peer_id: *peer_id, reachable: false }
+            ));
+        }
     }
 
     fn inject_disconnected(&mut self, id: &PeerId) {
@@ -1791,10 +2066,14 @@ where
                 self.discovered(&user_data, &source, peers);
                 if let Some(query) = self.queries.get_mut(&user_data) {
                     if let QueryInfo::GetProviders {
-                        providers, ..
+                        providers, stream, ..
                     } = &mut query.inner.info {
                         for peer in provider_peers {
-                            providers.insert(peer.node_id);
+                            if providers.insert(peer.node_id) && *stream {
+                                self.queued_events.push_back(NetworkBehaviourAction::GenerateEvent(
+                                    KademliaEvent::ProviderFound { id: user_data, provider: peer.node_id }
+                                ));
+                            }
                         }
                     }
                 }
@@ -1864,19 +2143,34 @@ where
             } => {
                 if let Some(query) = self.queries.get_mut(&user_data) {
                     if let QueryInfo::GetRecord {
-                        key, records, quorum, cache_candidates
+                        key, records, quorum, confirm, stream, cache_candidates
                     } = &mut query.inner.info {
                         if let Some(record) = record {
-                            records.push(PeerRecord{ peer: Some(source), record });
-
-                            let quorum = quorum.get();
-                            if records.len() >= quorum {
+                            let record = PeerRecord{ peer: Some(source), record };
+                            if *stream {
+                                self.queued_events.push_back(NetworkBehaviourAction::GenerateEvent(
+                                    KademliaEvent::RecordFound { id: user_data, record: record.clone() }
+                                ));
+                            }
+                            records.push(record);
+
+                            let peers = if *confirm {
+                                confirming_records(records, *quorum).map(|confirmed| {
+                                    confirmed.into_iter()
+                                        .filter_map(|PeerRecord{ peer, .. }| peer)
+                                        .collect::<Vec<_>>()
+                                })
+                            } else if records.len() >= quorum.get() {
+                                Some(records.iter()
+                                    .filter_map(|PeerRecord{ peer, .. }| *peer)
+                                    .collect::<Vec<_>>())
+                            } else {
+                                None
+                            };
+                            if let Some(peers) = peers {
                                 // Desired quorum reached. The query may finish. See
                                 // [`Query::try_finish`] for details.
-                                let peers = records.iter()
-                                    .filter_map(|PeerRecord{ peer, .. }| peer.as_ref())
-                                    .cloned()
-                                    .collect::<Vec<_>>();
+                                let quorum = quorum.get();
                                 let finished = query.try_finish(peers.iter());
                                 if !finished {
                                     debug!(
@@ -2003,6 +2297,18 @@ where
             self.put_record_job = Some(job);
         }
 
+        // Recheck the bootstrap peers that are not connected.
+        if let Poll::Ready(peers) = self.bootstrap_peers.poll_recheck(cx) {
+            for peer_id in peers {
+                if !self.connected_peers.contains(&peer_id) {
+                    self.queued_events.push_back(NetworkBehaviourAction::DialPeer {
+                        peer_id,
+                        condition: DialPeerCondition::Disconnected,
+                    });
+                }
+            }
+        }
+
         loop {
             // Drain queued events first.
             if let Some(event) = self.queued_events.pop_front() {
@@ -2099,6 +2405,14 @@ impl Quorum {
     }
 }
 
+/// Returns the records with the value that at least `quorum` of the given
+/// records agree on, if any.
+fn confirming_records(records: &[PeerRecord], quorum: NonZeroUsize) -> Option<Vec<PeerRecord>> {
+    records.iter()
+        .find(|r| records.iter().filter(|o| o.record.value == r.record.value).count() >= quorum.get())
+        .map(|r| records.iter().filter(|o| o.record.value == r.record.value).cloned().collect())
+}
+
 /// A record either received by the given peer or retrieved from the local
 /// record store.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -2126,6 +2440,24 @@ pub enum KademliaEvent {
         request: InboundRequest,
     },
 
+    /// A record has been found by a lookup started with
+    /// [`Kademlia::get_record_streaming`], which may still be in progress.
+    RecordFound {
+        /// The ID of the lookup.
+        id: QueryId,
+        /// The record found, with the ID of the peer that returned it.
+        record: PeerRecord,
+    },
+
+    /// A provider has been found by a lookup started with
+    /// [`Kademlia::get_providers_streaming`], which may still be in progress.
+    ProviderFound {
+        /// The ID of the lookup.
+        id: QueryId,
+        /// The ID of the provider.
+        provider: PeerId,
+    },
+
     /// An outbound query has produced a result.
     OutboundQueryCompleted {
         /// The ID of the query that finished.
@@ -2154,6 +2486,30 @@ pub enum KademliaEvent {
         old_peer: Option<PeerId>,
     },
 
+    /// A peer has been removed from the routing table via
+    /// [`Kademlia::remove_address`] or [`Kademlia::remove_peer`].
+    ///
+    /// Peers evicted to make room for a new peer are reported as the
+    /// `old_peer` of a [`KademliaEvent::RoutingUpdated`] instead.
+    RoutingRemoved {
+        /// The ID of the peer that was removed.
+        peer: PeerId,
+        /// The addresses of `peer` known at the time of its removal.
+        addresses: Addresses,
+        /// Returns the minimum inclusive and maximum inclusive [`Distance`] for
+        /// the bucket of the peer.
+        bucket_range: (Distance, Distance),
+    },
+
+    /// The reachability of a bootstrap peer changed.
+    ///
+    /// See [`KademliaConfig::set_bootstrap_peers`].
+    BootstrapPeerStateChanged {
+        peer_id: PeerId,
+        /// Whether the bootstrap peer is now reachable.
+        reachable: bool,
+    },
+
     /// A peer has connected for whom no listen address is known.
     ///
     /// If the peer is to be added to the routing table, a known
@@ -2289,6 +2645,13 @@ pub enum GetRecordError {
         key: record::Key,
         records: Vec<PeerRecord>,
         quorum: NonZeroUsize
+    },
+    /// The records found by [`Kademlia::get_record_with_quorum`] did not
+    /// reach the quorum and have differing values.
+    Conflict {
+        key: record::Key,
+        records: Vec<PeerRecord>,
+        quorum: NonZeroUsize
     }
 }
 
@@ -2299,6 +2662,7 @@ impl GetRecordError {
             GetRecordError::QuorumFailed { key, .. } => key,
             GetRecordError::Timeout { key, .. } => key,
             GetRecordError::NotFound { key, .. } => key,
+            GetRecordError::Conflict { key, .. } => key,
         }
     }
 
@@ -2309,6 +2673,7 @@ impl GetRecordError {
             GetRecordError::QuorumFailed { key, .. } => key,
             GetRecordError::Timeout { key, .. } => key,
             GetRecordError::NotFound { key, .. } => key,
+            GetRecordError::Conflict { key, .. } => key,
         }
     }
 }
@@ -2579,6 +2944,9 @@ pub enum QueryInfo {
         key: record::Key,
         /// The found providers.
         providers: HashSet<PeerId>,
+        /// Whether every provider found is reported immediately,
+        /// see [`Kademlia::get_providers_streaming`].
+        stream: bool,
     },
 
     /// A (repeated) query initiated by [`Kademlia::start_providing`].
@@ -2611,6 +2979,12 @@ pub enum QueryInfo {
         records: Vec<PeerRecord>,
         /// The number of records to look for.
         quorum: NonZeroUsize,
+        /// Whether the `quorum` must consist of records with identical values,
+        /// see [`Kademlia::get_record_with_quorum`].
+        confirm: bool,
+        /// Whether every record found is reported immediately,
+        /// see [`Kademlia::get_record_streaming`].
+        stream: bool,
         /// The peers closest to the `key` that were queried but did not return a record,
         /// i.e. the peers that are candidates for caching the record.
         cache_candidates: BTreeMap<kbucket::Distance, PeerId>,
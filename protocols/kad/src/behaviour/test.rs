@@ -24,7 +24,7 @@ use super::*;
 
 use crate::K_VALUE;
 use crate::kbucket::Distance;
-use crate::record::{Key, store::MemoryStore};
+use crate::record::{Key, ExpiryPolicy, store::MemoryStore};
 use futures::{
     prelude::*,
     executor::block_on,
@@ -56,6 +56,13 @@ fn build_node() -> (Multiaddr, TestSwarm) {
 
 fn build_node_with_config(cfg: KademliaConfig) -> (Multiaddr, TestSwarm) {
     let local_key = identity::Keypair::generate_ed25519();
+    let address: Multiaddr = Protocol::Memory(random::<u64>()).into();
+    build_node_with_identity(local_key, address, cfg)
+}
+
+fn build_node_with_identity(local_key: identity::Keypair, address: Multiaddr, cfg: KademliaConfig)
+    -> (Multiaddr, TestSwarm)
+{
     let local_public_key = local_key.public();
     let noise_keys = noise::Keypair::<noise::X25519>::new().into_authentic(&local_key).unwrap();
     let transport = MemoryTransport::default()
@@ -70,7 +77,6 @@ fn build_node_with_config(cfg: KademliaConfig) -> (Multiaddr, TestSwarm) {
 
     let mut swarm = Swarm::new(transport, behaviour, local_id);
 
-    let address: Multiaddr = Protocol::Memory(random::<u64>()).into();
     swarm.listen_on(address.clone()).unwrap();
 
     (address, swarm)
@@ -683,6 +689,159 @@ fn get_record() {
     )
 }
 
+#[test]
+fn put_record_local() {
+    let mut swarms = build_nodes(3);
+
+    // Let first peer know of second peer and second peer know of third peer.
+    for i in 0..2 {
+        let (peer_id, address) = (*Swarm::local_peer_id(&swarms[i+1].1), swarms[i+1].0.clone());
+        swarms[i].1.behaviour_mut().add_address(&peer_id, address);
+    }
+
+    let mut swarms = swarms.into_iter().map(|(_addr, swarm)| swarm).collect::<Vec<_>>();
+
+    let record = Record::new(random_multihash(), vec![4,5,6]);
+    swarms[2].behaviour_mut().put_record_local(record.clone()).unwrap();
+
+    // The record is stored without starting a query.
+    assert_eq!(swarms[2].behaviour_mut().iter_queries().count(), 0);
+    assert!(swarms[1].behaviour_mut().store.get(&record.key).is_none());
+
+    let qid = swarms[0].behaviour_mut().get_record(&record.key, Quorum::One);
+
+    block_on(
+        poll_fn(move |ctx| {
+            for swarm in &mut swarms {
+                loop {
+                    match swarm.poll_next_unpin(ctx) {
+                        Poll::Ready(Some(SwarmEvent::Behaviour(KademliaEvent::OutboundQueryCompleted {
+                            id,
+                            result: QueryResult::GetRecord(Ok(GetRecordOk { records, .. })),
+                            ..
+                        }))) => {
+                            assert_eq!(id, qid);
+                            assert_eq!(records.len(), 1);
+                            assert_eq!(records.first().unwrap().record, record);
+                            return Poll::Ready(());
+                        }
+                        // Ignore any other event.
+                        Poll::Ready(Some(_)) => (),
+                        e @ Poll::Ready(_) => panic!("Unexpected return value: {:?}", e),
+                        Poll::Pending => break,
+                    }
+                }
+            }
+
+            Poll::Pending
+        })
+    )
+}
+
+#[test]
+fn start_providing_local() {
+    let mut swarms = build_nodes(3);
+
+    // Let first peer know of second peer and second peer know of third peer.
+    for i in 0..2 {
+        let (peer_id, address) = (*Swarm::local_peer_id(&swarms[i+1].1), swarms[i+1].0.clone());
+        swarms[i].1.behaviour_mut().add_address(&peer_id, address);
+    }
+
+    let mut swarms = swarms.into_iter().map(|(_addr, swarm)| swarm).collect::<Vec<_>>();
+
+    let key = Key::from(random_multihash());
+    let provider = *Swarm::local_peer_id(&swarms[2]);
+    swarms[2].behaviour_mut().start_providing_local(key.clone()).unwrap();
+
+    // The provider record is stored without starting a query.
+    assert_eq!(swarms[2].behaviour_mut().iter_queries().count(), 0);
+
+    let qid = swarms[0].behaviour_mut().get_providers(key.clone());
+
+    block_on(
+        poll_fn(move |ctx| {
+            for swarm in &mut swarms {
+                loop {
+                    match swarm.poll_next_unpin(ctx) {
+                        Poll::Ready(Some(SwarmEvent::Behaviour(KademliaEvent::OutboundQueryCompleted {
+                            id,
+                            result: QueryResult::GetProviders(Ok(ok)),
+                            ..
+                        }))) => {
+                            assert_eq!(id, qid);
+                            assert_eq!(ok.key, key);
+                            assert!(ok.providers.contains(&provider));
+                            return Poll::Ready(());
+                        }
+                        // Ignore any other event.
+                        Poll::Ready(Some(_)) => (),
+                        e @ Poll::Ready(_) => panic!("Unexpected return value: {:?}", e),
+                        Poll::Pending => break,
+                    }
+                }
+            }
+
+            Poll::Pending
+        })
+    )
+}
+
+#[test]
+fn get_providers_streaming() {
+    let mut swarms = build_nodes(3);
+
+    // Let first peer know of second peer and second peer know of third peer.
+    for i in 0..2 {
+        let (peer_id, address) = (*Swarm::local_peer_id(&swarms[i+1].1), swarms[i+1].0.clone());
+        swarms[i].1.behaviour_mut().add_address(&peer_id, address);
+    }
+
+    let mut swarms = swarms.into_iter().map(|(_addr, swarm)| swarm).collect::<Vec<_>>();
+
+    let key = Key::from(random_multihash());
+    let provider = *Swarm::local_peer_id(&swarms[2]);
+    swarms[2].behaviour_mut().start_providing_local(key.clone()).unwrap();
+
+    let qid = swarms[0].behaviour_mut().get_providers_streaming(key.clone());
+    let mut found = Vec::new();
+
+    block_on(
+        poll_fn(move |ctx| {
+            for swarm in &mut swarms {
+                loop {
+                    match swarm.poll_next_unpin(ctx) {
+                        Poll::Ready(Some(SwarmEvent::Behaviour(KademliaEvent::ProviderFound {
+                            id, provider: p
+                        }))) => {
+                            assert_eq!(id, qid);
+                            found.push(p);
+                        }
+                        Poll::Ready(Some(SwarmEvent::Behaviour(KademliaEvent::OutboundQueryCompleted {
+                            id,
+                            result: QueryResult::GetProviders(Ok(ok)),
+                            ..
+                        }))) => {
+                            assert_eq!(id, qid);
+                            // The provider has been reported, exactly once, before
+                            // the lookup completed.
+                            assert_eq!(found, vec![provider]);
+                            assert!(ok.providers.contains(&provider));
+                            return Poll::Ready(());
+                        }
+                        // Ignore any other event.
+                        Poll::Ready(Some(_)) => (),
+                        e @ Poll::Ready(_) => panic!("Unexpected return value: {:?}", e),
+                        Poll::Pending => break,
+                    }
+                }
+            }
+
+            Poll::Pending
+        })
+    )
+}
+
 #[test]
 fn get_record_many() {
     // TODO: Randomise
@@ -728,6 +887,128 @@ fn get_record_many() {
     )
 }
 
+#[test]
+fn get_record_streaming() {
+    let num_nodes = 12;
+    let mut swarms = build_connected_nodes(num_nodes, 3).into_iter()
+        .map(|(_addr, swarm)| swarm)
+        .collect::<Vec<_>>();
+    let num_results = 10;
+
+    let record = Record::new(random_multihash(), vec![4,5,6]);
+
+    for swarm in swarms.iter_mut().skip(1) {
+        swarm.behaviour_mut().store.put(record.clone()).unwrap();
+    }
+
+    let quorum = Quorum::N(NonZeroUsize::new(num_results).unwrap());
+    let qid = swarms[0].behaviour_mut().get_record_streaming(&record.key, quorum);
+    let mut found = Vec::new();
+
+    block_on(
+        poll_fn(move |ctx| {
+            for swarm in &mut swarms {
+                loop {
+                    match swarm.poll_next_unpin(ctx) {
+                        Poll::Ready(Some(SwarmEvent::Behaviour(KademliaEvent::RecordFound {
+                            id, record: r
+                        }))) => {
+                            assert_eq!(id, qid);
+                            assert!(r.peer.is_some());
+                            found.push(r);
+                        }
+                        Poll::Ready(Some(SwarmEvent::Behaviour(KademliaEvent::OutboundQueryCompleted {
+                            id,
+                            result: QueryResult::GetRecord(Ok(GetRecordOk { records, .. })),
+                            ..
+                        }))) => {
+                            assert_eq!(id, qid);
+                            // Every record has been reported before the lookup completed.
+                            assert_eq!(found.len(), records.len());
+                            assert!(found.iter().all(|r| r.record == record));
+                            return Poll::Ready(());
+                        }
+                        // Ignore any other event.
+                        Poll::Ready(Some(_)) => (),
+                        e @ Poll::Ready(_) => panic!("Unexpected return value: {:?}", e),
+                        Poll::Pending => break,
+                    }
+                }
+            }
+            Poll::Pending
+        })
+    )
+}
+
+/// Looks up a record with [`Kademlia::get_record_with_quorum`] and a quorum of
+/// two from a node connected to two nodes storing the given values.
+fn get_record_with_quorum_of_two(values: [Vec<u8>; 2]) -> GetRecordResult {
+    let mut swarms = build_nodes(3);
+
+    for i in 1 .. 3 {
+        let (peer_id, address) = (*Swarm::local_peer_id(&swarms[i].1), swarms[i].0.clone());
+        swarms[0].1.behaviour_mut().add_address(&peer_id, address);
+    }
+
+    let mut swarms = swarms.into_iter().map(|(_addr, swarm)| swarm).collect::<Vec<_>>();
+
+    let key = Key::from(random_multihash());
+    for (swarm, value) in swarms[1 ..].iter_mut().zip(values.iter()) {
+        swarm.behaviour_mut().store.put(Record::new(key.clone(), value.clone())).unwrap();
+    }
+
+    let quorum = Quorum::N(NonZeroUsize::new(2).unwrap());
+    let qid = swarms[0].behaviour_mut().get_record_with_quorum(&key, quorum);
+
+    block_on(
+        poll_fn(move |ctx| {
+            for swarm in &mut swarms {
+                loop {
+                    match swarm.poll_next_unpin(ctx) {
+                        Poll::Ready(Some(SwarmEvent::Behaviour(KademliaEvent::OutboundQueryCompleted {
+                            id,
+                            result: QueryResult::GetRecord(result),
+                            ..
+                        }))) => {
+                            assert_eq!(id, qid);
+                            return Poll::Ready(result);
+                        }
+                        // Ignore any other event.
+                        Poll::Ready(Some(_)) => (),
+                        e @ Poll::Ready(_) => panic!("Unexpected return value: {:?}", e),
+                        Poll::Pending => break,
+                    }
+                }
+            }
+            Poll::Pending
+        })
+    )
+}
+
+#[test]
+fn get_record_with_quorum_confirmed() {
+    match get_record_with_quorum_of_two([vec![1, 2, 3], vec![1, 2, 3]]) {
+        Ok(GetRecordOk { records, .. }) => {
+            assert_eq!(records.len(), 2);
+            assert!(records.iter().all(|r| r.record.value == vec![1, 2, 3]));
+        }
+        e => panic!("Unexpected result: {:?}", e),
+    }
+}
+
+#[test]
+fn get_record_with_quorum_conflict() {
+    match get_record_with_quorum_of_two([vec![1, 2, 3], vec![4, 5, 6]]) {
+        Err(GetRecordError::Conflict { records, quorum, .. }) => {
+            assert_eq!(quorum.get(), 2);
+            let mut values = records.into_iter().map(|r| r.record.value).collect::<Vec<_>>();
+            values.sort();
+            assert_eq!(values, vec![vec![1, 2, 3], vec![4, 5, 6]]);
+        }
+        e => panic!("Unexpected result: {:?}", e),
+    }
+}
+
 /// A node joining a fully connected network via three (ALPHA_VALUE) bootnodes
 /// should be able to add itself as a provider to the X closest nodes of the
 /// network where X is equal to the configured replication factor.
@@ -935,6 +1216,89 @@ fn exp_decr_expiration_overflow() {
     quickcheck(prop_no_panic as fn(_, _))
 }
 
+/// Expires records whose keys start with `short/` after a minute and
+/// all other records and provider records never.
+#[derive(Debug)]
+struct PrefixExpiryPolicy;
+
+impl ExpiryPolicy for PrefixExpiryPolicy {
+    fn record_ttl(&self, record: &Record) -> Option<Duration> {
+        if record.key.as_ref().starts_with(b"short/") {
+            Some(Duration::from_secs(60))
+        } else {
+            None
+        }
+    }
+
+    fn provider_ttl(&self, key: &Key) -> Option<Duration> {
+        if key.as_ref().starts_with(b"short/") {
+            Some(Duration::from_secs(60))
+        } else {
+            None
+        }
+    }
+}
+
+#[test]
+fn expiry_policy() {
+    // The publisher does not set an expiration, so the receiver's policy prevails.
+    let mut publisher_cfg = KademliaConfig::default();
+    publisher_cfg.set_record_ttl(None);
+    let mut receiver_cfg = KademliaConfig::default();
+    receiver_cfg.set_expiry_policy(PrefixExpiryPolicy);
+
+    let (_, mut publisher) = build_node_with_config(publisher_cfg);
+    let (receiver_addr, mut receiver) = build_node_with_config(receiver_cfg);
+    publisher.behaviour_mut().add_address(Swarm::local_peer_id(&receiver), receiver_addr);
+
+    let short = Record::new(Key::new(&"short/record"), vec![1]);
+    let long = Record::new(Key::new(&"long/record"), vec![2]);
+    publisher.behaviour_mut().put_record(short.clone(), Quorum::One).unwrap();
+    publisher.behaviour_mut().put_record(long.clone(), Quorum::One).unwrap();
+
+    let mut num_completed = 0;
+    block_on(
+        poll_fn(|ctx| {
+            for swarm in [&mut publisher, &mut receiver].iter_mut() {
+                loop {
+                    match swarm.poll_next_unpin(ctx) {
+                        Poll::Ready(Some(SwarmEvent::Behaviour(KademliaEvent::OutboundQueryCompleted {
+                            result: QueryResult::PutRecord(res), ..
+                        }))) => {
+                            assert!(res.is_ok(), "{:?}", res);
+                            num_completed += 1;
+                            if num_completed == 2 {
+                                return Poll::Ready(())
+                            }
+                        }
+                        // Ignore any other event.
+                        Poll::Ready(Some(_)) => (),
+                        e @ Poll::Ready(_) => panic!("Unexpected return value: {:?}", e),
+                        Poll::Pending => break,
+                    }
+                }
+            }
+            Poll::Pending
+        })
+    );
+
+    let store = &mut receiver.behaviour_mut().store;
+    let expires = store.get(&short.key).expect("record to be stored").expires.expect("record to expire");
+    assert!(expires <= Instant::now() + Duration::from_secs(60));
+    assert_eq!(store.get(&long.key).expect("record to be stored").expires, None);
+
+    let provider = KadPeer {
+        node_id: PeerId::random(),
+        multiaddrs: Vec::new(),
+        connection_ty: KadConnectionType::Connected,
+    };
+    let behaviour = receiver.behaviour_mut();
+    behaviour.provider_received(short.key.clone(), provider.clone());
+    behaviour.provider_received(long.key.clone(), provider);
+    assert!(behaviour.store.providers(&short.key)[0].expires.is_some());
+    assert!(behaviour.store.providers(&long.key)[0].expires.is_none());
+}
+
 #[test]
 fn disjoint_query_does_not_finish_before_all_paths_did() {
     let mut config = KademliaConfig::default();
@@ -1115,6 +1479,166 @@ fn manual_bucket_inserts() {
     }));
 }
 
+#[test]
+fn distinct_protocol_names_do_not_discover_each_other() {
+    let mut cfg = KademliaConfig::default();
+    cfg.set_genesis_hash([1; 32]);
+    assert_eq!(
+        cfg.protocol_config.protocol_name(),
+        format!("/{}/kad/1.0.0", "01".repeat(32)).as_bytes()
+    );
+
+    // Nodes 0 and 1 share a protocol name, node 2 uses the default one.
+    let mut swarms = build_nodes_with_config(2, cfg);
+    swarms.push(build_node());
+    let swarm_ids: Vec<_> = swarms.iter()
+        .map(|(addr, swarm)| (addr.clone(), *swarm.local_peer_id()))
+        .collect();
+    for (addr, peer_id) in swarm_ids.iter().skip(1) {
+        swarms[0].1.behaviour_mut().add_address(peer_id, addr.clone());
+    }
+
+    let mut swarms = swarms.into_iter().map(|(_a, s)| s).collect::<Vec<_>>();
+    let qid = swarms[0].behaviour_mut().get_closest_peers(PeerId::random());
+
+    block_on(poll_fn(move |ctx| {
+        for i in 0..swarms.len() {
+            loop {
+                match swarms[i].poll_next_unpin(ctx) {
+                    Poll::Ready(Some(SwarmEvent::Behaviour(KademliaEvent::OutboundQueryCompleted {
+                        id, result: QueryResult::GetClosestPeers(Ok(ok)), ..
+                    }))) => {
+                        assert_eq!(id, qid);
+                        assert_eq!(ok.peers, vec![swarm_ids[1].1]);
+                        // Node 2 never confirmed node 0 as a Kademlia peer.
+                        assert_eq!(swarms[2].behaviour_mut().kbuckets().count(), 0);
+                        return Poll::Ready(())
+                    }
+                    Poll::Ready(Some(_)) => {},
+                    e @ Poll::Ready(_) => panic!("Unexpected return value: {:?}", e),
+                    Poll::Pending => break,
+                }
+            }
+        }
+        Poll::Pending
+    }));
+}
+
+#[test]
+fn routing_updated_on_eviction() {
+    let mut cfg = KademliaConfig::default();
+    cfg.set_kbucket_pending_timeout(Duration::from_secs(0));
+    let (_, mut swarm) = build_node_with_config(cfg);
+    let (remote_addr, mut remote) = build_node();
+    let local_key = kbucket::Key::from(*Swarm::local_peer_id(&swarm));
+    let remote_id = *Swarm::local_peer_id(&remote);
+    let bucket = kbucket::Key::from(remote_id).distance(&local_key).ilog2();
+
+    // Fill the bucket of the remote with disconnected peers.
+    let peers = std::iter::repeat_with(PeerId::random)
+        .filter(|p| kbucket::Key::from(*p).distance(&local_key).ilog2() == bucket)
+        .take(K_VALUE.get())
+        .collect::<Vec<_>>();
+    for peer in &peers {
+        let address = Protocol::Memory(random::<u64>()).into();
+        let update = swarm.behaviour_mut().add_address(peer, address);
+        assert!(matches!(update, RoutingUpdate::Success));
+    }
+
+    // Once the remote is confirmed to support the protocol, it is pending
+    // insertion into the full bucket and evicts the least-recently connected,
+    // disconnected peer upon the next access to the bucket.
+    swarm.dial_addr(remote_addr).unwrap();
+
+    block_on(poll_fn(move |ctx| {
+        while let Poll::Ready(Some(_)) = remote.poll_next_unpin(ctx) {}
+        loop {
+            match swarm.poll_next_unpin(ctx) {
+                Poll::Ready(Some(SwarmEvent::ConnectionEstablished { peer_id, .. })) => {
+                    assert_eq!(peer_id, remote_id);
+                    let record = Record::new(random_multihash(), vec![1]);
+                    swarm.behaviour_mut().put_record_to(record, std::iter::once(remote_id), Quorum::One);
+                }
+                Poll::Ready(Some(SwarmEvent::Behaviour(KademliaEvent::PendingRoutablePeer {
+                    peer, ..
+                }))) => {
+                    assert_eq!(peer, remote_id);
+                    assert!(swarm.behaviour_mut().kbucket(remote_id).is_some());
+                }
+                Poll::Ready(Some(SwarmEvent::Behaviour(KademliaEvent::RoutingUpdated {
+                    peer, is_new_peer, old_peer, bucket_range, ..
+                }))) if peer == remote_id => {
+                    assert!(is_new_peer);
+                    assert_eq!(old_peer, Some(peers[0]));
+                    assert_eq!(bucket_range.1.ilog2(), bucket);
+                    return Poll::Ready(())
+                }
+                Poll::Ready(Some(_)) => {}
+                e @ Poll::Ready(None) => panic!("Unexpected return value: {:?}", e),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }))
+}
+
+#[test]
+fn routing_removed_on_last_address_removal() {
+    let (_, mut swarm) = build_node();
+    let peer = PeerId::random();
+    let addr1: Multiaddr = Protocol::Memory(random::<u64>()).into();
+    let addr2: Multiaddr = Protocol::Memory(random::<u64>()).into();
+    swarm.behaviour_mut().add_address(&peer, addr1.clone());
+    swarm.behaviour_mut().add_address(&peer, addr2.clone());
+    // Removing an address of a peer that remains in the routing table
+    // does not emit an event.
+    assert!(swarm.behaviour_mut().remove_address(&peer, &addr1).is_none());
+    assert!(swarm.behaviour_mut().remove_address(&peer, &addr2).is_some());
+
+    let mut updates = 0;
+    block_on(poll_fn(move |ctx| {
+        loop {
+            match swarm.poll_next_unpin(ctx) {
+                Poll::Ready(Some(SwarmEvent::Behaviour(KademliaEvent::RoutingUpdated {
+                    peer: p, ..
+                }))) => {
+                    assert_eq!(p, peer);
+                    updates += 1;
+                }
+                Poll::Ready(Some(SwarmEvent::Behaviour(KademliaEvent::RoutingRemoved {
+                    peer: p, addresses, ..
+                }))) => {
+                    assert_eq!(p, peer);
+                    assert_eq!(addresses.into_vec(), vec![addr2.clone()]);
+                    // One update for each added address, none for the removal.
+                    assert_eq!(updates, 2);
+                    return Poll::Ready(())
+                }
+                Poll::Ready(Some(_)) => {}
+                e @ Poll::Ready(None) => panic!("Unexpected return value: {:?}", e),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }))
+}
+
+#[test]
+fn addresses_of_peer_with_metadata() {
+    let (_, mut swarm) = build_node();
+    let peer = PeerId::random();
+    let addr: Multiaddr = Protocol::Memory(random::<u64>()).into();
+    swarm.behaviour_mut().add_address(&peer, addr.clone());
+
+    let addresses = swarm.behaviour_mut().addresses_of_peer_with_metadata(&peer);
+    assert_eq!(addresses.len(), 1);
+    let (a, meta) = &addresses[0];
+    assert_eq!(*a, addr);
+    assert_eq!(meta.source, AddressSource::Kademlia);
+    // The peer is in the routing table, but not connected.
+    assert_eq!(meta.score, 0.75);
+
+    assert_eq!(swarm.behaviour_mut().addresses_of_peer(&peer), vec![addr]);
+}
+
 #[test]
 fn network_behaviour_inject_address_change() {
     let local_peer_id = PeerId::random();
@@ -1169,3 +1693,54 @@ fn network_behaviour_inject_address_change() {
         kademlia.addresses_of_peer(&remote_peer_id),
     );
 }
+
+#[test]
+fn bootstrap_peer_recheck() {
+    let bootstrap_key = identity::Keypair::generate_ed25519();
+    let bootstrap_id = bootstrap_key.public().into_peer_id();
+    let bootstrap_addr: Multiaddr = Protocol::Memory(random::<u64>()).into();
+
+    let mut cfg = KademliaConfig::default();
+    cfg.set_bootstrap_peers(vec![(bootstrap_id, vec![bootstrap_addr.clone()])]);
+    cfg.set_bootstrap_recheck_interval(Duration::from_millis(100));
+    let (_, mut swarm) = build_node_with_config(cfg);
+
+    fn in_routing_table(swarm: &mut TestSwarm, peer: &PeerId) -> bool {
+        swarm.behaviour_mut().kbucket(*peer)
+            .map_or(false, |b| b.iter().any(|e| e.node.key.preimage() == peer))
+    }
+
+    // Polls the swarms until the reachability of the bootstrap peer changes.
+    fn next_state_change(swarms: &mut [&mut TestSwarm], bootstrap_id: PeerId) -> bool {
+        block_on(poll_fn(|ctx| {
+            for swarm in swarms.iter_mut() {
+                loop {
+                    match swarm.poll_next_unpin(ctx) {
+                        Poll::Ready(Some(SwarmEvent::Behaviour(
+                            KademliaEvent::BootstrapPeerStateChanged { peer_id, reachable }
+                        ))) => {
+                            assert_eq!(peer_id, bootstrap_id);
+                            return Poll::Ready(reachable)
+                        }
+                        Poll::Ready(..) => {},
+                        Poll::Pending => break
+                    }
+                }
+            }
+            Poll::Pending
+        }))
+    }
+
+    assert!(in_routing_table(&mut swarm, &bootstrap_id));
+
+    // The bootstrap peer is not online, hence the first check fails.
+    assert!(!next_state_change(&mut [&mut swarm], bootstrap_id));
+    assert_eq!(swarm.behaviour().failed_bootstrap_peers().collect::<Vec<_>>(), vec![&bootstrap_id]);
+    assert!(!in_routing_table(&mut swarm, &bootstrap_id));
+
+    // Once the bootstrap peer is online, a recheck succeeds.
+    let (_, mut bootstrap) = build_node_with_identity(bootstrap_key, bootstrap_addr, Default::default());
+    assert!(next_state_change(&mut [&mut swarm, &mut bootstrap], bootstrap_id));
+    assert_eq!(swarm.behaviour().failed_bootstrap_peers().count(), 0);
+    assert!(in_routing_table(&mut swarm, &bootstrap_id));
+}
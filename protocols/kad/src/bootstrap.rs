@@ -0,0 +1,113 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Health checking of configured bootstrap peers.
+//!
+//! Every bootstrap peer that is not connected is dialed periodically. A
+//! bootstrap peer that cannot be dialed is considered unreachable and removed
+//! from the routing table, while being retained in the list of bootstrap
+//! peers, such that it is re-added once a later dialing attempt succeeds.
+
+use futures::prelude::*;
+use libp2p_core::{Multiaddr, PeerId};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use wasm_timer::{Delay, Instant};
+
+/// The configured bootstrap peers and their reachability.
+pub(crate) struct BootstrapPeers {
+    /// The bootstrap peers, in the order in which they were configured.
+    peers: Vec<BootstrapPeer>,
+    /// The interval at which bootstrap peers are rechecked.
+    recheck_interval: Duration,
+    /// The delay until the next recheck.
+    recheck: Delay,
+}
+
+/// A configured bootstrap peer.
+struct BootstrapPeer {
+    peer_id: PeerId,
+    addresses: Vec<Multiaddr>,
+    reachable: bool,
+}
+
+impl BootstrapPeers {
+    /// Creates a new `BootstrapPeers` that initially assumes all given peers
+    /// to be reachable. The first check is due immediately.
+    pub(crate) fn new(peers: Vec<(PeerId, Vec<Multiaddr>)>, recheck_interval: Duration) -> Self {
+        BootstrapPeers {
+            peers: peers.into_iter()
+                .map(|(peer_id, addresses)| BootstrapPeer { peer_id, addresses, reachable: true })
+                .collect(),
+            recheck_interval,
+            recheck: Delay::new_at(Instant::now()),
+        }
+    }
+
+    /// Returns the configured bootstrap peers with their addresses.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&PeerId, &[Multiaddr])> {
+        self.peers.iter().map(|p| (&p.peer_id, p.addresses.as_slice()))
+    }
+
+    /// Returns the bootstrap peers currently considered unreachable.
+    pub(crate) fn failed(&self) -> impl Iterator<Item = &PeerId> {
+        self.peers.iter().filter(|p| !p.reachable).map(|p| &p.peer_id)
+    }
+
+    /// Returns the configured addresses of the given peer, if it is a bootstrap peer.
+    pub(crate) fn addresses(&self, peer_id: &PeerId) -> Option<&[Multiaddr]> {
+        self.peers.iter()
+            .find(|p| p.peer_id == *peer_id)
+            .map(|p| p.addresses.as_slice())
+    }
+
+    /// Records whether the given peer is reachable.
+    ///
+    /// Returns `true` if the peer is a bootstrap peer whose reachability
+    /// changed, `false` otherwise.
+    pub(crate) fn set_reachable(&mut self, peer_id: &PeerId, reachable: bool) -> bool {
+        match self.peers.iter_mut().find(|p| p.peer_id == *peer_id) {
+            Some(peer) if peer.reachable != reachable => {
+                peer.reachable = reachable;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Polls for the next recheck, returning the bootstrap peers to check
+    /// once it is due.
+    pub(crate) fn poll_recheck(&mut self, cx: &mut Context<'_>) -> Poll<Vec<PeerId>> {
+        if self.peers.is_empty() {
+            return Poll::Pending
+        }
+
+        match Future::poll(Pin::new(&mut self.recheck), cx) {
+            Poll::Ready(_) => {
+                self.recheck.reset_at(Instant::now() + self.recheck_interval);
+                // Register the waker with the new deadline.
+                let _ = Future::poll(Pin::new(&mut self.recheck), cx);
+                Poll::Ready(self.peers.iter().map(|p| p.peer_id).collect())
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
@@ -61,11 +61,12 @@
 //! > to the size of all stored records. As a job runs, the records are moved
 //! > out of the job to the consumer, where they can be dropped after being sent.
 
-use crate::record::{self, Record, ProviderRecord, store::RecordStore};
+use crate::record::{self, ExpiryPolicy, Record, ProviderRecord, store::RecordStore};
 use libp2p_core::PeerId;
 use futures::prelude::*;
 use std::collections::HashSet;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::time::Duration;
 use std::vec;
@@ -132,7 +133,7 @@ pub struct PutRecordJob {
     local_id: PeerId,
     next_publish: Option<Instant>,
     publish_interval: Option<Duration>,
-    record_ttl: Option<Duration>,
+    expiry_policy: Arc<dyn ExpiryPolicy>,
     skipped: HashSet<record::Key>,
     inner: PeriodicJob<vec::IntoIter<Record>>,
 }
@@ -144,7 +145,7 @@ impl PutRecordJob {
         local_id: PeerId,
         replicate_interval: Duration,
         publish_interval: Option<Duration>,
-        record_ttl: Option<Duration>,
+        expiry_policy: Arc<dyn ExpiryPolicy>,
     ) -> Self {
         let now = Instant::now();
         let deadline = now + replicate_interval;
@@ -154,7 +155,7 @@ impl PutRecordJob {
             local_id,
             next_publish,
             publish_interval,
-            record_ttl,
+            expiry_policy,
             skipped: HashSet::new(),
             inner: PeriodicJob {
                 interval: replicate_interval,
@@ -205,7 +206,7 @@ impl PutRecordJob {
                         let mut record = r.into_owned();
                         if publish && is_publisher {
                             record.expires = record.expires.or_else(||
-                                self.record_ttl.map(|ttl| now + ttl));
+                                self.expiry_policy.record_ttl(&record).map(|ttl| now + ttl));
                         }
                         Some(record)
                     }
@@ -317,7 +318,7 @@ impl AddProviderJob {
 
 #[cfg(test)]
 mod tests {
-    use crate::record::store::MemoryStore;
+    use crate::record::{FixedExpiryPolicy, store::MemoryStore};
     use futures::{executor::block_on, future::poll_fn};
     use quickcheck::*;
     use rand::Rng;
@@ -329,7 +330,8 @@ mod tests {
         let replicate_interval = Duration::from_secs(rng.gen_range(1, 60));
         let publish_interval = Some(replicate_interval * rng.gen_range(1, 10));
         let record_ttl = Some(Duration::from_secs(rng.gen_range(1, 600)));
-        PutRecordJob::new(id.clone(), replicate_interval, publish_interval, record_ttl)
+        let expiry_policy = Arc::new(FixedExpiryPolicy::new(record_ttl, None));
+        PutRecordJob::new(id, replicate_interval, publish_interval, expiry_policy)
     }
 
     fn rand_add_provider_job() -> AddProviderJob {
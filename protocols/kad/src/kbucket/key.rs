@@ -249,6 +249,19 @@ mod tests {
         quickcheck(prop as fn(_,_,_) -> _)
     }
 
+    #[test]
+    fn matches_peer_id_distance() {
+        fn prop(a: Key<PeerId>, b: Key<PeerId>) -> bool {
+            let d = a.distance(&b);
+            let mut bytes = [0; 32];
+            d.0.to_big_endian(&mut bytes);
+            let (a, b) = (a.preimage(), b.preimage());
+            libp2p_core::Distance::from(bytes) == a.xor_distance(b)
+                && d.ilog2().map(|i| i as u8) == a.bucket_index(b)
+        }
+        quickcheck(prop as fn(_,_) -> _)
+    }
+
     #[test]
     fn unidirectionality() {
         fn prop(a: Key<PeerId>, b: Key<PeerId>) -> bool {
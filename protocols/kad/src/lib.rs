@@ -31,7 +31,10 @@ pub mod record;
 
 mod addresses;
 mod behaviour;
+mod bootstrap;
 mod jobs;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 mod query;
 
 mod dht_proto {
@@ -89,7 +92,7 @@ pub use behaviour::{
 };
 pub use query::QueryId;
 pub use protocol::KadConnectionType;
-pub use record::{store, Record, ProviderRecord};
+pub use record::{store, ExpiryPolicy, FixedExpiryPolicy, Record, ProviderRecord};
 
 use std::num::NonZeroUsize;
 
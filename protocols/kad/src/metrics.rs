@@ -0,0 +1,120 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Prometheus metrics of the [`Kademlia`](crate::Kademlia) behaviour.
+
+use crate::{InboundRequest, KademliaEvent, QueryResult};
+use prometheus::{
+    exponential_buckets, Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, Opts,
+    Registry,
+};
+
+/// The metrics recorded from [`KademliaEvent`]s.
+#[derive(Clone)]
+pub struct Metrics {
+    inbound_requests: IntCounterVec,
+    queries: IntCounterVec,
+    query_duration: HistogramVec,
+    query_requests: Histogram,
+    routing_updated: IntCounter,
+    routing_removed: IntCounter,
+}
+
+impl Metrics {
+    /// Creates the metrics and registers them with the given registry.
+    pub fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let metrics = Metrics {
+            inbound_requests: IntCounterVec::new(
+                Opts::new("libp2p_kad_inbound_requests_total", "Number of inbound requests served by type."),
+                &["type"],
+            )?,
+            queries: IntCounterVec::new(
+                Opts::new("libp2p_kad_queries_total", "Number of completed outbound queries by type and outcome."),
+                &["type", "outcome"],
+            )?,
+            query_duration: HistogramVec::new(
+                HistogramOpts::new("libp2p_kad_query_duration_seconds", "Duration of completed outbound queries in seconds.")
+                    .buckets(exponential_buckets(0.1, 2.0, 10)?),
+                &["type"],
+            )?,
+            query_requests: Histogram::with_opts(
+                HistogramOpts::new("libp2p_kad_query_requests", "Number of requests sent by completed outbound queries.")
+                    .buckets(exponential_buckets(1.0, 2.0, 10)?),
+            )?,
+            routing_updated: IntCounter::new(
+                "libp2p_kad_routing_updated_total",
+                "Number of peers added to or updated in the routing table.",
+            )?,
+            routing_removed: IntCounter::new(
+                "libp2p_kad_routing_removed_total",
+                "Number of peers removed from the routing table.",
+            )?,
+        };
+        registry.register(Box::new(metrics.inbound_requests.clone()))?;
+        registry.register(Box::new(metrics.queries.clone()))?;
+        registry.register(Box::new(metrics.query_duration.clone()))?;
+        registry.register(Box::new(metrics.query_requests.clone()))?;
+        registry.register(Box::new(metrics.routing_updated.clone()))?;
+        registry.register(Box::new(metrics.routing_removed.clone()))?;
+        Ok(metrics)
+    }
+
+    /// Records an event emitted by the [`Kademlia`](crate::Kademlia) behaviour.
+    pub fn record(&self, event: &KademliaEvent) {
+        match event {
+            KademliaEvent::InboundRequestServed { request } => {
+                let kind = match request {
+                    InboundRequest::FindNode { .. } => "find_node",
+                    InboundRequest::GetProvider { .. } => "get_provider",
+                    InboundRequest::AddProvider { .. } => "add_provider",
+                    InboundRequest::GetRecord { .. } => "get_record",
+                    InboundRequest::PutRecord { .. } => "put_record",
+                };
+                self.inbound_requests.with_label_values(&[kind]).inc();
+            }
+            KademliaEvent::OutboundQueryCompleted { result, stats, .. } => {
+                let (kind, ok) = match result {
+                    QueryResult::Bootstrap(r) => ("bootstrap", r.is_ok()),
+                    QueryResult::GetClosestPeers(r) => ("get_closest_peers", r.is_ok()),
+                    QueryResult::GetProviders(r) => ("get_providers", r.is_ok()),
+                    QueryResult::StartProviding(r) => ("start_providing", r.is_ok()),
+                    QueryResult::RepublishProvider(r) => ("republish_provider", r.is_ok()),
+                    QueryResult::GetRecord(r) => ("get_record", r.is_ok()),
+                    QueryResult::PutRecord(r) => ("put_record", r.is_ok()),
+                    QueryResult::RepublishRecord(r) => ("republish_record", r.is_ok()),
+                };
+                let outcome = if ok { "success" } else { "failure" };
+                self.queries.with_label_values(&[kind, outcome]).inc();
+                if let Some(duration) = stats.duration() {
+                    self.query_duration.with_label_values(&[kind]).observe(duration.as_secs_f64());
+                }
+                self.query_requests.observe(f64::from(stats.num_requests()));
+            }
+            KademliaEvent::RoutingUpdated { .. } => self.routing_updated.inc(),
+            KademliaEvent::RoutingRemoved { .. } => self.routing_removed.inc(),
+            KademliaEvent::RecordFound { .. }
+            | KademliaEvent::ProviderFound { .. }
+            | KademliaEvent::BootstrapPeerStateChanged { .. }
+            | KademliaEvent::UnroutablePeer { .. }
+            | KademliaEvent::RoutablePeer { .. }
+            | KademliaEvent::PendingRoutablePeer { .. } => {}
+        }
+    }
+}
@@ -40,7 +40,12 @@ use std::{io, iter};
 use unsigned_varint::codec;
 use wasm_timer::Instant;
 
-/// The protocol name used for negotiating with multistream-select.
+/// The protocol name used for negotiating with multistream-select, unless
+/// configured otherwise.
+///
+/// This is the protocol name of the IPFS DHT. Deployments that do not intend
+/// to join the IPFS DHT should configure a different protocol name, see
+/// [`KademliaConfig::set_protocol_name`](crate::KademliaConfig::set_protocol_name).
 pub const DEFAULT_PROTO_NAME: &[u8] = b"/ipfs/kad/1.0.0";
 
 /// The default maximum size for a varint length-delimited packet.
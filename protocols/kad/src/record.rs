@@ -22,6 +22,10 @@
 
 pub mod store;
 
+mod expiry;
+
+pub use expiry::{ExpiryPolicy, FixedExpiryPolicy};
+
 use bytes::Bytes;
 use libp2p_core::{PeerId, Multiaddr, multihash::Multihash};
 use std::borrow::Borrow;
@@ -0,0 +1,70 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use super::{Key, Record};
+use std::{fmt, time::Duration};
+
+/// Determines the TTLs of records and provider records.
+///
+/// The TTL of a record applies whenever the record is published or
+/// replicated and has no explicit expiration, as well as when a record
+/// received from a remote is stored. The TTL of a provider record applies
+/// when a provider record received from a remote is stored.
+///
+/// A policy can be configured via [`KademliaConfig::set_expiry_policy`](crate::KademliaConfig::set_expiry_policy),
+/// e.g. to give records with different key prefixes different TTLs. By
+/// default, a [`FixedExpiryPolicy`] with the TTLs configured via
+/// [`KademliaConfig::set_record_ttl`](crate::KademliaConfig::set_record_ttl) and
+/// [`KademliaConfig::set_provider_record_ttl`](crate::KademliaConfig::set_provider_record_ttl)
+/// is used.
+pub trait ExpiryPolicy: fmt::Debug + Send + Sync + 'static {
+    /// Returns the TTL of the given record, `None` meaning that the
+    /// record never expires.
+    fn record_ttl(&self, record: &Record) -> Option<Duration>;
+
+    /// Returns the TTL of provider records for the given key, `None`
+    /// meaning that the provider records never expire.
+    fn provider_ttl(&self, key: &Key) -> Option<Duration>;
+}
+
+/// An [`ExpiryPolicy`] with the same TTLs for all records and for all
+/// provider records, respectively.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FixedExpiryPolicy {
+    record_ttl: Option<Duration>,
+    provider_ttl: Option<Duration>,
+}
+
+impl FixedExpiryPolicy {
+    /// Creates a new `FixedExpiryPolicy` with the given TTLs.
+    pub fn new(record_ttl: Option<Duration>, provider_ttl: Option<Duration>) -> Self {
+        FixedExpiryPolicy { record_ttl, provider_ttl }
+    }
+}
+
+impl ExpiryPolicy for FixedExpiryPolicy {
+    fn record_ttl(&self, _: &Record) -> Option<Duration> {
+        self.record_ttl
+    }
+
+    fn provider_ttl(&self, _: &Key) -> Option<Duration> {
+        self.provider_ttl
+    }
+}
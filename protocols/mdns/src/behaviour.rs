@@ -29,8 +29,8 @@ use libp2p_core::{
     address_translation, connection::ConnectionId, multiaddr::Protocol, Multiaddr, PeerId,
 };
 use libp2p_swarm::{
-    protocols_handler::DummyProtocolsHandler, NetworkBehaviour, NetworkBehaviourAction,
-    PollParameters, ProtocolsHandler,
+    protocols_handler::DummyProtocolsHandler, AddressMeta, AddressSource, NetworkBehaviour,
+    NetworkBehaviourAction, PollParameters, ProtocolsHandler,
 };
 use smallvec::SmallVec;
 use socket2::{Domain, Socket, Type};
@@ -100,7 +100,7 @@ pub struct Mdns {
     ///
     /// Each combination of `PeerId` and `Multiaddr` can only appear once, but the same `PeerId`
     /// can appear multiple times.
-    discovered_nodes: SmallVec<[(PeerId, Multiaddr, Instant); 8]>,
+    discovered_nodes: SmallVec<[(PeerId, Multiaddr, Instant, Instant); 8]>,
 
     /// Future that fires when the TTL of at least one node in `discovered_nodes` expires.
     ///
@@ -161,7 +161,18 @@ impl Mdns {
 
     /// Returns the list of nodes that we have discovered through mDNS and that are not expired.
     pub fn discovered_nodes(&self) -> impl ExactSizeIterator<Item = &PeerId> {
-        self.discovered_nodes.iter().map(|(p, _, _)| p)
+        self.discovered_nodes.iter().map(|(p, _, _, _)| p)
+    }
+
+    /// Sets the timer for the earliest expiration among `discovered_nodes`.
+    fn update_closest_expiration(&mut self) {
+        self.closest_expiration = self
+            .discovered_nodes
+            .iter()
+            .fold(None, |exp, &(_, _, elem_exp, _)| {
+                Some(exp.map(|exp| cmp::min(exp, elem_exp)).unwrap_or(elem_exp))
+            })
+            .map(Timer::at);
     }
 
     fn inject_mdns_packet(&mut self, packet: MdnsPacket, params: &impl PollParameters) {
@@ -191,7 +202,8 @@ impl Mdns {
                         continue;
                     }
 
-                    let new_expiration = Instant::now() + peer.ttl();
+                    let now = Instant::now();
+                    let new_expiration = now + peer.ttl();
 
                     let mut addrs: Vec<Multiaddr> = Vec::new();
                     for addr in peer.addresses() {
@@ -202,27 +214,26 @@ impl Mdns {
                     }
 
                     for addr in addrs {
-                        if let Some((_, _, cur_expires)) = self
+                        if let Some((_, _, cur_expires, last_seen)) = self
                             .discovered_nodes
                             .iter_mut()
-                            .find(|(p, a, _)| p == peer.id() && *a == addr)
+                            .find(|(p, a, _, _)| p == peer.id() && *a == addr)
                         {
                             *cur_expires = cmp::max(*cur_expires, new_expiration);
+                            *last_seen = now;
                         } else {
-                            self.discovered_nodes
-                                .push((*peer.id(), addr.clone(), new_expiration));
+                            self.discovered_nodes.push((
+                                *peer.id(),
+                                addr.clone(),
+                                new_expiration,
+                                now,
+                            ));
                             discovered.push((*peer.id(), addr));
                         }
                     }
                 }
 
-                self.closest_expiration = self
-                    .discovered_nodes
-                    .iter()
-                    .fold(None, |exp, &(_, _, elem_exp)| {
-                        Some(exp.map(|exp| cmp::min(exp, elem_exp)).unwrap_or(elem_exp))
-                    })
-                    .map(Timer::at);
+                self.update_closest_expiration();
 
                 self.events
                     .push_back(MdnsEvent::Discovered(DiscoveredAddrsIter {
@@ -246,11 +257,26 @@ impl NetworkBehaviour for Mdns {
     }
 
     fn addresses_of_peer(&mut self, peer_id: &PeerId) -> Vec<Multiaddr> {
+        self.addresses_of_peer_with_metadata(peer_id)
+            .into_iter()
+            .map(|(addr, _)| addr)
+            .collect()
+    }
+
+    fn addresses_of_peer_with_metadata(
+        &mut self,
+        peer_id: &PeerId,
+    ) -> Vec<(Multiaddr, AddressMeta)> {
         let now = Instant::now();
         self.discovered_nodes
             .iter()
-            .filter(move |(p, _, expires)| p == peer_id && *expires > now)
-            .map(|(_, addr, _)| addr.clone())
+            .filter(move |(p, _, expires, _)| p == peer_id && *expires > now)
+            .map(|(_, addr, _, last_seen)| {
+                let meta = AddressMeta::new(AddressSource::Mdns)
+                    .with_score(1.0)
+                    .with_last_seen(*last_seen);
+                (addr.clone(), meta)
+            })
             .collect()
     }
 
@@ -361,11 +387,12 @@ impl NetworkBehaviour for Mdns {
                 while let Some(pos) = self
                     .discovered_nodes
                     .iter()
-                    .position(|(_, _, exp)| *exp < now)
+                    .position(|(_, _, exp, _)| *exp <= now)
                 {
-                    let (peer_id, addr, _) = self.discovered_nodes.remove(pos);
+                    let (peer_id, addr, _, _) = self.discovered_nodes.remove(pos);
                     expired.push((peer_id, addr));
                 }
+                self.update_closest_expiration();
 
                 if !expired.is_empty() {
                     let event = MdnsEvent::Expired(ExpiredAddrsIter {
@@ -56,6 +56,9 @@ pub struct PingConfig {
     /// Whether the connection should generally be kept alive unless
     /// `max_failures` occur.
     keep_alive: bool,
+    /// The round-trip time above which a successful outbound ping is
+    /// reported as [`PingSuccess::RttExceeded`].
+    rtt_threshold: Option<Duration>,
 }
 
 impl PingConfig {
@@ -65,6 +68,7 @@ impl PingConfig {
     ///   * [`PingConfig::with_timeout`] 20s
     ///   * [`PingConfig::with_max_failures`] 1
     ///   * [`PingConfig::with_keep_alive`] false
+    ///   * [`PingConfig::with_rtt_threshold`] none
     ///
     /// These settings have the following effect:
     ///
@@ -76,12 +80,15 @@ impl PingConfig {
     ///   * The connection may be closed at any time as far as the ping protocol
     ///     is concerned, i.e. the ping protocol itself does not keep the
     ///     connection alive.
+    ///   * Round-trip times are reported as measured, without any additional
+    ///     events for slow pings.
     pub fn new() -> Self {
         Self {
             timeout: Duration::from_secs(20),
             interval: Duration::from_secs(15),
             max_failures: NonZeroU32::new(1).expect("1 != 0"),
-            keep_alive: false
+            keep_alive: false,
+            rtt_threshold: None,
         }
     }
 
@@ -118,6 +125,22 @@ impl PingConfig {
         self.keep_alive = b;
         self
     }
+
+    /// Sets the round-trip time above which a successful outbound ping is
+    /// considered slow.
+    ///
+    /// Whenever the measured round-trip time of an outbound ping exceeds the
+    /// threshold, the ping is reported as [`PingSuccess::RttExceeded`]
+    /// instead of [`PingSuccess::Ping`].
+    pub fn with_rtt_threshold(mut self, d: Duration) -> Self {
+        self.rtt_threshold = Some(d);
+        self
+    }
+
+    /// Returns the configured round-trip time threshold, if any.
+    pub fn rtt_threshold(&self) -> Option<Duration> {
+        self.rtt_threshold
+    }
 }
 
 /// The result of an inbound or outbound ping.
@@ -132,6 +155,12 @@ pub enum PingSuccess {
     ///
     /// Includes the round-trip time.
     Ping { rtt: Duration },
+    /// Sent a ping and received back a pong, but the round-trip time
+    /// exceeded the threshold set via [`PingConfig::with_rtt_threshold`].
+    ///
+    /// Reported in place of [`PingSuccess::Ping`], i.e. every successful
+    /// outbound ping is reported exactly once.
+    RttExceeded { rtt: Duration, threshold: Duration },
 }
 
 /// An outbound ping failure.
@@ -42,6 +42,8 @@
 
 pub mod protocol;
 pub mod handler;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 
 pub use handler::{PingConfig, PingResult, PingSuccess, PingFailure};
 use handler::PingHandler;
@@ -104,6 +106,11 @@ impl NetworkBehaviour for Ping {
     fn inject_disconnected(&mut self, _: &PeerId) {}
 
     fn inject_event(&mut self, peer: PeerId, _: ConnectionId, result: PingResult) {
+        let result = match (result, self.config.rtt_threshold()) {
+            (Ok(PingSuccess::Ping { rtt }), Some(threshold)) if rtt > threshold =>
+                Ok(PingSuccess::RttExceeded { rtt, threshold }),
+            (result, _) => result,
+        };
         self.events.push_front(PingEvent { peer, result })
     }
 
@@ -0,0 +1,76 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Prometheus metrics of the [`Ping`](crate::Ping) behaviour.
+
+use crate::{PingEvent, PingFailure, PingSuccess};
+use prometheus::{Histogram, HistogramOpts, IntCounter, IntCounterVec, Opts, Registry};
+
+/// The metrics recorded from [`PingEvent`]s.
+#[derive(Clone)]
+pub struct Metrics {
+    rtt: Histogram,
+    rtt_exceeded: IntCounter,
+    pongs_sent: IntCounter,
+    failures: IntCounterVec,
+}
+
+impl Metrics {
+    /// Creates the metrics and registers them with the given registry.
+    pub fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let metrics = Metrics {
+            rtt: Histogram::with_opts(HistogramOpts::new(
+                "libp2p_ping_rtt_seconds",
+                "Round-trip times of successful outbound pings in seconds.",
+            ))?,
+            rtt_exceeded: IntCounter::new(
+                "libp2p_ping_rtt_exceeded_total",
+                "Number of outbound pings exceeding the configured round-trip time threshold.",
+            )?,
+            pongs_sent: IntCounter::new(
+                "libp2p_ping_pongs_sent_total",
+                "Number of inbound pings answered.",
+            )?,
+            failures: IntCounterVec::new(
+                Opts::new("libp2p_ping_failures_total", "Number of failed pings by reason."),
+                &["reason"],
+            )?,
+        };
+        registry.register(Box::new(metrics.rtt.clone()))?;
+        registry.register(Box::new(metrics.rtt_exceeded.clone()))?;
+        registry.register(Box::new(metrics.pongs_sent.clone()))?;
+        registry.register(Box::new(metrics.failures.clone()))?;
+        Ok(metrics)
+    }
+
+    /// Records an event emitted by the [`Ping`](crate::Ping) behaviour.
+    pub fn record(&self, event: &PingEvent) {
+        match &event.result {
+            Ok(PingSuccess::Pong) => self.pongs_sent.inc(),
+            Ok(PingSuccess::Ping { rtt }) => self.rtt.observe(rtt.as_secs_f64()),
+            Ok(PingSuccess::RttExceeded { rtt, .. }) => {
+                self.rtt.observe(rtt.as_secs_f64());
+                self.rtt_exceeded.inc();
+            }
+            Err(PingFailure::Timeout) => self.failures.with_label_values(&["timeout"]).inc(),
+            Err(PingFailure::Other { .. }) => self.failures.with_label_values(&["other"]).inc(),
+        }
+    }
+}
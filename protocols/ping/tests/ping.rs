@@ -189,6 +189,65 @@ fn max_failures() {
     QuickCheck::new().tests(10).quickcheck(prop as fn(_,_))
 }
 
+/// Tests that a ping with a round-trip time above the configured
+/// threshold is reported as such instead of as a regular ping.
+#[test]
+fn rtt_threshold() {
+    fn prop(muxer: MuxerChoice) {
+        // No pong can arrive within zero time, so every ping is too slow.
+        let threshold = Duration::from_secs(0);
+        let cfg = PingConfig::new()
+            .with_keep_alive(true)
+            .with_interval(Duration::from_millis(10))
+            .with_rtt_threshold(threshold);
+
+        let (peer1_id, trans) = mk_transport(muxer);
+        let mut swarm1 = Swarm::new(trans, Ping::new(cfg.clone()), peer1_id);
+
+        let (peer2_id, trans) = mk_transport(muxer);
+        let mut swarm2 = Swarm::new(trans, Ping::new(cfg), peer2_id);
+
+        let (mut tx, mut rx) = mpsc::channel::<Multiaddr>(1);
+
+        let addr = "/ip4/127.0.0.1/tcp/0".parse().unwrap();
+        swarm1.listen_on(addr).unwrap();
+
+        let peer1 = async move {
+            loop {
+                match swarm1.select_next_some().await {
+                    SwarmEvent::NewListenAddr { address, .. } => tx.send(address).await.unwrap(),
+                    SwarmEvent::Behaviour(PingEvent {
+                        peer,
+                        result: Ok(PingSuccess::RttExceeded { rtt, threshold: t }),
+                    }) => {
+                        assert_eq!(peer, peer2_id);
+                        assert_eq!(t, threshold);
+                        assert!(rtt > threshold);
+                        return
+                    }
+                    SwarmEvent::Behaviour(PingEvent {
+                        result: Ok(PingSuccess::Ping { rtt }), ..
+                    }) => panic!("Ping within threshold: {:?}", rtt),
+                    SwarmEvent::Behaviour(PingEvent { result: Err(e), .. }) => panic!("Ping failure: {:?}", e),
+                    _ => {}
+                }
+            }
+        };
+
+        let peer2 = async move {
+            swarm2.dial_addr(rx.next().await.unwrap()).unwrap();
+
+            loop {
+                swarm2.select_next_some().await;
+            }
+        };
+
+        let result = future::select(Box::pin(peer1), Box::pin(peer2));
+        async_std::task::block_on(result);
+    }
+
+    QuickCheck::new().tests(10).quickcheck(prop as fn(_))
+}
 
 fn mk_transport(muxer: MuxerChoice) -> (
     PeerId,
@@ -67,6 +67,14 @@ pub struct Relay {
     /// Channel sender to listener listening for incoming relayed connections from relay nodes via
     /// which the local node is not explicitly listening.
     listener_any_relay: Option<mpsc::Sender<BehaviourToListenerMsg>>,
+
+    /// Connected peers advertising support for the relay protocol, see
+    /// [`RelayConfig::auto_discovery`].
+    relay_candidates: HashSet<PeerId>,
+
+    /// The metrics given via [`Relay::set_metrics`], if any.
+    #[cfg(feature = "metrics")]
+    metrics: Option<crate::metrics::Metrics>,
 }
 
 #[derive(Default)]
@@ -115,6 +123,13 @@ pub struct RelayConfig {
     /// destination node should establish a connection to a relay node before
     /// advertising their relayed address via that relay node to a source node.
     pub actively_connect_to_dst_nodes: bool,
+    /// Whether to collect connected peers advertising support for the relay
+    /// protocol as relay candidates, see [`Relay::relay_candidates`].
+    ///
+    /// The supported protocols of a peer are learned through
+    /// [`Relay::on_identify_protocols`], e.g. on every
+    /// `IdentifyEvent::Received` of the identify protocol.
+    pub auto_discovery: bool,
 }
 
 impl Default for RelayConfig {
@@ -122,6 +137,7 @@ impl Default for RelayConfig {
         RelayConfig {
             connection_idle_timeout: Duration::from_secs(10),
             actively_connect_to_dst_nodes: false,
+            auto_discovery: false,
         }
     }
 }
@@ -146,7 +162,55 @@ impl Relay {
             outgoing_relay_reqs: Default::default(),
             listeners: Default::default(),
             listener_any_relay: Default::default(),
+            relay_candidates: Default::default(),
+            #[cfg(feature = "metrics")]
+            metrics: None,
+        }
+    }
+
+    /// Records metrics of the relayed connections and requests of this
+    /// behaviour in the given [`Metrics`](crate::metrics::Metrics).
+    #[cfg(feature = "metrics")]
+    pub fn set_metrics(&mut self, metrics: crate::metrics::Metrics) {
+        self.metrics = Some(metrics);
+    }
+
+    /// Records a metric via the given function, if metrics were set.
+    #[cfg(feature = "metrics")]
+    fn record(&self, f: impl FnOnce(&crate::metrics::Metrics)) {
+        if let Some(metrics) = &self.metrics {
+            f(metrics)
+        }
+    }
+
+    /// Informs the behaviour about the protocols supported by a connected
+    /// peer, as reported e.g. by the identify protocol.
+    ///
+    /// With [`RelayConfig::auto_discovery`] enabled, the peer is added to the
+    /// relay candidates if it supports the relay protocol and removed
+    /// otherwise. Does nothing if auto discovery is disabled.
+    pub fn on_identify_protocols<P: AsRef<str>>(&mut self, peer_id: &PeerId, protocols: &[P]) {
+        if !self.config.auto_discovery || !self.connected_peers.contains_key(peer_id) {
+            return;
         }
+
+        let supports_relay = protocols
+            .iter()
+            .any(|p| p.as_ref().as_bytes() == &protocol::PROTOCOL_NAME[..]);
+        if supports_relay {
+            self.relay_candidates.insert(*peer_id);
+        } else {
+            self.relay_candidates.remove(peer_id);
+        }
+    }
+
+    /// Returns the connected peers discovered to support the relay protocol,
+    /// see [`RelayConfig::auto_discovery`].
+    ///
+    /// The local node can listen for incoming relayed connections via any of
+    /// them through `Swarm::listen_on(<relay_addr>/p2p/<relay>/p2p-circuit)`.
+    pub fn relay_candidates(&self) -> impl Iterator<Item = &PeerId> {
+        self.relay_candidates.iter()
     }
 }
 
@@ -412,6 +476,7 @@ impl NetworkBehaviour for Relay {
 
     fn inject_disconnected(&mut self, id: &PeerId) {
         self.connected_peers.remove(id);
+        self.relay_candidates.remove(id);
 
         if let Some(reqs) = self.incoming_relay_reqs.remove(id) {
             for req in reqs {
@@ -446,6 +511,8 @@ impl NetworkBehaviour for Relay {
                 req,
             } => {
                 if self.connected_peers.get(&req.dst_peer().peer_id).is_some() {
+                    #[cfg(feature = "metrics")]
+                    self.record(|m| m.incoming_relay_req("forwarded"));
                     let dest_id = req.dst_peer().peer_id;
                     let event = RelayHandlerIn::OutgoingDstReq {
                         src_peer_id: event_source,
@@ -462,6 +529,8 @@ impl NetworkBehaviour for Relay {
                         });
                 } else {
                     if self.config.actively_connect_to_dst_nodes {
+                        #[cfg(feature = "metrics")]
+                        self.record(|m| m.incoming_relay_req("dialing_dst"));
                         let dest_id = req.dst_peer().peer_id;
                         self.incoming_relay_reqs.entry(dest_id).or_default().push(
                             IncomingRelayReq::DialingDst {
@@ -478,6 +547,8 @@ impl NetworkBehaviour for Relay {
                                 condition: DialPeerCondition::NotDialing,
                             });
                     } else {
+                        #[cfg(feature = "metrics")]
+                        self.record(|m| m.incoming_relay_req("denied"));
                         self.outbox_to_swarm
                             .push_back(NetworkBehaviourAction::NotifyHandler {
                                 peer_id: event_source,
@@ -502,7 +573,10 @@ impl NetworkBehaviour for Relay {
                     .map(|l| !l.is_closed())
                     .unwrap_or(false);
 
-                let send_back = if got_explicit_listener || got_listener_for_any_relay {
+                let accept = got_explicit_listener || got_listener_for_any_relay;
+                #[cfg(feature = "metrics")]
+                self.record(|m| m.incoming_dst_req(if accept { "accepted" } else { "denied" }));
+                let send_back = if accept {
                     RelayHandlerIn::AcceptDstReq(request)
                 } else {
                     RelayHandlerIn::DenyDstReq(request)
@@ -516,12 +590,16 @@ impl NetworkBehaviour for Relay {
                     });
             }
             RelayHandlerEvent::OutgoingRelayReqError(_dst_peer_id, request_id) => {
+                #[cfg(feature = "metrics")]
+                self.record(|m| m.outgoing_relay_req("failure"));
                 self.outgoing_relay_reqs
                     .upgrading
                     .remove(&request_id)
                     .expect("Outgoing relay request error for unknown request.");
             }
             RelayHandlerEvent::OutgoingRelayReqSuccess(_dst, request_id, stream) => {
+                #[cfg(feature = "metrics")]
+                self.record(|m| m.outgoing_relay_req("success"));
                 let send_back = self
                     .outgoing_relay_reqs
                     .upgrading
@@ -535,15 +613,19 @@ impl NetworkBehaviour for Relay {
                 src_peer_id,
                 relay_peer_id,
                 relay_addr,
-            } => self.outbox_to_listeners.push_back((
-                relay_peer_id,
-                BehaviourToListenerMsg::IncomingRelayedConnection {
-                    stream,
-                    src_peer_id,
+            } => {
+                #[cfg(feature = "metrics")]
+                self.record(|m| m.relayed_connection());
+                self.outbox_to_listeners.push_back((
                     relay_peer_id,
-                    relay_addr,
-                },
-            )),
+                    BehaviourToListenerMsg::IncomingRelayedConnection {
+                        stream,
+                        src_peer_id,
+                        relay_peer_id,
+                        relay_addr,
+                    },
+                ))
+            }
             RelayHandlerEvent::OutgoingDstReqError {
                 src_connection_id,
                 incoming_relay_req_deny_fut,
@@ -92,6 +92,8 @@ mod message_proto {
 }
 
 mod handler;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 mod protocol;
 mod transport;
 
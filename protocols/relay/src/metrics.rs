@@ -0,0 +1,97 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Prometheus metrics of the [`Relay`](crate::Relay) behaviour.
+//!
+//! Unlike other behaviours, the [`Relay`](crate::Relay) behaviour does not
+//! emit any events, thus records its metrics itself once given them via
+//! [`Relay::set_metrics`](crate::Relay::set_metrics).
+
+use prometheus::{IntCounter, IntCounterVec, Opts, Registry};
+
+/// The metrics recorded by the [`Relay`](crate::Relay) behaviour.
+#[derive(Clone)]
+pub struct Metrics {
+    incoming_relay_reqs: IntCounterVec,
+    incoming_dst_reqs: IntCounterVec,
+    outgoing_relay_reqs: IntCounterVec,
+    relayed_connections: IntCounter,
+}
+
+impl Metrics {
+    /// Creates the metrics and registers them with the given registry.
+    pub fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let metrics = Metrics {
+            incoming_relay_reqs: IntCounterVec::new(
+                Opts::new(
+                    "libp2p_relay_incoming_relay_reqs_total",
+                    "Number of requests from sources to relay a connection by outcome.",
+                ),
+                &["outcome"],
+            )?,
+            incoming_dst_reqs: IntCounterVec::new(
+                Opts::new(
+                    "libp2p_relay_incoming_dst_reqs_total",
+                    "Number of requests from relays to act as destination by outcome.",
+                ),
+                &["outcome"],
+            )?,
+            outgoing_relay_reqs: IntCounterVec::new(
+                Opts::new(
+                    "libp2p_relay_outgoing_relay_reqs_total",
+                    "Number of requests to relays to relay a connection by outcome.",
+                ),
+                &["outcome"],
+            )?,
+            relayed_connections: IntCounter::new(
+                "libp2p_relay_relayed_connections_total",
+                "Number of incoming relayed connections.",
+            )?,
+        };
+        registry.register(Box::new(metrics.incoming_relay_reqs.clone()))?;
+        registry.register(Box::new(metrics.incoming_dst_reqs.clone()))?;
+        registry.register(Box::new(metrics.outgoing_relay_reqs.clone()))?;
+        registry.register(Box::new(metrics.relayed_connections.clone()))?;
+        Ok(metrics)
+    }
+
+    /// Records the outcome of an incoming relay request, one of
+    /// `forwarded`, `dialing_dst` or `denied`.
+    pub(crate) fn incoming_relay_req(&self, outcome: &str) {
+        self.incoming_relay_reqs.with_label_values(&[outcome]).inc();
+    }
+
+    /// Records the outcome of an incoming destination request, one of
+    /// `accepted` or `denied`.
+    pub(crate) fn incoming_dst_req(&self, outcome: &str) {
+        self.incoming_dst_reqs.with_label_values(&[outcome]).inc();
+    }
+
+    /// Records the outcome of an outgoing relay request, one of
+    /// `success` or `failure`.
+    pub(crate) fn outgoing_relay_req(&self, outcome: &str) {
+        self.outgoing_relay_reqs.with_label_values(&[outcome]).inc();
+    }
+
+    /// Records an incoming relayed connection.
+    pub(crate) fn relayed_connection(&self) {
+        self.relayed_connections.inc();
+    }
+}
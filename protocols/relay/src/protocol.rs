@@ -38,7 +38,7 @@ use std::{convert::TryFrom, error, fmt};
 // length to 10 times that limit is an unproven estimate. Feel free to refine this in the future.
 const MAX_ACCEPTED_MESSAGE_LEN: usize = 10 * 1024;
 
-const PROTOCOL_NAME: &[u8; 27] = b"/libp2p/circuit/relay/0.1.0";
+pub(crate) const PROTOCOL_NAME: &[u8; 27] = b"/libp2p/circuit/relay/0.1.0";
 
 // Source -> Relay
 mod incoming_relay_req;
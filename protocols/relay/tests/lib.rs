@@ -1115,6 +1115,50 @@ fn yield_incoming_connection_through_correct_listener() {
     });
 }
 
+#[test]
+fn discover_relay_via_identify() {
+    let _ = env_logger::try_init();
+
+    let mut pool = LocalPool::new();
+
+    let mut src_swarm = build_swarm_with_config(
+        Reachability::Firewalled,
+        RelayConfig {
+            auto_discovery: true,
+            ..Default::default()
+        },
+    );
+    let mut relay_swarm = build_swarm(Reachability::Routable, RelayMode::Passive);
+
+    let relay_peer_id = *relay_swarm.local_peer_id();
+    let relay_addr: Multiaddr = Protocol::Memory(rand::random::<u64>()).into();
+
+    relay_swarm.listen_on(relay_addr.clone()).unwrap();
+    spawn_swarm_on_pool(&pool, relay_swarm);
+
+    assert_eq!(src_swarm.behaviour().relay.relay_candidates().count(), 0);
+
+    src_swarm.dial_addr(relay_addr).unwrap();
+    pool.run_until(async {
+        // Relay candidates are updated once the identify information of the
+        // relay is received.
+        while src_swarm.behaviour().relay.relay_candidates().next().is_none() {
+            match src_swarm.select_next_some().await {
+                SwarmEvent::Dialing(_) => {}
+                SwarmEvent::ConnectionEstablished { peer_id, .. } if peer_id == relay_peer_id => {}
+                SwarmEvent::Behaviour(CombinedEvent::Ping(_)) => {}
+                SwarmEvent::Behaviour(CombinedEvent::Kad(KademliaEvent::RoutingUpdated {
+                    ..
+                })) => {}
+                e => panic!("{:?}", e),
+            }
+        }
+    });
+
+    let candidates: Vec<_> = src_swarm.behaviour().relay.relay_candidates().collect();
+    assert_eq!(candidates, vec![&relay_peer_id]);
+}
+
 #[derive(NetworkBehaviour)]
 #[behaviour(out_event = "CombinedEvent", poll_method = "poll")]
 struct CombinedBehaviour {
@@ -1163,10 +1207,11 @@ impl NetworkBehaviourEventProcess<IdentifyEvent> for CombinedBehaviour {
     fn inject_event(&mut self, event: IdentifyEvent) {
         if let IdentifyEvent::Received {
             peer_id,
-            info: IdentifyInfo { listen_addrs, .. },
+            info: IdentifyInfo { listen_addrs, protocols, .. },
             ..
         } = event
         {
+            self.relay.on_identify_protocols(&peer_id, &protocols);
             for addr in listen_addrs {
                 self.kad.add_address(&peer_id, addr);
             }
@@ -1244,6 +1289,19 @@ impl<T: Transport> Transport for Firewall<T> {
 }
 
 fn build_swarm(reachability: Reachability, relay_mode: RelayMode) -> Swarm<CombinedBehaviour> {
+    build_swarm_with_config(
+        reachability,
+        RelayConfig {
+            actively_connect_to_dst_nodes: relay_mode.into(),
+            ..Default::default()
+        },
+    )
+}
+
+fn build_swarm_with_config(
+    reachability: Reachability,
+    relay_config: RelayConfig,
+) -> Swarm<CombinedBehaviour> {
     let local_key = identity::Keypair::generate_ed25519();
     let local_public_key = local_key.public();
     let plaintext = PlainText2Config {
@@ -1258,13 +1316,8 @@ fn build_swarm(reachability: Reachability, relay_mode: RelayMode) -> Swarm<Combi
         Reachability::Routable => EitherTransport::Right(transport),
     };
 
-    let (transport, relay_behaviour) = libp2p_relay::new_transport_and_behaviour(
-        RelayConfig {
-            actively_connect_to_dst_nodes: relay_mode.into(),
-            ..Default::default()
-        },
-        transport,
-    );
+    let (transport, relay_behaviour) =
+        libp2p_relay::new_transport_and_behaviour(relay_config, transport);
 
     let transport = transport
         .upgrade(upgrade::Version::V1)
@@ -1279,10 +1332,12 @@ fn build_swarm(reachability: Reachability, relay_mode: RelayMode) -> Swarm<Combi
             local_peer_id.clone(),
             MemoryStore::new(local_peer_id.clone()),
         ),
-        identify: Identify::new(IdentifyConfig::new(
-            "test".to_string(),
-            local_public_key.clone(),
-        )),
+        // Push new listen addresses, e.g. those via a relay, right away
+        // instead of waiting for the peer to request them.
+        identify: Identify::new(
+            IdentifyConfig::new("test".to_string(), local_public_key.clone())
+                .with_push_listen_addr_updates(true),
+        ),
         events: Default::default(),
     };
 
@@ -58,6 +58,8 @@
 
 pub mod codec;
 pub mod handler;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 pub mod throttled;
 
 pub use codec::{RequestResponseCodec, ProtocolName};
@@ -179,6 +181,11 @@ pub enum OutboundFailure {
     ConnectionClosed,
     /// The remote supports none of the requested protocols.
     UnsupportedProtocols,
+    /// The request was cancelled via [`RequestResponse::cancel_request`].
+    ///
+    /// It is not known whether the request may have been
+    /// received (and processed) by the remote peer.
+    Cancelled,
 }
 
 impl fmt::Display for OutboundFailure {
@@ -187,7 +194,8 @@ impl fmt::Display for OutboundFailure {
             OutboundFailure::DialFailure => write!(f, "Failed to dial the requested peer"),
             OutboundFailure::Timeout => write!(f, "Timeout while waiting for a response"),
             OutboundFailure::ConnectionClosed => write!(f, "Connection was closed before a response was received"),
-            OutboundFailure::UnsupportedProtocols => write!(f, "The remote supports none of the requested protocols")
+            OutboundFailure::UnsupportedProtocols => write!(f, "The remote supports none of the requested protocols"),
+            OutboundFailure::Cancelled => write!(f, "The request was cancelled")
         }
     }
 }
@@ -450,6 +458,60 @@ where
         }
     }
 
+    /// Cancels a pending outbound request initiated by
+    /// [`RequestResponse::send_request`].
+    ///
+    /// If the request has not been sent yet, it is discarded. Otherwise
+    /// the response, or any failure, is discarded once received.
+    /// [`RequestResponseEvent::OutboundFailure`] with
+    /// [`OutboundFailure::Cancelled`] is emitted in either case.
+    ///
+    /// Returns `true` if the request was pending, `false` otherwise.
+    pub fn cancel_request(&mut self, request_id: RequestId) -> bool {
+        let mut cancelled = None;
+
+        // Check if request is already sent on established connection.
+        for (peer, connections) in self.connected.iter_mut() {
+            for conn in connections.iter_mut() {
+                if conn.pending_inbound_responses.remove(&request_id) {
+                    conn.cancelled_requests.insert(request_id);
+                    cancelled = Some(*peer);
+                }
+            }
+        }
+
+        // Check if request is still pending to be sent.
+        if cancelled.is_none() {
+            for (peer, requests) in self.pending_outbound_requests.iter_mut() {
+                if let Some(ix) = requests.iter().position(|r| r.request_id == request_id) {
+                    requests.remove(ix);
+                    cancelled = Some(*peer);
+                    break
+                }
+            }
+            if let Some(peer) = cancelled {
+                if let Some(requests) = self.pending_outbound_requests.get(&peer) {
+                    if requests.is_empty() {
+                        self.pending_outbound_requests.remove(&peer);
+                    }
+                }
+            }
+        }
+
+        if let Some(peer) = cancelled {
+            self.pending_events.push_back(NetworkBehaviourAction::GenerateEvent(
+                RequestResponseEvent::OutboundFailure {
+                    peer,
+                    request_id,
+                    error: OutboundFailure::Cancelled,
+                }
+            ));
+            true
+        } else {
+            false
+        }
+    }
+
     /// Checks whether a peer is currently connected.
     pub fn is_connected(&self, peer: &PeerId) -> bool {
         if let Some(connections) = self.connected.get(peer) {
@@ -484,6 +546,43 @@ where
             .unwrap_or(false)
     }
 
+    /// Returns the number of outbound requests to the peer with the provided
+    /// [`PeerId`] that are still pending, i.e. waiting for a response,
+    /// including those waiting for a connection to be established.
+    pub fn pending_outbound_requests(&self, peer: &PeerId) -> usize {
+        let est_conn = self.connected.get(peer)
+            .map(|cs| cs.iter().map(|c| c.pending_inbound_responses.len()).sum())
+            .unwrap_or(0);
+        let pen_conn = self.pending_outbound_requests.get(peer)
+            .map(|rps| rps.len())
+            .unwrap_or(0);
+
+        est_conn + pen_conn
+    }
+
+    /// Returns the number of inbound requests from the peer with the provided
+    /// [`PeerId`] that are still pending, i.e. waiting for a response by the
+    /// local node through [`RequestResponse::send_response`].
+    pub fn pending_inbound_requests(&self, peer: &PeerId) -> usize {
+        self.connected.get(peer)
+            .map(|cs| cs.iter().map(|c| c.pending_outbound_responses.len()).sum())
+            .unwrap_or(0)
+    }
+
+    /// Returns the number of outbound requests that are still pending
+    /// across all peers, see [`RequestResponse::pending_outbound_requests`].
+    pub fn total_pending_outbound(&self) -> usize {
+        let est_conn: usize = self.connected.values()
+            .flat_map(|cs| cs.iter())
+            .map(|c| c.pending_inbound_responses.len())
+            .sum();
+        let pen_conn: usize = self.pending_outbound_requests.values()
+            .map(|rps| rps.len())
+            .sum();
+
+        est_conn + pen_conn
+    }
+
     /// Returns the next request ID.
     fn next_request_id(&mut self) -> RequestId {
         let request_id = self.next_request_id;
@@ -531,6 +630,23 @@ where
             .unwrap_or(false)
     }
 
+    /// Remove a request cancelled via [`RequestResponse::cancel_request`]
+    /// for the given peer and connection.
+    ///
+    /// Returns `true` if the provided connection to the given peer is still
+    /// alive and the [`RequestId`] was previously present and is now removed.
+    /// Returns `false` otherwise.
+    fn remove_cancelled_request(
+        &mut self,
+        peer: &PeerId,
+        connection: ConnectionId,
+        request: &RequestId,
+    ) -> bool {
+        self.get_connection_mut(peer, connection)
+            .map(|c| c.cancelled_requests.remove(request))
+            .unwrap_or(false)
+    }
+
     /// Remove pending inbound response for the given peer and connection.
     ///
     /// Returns `true` if the provided connection to the given peer is still
@@ -674,6 +790,9 @@ where
     ) {
         match event {
             RequestResponseHandlerEvent::Response { request_id, response } => {
+                if self.remove_cancelled_request(&peer, connection, &request_id) {
+                    return
+                }
                 let removed = self.remove_pending_inbound_response(&peer, connection, &request_id);
                 debug_assert!(
                     removed,
@@ -733,6 +852,9 @@ where
                         }));
             }
             RequestResponseHandlerEvent::OutboundTimeout(request_id) => {
+                if self.remove_cancelled_request(&peer, connection, &request_id) {
+                    return
+                }
                 let removed = self.remove_pending_inbound_response(&peer, connection, &request_id);
                 debug_assert!(removed, "Expect request_id to be pending before request times out.");
 
@@ -760,6 +882,9 @@ where
                         }));
             }
             RequestResponseHandlerEvent::OutboundUnsupportedProtocols(request_id) => {
+                if self.remove_cancelled_request(&peer, connection, &request_id) {
+                    return
+                }
                 let removed = self.remove_pending_inbound_response(&peer, connection, &request_id);
                 debug_assert!(
                     removed,
@@ -821,7 +946,10 @@ struct Connection {
     pending_outbound_responses: HashSet<RequestId>,
     /// Pending inbound responses for previously sent requests on this
     /// connection.
-    pending_inbound_responses: HashSet<RequestId>
+    pending_inbound_responses: HashSet<RequestId>,
+    /// Previously sent requests on this connection that have been cancelled
+    /// and whose responses are to be discarded.
+    cancelled_requests: HashSet<RequestId>,
 }
 
 impl Connection {
@@ -831,6 +959,7 @@ impl Connection {
             address,
             pending_outbound_responses: Default::default(),
             pending_inbound_responses: Default::default(),
+            cancelled_requests: Default::default(),
         }
     }
 }
@@ -0,0 +1,107 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Prometheus metrics of the [`RequestResponse`](crate::RequestResponse) behaviour.
+
+use crate::{InboundFailure, OutboundFailure, RequestResponseEvent, RequestResponseMessage};
+use prometheus::{IntCounter, IntCounterVec, Opts, Registry};
+
+/// The metrics recorded from [`RequestResponseEvent`]s.
+#[derive(Clone)]
+pub struct Metrics {
+    requests_received: IntCounter,
+    responses_received: IntCounter,
+    responses_sent: IntCounter,
+    outbound_failures: IntCounterVec,
+    inbound_failures: IntCounterVec,
+}
+
+impl Metrics {
+    /// Creates the metrics and registers them with the given registry.
+    pub fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let metrics = Metrics {
+            requests_received: IntCounter::new(
+                "libp2p_request_response_requests_received_total",
+                "Number of inbound requests received.",
+            )?,
+            responses_received: IntCounter::new(
+                "libp2p_request_response_responses_received_total",
+                "Number of responses received to outbound requests.",
+            )?,
+            responses_sent: IntCounter::new(
+                "libp2p_request_response_responses_sent_total",
+                "Number of responses sent to inbound requests.",
+            )?,
+            outbound_failures: IntCounterVec::new(
+                Opts::new(
+                    "libp2p_request_response_outbound_failures_total",
+                    "Number of failed outbound requests by reason.",
+                ),
+                &["reason"],
+            )?,
+            inbound_failures: IntCounterVec::new(
+                Opts::new(
+                    "libp2p_request_response_inbound_failures_total",
+                    "Number of failed inbound requests by reason.",
+                ),
+                &["reason"],
+            )?,
+        };
+        registry.register(Box::new(metrics.requests_received.clone()))?;
+        registry.register(Box::new(metrics.responses_received.clone()))?;
+        registry.register(Box::new(metrics.responses_sent.clone()))?;
+        registry.register(Box::new(metrics.outbound_failures.clone()))?;
+        registry.register(Box::new(metrics.inbound_failures.clone()))?;
+        Ok(metrics)
+    }
+
+    /// Records an event emitted by the [`RequestResponse`](crate::RequestResponse) behaviour.
+    pub fn record<TRequest, TResponse, TChannelResponse>(
+        &self,
+        event: &RequestResponseEvent<TRequest, TResponse, TChannelResponse>,
+    ) {
+        match event {
+            RequestResponseEvent::Message { message: RequestResponseMessage::Request { .. }, .. } =>
+                self.requests_received.inc(),
+            RequestResponseEvent::Message { message: RequestResponseMessage::Response { .. }, .. } =>
+                self.responses_received.inc(),
+            RequestResponseEvent::ResponseSent { .. } => self.responses_sent.inc(),
+            RequestResponseEvent::OutboundFailure { error, .. } => {
+                let reason = match error {
+                    OutboundFailure::DialFailure => "dial_failure",
+                    OutboundFailure::Timeout => "timeout",
+                    OutboundFailure::ConnectionClosed => "connection_closed",
+                    OutboundFailure::UnsupportedProtocols => "unsupported_protocols",
+                    OutboundFailure::Cancelled => "cancelled",
+                };
+                self.outbound_failures.with_label_values(&[reason]).inc();
+            }
+            RequestResponseEvent::InboundFailure { error, .. } => {
+                let reason = match error {
+                    InboundFailure::Timeout => "timeout",
+                    InboundFailure::ConnectionClosed => "connection_closed",
+                    InboundFailure::UnsupportedProtocols => "unsupported_protocols",
+                    InboundFailure::ResponseOmission => "response_omission",
+                };
+                self.inbound_failures.with_label_values(&[reason]).inc();
+            }
+        }
+    }
+}
@@ -40,7 +40,7 @@ use codec::{Codec, Message, ProtocolWrapper, Type};
 use crate::handler::{RequestProtocol, RequestResponseHandler, RequestResponseHandlerEvent};
 use futures::ready;
 use libp2p_core::{ConnectedPoint, connection::ConnectionId, Multiaddr, PeerId};
-use libp2p_swarm::{NetworkBehaviour, NetworkBehaviourAction, PollParameters};
+use libp2p_swarm::{NetworkBehaviour, NetworkBehaviourAction, PollParameters, TimerId};
 use lru::LruCache;
 use std::{collections::{HashMap, HashSet, VecDeque}, task::{Context, Poll}};
 use std::{cmp::max, num::NonZeroU16};
@@ -359,6 +359,27 @@ where
         self.behaviour.is_pending_inbound(p, r)
     }
 
+    /// How many requests to the given peer are we waiting for a response to?
+    ///
+    /// See [`RequestResponse::pending_outbound_requests`] for details.
+    pub fn pending_outbound_requests(&self, p: &PeerId) -> usize {
+        self.behaviour.pending_outbound_requests(p)
+    }
+
+    /// How many requests of the given peer is the local node yet to respond to?
+    ///
+    /// See [`RequestResponse::pending_inbound_requests`] for details.
+    pub fn pending_inbound_requests(&self, p: &PeerId) -> usize {
+        self.behaviour.pending_inbound_requests(p)
+    }
+
+    /// How many requests across all peers are we waiting for a response to?
+    ///
+    /// See [`RequestResponse::total_pending_outbound`] for details.
+    pub fn total_pending_outbound(&self) -> usize {
+        self.behaviour.total_pending_outbound()
+    }
+
     /// Send a credit grant to the given peer.
     fn send_credit(&mut self, p: &PeerId, credit: u16) {
         if let Some(info) = self.peer_info.get_mut(p) {
@@ -451,6 +472,10 @@ where
         self.behaviour.inject_dial_failure(p)
     }
 
+    fn inject_timeout(&mut self, t: TimerId) {
+        self.behaviour.inject_timeout(t)
+    }
+
     fn inject_event(&mut self, p: PeerId, i: ConnectionId, e: RequestResponseHandlerEvent<Codec<C>>) {
         self.behaviour.inject_event(p, i, e)
     }
@@ -656,10 +681,16 @@ where
                     NetworkBehaviourAction::DialPeer { peer_id, condition },
                 | NetworkBehaviourAction::NotifyHandler { peer_id, handler, event } =>
                     NetworkBehaviourAction::NotifyHandler { peer_id, handler, event },
-                | NetworkBehaviourAction::ReportObservedAddr { address, score } =>
-                    NetworkBehaviourAction::ReportObservedAddr { address, score },
+                | NetworkBehaviourAction::ReportObservedAddr { address, score, observer } =>
+                    NetworkBehaviourAction::ReportObservedAddr { address, score, observer },
                 | NetworkBehaviourAction::CloseConnection { peer_id, connection } =>
-                    NetworkBehaviourAction::CloseConnection { peer_id, connection }
+                    NetworkBehaviourAction::CloseConnection { peer_id, connection },
+                | NetworkBehaviourAction::TagConnection { connection, tag } =>
+                    NetworkBehaviourAction::TagConnection { connection, tag },
+                | NetworkBehaviourAction::SpawnTask(task) =>
+                    NetworkBehaviourAction::SpawnTask(task),
+                | NetworkBehaviourAction::SetTimer { timer_id, duration } =>
+                    NetworkBehaviourAction::SetTimer { timer_id, duration },
             };
 
             return Poll::Ready(event)
@@ -280,6 +280,137 @@ fn emits_inbound_connection_closed_if_channel_is_dropped() {
     });
 }
 
+/// Cancelling a request emits an [`OutboundFailure::Cancelled`] and
+/// discards the response to it.
+#[test]
+fn cancel_request() {
+    let ping = Ping("ping".to_string().into_bytes());
+    let pong = Pong("pong".to_string().into_bytes());
+
+    let protocols = iter::once((PingProtocol(), ProtocolSupport::Full));
+    let cfg = RequestResponseConfig::default();
+
+    let (peer1_id, trans) = mk_transport();
+    let ping_proto1 = RequestResponse::new(PingCodec(), protocols.clone(), cfg.clone());
+    let mut swarm1 = Swarm::new(trans, ping_proto1, peer1_id);
+
+    let (peer2_id, trans) = mk_transport();
+    let ping_proto2 = RequestResponse::new(PingCodec(), protocols, cfg);
+    let mut swarm2 = Swarm::new(trans, ping_proto2, peer2_id);
+
+    let addr = "/ip4/127.0.0.1/tcp/0".parse().unwrap();
+    swarm1.listen_on(addr).unwrap();
+
+    futures::executor::block_on(async move {
+        while swarm1.next().now_or_never().is_some() {}
+        let addr1 = Swarm::listeners(&swarm1).next().unwrap();
+
+        swarm2.behaviour_mut().add_address(&peer1_id, addr1.clone());
+        let cancelled_id = swarm2.behaviour_mut().send_request(&peer1_id, ping.clone());
+
+        let mut request_id = None;
+        let mut num_requests = 0;
+        loop {
+            futures::select!(
+                event = swarm1.select_next_some() => match event {
+                    SwarmEvent::Behaviour(RequestResponseEvent::Message {
+                        message: RequestResponseMessage::Request { channel, .. }, ..
+                    }) => {
+                        num_requests += 1;
+                        if num_requests == 1 {
+                            // Cancel the request after it has been received by the remote.
+                            assert!(swarm2.behaviour_mut().cancel_request(cancelled_id));
+                            assert!(!swarm2.behaviour_mut().cancel_request(cancelled_id));
+                            assert!(!swarm2.behaviour().is_pending_outbound(&peer1_id, &cancelled_id));
+                        }
+                        swarm1.behaviour_mut().send_response(channel, pong.clone()).unwrap();
+                    }
+                    SwarmEvent::Behaviour(RequestResponseEvent::ResponseSent { .. }) => {}
+                    SwarmEvent::Behaviour(e) => panic!("Peer1: Unexpected event: {:?}", e),
+                    _ => {}
+                },
+                event = swarm2.select_next_some() => match event {
+                    SwarmEvent::Behaviour(RequestResponseEvent::OutboundFailure {
+                        peer, request_id: id, error
+                    }) => {
+                        assert_eq!(peer, peer1_id);
+                        assert_eq!(id, cancelled_id);
+                        assert_eq!(error, OutboundFailure::Cancelled);
+                        request_id = Some(swarm2.behaviour_mut().send_request(&peer1_id, ping.clone()));
+                    }
+                    SwarmEvent::Behaviour(RequestResponseEvent::Message {
+                        message: RequestResponseMessage::Response { request_id: id, .. }, ..
+                    }) => {
+                        // Only the response to the second request is reported.
+                        assert_eq!(Some(id), request_id);
+                        break
+                    }
+                    SwarmEvent::Behaviour(e) => panic!("Peer2: Unexpected event: {:?}", e),
+                    _ => {}
+                },
+            )
+        }
+
+        assert_eq!(num_requests, 2);
+    });
+}
+
+/// Requests a remote never responds to are counted as pending on both sides.
+#[test]
+fn pending_requests() {
+    let ping = Ping("ping".to_string().into_bytes());
+
+    let protocols = iter::once((PingProtocol(), ProtocolSupport::Full));
+    let cfg = RequestResponseConfig::default();
+
+    let (peer1_id, trans) = mk_transport();
+    let ping_proto1 = RequestResponse::new(PingCodec(), protocols.clone(), cfg.clone());
+    let mut swarm1 = Swarm::new(trans, ping_proto1, peer1_id);
+
+    let (peer2_id, trans) = mk_transport();
+    let ping_proto2 = RequestResponse::new(PingCodec(), protocols, cfg);
+    let mut swarm2 = Swarm::new(trans, ping_proto2, peer2_id);
+
+    let addr = "/ip4/127.0.0.1/tcp/0".parse().unwrap();
+    swarm1.listen_on(addr).unwrap();
+
+    futures::executor::block_on(async move {
+        while swarm1.next().now_or_never().is_some() {}
+        let addr1 = Swarm::listeners(&swarm1).next().unwrap();
+
+        swarm2.behaviour_mut().add_address(&peer1_id, addr1.clone());
+        for _ in 0..5 {
+            swarm2.behaviour_mut().send_request(&peer1_id, ping.clone());
+        }
+        assert_eq!(swarm2.behaviour().pending_outbound_requests(&peer1_id), 5);
+        assert_eq!(swarm2.behaviour().total_pending_outbound(), 5);
+
+        // Keep the response channels open without ever responding.
+        let mut channels = Vec::new();
+        while channels.len() < 5 {
+            futures::select!(
+                event = swarm1.select_next_some() => match event {
+                    SwarmEvent::Behaviour(RequestResponseEvent::Message {
+                        message: RequestResponseMessage::Request { channel, .. }, ..
+                    }) => channels.push(channel),
+                    SwarmEvent::Behaviour(e) => panic!("Peer1: Unexpected event: {:?}", e),
+                    _ => {}
+                },
+                event = swarm2.select_next_some() => if let SwarmEvent::Behaviour(e) = event {
+                    panic!("Peer2: Unexpected event: {:?}", e)
+                },
+            )
+        }
+
+        assert_eq!(swarm2.behaviour().pending_outbound_requests(&peer1_id), 5);
+        assert_eq!(swarm2.behaviour().pending_outbound_requests(&PeerId::random()), 0);
+        assert_eq!(swarm2.behaviour().total_pending_outbound(), 5);
+        assert_eq!(swarm2.behaviour().pending_inbound_requests(&peer1_id), 0);
+        assert_eq!(swarm1.behaviour().pending_inbound_requests(&peer2_id), 5);
+        assert_eq!(swarm1.behaviour().pending_outbound_requests(&peer2_id), 0);
+    });
+}
+
 #[test]
 fn ping_protocol_throttled() {
     let ping = Ping("ping".to_string().into_bytes());
@@ -36,7 +36,7 @@
 pub use bytes;
 pub use futures;
 #[doc(inline)]
-pub use multiaddr;
+pub use libp2p_core::multiaddr;
 #[doc(inline)]
 pub use libp2p_core::multihash;
 
@@ -60,6 +60,11 @@ pub use libp2p_identify as identify;
 #[cfg_attr(docsrs, doc(cfg(feature = "kad")))]
 #[doc(inline)]
 pub use libp2p_kad as kad;
+#[cfg(feature = "discovery")]
+#[cfg_attr(docsrs, doc(cfg(feature = "discovery")))]
+#[cfg(not(any(target_os = "emscripten", target_os = "wasi", target_os = "unknown")))]
+#[doc(inline)]
+pub use libp2p_discovery as discovery;
 #[cfg(feature = "floodsub")]
 #[cfg_attr(docsrs, doc(cfg(feature = "floodsub")))]
 #[doc(inline)]
@@ -129,6 +134,9 @@ pub use libp2p_request_response as request_response;
 mod transport_ext;
 
 pub mod bandwidth;
+#[cfg(feature = "metrics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+pub mod metrics;
 pub mod simple;
 
 #[cfg(doc)]
@@ -141,7 +149,7 @@ pub use self::core::{
     transport::TransportError,
     upgrade::{InboundUpgrade, InboundUpgradeExt, OutboundUpgrade, OutboundUpgradeExt}
 };
-pub use libp2p_swarm_derive::NetworkBehaviour;
+pub use libp2p_swarm_derive::{NetworkBehaviour, UpgradeInfo};
 pub use self::multiaddr::{Multiaddr, multiaddr as build_multiaddr};
 pub use self::simple::SimpleProtocol;
 pub use self::swarm::Swarm;
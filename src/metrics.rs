@@ -0,0 +1,248 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Prometheus metrics of the behaviours of libp2p.
+//!
+//! A [`MetricsBuilder`] creates the metrics of the selected behaviours and
+//! registers them with a single [`Registry`]. The resulting [`Metrics`]
+//! record the events the behaviours emit via the [`Recorder`] trait.
+//! Passing the same registry to [`SwarmBuilder::with_metrics`](crate::swarm::SwarmBuilder::with_metrics)
+//! additionally records the metrics of the `Swarm` itself and retains the
+//! registry for retrieval via [`Swarm::metrics_registry`](crate::swarm::ExpandedSwarm::metrics_registry).
+//!
+//! ```
+//! # #[cfg(feature = "ping")] {
+//! use libp2p::metrics::{MetricsBuilder, Recorder, Registry};
+//! use libp2p::ping::PingEvent;
+//!
+//! let registry = Registry::new();
+//! let metrics = MetricsBuilder::new(&registry).ping().build().unwrap();
+//!
+//! // For every `PingEvent` emitted by the `Swarm`:
+//! # fn on_event(metrics: &libp2p::metrics::Metrics, event: PingEvent) {
+//! metrics.record(&event);
+//! # }
+//! # }
+//! ```
+
+pub use prometheus::{self, Registry};
+
+/// Records the events of type `TEvent` emitted by a behaviour.
+pub trait Recorder<TEvent> {
+    /// Records the given event.
+    fn record(&self, event: &TEvent);
+}
+
+/// Builds [`Metrics`] for a selection of behaviours.
+///
+/// Behaviours that are not selected are not recorded.
+pub struct MetricsBuilder<'a> {
+    registry: &'a Registry,
+    #[cfg(feature = "gossipsub")]
+    gossipsub: bool,
+    #[cfg(feature = "identify")]
+    identify: bool,
+    #[cfg(feature = "kad")]
+    kad: bool,
+    #[cfg(feature = "ping")]
+    ping: bool,
+    #[cfg(feature = "relay")]
+    relay: Option<&'a mut crate::relay::Relay>,
+    #[cfg(feature = "request-response")]
+    request_response: bool,
+}
+
+impl<'a> MetricsBuilder<'a> {
+    /// Creates a new `MetricsBuilder` registering the metrics with the given registry.
+    pub fn new(registry: &'a Registry) -> Self {
+        MetricsBuilder {
+            registry,
+            #[cfg(feature = "gossipsub")]
+            gossipsub: false,
+            #[cfg(feature = "identify")]
+            identify: false,
+            #[cfg(feature = "kad")]
+            kad: false,
+            #[cfg(feature = "ping")]
+            ping: false,
+            #[cfg(feature = "relay")]
+            relay: None,
+            #[cfg(feature = "request-response")]
+            request_response: false,
+        }
+    }
+
+    /// Records the events of the `Gossipsub` behaviour.
+    #[cfg(feature = "gossipsub")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "gossipsub")))]
+    pub fn gossipsub(mut self) -> Self {
+        self.gossipsub = true;
+        self
+    }
+
+    /// Records the events of the `Identify` behaviour.
+    #[cfg(feature = "identify")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "identify")))]
+    pub fn identify(mut self) -> Self {
+        self.identify = true;
+        self
+    }
+
+    /// Records the events of the `Kademlia` behaviour.
+    #[cfg(feature = "kad")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "kad")))]
+    pub fn kad(mut self) -> Self {
+        self.kad = true;
+        self
+    }
+
+    /// Records the events of the `Ping` behaviour.
+    #[cfg(feature = "ping")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "ping")))]
+    pub fn ping(mut self) -> Self {
+        self.ping = true;
+        self
+    }
+
+    /// Records the relayed connections and requests of the given `Relay`
+    /// behaviour, which emits no events and thus records them itself.
+    #[cfg(feature = "relay")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "relay")))]
+    pub fn relay(mut self, relay: &'a mut crate::relay::Relay) -> Self {
+        self.relay = Some(relay);
+        self
+    }
+
+    /// Records the events of the `RequestResponse` behaviour.
+    #[cfg(feature = "request-response")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "request-response")))]
+    pub fn request_response(mut self) -> Self {
+        self.request_response = true;
+        self
+    }
+
+    /// Creates the metrics of the selected behaviours and registers them.
+    ///
+    /// Fails if the registry already contains metrics of the same name.
+    pub fn build(self) -> Result<Metrics, prometheus::Error> {
+        let registry = self.registry;
+        #[cfg(feature = "relay")]
+        if let Some(relay) = self.relay {
+            relay.set_metrics(crate::relay::metrics::Metrics::new(registry)?);
+        }
+        Ok(Metrics {
+            registry: registry.clone(),
+            #[cfg(feature = "gossipsub")]
+            gossipsub: self.gossipsub
+                .then(|| crate::gossipsub::metrics::Metrics::new(registry))
+                .transpose()?,
+            #[cfg(feature = "identify")]
+            identify: self.identify
+                .then(|| crate::identify::metrics::Metrics::new(registry))
+                .transpose()?,
+            #[cfg(feature = "kad")]
+            kad: self.kad
+                .then(|| crate::kad::metrics::Metrics::new(registry))
+                .transpose()?,
+            #[cfg(feature = "ping")]
+            ping: self.ping
+                .then(|| crate::ping::metrics::Metrics::new(registry))
+                .transpose()?,
+            #[cfg(feature = "request-response")]
+            request_response: self.request_response
+                .then(|| crate::request_response::metrics::Metrics::new(registry))
+                .transpose()?,
+        })
+    }
+}
+
+/// The metrics of the behaviours selected via a [`MetricsBuilder`].
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Registry,
+    #[cfg(feature = "gossipsub")]
+    gossipsub: Option<crate::gossipsub::metrics::Metrics>,
+    #[cfg(feature = "identify")]
+    identify: Option<crate::identify::metrics::Metrics>,
+    #[cfg(feature = "kad")]
+    kad: Option<crate::kad::metrics::Metrics>,
+    #[cfg(feature = "ping")]
+    ping: Option<crate::ping::metrics::Metrics>,
+    #[cfg(feature = "request-response")]
+    request_response: Option<crate::request_response::metrics::Metrics>,
+}
+
+impl Metrics {
+    /// Returns the registry the metrics are registered with.
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+}
+
+#[cfg(feature = "gossipsub")]
+impl Recorder<crate::gossipsub::GossipsubEvent> for Metrics {
+    fn record(&self, event: &crate::gossipsub::GossipsubEvent) {
+        if let Some(metrics) = &self.gossipsub {
+            metrics.record(event)
+        }
+    }
+}
+
+#[cfg(feature = "identify")]
+impl Recorder<crate::identify::IdentifyEvent> for Metrics {
+    fn record(&self, event: &crate::identify::IdentifyEvent) {
+        if let Some(metrics) = &self.identify {
+            metrics.record(event)
+        }
+    }
+}
+
+#[cfg(feature = "kad")]
+impl Recorder<crate::kad::KademliaEvent> for Metrics {
+    fn record(&self, event: &crate::kad::KademliaEvent) {
+        if let Some(metrics) = &self.kad {
+            metrics.record(event)
+        }
+    }
+}
+
+#[cfg(feature = "ping")]
+impl Recorder<crate::ping::PingEvent> for Metrics {
+    fn record(&self, event: &crate::ping::PingEvent) {
+        if let Some(metrics) = &self.ping {
+            metrics.record(event)
+        }
+    }
+}
+
+#[cfg(feature = "request-response")]
+impl<TRequest, TResponse, TChannelResponse>
+    Recorder<crate::request_response::RequestResponseEvent<TRequest, TResponse, TChannelResponse>>
+    for Metrics
+{
+    fn record(
+        &self,
+        event: &crate::request_response::RequestResponseEvent<TRequest, TResponse, TChannelResponse>,
+    ) {
+        if let Some(metrics) = &self.request_response {
+            metrics.record(event)
+        }
+    }
+}
@@ -24,6 +24,8 @@ use quote::quote;
 use proc_macro::TokenStream;
 use syn::{parse_macro_input, DeriveInput, Data, DataStruct, Ident};
 
+mod upgrade_info;
+
 /// Generates a delegating `NetworkBehaviour` implementation for the struct this is used for. See
 /// the trait documentation for better description.
 #[proc_macro_derive(NetworkBehaviour, attributes(behaviour))]
@@ -32,6 +34,17 @@ pub fn hello_macro_derive(input: TokenStream) -> TokenStream {
     build(&ast)
 }
 
+/// Generates an `UpgradeInfo` implementation for the type this is used for.
+///
+/// The protocol names are given by one or more `#[protocol("/my/protocol/1.0.0")]`
+/// attributes and are advertised in the order of the attributes, i.e. the first
+/// one is preferred during protocol negotiation.
+#[proc_macro_derive(UpgradeInfo, attributes(protocol))]
+pub fn upgrade_info_derive(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    upgrade_info::build(&ast)
+}
+
 /// The actual implementation.
 fn build(ast: &DeriveInput) -> TokenStream {
     match ast.data {
@@ -46,6 +59,7 @@ fn build_struct(ast: &DeriveInput, data_struct: &DataStruct) -> TokenStream {
     let name = &ast.ident;
     let (_, ty_generics, where_clause) = ast.generics.split_for_impl();
     let multiaddr = quote!{::libp2p::core::Multiaddr};
+    let address_meta = quote!{::libp2p::swarm::AddressMeta};
     let trait_to_impl = quote!{::libp2p::swarm::NetworkBehaviour};
     let net_behv_event_proc = quote!{::libp2p::swarm::NetworkBehaviourEventProcess};
     let either_ident = quote!{::libp2p::core::either::EitherOutput};
@@ -57,6 +71,7 @@ fn build_struct(ast: &DeriveInput, data_struct: &DataStruct) -> TokenStream {
     let connection_id = quote!{::libp2p::core::connection::ConnectionId};
     let connected_point = quote!{::libp2p::core::ConnectedPoint};
     let listener_id = quote!{::libp2p::core::connection::ListenerId};
+    let timer_id = quote!{::libp2p::swarm::TimerId};
 
     let poll_parameters = quote!{::libp2p::swarm::PollParameters};
 
@@ -151,6 +166,20 @@ fn build_struct(ast: &DeriveInput, data_struct: &DataStruct) -> TokenStream {
         })
     };
 
+    // Build the list of statements to put in the body of `addresses_of_peer_with_metadata()`.
+    let addresses_of_peer_with_metadata_stmts = {
+        data_struct.fields.iter().enumerate().filter_map(move |(field_n, field)| {
+            if is_ignored(field) {
+                return None;
+            }
+
+            Some(match field.ident {
+                Some(ref i) => quote!{ out.extend(self.#i.addresses_of_peer_with_metadata(peer_id)); },
+                None => quote!{ out.extend(self.#field_n.addresses_of_peer_with_metadata(peer_id)); },
+            })
+        })
+    };
+
     // Build the list of statements to put in the body of `inject_connected()`.
     let inject_connected_stmts = {
         data_struct.fields.iter().enumerate().filter_map(move |(field_n, field)| {
@@ -314,6 +343,20 @@ fn build_struct(ast: &DeriveInput, data_struct: &DataStruct) -> TokenStream {
         })
     };
 
+    // Build the list of statements to put in the body of `inject_timeout()`.
+    let inject_timeout_stmts = {
+        data_struct.fields.iter().enumerate().filter_map(move |(field_n, field)| {
+            if is_ignored(field) {
+                return None;
+            }
+
+            Some(match field.ident {
+                Some(ref i) => quote!{ self.#i.inject_timeout(timer_id); },
+                None => quote!{ self.#field_n.inject_timeout(timer_id); },
+            })
+        })
+    };
+
     // Build the list of statements to put in the body of `inject_listener_error()`.
     let inject_listener_error_stmts = {
         data_struct.fields.iter().enumerate().filter_map(move |(field_n, field)| {
@@ -476,12 +519,21 @@ fn build_struct(ast: &DeriveInput, data_struct: &DataStruct) -> TokenStream {
                             event: #wrapped_event,
                         });
                     }
-                    std::task::Poll::Ready(#network_behaviour_action::ReportObservedAddr { address, score }) => {
-                        return std::task::Poll::Ready(#network_behaviour_action::ReportObservedAddr { address, score });
+                    std::task::Poll::Ready(#network_behaviour_action::ReportObservedAddr { address, score, observer }) => {
+                        return std::task::Poll::Ready(#network_behaviour_action::ReportObservedAddr { address, score, observer });
                     }
                     std::task::Poll::Ready(#network_behaviour_action::CloseConnection { peer_id, connection }) => {
                         return std::task::Poll::Ready(#network_behaviour_action::CloseConnection { peer_id, connection });
                     }
+                    std::task::Poll::Ready(#network_behaviour_action::TagConnection { connection, tag }) => {
+                        return std::task::Poll::Ready(#network_behaviour_action::TagConnection { connection, tag });
+                    }
+                    std::task::Poll::Ready(#network_behaviour_action::SpawnTask(task)) => {
+                        return std::task::Poll::Ready(#network_behaviour_action::SpawnTask(task));
+                    }
+                    std::task::Poll::Ready(#network_behaviour_action::SetTimer { timer_id, duration }) => {
+                        return std::task::Poll::Ready(#network_behaviour_action::SetTimer { timer_id, duration });
+                    }
                     std::task::Poll::Pending => break,
                 }
             }
@@ -507,6 +559,12 @@ fn build_struct(ast: &DeriveInput, data_struct: &DataStruct) -> TokenStream {
                 out
             }
 
+            fn addresses_of_peer_with_metadata(&mut self, peer_id: &#peer_id) -> Vec<(#multiaddr, #address_meta)> {
+                let mut out = Vec::new();
+                #(#addresses_of_peer_with_metadata_stmts);*
+                out
+            }
+
             fn inject_connected(&mut self, peer_id: &#peer_id) {
                 #(#inject_connected_stmts);*
             }
@@ -555,6 +613,10 @@ fn build_struct(ast: &DeriveInput, data_struct: &DataStruct) -> TokenStream {
                 #(#inject_expired_external_addr_stmts);*
             }
 
+            fn inject_timeout(&mut self, timer_id: #timer_id) {
+                #(#inject_timeout_stmts);*
+            }
+
             fn inject_listener_error(&mut self, id: #listener_id, err: &(dyn std::error::Error + 'static)) {
                 #(#inject_listener_error_stmts);*
             }
@@ -0,0 +1,82 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Implementation of `#[derive(UpgradeInfo)]`.
+
+use quote::quote;
+use proc_macro::TokenStream;
+use syn::{DeriveInput, Lit, LitByteStr, Meta, NestedMeta};
+
+/// Builds the `UpgradeInfo` implementation from the `#[protocol("...")]`
+/// attributes of the given type.
+pub fn build(ast: &DeriveInput) -> TokenStream {
+    match protocols(ast) {
+        Ok(protocols) => {
+            let name = &ast.ident;
+            let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+            let upgrade_info = quote!{::libp2p::core::UpgradeInfo};
+
+            let final_quote = quote!{
+                impl #impl_generics #upgrade_info for #name #ty_generics #where_clause {
+                    type Info = &'static [u8];
+                    type InfoIter = ::std::iter::Copied<::std::slice::Iter<'static, &'static [u8]>>;
+
+                    fn protocol_info(&self) -> Self::InfoIter {
+                        const PROTOCOLS: &[&[u8]] = &[#(#protocols),*];
+                        PROTOCOLS.iter().copied()
+                    }
+                }
+            };
+
+            final_quote.into()
+        }
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Collects the protocol names of all `#[protocol("...")]` attributes, in order.
+fn protocols(ast: &DeriveInput) -> syn::Result<Vec<LitByteStr>> {
+    let mut protocols = Vec::new();
+
+    for attr in ast.attrs.iter().filter(|a| a.path.is_ident("protocol")) {
+        let nested = match attr.parse_meta()? {
+            Meta::List(list) => list.nested,
+            meta => return Err(syn::Error::new_spanned(meta, "expected `#[protocol(\"...\")]`")),
+        };
+        for item in nested {
+            match item {
+                NestedMeta::Lit(Lit::Str(s)) => {
+                    protocols.push(LitByteStr::new(s.value().as_bytes(), s.span()))
+                }
+                NestedMeta::Lit(Lit::ByteStr(s)) => protocols.push(s),
+                item => return Err(syn::Error::new_spanned(item, "expected a protocol name")),
+            }
+        }
+    }
+
+    if protocols.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &ast.ident,
+            "deriving `UpgradeInfo` requires at least one `#[protocol(\"...\")]` attribute"
+        ));
+    }
+
+    Ok(protocols)
+}
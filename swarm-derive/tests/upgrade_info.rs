@@ -0,0 +1,56 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use libp2p::core::UpgradeInfo;
+use std::marker::PhantomData;
+
+#[test]
+fn single_protocol() {
+    #[derive(libp2p::UpgradeInfo)]
+    #[protocol("/foo/1.0.0")]
+    struct Foo;
+
+    assert_eq!(Foo.protocol_info().collect::<Vec<_>>(), vec![b"/foo/1.0.0"]);
+}
+
+#[test]
+fn multiple_protocols() {
+    #[derive(libp2p::UpgradeInfo)]
+    #[protocol("/foo/2.0.0")]
+    #[protocol("/foo/1.1.0", "/foo/1.0.0")]
+    struct Foo;
+
+    assert_eq!(
+        Foo.protocol_info().collect::<Vec<_>>(),
+        vec![&b"/foo/2.0.0"[..], &b"/foo/1.1.0"[..], &b"/foo/1.0.0"[..]]
+    );
+}
+
+#[test]
+fn generics() {
+    #[derive(libp2p::UpgradeInfo)]
+    #[protocol(b"/bar/1.0.0")]
+    enum Bar<T: Send> {
+        Bar(PhantomData<T>),
+    }
+
+    let bar = Bar::<u8>::Bar(PhantomData);
+    assert_eq!(bar.protocol_info().collect::<Vec<_>>(), vec![b"/bar/1.0.0"]);
+}
@@ -20,8 +20,11 @@
 
 use crate::{AddressScore, AddressRecord};
 use crate::protocols_handler::{IntoProtocolsHandler, ProtocolsHandler};
+use futures::future::BoxFuture;
 use libp2p_core::{ConnectedPoint, Multiaddr, PeerId, connection::{ConnectionId, ListenerId}};
-use std::{error, task::Context, task::Poll};
+use std::{error, fmt, sync::Arc, task::Context, task::Poll, time::Duration};
+use std::sync::atomic::{AtomicU64, Ordering};
+use wasm_timer::Instant;
 
 /// A behaviour for the network. Allows customizing the swarm.
 ///
@@ -82,6 +85,19 @@ pub trait NetworkBehaviour: Send + 'static {
     /// address should be the most likely to be reachable.
     fn addresses_of_peer(&mut self, peer_id: &PeerId) -> Vec<Multiaddr>;
 
+    /// Like [`NetworkBehaviour::addresses_of_peer`], but additionally returns
+    /// the [`AddressMeta`] of every address, describing where the address
+    /// has been learned from and how likely it is to be reachable.
+    ///
+    /// The default implementation returns the addresses of
+    /// [`NetworkBehaviour::addresses_of_peer`] with [`AddressMeta::default`].
+    fn addresses_of_peer_with_metadata(&mut self, peer_id: &PeerId) -> Vec<(Multiaddr, AddressMeta)> {
+        self.addresses_of_peer(peer_id)
+            .into_iter()
+            .map(|addr| (addr, AddressMeta::default()))
+            .collect()
+    }
+
     /// Indicate to the behaviour that we connected to the node with the given peer id.
     ///
     /// This node now has a handler (as spawned by `new_handler`) running in the background.
@@ -176,6 +192,14 @@ pub trait NetworkBehaviour: Send + 'static {
     fn inject_expired_external_addr(&mut self, _addr: &Multiaddr) {
     }
 
+    /// Indicates to the behaviour that a timer set via
+    /// [`NetworkBehaviourAction::SetTimer`] has elapsed.
+    ///
+    /// Behaviours composed of others pass every timeout on to all of them,
+    /// each ignoring the timers it did not set itself.
+    fn inject_timeout(&mut self, _timer_id: TimerId) {
+    }
+
     /// Polls for things that swarm should do.
     ///
     /// This API mimics the API of the `Stream` trait. The method may register the current task in
@@ -184,6 +208,60 @@ pub trait NetworkBehaviour: Send + 'static {
         -> Poll<NetworkBehaviourAction<<<Self::ProtocolsHandler as IntoProtocolsHandler>::Handler as ProtocolsHandler>::InEvent, Self::OutEvent>>;
 }
 
+/// Where an address returned by [`NetworkBehaviour::addresses_of_peer_with_metadata`]
+/// has been learned from.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum AddressSource {
+    /// The address has been learned from the Kademlia DHT.
+    Kademlia,
+    /// The address has been reported by the peer via the identify protocol.
+    Identify,
+    /// The address has been discovered via mDNS on the local network.
+    Mdns,
+    /// The address has been provided by the user, e.g. via an `add_address` method.
+    UserProvided,
+    /// The source of the address is not known.
+    Unknown,
+}
+
+/// Metadata of an address returned by [`NetworkBehaviour::addresses_of_peer_with_metadata`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AddressMeta {
+    /// How likely the address is to be reachable, from `0.0` (unlikely) to
+    /// `1.0` (very likely).
+    pub score: f32,
+    /// Where the address has been learned from.
+    pub source: AddressSource,
+    /// When the address has last been confirmed to belong to the peer, if known.
+    pub last_seen: Option<Instant>,
+}
+
+impl AddressMeta {
+    /// Creates metadata for an address from the given source, with a neutral
+    /// score of `0.5`.
+    pub fn new(source: AddressSource) -> Self {
+        AddressMeta { score: 0.5, source, last_seen: None }
+    }
+
+    /// Sets the score of the address, clamped to `[0.0, 1.0]`.
+    pub fn with_score(mut self, score: f32) -> Self {
+        self.score = score.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets when the address has last been confirmed to belong to the peer.
+    pub fn with_last_seen(mut self, last_seen: Instant) -> Self {
+        self.last_seen = Some(last_seen);
+        self
+    }
+}
+
+impl Default for AddressMeta {
+    fn default() -> Self {
+        AddressMeta::new(AddressSource::Unknown)
+    }
+}
+
 /// Parameters passed to `poll()`, that the `NetworkBehaviour` has access to.
 pub trait PollParameters {
     /// Iterator returned by [`supported_protocols`](PollParameters::supported_protocols).
@@ -226,7 +304,6 @@ pub trait NetworkBehaviourEventProcess<TEvent> {
 /// in whose context it is executing.
 ///
 /// [`Swarm`]: super::Swarm
-#[derive(Debug)]
 pub enum NetworkBehaviourAction<TInEvent, TOutEvent> {
     /// Instructs the `Swarm` to return an event when it is being polled.
     GenerateEvent(TOutEvent),
@@ -292,6 +369,12 @@ pub enum NetworkBehaviourAction<TInEvent, TOutEvent> {
         /// an indicator for the trusworthiness of this address
         /// relative to other observed addresses.
         score: AddressScore,
+        /// The remote peer that observed the address, if known.
+        ///
+        /// Distinct observers of the same address are counted by the
+        /// [`Swarm`](crate::Swarm), see
+        /// [`ExpandedSwarm::external_addresses_with_scores`](crate::ExpandedSwarm::external_addresses_with_scores).
+        observer: Option<PeerId>,
     },
 
     /// Instructs the `Swarm` to initiate a graceful close of one or all connections
@@ -309,6 +392,93 @@ pub enum NetworkBehaviourAction<TInEvent, TOutEvent> {
         peer_id: PeerId,
         /// Whether to close a specific or all connections to the given peer.
         connection: CloseConnection,
+    },
+
+    /// Instructs the `Swarm` to add a tag to an established connection.
+    ///
+    /// Tags are human-readable annotations, e.g. describing why a connection
+    /// was established. See [`ExpandedSwarm::tag_connection_add`](crate::ExpandedSwarm::tag_connection_add).
+    ///
+    /// If the specified connection no longer exists, the tag is silently dropped.
+    TagConnection {
+        /// The connection to tag.
+        connection: ConnectionId,
+        /// The tag to add.
+        tag: Arc<str>,
+    },
+
+    /// Instructs the `Swarm` to run a background task on its executor.
+    ///
+    /// This allows a behaviour to perform asynchronous work, e.g. I/O, without
+    /// blocking its `poll` method. Tasks report back to the behaviour through
+    /// means of its own choosing, typically a channel whose receiving end is
+    /// polled by the behaviour, which must thus ensure that it is woken up.
+    ///
+    /// If no executor is configured, the task is polled on the current thread
+    /// whenever the `Swarm` is polled.
+    SpawnTask(BoxFuture<'static, ()>),
+
+    /// Instructs the `Swarm` to call [`NetworkBehaviour::inject_timeout`]
+    /// with the given timer ID once the given duration has elapsed.
+    ///
+    /// This spares behaviours from driving timers of their own, e.g. for
+    /// periodic work.
+    SetTimer {
+        /// The ID of the timer, see [`TimerId::new`].
+        timer_id: TimerId,
+        /// The duration after which the timer elapses.
+        duration: Duration,
+    },
+}
+
+impl<TInEvent, TOutEvent> fmt::Debug for NetworkBehaviourAction<TInEvent, TOutEvent>
+where
+    TInEvent: fmt::Debug,
+    TOutEvent: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetworkBehaviourAction::GenerateEvent(event) =>
+                f.debug_tuple("GenerateEvent").field(event).finish(),
+            NetworkBehaviourAction::DialAddress { address } =>
+                f.debug_struct("DialAddress")
+                    .field("address", address)
+                    .finish(),
+            NetworkBehaviourAction::DialPeer { peer_id, condition } =>
+                f.debug_struct("DialPeer")
+                    .field("peer_id", peer_id)
+                    .field("condition", condition)
+                    .finish(),
+            NetworkBehaviourAction::NotifyHandler { peer_id, handler, event } =>
+                f.debug_struct("NotifyHandler")
+                    .field("peer_id", peer_id)
+                    .field("handler", handler)
+                    .field("event", event)
+                    .finish(),
+            NetworkBehaviourAction::ReportObservedAddr { address, score, observer } =>
+                f.debug_struct("ReportObservedAddr")
+                    .field("address", address)
+                    .field("score", score)
+                    .field("observer", observer)
+                    .finish(),
+            NetworkBehaviourAction::CloseConnection { peer_id, connection } =>
+                f.debug_struct("CloseConnection")
+                    .field("peer_id", peer_id)
+                    .field("connection", connection)
+                    .finish(),
+            NetworkBehaviourAction::TagConnection { connection, tag } =>
+                f.debug_struct("TagConnection")
+                    .field("connection", connection)
+                    .field("tag", tag)
+                    .finish(),
+            NetworkBehaviourAction::SpawnTask(_) =>
+                f.debug_tuple("SpawnTask").finish(),
+            NetworkBehaviourAction::SetTimer { timer_id, duration } =>
+                f.debug_struct("SetTimer")
+                    .field("timer_id", timer_id)
+                    .field("duration", duration)
+                    .finish(),
+        }
     }
 }
 
@@ -328,10 +498,16 @@ impl<TInEvent, TOutEvent> NetworkBehaviourAction<TInEvent, TOutEvent> {
                     handler,
                     event: f(event)
                 },
-            NetworkBehaviourAction::ReportObservedAddr { address, score } =>
-                NetworkBehaviourAction::ReportObservedAddr { address, score },
+            NetworkBehaviourAction::ReportObservedAddr { address, score, observer } =>
+                NetworkBehaviourAction::ReportObservedAddr { address, score, observer },
             NetworkBehaviourAction::CloseConnection { peer_id, connection } =>
-                NetworkBehaviourAction::CloseConnection { peer_id, connection }
+                NetworkBehaviourAction::CloseConnection { peer_id, connection },
+            NetworkBehaviourAction::TagConnection { connection, tag } =>
+                NetworkBehaviourAction::TagConnection { connection, tag },
+            NetworkBehaviourAction::SpawnTask(task) =>
+                NetworkBehaviourAction::SpawnTask(task),
+            NetworkBehaviourAction::SetTimer { timer_id, duration } =>
+                NetworkBehaviourAction::SetTimer { timer_id, duration },
         }
     }
 
@@ -346,14 +522,41 @@ impl<TInEvent, TOutEvent> NetworkBehaviourAction<TInEvent, TOutEvent> {
                 NetworkBehaviourAction::DialPeer { peer_id, condition },
             NetworkBehaviourAction::NotifyHandler { peer_id, handler, event } =>
                 NetworkBehaviourAction::NotifyHandler { peer_id, handler, event },
-            NetworkBehaviourAction::ReportObservedAddr { address, score } =>
-                NetworkBehaviourAction::ReportObservedAddr { address, score },
+            NetworkBehaviourAction::ReportObservedAddr { address, score, observer } =>
+                NetworkBehaviourAction::ReportObservedAddr { address, score, observer },
             NetworkBehaviourAction::CloseConnection { peer_id, connection } =>
-                NetworkBehaviourAction::CloseConnection { peer_id, connection }
+                NetworkBehaviourAction::CloseConnection { peer_id, connection },
+            NetworkBehaviourAction::TagConnection { connection, tag } =>
+                NetworkBehaviourAction::TagConnection { connection, tag },
+            NetworkBehaviourAction::SpawnTask(task) =>
+                NetworkBehaviourAction::SpawnTask(task),
+            NetworkBehaviourAction::SetTimer { timer_id, duration } =>
+                NetworkBehaviourAction::SetTimer { timer_id, duration },
         }
     }
 }
 
+/// Identifies a timer set via [`NetworkBehaviourAction::SetTimer`] or
+/// [`ProtocolsHandlerEvent::SetTimer`](crate::ProtocolsHandlerEvent::SetTimer).
+///
+/// Timer IDs are unique within the process.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct TimerId(u64);
+
+impl TimerId {
+    /// Creates a new, unique `TimerId`.
+    pub fn new() -> Self {
+        static NEXT_TIMER_ID: AtomicU64 = AtomicU64::new(0);
+        TimerId(NEXT_TIMER_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl Default for TimerId {
+    fn default() -> Self {
+        TimerId::new()
+    }
+}
+
 /// The options w.r.t. which connection handler to notify of an event.
 #[derive(Debug, Clone)]
 pub enum NotifyHandler {
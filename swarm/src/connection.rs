@@ -0,0 +1,181 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Classification of connection errors.
+//!
+//! Errors reported for failed connection attempts, e.g. via
+//! [`SwarmEvent::UnreachableAddr`](crate::SwarmEvent::UnreachableAddr), are
+//! classified as either transient or permanent. Transient errors, like a
+//! timeout or a connection reset, may well not occur again on a later attempt,
+//! whereas permanent errors, like a failed authentication or a protocol
+//! mismatch, are expected to persist.
+
+use libp2p_core::connection::PendingConnectionError;
+use libp2p_core::transport::TransportError;
+use std::{error::Error, io, time::Duration};
+
+/// Suggested delay before retrying after a timeout.
+const TIMEOUT_RETRY_DELAY: Duration = Duration::from_secs(5);
+/// Suggested delay before retrying after the connection was reset or aborted.
+const RESET_RETRY_DELAY: Duration = Duration::from_secs(1);
+/// Suggested delay before retrying after the connection was refused.
+const REFUSED_RETRY_DELAY: Duration = Duration::from_secs(10);
+
+/// The class of a connection error, as determined by [`classify_connection_error`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ConnectionErrorClass {
+    /// The error is likely to be temporary and the connection attempt may be retried.
+    Transient(RetryGuidance),
+    /// The error is likely to persist and retrying is not advised.
+    Permanent,
+}
+
+impl ConnectionErrorClass {
+    /// Returns `true` if the error is likely to be temporary.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, ConnectionErrorClass::Transient(_))
+    }
+
+    /// Returns the [`RetryGuidance`] for transient errors, `None` for permanent ones.
+    pub fn retry_guidance(&self) -> Option<RetryGuidance> {
+        match self {
+            ConnectionErrorClass::Transient(guidance) => Some(*guidance),
+            ConnectionErrorClass::Permanent => None,
+        }
+    }
+}
+
+/// Guidance on retrying a connection attempt that failed with a transient error.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RetryGuidance {
+    /// Suggested delay before the next connection attempt, if any.
+    pub retry_after: Option<Duration>,
+}
+
+/// Classifies the error of a failed connection attempt.
+pub fn classify_connection_error(error: &PendingConnectionError<io::Error>) -> ConnectionErrorClass {
+    match error {
+        PendingConnectionError::Transport(TransportError::MultiaddrNotSupported(_)) =>
+            ConnectionErrorClass::Permanent,
+        PendingConnectionError::Transport(TransportError::Other(err)) =>
+            classify_io_error(err),
+        PendingConnectionError::InvalidPeerId
+        | PendingConnectionError::PeerIdMismatch { .. } =>
+            ConnectionErrorClass::Permanent,
+        // Other connections may be closed in the meantime.
+        PendingConnectionError::ConnectionLimit(_) =>
+            ConnectionErrorClass::Transient(RetryGuidance { retry_after: None }),
+        PendingConnectionError::IO(err) =>
+            classify_io_error(err),
+    }
+}
+
+fn classify_io_error(error: &io::Error) -> ConnectionErrorClass {
+    let retry_after = match io_error_kind(error) {
+        io::ErrorKind::TimedOut
+        | io::ErrorKind::WouldBlock => TIMEOUT_RETRY_DELAY,
+        io::ErrorKind::ConnectionReset
+        | io::ErrorKind::ConnectionAborted
+        | io::ErrorKind::BrokenPipe
+        | io::ErrorKind::UnexpectedEof
+        | io::ErrorKind::Interrupted => RESET_RETRY_DELAY,
+        io::ErrorKind::ConnectionRefused
+        | io::ErrorKind::AddrNotAvailable
+        | io::ErrorKind::NotConnected => REFUSED_RETRY_DELAY,
+        _ => return ConnectionErrorClass::Permanent,
+    };
+
+    ConnectionErrorClass::Transient(RetryGuidance { retry_after: Some(retry_after) })
+}
+
+/// Returns the most specific [`io::ErrorKind`] of an error.
+///
+/// Transports commonly wrap the errors of the transports they are built
+/// upon in an [`io::Error`] of kind [`io::ErrorKind::Other`], hence the
+/// chain of errors is searched for the first [`io::Error`] of another kind.
+fn io_error_kind(error: &io::Error) -> io::ErrorKind {
+    let mut current: Option<&(dyn Error + 'static)> = Some(error);
+
+    while let Some(err) = current {
+        current = match err.downcast_ref::<io::Error>() {
+            Some(io_err) if io_err.kind() != io::ErrorKind::Other => return io_err.kind(),
+            Some(io_err) => io_err.get_ref().map(|e| e as &(dyn Error + 'static)),
+            None => err.source(),
+        };
+    }
+
+    io::ErrorKind::Other
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libp2p_core::connection::ConnectionLimit;
+
+    #[test]
+    fn transient_io_errors() {
+        let error = PendingConnectionError::IO(io::Error::from(io::ErrorKind::TimedOut));
+        let class = classify_connection_error(&error);
+        assert!(class.is_transient());
+        assert_eq!(class.retry_guidance(), Some(RetryGuidance { retry_after: Some(TIMEOUT_RETRY_DELAY) }));
+
+        let error = PendingConnectionError::IO(io::Error::from(io::ErrorKind::ConnectionReset));
+        assert!(classify_connection_error(&error).is_transient());
+    }
+
+    #[test]
+    fn wrapped_transport_errors() {
+        let inner = io::Error::from(io::ErrorKind::ConnectionRefused);
+        let error = PendingConnectionError::Transport(
+            TransportError::Other(io::Error::new(io::ErrorKind::Other, inner))
+        );
+        assert_eq!(
+            classify_connection_error(&error),
+            ConnectionErrorClass::Transient(RetryGuidance { retry_after: Some(REFUSED_RETRY_DELAY) })
+        );
+
+        let error = PendingConnectionError::Transport(
+            TransportError::Other(io::Error::new(io::ErrorKind::Other, "protocol mismatch"))
+        );
+        assert_eq!(classify_connection_error(&error), ConnectionErrorClass::Permanent);
+    }
+
+    #[test]
+    fn permanent_errors() {
+        let error = PendingConnectionError::<io::Error>::InvalidPeerId;
+        assert!(!classify_connection_error(&error).is_transient());
+
+        let error = PendingConnectionError::<io::Error>::Transport(
+            TransportError::MultiaddrNotSupported("/memory/1234".parse().unwrap())
+        );
+        assert_eq!(classify_connection_error(&error).retry_guidance(), None);
+    }
+
+    #[test]
+    fn connection_limit() {
+        let error = PendingConnectionError::<io::Error>::ConnectionLimit(
+            ConnectionLimit { limit: 1, current: 1 }
+        );
+        assert_eq!(
+            classify_connection_error(&error),
+            ConnectionErrorClass::Transient(RetryGuidance { retry_after: None })
+        );
+    }
+}
@@ -0,0 +1,102 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Swarm-wide strategies for keeping idle connections alive.
+//!
+//! A [`ProtocolsHandler`](crate::ProtocolsHandler) decides whether a connection
+//! is still needed from the point of view of the protocols it handles, via
+//! [`ProtocolsHandler::connection_keep_alive`](crate::ProtocolsHandler::connection_keep_alive).
+//! A [`ConnectionKeepAliveStrategy`] configured on the [`SwarmBuilder`](crate::SwarmBuilder)
+//! is consulted in addition, allowing connections to be retained based on
+//! information that is not available to any individual handler, e.g. because
+//! the connection may soon be needed again.
+
+use crate::protocols_handler::KeepAlive;
+use libp2p_core::{Endpoint, PeerId};
+use std::time::Duration;
+use wasm_timer::Instant;
+
+/// A strategy that determines for how long an idle connection is kept alive.
+///
+/// The strategy is consulted for every connection whose handler no longer
+/// requests the connection to be kept alive, i.e. whose
+/// [`ProtocolsHandler::connection_keep_alive`](crate::ProtocolsHandler::connection_keep_alive)
+/// returns something other than [`KeepAlive::Yes`]. The larger of the two
+/// [`KeepAlive`] values takes precedence.
+pub trait ConnectionKeepAliveStrategy: Send + Sync + 'static {
+    /// Returns until when the connection to `peer_id` should be kept alive.
+    ///
+    /// `since` is the instant since which the connection is idle, i.e. the
+    /// last time the handler requested the connection to be kept alive, or
+    /// the time at which the connection was established, whichever is later.
+    /// `role` is the local role on the connection.
+    fn keep_alive(&self, peer_id: &PeerId, since: Instant, role: Endpoint) -> KeepAlive;
+}
+
+/// Keeps idle connections alive for a fixed duration.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct IdleTimeoutStrategy {
+    /// For how long an idle connection is kept alive.
+    pub timeout: Duration,
+}
+
+impl ConnectionKeepAliveStrategy for IdleTimeoutStrategy {
+    fn keep_alive(&self, _: &PeerId, since: Instant, _: Endpoint) -> KeepAlive {
+        KeepAlive::Until(since + self.timeout)
+    }
+}
+
+/// Keeps all connections alive, regardless of whether they are idle.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct PermanentStrategy;
+
+impl ConnectionKeepAliveStrategy for PermanentStrategy {
+    fn keep_alive(&self, _: &PeerId, _: Instant, _: Endpoint) -> KeepAlive {
+        KeepAlive::Yes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn idle_timeout_strategy() {
+        let strategy = IdleTimeoutStrategy { timeout: Duration::from_secs(10) };
+        let since = Instant::now();
+        let peer_id = PeerId::random();
+
+        for role in [Endpoint::Dialer, Endpoint::Listener].iter() {
+            assert_eq!(
+                strategy.keep_alive(&peer_id, since, *role),
+                KeepAlive::Until(since + Duration::from_secs(10))
+            );
+        }
+    }
+
+    #[test]
+    fn permanent_strategy() {
+        let peer_id = PeerId::random();
+
+        for role in [Endpoint::Dialer, Endpoint::Listener].iter() {
+            assert_eq!(PermanentStrategy.keep_alive(&peer_id, Instant::now(), *role), KeepAlive::Yes);
+        }
+    }
+}
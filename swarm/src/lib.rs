@@ -53,7 +53,23 @@
 //! are supported, when to open a new outbound substream, etc.
 //!
 
+/// Logs a record at the given level, attaching the given key-values as
+/// structured fields if the `log-kv` feature is enabled.
+macro_rules! log_kv {
+    ($lvl:ident, [$($kv:tt)*], $($arg:tt)+) => {{
+        #[cfg(feature = "log-kv")]
+        log::$lvl!($($kv)*; $($arg)+);
+        #[cfg(not(feature = "log-kv"))]
+        log::$lvl!($($arg)+);
+    }};
+}
+
 mod behaviour;
+mod connection;
+mod keep_alive;
+#[cfg(feature = "metrics")]
+mod metrics;
+mod network_info;
 mod registry;
 #[cfg(test)]
 mod test;
@@ -63,13 +79,16 @@ pub mod protocols_handler;
 pub mod toggle;
 
 pub use behaviour::{
+    AddressMeta,
+    AddressSource,
     NetworkBehaviour,
     NetworkBehaviourAction,
     NetworkBehaviourEventProcess,
     PollParameters,
     NotifyHandler,
     DialPeerCondition,
-    CloseConnection
+    CloseConnection,
+    TimerId
 };
 pub use protocols_handler::{
     IntoProtocolsHandler,
@@ -83,16 +102,21 @@ pub use protocols_handler::{
     OneShotHandlerConfig,
     SubstreamProtocol
 };
-pub use registry::{AddressScore, AddressRecord, AddAddressResult};
+pub use connection::{classify_connection_error, ConnectionErrorClass, RetryGuidance};
+pub use keep_alive::{ConnectionKeepAliveStrategy, IdleTimeoutStrategy, PermanentStrategy};
+pub use network_info::{ConnectionInfo, NetworkInfo, PeerInfo};
+pub use registry::{AddressConfidence, AddressScore, AddressRecord, AddAddressResult, ExternalAddressScore};
 
 use protocols_handler::{
     NodeHandlerWrapperBuilder,
     NodeHandlerWrapperError,
+    NodeHandlerWrapperEvent,
 };
 use futures::{
     prelude::*,
     executor::ThreadPoolBuilder,
-    stream::FusedStream,
+    future::BoxFuture,
+    stream::{FusedStream, FuturesUnordered},
 };
 use libp2p_core::{
     Executor,
@@ -111,25 +135,37 @@ use libp2p_core::{
         PendingConnectionError,
         Substream
     },
-    transport::{self, TransportError},
+    transport::{self, TransportError, timeout::{TransportTimeout, TransportTimeoutError}},
     muxing::StreamMuxerBox,
     network::{
         self,
         ConnectionLimits,
         Network,
-        NetworkInfo,
         NetworkEvent,
         NetworkConfig,
         peer::ConnectedPeer,
     },
-    upgrade::{ProtocolName},
+    multiaddr::Protocol,
+    upgrade::{ProtocolName, StreamProtocol},
 };
-use registry::{Addresses, AddressIntoIter};
+use network_info::EstablishedConnectionMeta;
+use registry::{Addresses, AddressIntoIter, Observations};
 use smallvec::SmallVec;
 use std::{error, fmt, io, pin::Pin, task::{Context, Poll}};
-use std::collections::HashSet;
+use std::net::Ipv4Addr;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::convert::TryFrom;
 use std::num::{NonZeroU32, NonZeroUsize};
+use std::sync::Arc;
+use std::time::Duration;
 use upgrade::UpgradeInfoSend as _;
+use wasm_timer::{Delay, Instant};
+
+/// The number of distinct remote peers that need to observe an external
+/// address of the local node, reported via
+/// [`NetworkBehaviourAction::ReportObservedAddr`], for the address to be
+/// confirmed.
+pub const MIN_CONFIRMING_OBSERVERS: usize = 2;
 
 /// Contains the state of the network, plus the way it should behave.
 pub type Swarm<TBehaviour> = ExpandedSwarm<
@@ -154,17 +190,24 @@ pub enum SwarmEvent<TBvEv, THandleErr> {
     ConnectionEstablished {
         /// Identity of the peer that we have connected to.
         peer_id: PeerId,
+        /// Identifier of the connection.
+        connection_id: ConnectionId,
         /// Endpoint of the connection that has been opened.
         endpoint: ConnectedPoint,
         /// Number of established connections to this peer, including the one that has just been
         /// opened.
         num_established: NonZeroU32,
+        /// Tags of the connection at the time the event is reported.
+        /// See [`ExpandedSwarm::tag_connection`].
+        tags: Vec<Arc<str>>,
     },
     /// A connection with the given peer has been closed,
     /// possibly as a result of an error.
     ConnectionClosed {
         /// Identity of the peer that we have connected to.
         peer_id: PeerId,
+        /// Identifier of the connection.
+        connection_id: ConnectionId,
         /// Endpoint of the connection that has been closed.
         endpoint: ConnectedPoint,
         /// Number of other remaining connections to this same peer.
@@ -172,6 +215,9 @@ pub enum SwarmEvent<TBvEv, THandleErr> {
         /// Reason for the disconnection, if it was not a successful
         /// active close.
         cause: Option<ConnectionError<NodeHandlerWrapperError<THandleErr>>>,
+        /// Tags the connection had when it was closed.
+        /// See [`ExpandedSwarm::tag_connection`].
+        tags: Vec<Arc<str>>,
     },
     /// A new connection arrived on a listener and is in the process of protocol negotiation.
     ///
@@ -216,6 +262,9 @@ pub enum SwarmEvent<TBvEv, THandleErr> {
         address: Multiaddr,
         /// Error that has been encountered.
         error: PendingConnectionError<io::Error>,
+        /// Guidance on retrying the connection attempt if the error is transient,
+        /// `None` if the error is permanent. See [`classify_connection_error`].
+        retry_guidance: Option<RetryGuidance>,
         /// Number of remaining connection attempts that are being tried for this peer.
         attempts_remaining: u32,
     },
@@ -227,6 +276,9 @@ pub enum SwarmEvent<TBvEv, THandleErr> {
         address: Multiaddr,
         /// Error that has been encountered.
         error: PendingConnectionError<io::Error>,
+        /// Guidance on retrying the connection attempt if the error is transient,
+        /// `None` if the error is permanent. See [`classify_connection_error`].
+        retry_guidance: Option<RetryGuidance>,
     },
     /// One of our listeners has reported a new local listening address.
     NewListenAddr{
@@ -261,6 +313,24 @@ pub enum SwarmEvent<TBvEv, THandleErr> {
         /// The listener error.
         error: io::Error,
     },
+    /// A protocol supported by the `NetworkBehaviour` is now advertised on
+    /// inbound connections, since the first listener has been added via
+    /// [`ExpandedSwarm::listen_on`].
+    ///
+    /// Only reported if enabled via [`SwarmBuilder::report_listen_protocols`].
+    ListenProtocolAdded {
+        /// The name of the protocol.
+        protocol: StreamProtocol,
+    },
+    /// A protocol supported by the `NetworkBehaviour` is no longer
+    /// advertised on inbound connections, since the last listener has been
+    /// removed via [`ExpandedSwarm::remove_listener`] or has closed.
+    ///
+    /// Only reported if enabled via [`SwarmBuilder::report_listen_protocols`].
+    ListenProtocolRemoved {
+        /// The name of the protocol.
+        protocol: StreamProtocol,
+    },
     /// A new dialing attempt has been initiated.
     ///
     /// A [`ConnectionEstablished`](SwarmEvent::ConnectionEstablished)
@@ -270,6 +340,17 @@ pub enum SwarmEvent<TBvEv, THandleErr> {
     Dialing(PeerId),
 }
 
+/// The ports to listen on with [`ExpandedSwarm::listen_on_all_interfaces`].
+///
+/// A port of `0` lets the operating system pick a free port.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct ListenPorts {
+    /// The TCP port, used for `/ip4/0.0.0.0/tcp/<port>`.
+    pub tcp: u16,
+    /// The UDP port, used for `/ip4/0.0.0.0/udp/<port>/quic`.
+    pub quic: u16,
+}
+
 /// Contains the state of the network, plus the way it should behave.
 ///
 /// Note: Needs to be polled via `<ExpandedSwarm as Stream>` in order to make
@@ -281,7 +362,7 @@ where
     network: Network<
         transport::Boxed<(PeerId, StreamMuxerBox)>,
         TInEvent,
-        TOutEvent,
+        NodeHandlerWrapperEvent<TOutEvent>,
         NodeHandlerWrapperBuilder<THandler>,
     >,
 
@@ -295,10 +376,33 @@ where
     /// List of multiaddresses we're listening on.
     listened_addrs: SmallVec<[Multiaddr; 8]>,
 
+    /// The active listeners.
+    listeners: HashSet<ListenerId>,
+
+    /// The supported protocols reported as [`SwarmEvent::ListenProtocolAdded`]
+    /// and [`SwarmEvent::ListenProtocolRemoved`], empty unless enabled via
+    /// [`SwarmBuilder::report_listen_protocols`].
+    listen_protocols: SmallVec<[StreamProtocol; 16]>,
+
+    /// Changes of the protocols advertised on inbound connections that are
+    /// yet to be reported as [`SwarmEvent::ListenProtocolAdded`] and
+    /// [`SwarmEvent::ListenProtocolRemoved`].
+    pending_listen_protocol_events: VecDeque<ListenProtocolEvent>,
+
     /// List of multiaddresses we're listening on, after account for external IP addresses and
     /// similar mechanisms.
     external_addrs: Addresses,
 
+    /// The observations of the `external_addrs` reported by remote peers.
+    external_addr_observations: HashMap<Multiaddr, Observations>,
+
+    /// Tentative external addresses that are yet to be confirmed, see
+    /// [`ExpandedSwarm::add_external_address_with_confidence`].
+    tentative_external_addrs: HashSet<Multiaddr>,
+
+    /// Timers set via [`NetworkBehaviourAction::SetTimer`].
+    timers: FuturesUnordered<BoxFuture<'static, TimerId>>,
+
     /// List of nodes for which we deny any incoming connection.
     banned_peers: HashSet<PeerId>,
 
@@ -309,6 +413,30 @@ where
 
     /// The configured override for substream protocol upgrades, if any.
     substream_upgrade_protocol_override: Option<libp2p_core::upgrade::Version>,
+
+    /// The configured strategy for keeping idle connections alive, if any.
+    keep_alive_strategy: Option<Arc<dyn ConnectionKeepAliveStrategy>>,
+
+    /// The configured number of consecutive failed outbound substream
+    /// upgrades after which a connection is closed, if any.
+    max_failed_upgrades: Option<NonZeroUsize>,
+
+    /// Information about all established connections.
+    connections: HashMap<ConnectionId, EstablishedConnectionMeta>,
+
+    /// Tags of pending outgoing connections started via
+    /// [`ExpandedSwarm::dial_addr_with_tags`].
+    pending_tags: HashMap<ConnectionId, Vec<Arc<str>>>,
+
+    /// The metrics registry given via [`SwarmBuilder::with_behaviour_and_metrics`]
+    /// or [`SwarmBuilder::with_metrics`], if any.
+    #[cfg(feature = "metrics")]
+    metrics_registry: Option<prometheus::Registry>,
+
+    /// The metrics recorded from the events of the swarm, see
+    /// [`SwarmBuilder::with_metrics`].
+    #[cfg(feature = "metrics")]
+    metrics: Option<metrics::SwarmMetrics>,
 }
 
 impl<TBehaviour, TInEvent, TOutEvent, THandler> Unpin for
@@ -336,9 +464,17 @@ where TBehaviour: NetworkBehaviour<ProtocolsHandler = THandler>,
         SwarmBuilder::new(transport, behaviour, local_peer_id).build()
     }
 
+    /// Returns the metrics registry given via
+    /// [`SwarmBuilder::with_behaviour_and_metrics`] or
+    /// [`SwarmBuilder::with_metrics`], if any.
+    #[cfg(feature = "metrics")]
+    pub fn metrics_registry(&self) -> Option<&prometheus::Registry> {
+        self.metrics_registry.as_ref()
+    }
+
     /// Returns information about the [`Network`] underlying the `Swarm`.
     pub fn network_info(&self) -> NetworkInfo {
-        self.network.info()
+        NetworkInfo::new(self.network.info(), &self.connections)
     }
 
     /// Starts listening on the given address.
@@ -349,24 +485,170 @@ where TBehaviour: NetworkBehaviour<ProtocolsHandler = THandler>,
     pub fn listen_on(&mut self, addr: Multiaddr) -> Result<ListenerId, TransportError<io::Error>> {
         let id = self.network.listen_on(addr)?;
         self.behaviour.inject_new_listener(id);
+        if self.listeners.is_empty() {
+            self.pending_listen_protocol_events.extend(
+                self.listen_protocols.iter().cloned().map(ListenProtocolEvent::Added)
+            );
+        }
+        self.listeners.insert(id);
         Ok(id)
     }
 
+    /// Starts listening on all IPv4 interfaces on the given ports, both via
+    /// TCP and via QUIC.
+    ///
+    /// Addresses that the transport does not support, i.e. for which
+    /// [`ExpandedSwarm::listen_on`] fails with
+    /// [`TransportError::MultiaddrNotSupported`], are skipped. Returns the
+    /// IDs of the new listeners, one per address that is listened on. If the
+    /// transport supports none of the addresses,
+    /// [`TransportError::MultiaddrNotSupported`] is returned for the TCP one.
+    ///
+    /// If listening on an address fails for any other reason, the listeners
+    /// started so far by this call are removed again and the error is returned.
+    pub fn listen_on_all_interfaces(&mut self, ports: ListenPorts) -> Result<Vec<ListenerId>, TransportError<io::Error>> {
+        let addrs = [
+            Multiaddr::empty()
+                .with(Protocol::Ip4(Ipv4Addr::UNSPECIFIED))
+                .with(Protocol::Tcp(ports.tcp)),
+            Multiaddr::empty()
+                .with(Protocol::Ip4(Ipv4Addr::UNSPECIFIED))
+                .with(Protocol::Udp(ports.quic))
+                .with(Protocol::Quic),
+        ];
+
+        let mut ids = Vec::with_capacity(addrs.len());
+        let mut unsupported = None;
+        for addr in addrs.iter() {
+            match self.listen_on(addr.clone()) {
+                Ok(id) => ids.push(id),
+                Err(TransportError::MultiaddrNotSupported(addr)) => {
+                    log::debug!("Not listening on unsupported address {}.", addr);
+                    unsupported.get_or_insert(addr);
+                }
+                Err(err) => {
+                    for id in ids {
+                        let _ = self.remove_listener(id);
+                    }
+                    return Err(err)
+                }
+            }
+        }
+        match unsupported {
+            Some(addr) if ids.is_empty() => Err(TransportError::MultiaddrNotSupported(addr)),
+            _ => Ok(ids),
+        }
+    }
+
     /// Remove some listener.
     ///
     /// Returns `Ok(())` if there was a listener with this ID.
     pub fn remove_listener(&mut self, id: ListenerId) -> Result<(), ()> {
-        self.network.remove_listener(id)
+        self.network.remove_listener(id)?;
+        self.listener_removed(id);
+        Ok(())
+    }
+
+    /// Queues a [`SwarmEvent::ListenProtocolRemoved`] for every supported
+    /// protocol if the given listener was the last one.
+    fn listener_removed(&mut self, id: ListenerId) {
+        if self.listeners.remove(&id) && self.listeners.is_empty() {
+            self.pending_listen_protocol_events.extend(
+                self.listen_protocols.iter().cloned().map(ListenProtocolEvent::Removed)
+            );
+        }
     }
 
     /// Initiates a new dialing attempt to the given address.
     pub fn dial_addr(&mut self, addr: Multiaddr) -> Result<(), DialError> {
         let handler = self.behaviour.new_handler()
             .into_node_handler_builder()
-            .with_substream_upgrade_protocol_override(self.substream_upgrade_protocol_override);
+            .with_substream_upgrade_protocol_override(self.substream_upgrade_protocol_override)
+            .with_keep_alive_strategy(self.keep_alive_strategy.clone())
+            .with_max_failed_upgrades(self.max_failed_upgrades);
         Ok(self.network.dial(&addr, handler).map(|_id| ())?)
     }
 
+    /// Initiates a new dialing attempt to the given address, tagging the
+    /// resulting connection with the given tags.
+    ///
+    /// The tags are reported in [`SwarmEvent::ConnectionEstablished`] once
+    /// the connection is established. The returned [`ConnectionId`] can be
+    /// used with [`ExpandedSwarm::tag_connection`] before and after that.
+    pub fn dial_addr_with_tags<I, T>(&mut self, addr: Multiaddr, tags: I) -> Result<ConnectionId, DialError>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        let handler = self.behaviour.new_handler()
+            .into_node_handler_builder()
+            .with_substream_upgrade_protocol_override(self.substream_upgrade_protocol_override)
+            .with_keep_alive_strategy(self.keep_alive_strategy.clone())
+            .with_max_failed_upgrades(self.max_failed_upgrades);
+        let id = self.network.dial(&addr, handler)?;
+        let tags = tags.into_iter().map(|t| Arc::from(t.into())).collect();
+        self.pending_tags.insert(id, tags);
+        Ok(id)
+    }
+
+    /// Returns the tags of the established or pending connection with the given ID.
+    fn connection_tags_mut(&mut self, connection_id: &ConnectionId) -> Option<&mut Vec<Arc<str>>> {
+        if let Some(meta) = self.connections.get_mut(connection_id) {
+            return Some(&mut meta.tags);
+        }
+        self.pending_tags.get_mut(connection_id)
+    }
+
+    /// Dials the given address and drives the `Swarm` on the given runtime
+    /// until the connection is established, returning the `PeerId` of the
+    /// remote, or until the attempt fails or times out after 10 seconds.
+    ///
+    /// Meant for tests running on synchronous threads. All other events
+    /// produced by the `Swarm` in the meantime are discarded. The runtime
+    /// must have the time driver enabled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use libp2p_core::{Multiaddr, multiaddr::Protocol};
+    /// # use libp2p_swarm::{DummyBehaviour, SwarmBuilder};
+    /// # use futures::StreamExt;
+    /// let rt = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+    ///
+    /// let mut listener = SwarmBuilder::with_memory_transport(DummyBehaviour::default()).build();
+    /// let listener_id = *listener.local_peer_id();
+    /// let addr: Multiaddr = Protocol::Memory(41_0001).into();
+    /// listener.listen_on(addr.clone()).unwrap();
+    /// rt.spawn(async move { loop { listener.next().await; } });
+    ///
+    /// let mut dialer = SwarmBuilder::with_memory_transport(DummyBehaviour::default()).build();
+    /// assert_eq!(dialer.dial_blocking(addr, &rt).unwrap(), listener_id);
+    /// ```
+    #[cfg(feature = "test-utils")]
+    pub fn dial_blocking(&mut self, addr: Multiaddr, rt: &tokio::runtime::Runtime)
+        -> Result<PeerId, DialBlockingError>
+    {
+        self.dial_addr(addr.clone()).map_err(DialBlockingError::Dial)?;
+
+        let connect = future::poll_fn(|cx| loop {
+            match Pin::new(&mut *self).poll_next_event(cx) {
+                Poll::Ready(SwarmEvent::ConnectionEstablished {
+                    peer_id, endpoint: ConnectedPoint::Dialer { address }, ..
+                }) if address == addr => return Poll::Ready(Ok(peer_id)),
+                Poll::Ready(SwarmEvent::UnreachableAddr { address, error, .. })
+                | Poll::Ready(SwarmEvent::UnknownPeerUnreachableAddr { address, error, .. })
+                    if address == addr => return Poll::Ready(Err(DialBlockingError::Connection(error))),
+                Poll::Ready(_) => {},
+                Poll::Pending => return Poll::Pending,
+            }
+        });
+
+        rt.block_on(async {
+            tokio::time::timeout(Duration::from_secs(10), connect).await
+                .unwrap_or(Err(DialBlockingError::Timeout))
+        })
+    }
+
     /// Initiates a new dialing attempt to the given peer.
     pub fn dial(&mut self, peer_id: &PeerId) -> Result<(), DialError> {
         if self.banned_peers.contains(peer_id) {
@@ -383,7 +665,9 @@ where TBehaviour: NetworkBehaviour<ProtocolsHandler = THandler>,
             if let Some(first) = addrs.next() {
                 let handler = self.behaviour.new_handler()
                     .into_node_handler_builder()
-                    .with_substream_upgrade_protocol_override(self.substream_upgrade_protocol_override);
+                    .with_substream_upgrade_protocol_override(self.substream_upgrade_protocol_override)
+                    .with_keep_alive_strategy(self.keep_alive_strategy.clone())
+                    .with_max_failed_upgrades(self.max_failed_upgrades);
                 self.network.peer(*peer_id)
                     .dial(first, addrs, handler)
                     .map(|_| ())
@@ -393,7 +677,43 @@ where TBehaviour: NetworkBehaviour<ProtocolsHandler = THandler>,
             };
 
         if let Err(error) = &result {
-            log::debug!(
+            log_kv!(debug, [
+                peer_id:% = peer_id, error:% = error],
+                "New dialing attempt to peer {:?} failed: {:?}.",
+                peer_id, error);
+            self.behaviour.inject_dial_failure(&peer_id);
+        }
+
+        result
+    }
+
+    /// Initiates a new dialing attempt to the given peer via the given
+    /// address, regardless of the addresses known for the peer by the
+    /// `NetworkBehaviour`.
+    ///
+    /// Contrary to [`ExpandedSwarm::dial_addr`], the connection is only
+    /// established if the remote authenticates as `peer_id`. Otherwise it
+    /// is closed and [`SwarmEvent::UnreachableAddr`] is reported with a
+    /// [`PendingConnectionError::PeerIdMismatch`].
+    pub fn dial_with_peer_id(&mut self, peer_id: PeerId, addr: Multiaddr) -> Result<(), DialError> {
+        if self.banned_peers.contains(&peer_id) {
+            self.behaviour.inject_dial_failure(&peer_id);
+            return Err(DialError::Banned)
+        }
+
+        let handler = self.behaviour.new_handler()
+            .into_node_handler_builder()
+            .with_substream_upgrade_protocol_override(self.substream_upgrade_protocol_override)
+            .with_keep_alive_strategy(self.keep_alive_strategy.clone())
+            .with_max_failed_upgrades(self.max_failed_upgrades);
+        let result = self.network.peer(peer_id)
+            .dial(addr, Vec::new(), handler)
+            .map(|_| ())
+            .map_err(DialError::from);
+
+        if let Err(error) = &result {
+            log_kv!(debug, [
+                peer_id:% = peer_id, error:% = error],
                 "New dialing attempt to peer {:?} failed: {:?}.",
                 peer_id, error);
             self.behaviour.inject_dial_failure(&peer_id);
@@ -419,6 +739,21 @@ where TBehaviour: NetworkBehaviour<ProtocolsHandler = THandler>,
         self.external_addrs.iter()
     }
 
+    /// Returns the external addresses of the local node that have been
+    /// reported by remote peers via
+    /// [`NetworkBehaviourAction::ReportObservedAddr`], together with the
+    /// number of distinct peers that confirmed each address.
+    ///
+    /// The addresses are returned in the same order as by
+    /// [`ExpandedSwarm::external_addresses`]. Addresses that were only
+    /// added through [`ExpandedSwarm::add_external_address`] are omitted.
+    pub fn external_addresses_with_scores(&self) -> Vec<(Multiaddr, ExternalAddressScore)> {
+        self.external_addrs.iter()
+            .filter_map(|r| self.external_addr_observations.get(&r.addr)
+                .map(|o| (r.addr.clone(), o.score())))
+            .collect()
+    }
+
     /// Adds an external address record for the local node.
     ///
     /// An external address is an address of the local node known to
@@ -434,6 +769,7 @@ where TBehaviour: NetworkBehaviour<ProtocolsHandler = THandler>,
     /// [`NetworkBehaviourAction::ReportObservedAddr`] or explicitly
     /// through this method.
     pub fn add_external_address(&mut self, a: Multiaddr, s: AddressScore) -> AddAddressResult {
+        self.tentative_external_addrs.remove(&a);
         let result = self.external_addrs.add(a.clone(), s);
         let expired = match &result {
             AddAddressResult::Inserted { expired } => {
@@ -443,19 +779,92 @@ where TBehaviour: NetworkBehaviour<ProtocolsHandler = THandler>,
             AddAddressResult::Updated { expired } => expired,
         };
         for a in expired {
+            self.external_addr_observations.remove(&a.addr);
             self.behaviour.inject_expired_external_addr(&a.addr);
         }
         result
     }
 
+    /// Adds an external address of the local node with the given confidence.
+    ///
+    /// An address with [`AddressConfidence::Low`] is only recorded as a
+    /// tentative address, see [`ExpandedSwarm::tentative_external_addresses`].
+    /// It is neither returned by [`ExpandedSwarm::external_addresses`] nor
+    /// reported to the `NetworkBehaviour`, e.g. for inclusion in identify
+    /// information, until it is confirmed. Returns `None` in that case.
+    ///
+    /// An address with [`AddressConfidence::Confirmed`] is added as per
+    /// [`ExpandedSwarm::add_external_address`], at which point
+    /// [`NetworkBehaviour::inject_new_external_addr`] is called for a
+    /// previously tentative address. An address reported via
+    /// [`NetworkBehaviourAction::ReportObservedAddr`] is considered
+    /// confirmed once it has been observed by [`MIN_CONFIRMING_OBSERVERS`]
+    /// distinct remote peers.
+    pub fn add_external_address_with_confidence(
+        &mut self,
+        a: Multiaddr,
+        s: AddressScore,
+        confidence: AddressConfidence
+    ) -> Option<AddAddressResult> {
+        match confidence {
+            AddressConfidence::Confirmed => Some(self.add_external_address(a, s)),
+            AddressConfidence::Low => {
+                if !self.external_addrs.iter().any(|r| r.addr == a) {
+                    self.tentative_external_addrs.insert(a);
+                }
+                None
+            }
+        }
+    }
+
+    /// Records an observation of an external address of the local node
+    /// made by a remote peer.
+    ///
+    /// An address that is not yet an external address is kept as a tentative
+    /// address until it has been observed by [`MIN_CONFIRMING_OBSERVERS`]
+    /// distinct peers, at which point it is added as per
+    /// [`ExpandedSwarm::add_external_address`].
+    fn add_observed_address(&mut self, addr: Multiaddr, score: AddressScore, observer: Option<PeerId>) {
+        let confirmed_by = {
+            let observations = self.external_addr_observations.entry(addr.clone())
+                .or_insert_with(Observations::new);
+            observations.observe(observer);
+            observations.score().confirmed_by
+        };
+        if self.external_addrs.iter().any(|r| r.addr == addr)
+            || confirmed_by >= MIN_CONFIRMING_OBSERVERS
+        {
+            self.add_external_address(addr.clone(), score);
+            // The address may have expired right away, in which
+            // case its observations are not retained either.
+            if !self.external_addrs.iter().any(|r| r.addr == addr) {
+                self.external_addr_observations.remove(&addr);
+            }
+        } else {
+            self.tentative_external_addrs.insert(addr);
+        }
+    }
+
+    /// Returns an iterator over the tentative external addresses of the
+    /// local node that are yet to be confirmed, see
+    /// [`ExpandedSwarm::add_external_address_with_confidence`].
+    pub fn tentative_external_addresses(&self) -> impl Iterator<Item = &Multiaddr> {
+        self.tentative_external_addrs.iter()
+    }
+
     /// Removes an external address of the local node, regardless of
     /// its current score. See [`ExpandedSwarm::add_external_address`]
-    /// for details.
+    /// for details. Tentative addresses are removed as well.
     ///
     /// Returns `true` if the address existed and was removed, `false`
     /// otherwise.
     pub fn remove_external_address(&mut self, addr: &Multiaddr) -> bool {
+        if self.tentative_external_addrs.remove(addr) {
+            self.external_addr_observations.remove(addr);
+            return true
+        }
         if self.external_addrs.remove(addr) {
+            self.external_addr_observations.remove(addr);
             self.behaviour.inject_expired_external_addr(addr);
             true
         } else {
@@ -499,11 +908,53 @@ where TBehaviour: NetworkBehaviour<ProtocolsHandler = THandler>,
         Err(())
     }
 
+    /// Tags a connection, replacing any previous tags.
+    ///
+    /// Tags are human-readable annotations, e.g. describing why a connection
+    /// was established. They are reported in [`SwarmEvent::ConnectionEstablished`],
+    /// [`SwarmEvent::ConnectionClosed`] and via [`ExpandedSwarm::network_info`].
+    ///
+    /// Besides established connections, this applies to pending connections
+    /// started via [`ExpandedSwarm::dial_addr_with_tags`].
+    ///
+    /// Returns `false` if there is no such connection with the given ID.
+    pub fn tag_connection(&mut self, connection_id: ConnectionId, tag: impl Into<String>) -> bool {
+        if let Some(tags) = self.connection_tags_mut(&connection_id) {
+            *tags = vec![Arc::from(tag.into())];
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Adds a tag to a connection, retaining any previous tags.
+    ///
+    /// See [`ExpandedSwarm::tag_connection`].
+    pub fn tag_connection_add(&mut self, connection_id: ConnectionId, tag: impl Into<String>) -> bool {
+        if let Some(tags) = self.connection_tags_mut(&connection_id) {
+            tags.push(Arc::from(tag.into()));
+            true
+        } else {
+            false
+        }
+    }
+
     /// Checks whether the [`Network`] has an established connection to a peer.
     pub fn is_connected(&self, peer_id: &PeerId) -> bool {
         self.network.is_connected(peer_id)
     }
 
+    /// Returns the currently connected peers, i.e. the peers with whom the
+    /// [`Network`] has at least one established connection.
+    pub fn connected_peers(&self) -> impl Iterator<Item = &PeerId> {
+        self.network.connected_peers()
+    }
+
+    /// Returns the number of currently connected peers.
+    pub fn num_connected_peers(&self) -> usize {
+        self.network.info().num_peers()
+    }
+
     /// Returns a reference to the provided [`NetworkBehaviour`].
     pub fn behaviour(&self) -> &TBehaviour {
         &self.behaviour
@@ -519,12 +970,35 @@ where TBehaviour: NetworkBehaviour<ProtocolsHandler = THandler>,
     /// Polls the `Swarm` for the next event.
     fn poll_next_event(mut self: Pin<&mut Self>, cx: &mut Context<'_>)
         -> Poll<SwarmEvent<TBehaviour::OutEvent, THandleErr>>
+    {
+        let event = futures::ready!(self.as_mut().poll_next_event_inner(cx));
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            metrics.record(&event);
+        }
+        Poll::Ready(event)
+    }
+
+    fn poll_next_event_inner(mut self: Pin<&mut Self>, cx: &mut Context<'_>)
+        -> Poll<SwarmEvent<TBehaviour::OutEvent, THandleErr>>
     {
         // We use a `this` variable because the compiler can't mutably borrow multiple times
         // across a `Deref`.
         let this = &mut *self;
 
         loop {
+            match this.pending_listen_protocol_events.pop_front() {
+                Some(ListenProtocolEvent::Added(protocol)) => {
+                    log_kv!(debug, [protocol:% = protocol], "Listen protocol added: {}.", protocol);
+                    return Poll::Ready(SwarmEvent::ListenProtocolAdded { protocol })
+                }
+                Some(ListenProtocolEvent::Removed(protocol)) => {
+                    log_kv!(debug, [protocol:% = protocol], "Listen protocol removed: {}.", protocol);
+                    return Poll::Ready(SwarmEvent::ListenProtocolRemoved { protocol })
+                }
+                None => {}
+            }
+
             let mut network_not_ready = false;
 
             // First let the network make progress.
@@ -533,7 +1007,14 @@ where TBehaviour: NetworkBehaviour<ProtocolsHandler = THandler>,
                 Poll::Ready(NetworkEvent::ConnectionEvent { connection, event }) => {
                     let peer = connection.peer_id();
                     let connection = connection.id();
-                    this.behaviour.inject_event(peer, connection, event);
+                    match event {
+                        NodeHandlerWrapperEvent::Handler(event) =>
+                            this.behaviour.inject_event(peer, connection, event),
+                        NodeHandlerWrapperEvent::ProtocolNegotiated(protocol) =>
+                            if let Some(meta) = this.connections.get_mut(&connection) {
+                                meta.protocols.push(protocol);
+                            },
+                    }
                 },
                 Poll::Ready(NetworkEvent::AddressChange { connection, new_endpoint, old_endpoint }) => {
                     let peer = connection.peer_id();
@@ -543,55 +1024,94 @@ where TBehaviour: NetworkBehaviour<ProtocolsHandler = THandler>,
                 Poll::Ready(NetworkEvent::ConnectionEstablished { connection, num_established }) => {
                     let peer_id = connection.peer_id();
                     let endpoint = connection.endpoint().clone();
+                    let pending_tags = this.pending_tags.remove(&connection.id())
+                        .unwrap_or_default();
                     if this.banned_peers.contains(&peer_id) {
                         this.network.peer(peer_id)
                             .into_connected()
                             .expect("the Network just notified us that we were connected; QED")
                             .disconnect();
+                        log_kv!(debug, [
+                            peer_id:% = peer_id, addr:% = endpoint.get_remote_address()],
+                            "Disconnecting banned peer {:?}.", peer_id);
                         return Poll::Ready(SwarmEvent::BannedPeer {
                             peer_id,
                             endpoint,
                         });
                     } else {
-                        log::debug!("Connection established: {:?}; Total (peer): {}.",
+                        log_kv!(debug, [
+                            peer_id:% = peer_id,
+                            addr:% = connection.endpoint().get_remote_address(),
+                            connection_id:? = connection.id(),
+                            num_established = num_established.get()],
+                            "Connection established: {:?}; Total (peer): {}.",
                             connection.connected(), num_established);
                         let endpoint = connection.endpoint().clone();
-                        this.behaviour.inject_connection_established(&peer_id, &connection.id(), &endpoint);
+                        let connection_id = connection.id();
+                        let meta = this.connections.entry(connection_id).or_insert_with(|| EstablishedConnectionMeta {
+                            peer_id,
+                            endpoint: endpoint.clone(),
+                            established_at: Instant::now(),
+                            tags: pending_tags,
+                            protocols: Vec::new(),
+                        });
+                        let tags = meta.tags.clone();
+                        this.behaviour.inject_connection_established(&peer_id, &connection_id, &endpoint);
                         if num_established.get() == 1 {
                             this.behaviour.inject_connected(&peer_id);
                         }
                         return Poll::Ready(SwarmEvent::ConnectionEstablished {
-                            peer_id, num_established, endpoint
+                            peer_id, connection_id, num_established, endpoint, tags
                         });
                     }
                 },
                 Poll::Ready(NetworkEvent::ConnectionClosed { id, connected, error, num_established }) => {
                     if let Some(error) = error.as_ref() {
-                        log::debug!("Connection {:?} closed: {:?}", connected, error);
+                        log_kv!(debug, [
+                            peer_id:% = connected.peer_id,
+                            addr:% = connected.endpoint.get_remote_address(),
+                            connection_id:? = id,
+                            error:% = error],
+                            "Connection {:?} closed: {:?}", connected, error);
                     } else {
-                        log::debug!("Connection {:?} closed (active close).", connected);
+                        log_kv!(debug, [
+                            peer_id:% = connected.peer_id,
+                            addr:% = connected.endpoint.get_remote_address(),
+                            connection_id:? = id],
+                            "Connection {:?} closed (active close).", connected);
                     }
                     let peer_id = connected.peer_id;
                     let endpoint = connected.endpoint;
+                    let tags = this.connections.remove(&id).map(|meta| meta.tags).unwrap_or_default();
                     this.behaviour.inject_connection_closed(&peer_id, &id, &endpoint);
                     if num_established == 0 {
                         this.behaviour.inject_disconnected(&peer_id);
                     }
                     return Poll::Ready(SwarmEvent::ConnectionClosed {
                         peer_id,
+                        connection_id: id,
                         endpoint,
                         cause: error,
                         num_established,
+                        tags,
                     });
                 },
                 Poll::Ready(NetworkEvent::IncomingConnection { connection, .. }) => {
                     let handler = this.behaviour.new_handler()
                         .into_node_handler_builder()
-                        .with_substream_upgrade_protocol_override(this.substream_upgrade_protocol_override);
+                        .with_substream_upgrade_protocol_override(this.substream_upgrade_protocol_override)
+                        .with_keep_alive_strategy(this.keep_alive_strategy.clone())
+                        .with_max_failed_upgrades(this.max_failed_upgrades);
                     let local_addr = connection.local_addr.clone();
                     let send_back_addr = connection.send_back_addr.clone();
                     if let Err(e) = this.network.accept(connection, handler) {
-                        log::warn!("Incoming connection rejected: {:?}", e);
+                        log_kv!(warn, [
+                            addr:% = send_back_addr, error:? = e],
+                            "Incoming connection rejected: {:?}", e);
+                    } else {
+                        log_kv!(debug, [
+                            addr:% = send_back_addr, local_addr:% = local_addr],
+                            "Incoming connection from {:?} on {:?}.", send_back_addr, local_addr);
                     }
                     return Poll::Ready(SwarmEvent::IncomingConnection {
                         local_addr,
@@ -599,7 +1119,9 @@ where TBehaviour: NetworkBehaviour<ProtocolsHandler = THandler>,
                     });
                 },
                 Poll::Ready(NetworkEvent::NewListenerAddress { listener_id, listen_addr }) => {
-                    log::debug!("Listener {:?}; New address: {:?}", listener_id, listen_addr);
+                    log_kv!(debug, [
+                        listener_id:? = listener_id, addr:% = listen_addr],
+                        "Listener {:?}; New address: {:?}", listener_id, listen_addr);
                     if !this.listened_addrs.contains(&listen_addr) {
                         this.listened_addrs.push(listen_addr.clone())
                     }
@@ -610,7 +1132,9 @@ where TBehaviour: NetworkBehaviour<ProtocolsHandler = THandler>,
                     });
                 }
                 Poll::Ready(NetworkEvent::ExpiredListenerAddress { listener_id, listen_addr }) => {
-                    log::debug!("Listener {:?}; Expired address {:?}.", listener_id, listen_addr);
+                    log_kv!(debug, [
+                        listener_id:? = listener_id, addr:% = listen_addr],
+                        "Listener {:?}; Expired address {:?}.", listener_id, listen_addr);
                     this.listened_addrs.retain(|a| a != &listen_addr);
                     this.behaviour.inject_expired_listen_addr(listener_id, &listen_addr);
                     return Poll::Ready(SwarmEvent::ExpiredListenAddr{
@@ -619,7 +1143,9 @@ where TBehaviour: NetworkBehaviour<ProtocolsHandler = THandler>,
                     });
                 }
                 Poll::Ready(NetworkEvent::ListenerClosed { listener_id, addresses, reason }) => {
-                    log::debug!("Listener {:?}; Closed by {:?}.", listener_id, reason);
+                    log_kv!(debug, [
+                        listener_id:? = listener_id, error:? = reason.as_ref().err()],
+                        "Listener {:?}; Closed by {:?}.", listener_id, reason);
                     for addr in addresses.iter() {
                         this.behaviour.inject_expired_listen_addr(listener_id, addr);
                     }
@@ -627,6 +1153,7 @@ where TBehaviour: NetworkBehaviour<ProtocolsHandler = THandler>,
                         Ok(()) => Ok(()),
                         Err(err) => Err(err),
                     });
+                    this.listener_removed(listener_id);
                     return Poll::Ready(SwarmEvent::ListenerClosed {
                         listener_id,
                         addresses,
@@ -634,6 +1161,9 @@ where TBehaviour: NetworkBehaviour<ProtocolsHandler = THandler>,
                     });
                 }
                 Poll::Ready(NetworkEvent::ListenerError { listener_id, error }) => {
+                    log_kv!(debug, [
+                        listener_id:? = listener_id, error:% = error],
+                        "Listener {:?}; Error: {:?}.", listener_id, error);
                     this.behaviour.inject_listener_error(listener_id, &error);
                     return Poll::Ready(SwarmEvent::ListenerError {
                         listener_id,
@@ -641,7 +1171,9 @@ where TBehaviour: NetworkBehaviour<ProtocolsHandler = THandler>,
                     });
                 },
                 Poll::Ready(NetworkEvent::IncomingConnectionError { local_addr, send_back_addr, error }) => {
-                    log::debug!("Incoming connection failed: {:?}", error);
+                    log_kv!(debug, [
+                        addr:% = send_back_addr, local_addr:% = local_addr, error:% = error],
+                        "Incoming connection failed: {:?}", error);
                     return Poll::Ready(SwarmEvent::IncomingConnectionError {
                         local_addr,
                         send_back_addr,
@@ -649,27 +1181,38 @@ where TBehaviour: NetworkBehaviour<ProtocolsHandler = THandler>,
                     });
                 },
                 Poll::Ready(NetworkEvent::DialError { peer_id, multiaddr, error, attempts_remaining }) => {
-                    log::debug!(
+                    log_kv!(debug, [
+                        peer_id:% = peer_id,
+                        addr:% = multiaddr,
+                        error:% = error,
+                        attempts_remaining = attempts_remaining],
                         "Connection attempt to {:?} via {:?} failed with {:?}. Attempts remaining: {}.",
                         peer_id, multiaddr, error, attempts_remaining);
                     this.behaviour.inject_addr_reach_failure(Some(&peer_id), &multiaddr, &error);
                     if attempts_remaining == 0 {
                         this.behaviour.inject_dial_failure(&peer_id);
                     }
+                    let retry_guidance = classify_connection_error(&error).retry_guidance();
                     return Poll::Ready(SwarmEvent::UnreachableAddr {
                         peer_id,
                         address: multiaddr,
                         error,
+                        retry_guidance,
                         attempts_remaining,
                     });
                 },
-                Poll::Ready(NetworkEvent::UnknownPeerDialError { multiaddr, error, .. }) => {
-                    log::debug!("Connection attempt to address {:?} of unknown peer failed with {:?}",
+                Poll::Ready(NetworkEvent::UnknownPeerDialError { id, multiaddr, error }) => {
+                    this.pending_tags.remove(&id);
+                    log_kv!(debug, [
+                        addr:% = multiaddr, error:% = error],
+                        "Connection attempt to address {:?} of unknown peer failed with {:?}",
                         multiaddr, error);
                     this.behaviour.inject_addr_reach_failure(None, &multiaddr, &error);
+                    let retry_guidance = classify_connection_error(&error).retry_guidance();
                     return Poll::Ready(SwarmEvent::UnknownPeerUnreachableAddr {
                         address: multiaddr,
                         error,
+                        retry_guidance,
                     });
                 },
             }
@@ -702,6 +1245,10 @@ where TBehaviour: NetworkBehaviour<ProtocolsHandler = THandler>,
 
             debug_assert!(this.pending_event.is_none());
 
+            while let Poll::Ready(Some(timer_id)) = this.timers.poll_next_unpin(cx) {
+                this.behaviour.inject_timeout(timer_id);
+            }
+
             let behaviour_poll = {
                 let mut parameters = SwarmPollParameters {
                     local_peer_id: &mut this.network.local_peer_id(),
@@ -776,9 +1323,9 @@ where TBehaviour: NetworkBehaviour<ProtocolsHandler = THandler>,
                         }
                     }
                 },
-                Poll::Ready(NetworkBehaviourAction::ReportObservedAddr { address, score }) => {
+                Poll::Ready(NetworkBehaviourAction::ReportObservedAddr { address, score, observer }) => {
                     for addr in this.network.address_translation(&address) {
-                        this.add_external_address(addr, score);
+                        this.add_observed_address(addr, score, observer);
                     }
                 },
                 Poll::Ready(NetworkBehaviourAction::CloseConnection { peer_id, connection }) => {
@@ -795,6 +1342,17 @@ where TBehaviour: NetworkBehaviour<ProtocolsHandler = THandler>,
                         }
                     }
                 },
+                Poll::Ready(NetworkBehaviourAction::TagConnection { connection, tag }) => {
+                    if let Some(tags) = this.connection_tags_mut(&connection) {
+                        tags.push(tag);
+                    }
+                },
+                Poll::Ready(NetworkBehaviourAction::SpawnTask(task)) => {
+                    this.network.spawn(task);
+                },
+                Poll::Ready(NetworkBehaviourAction::SetTimer { timer_id, duration }) => {
+                    this.timers.push(Delay::new(duration).map(move |_| timer_id).boxed());
+                },
             }
         }
     }
@@ -890,6 +1448,11 @@ where
 ///
 /// Note: This stream is infinite and it is guaranteed that
 /// [`Stream::poll_next`] will never return `Poll::Ready(None)`.
+///
+/// Being a [`Stream`], the swarm can be driven with the combinators of
+/// [`StreamExt`](futures::StreamExt), e.g. `swarm.by_ref().take(10)`,
+/// as an alternative to calling [`select_next_some`](futures::StreamExt::select_next_some)
+/// in a loop.
 impl<TBehaviour, TInEvent, TOutEvent, THandler, THandleErr> Stream for
     ExpandedSwarm<TBehaviour, TInEvent, TOutEvent, THandler>
 where TBehaviour: NetworkBehaviour<ProtocolsHandler = THandler>,
@@ -956,12 +1519,35 @@ impl<'a> PollParameters for SwarmPollParameters<'a> {
 
 /// A `SwarmBuilder` provides an API for configuring and constructing a `Swarm`,
 /// including the underlying [`Network`].
+///
+/// Once built, a `Swarm` usually starts listening right away. To listen on all
+/// interfaces via every supported transport, use
+/// [`ExpandedSwarm::listen_on_all_interfaces`]:
+///
+/// ```no_run
+/// # use libp2p_core::{PeerId, muxing::StreamMuxerBox, transport::Boxed};
+/// # use libp2p_swarm::{DummyBehaviour, ListenPorts, SwarmBuilder};
+/// # fn example(transport: Boxed<(PeerId, StreamMuxerBox)>, local_peer_id: PeerId) {
+/// let mut swarm = SwarmBuilder::new(transport, DummyBehaviour::default(), local_peer_id)
+///     .build();
+/// // Listen via TCP and QUIC on ports picked by the operating system.
+/// swarm.listen_on_all_interfaces(ListenPorts::default()).unwrap();
+/// # }
+/// ```
 pub struct SwarmBuilder<TBehaviour> {
     local_peer_id: PeerId,
     transport: transport::Boxed<(PeerId, StreamMuxerBox)>,
     behaviour: TBehaviour,
     network_config: NetworkConfig,
     substream_upgrade_protocol_override: Option<libp2p_core::upgrade::Version>,
+    keep_alive_strategy: Option<Arc<dyn ConnectionKeepAliveStrategy>>,
+    max_failed_upgrades: Option<NonZeroUsize>,
+    connection_timeout: Option<Duration>,
+    report_listen_protocols: bool,
+    #[cfg(feature = "metrics")]
+    metrics_registry: Option<prometheus::Registry>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<metrics::SwarmMetrics>,
 }
 
 impl<TBehaviour> SwarmBuilder<TBehaviour>
@@ -981,7 +1567,76 @@ where TBehaviour: NetworkBehaviour,
             behaviour,
             network_config: Default::default(),
             substream_upgrade_protocol_override: None,
+            keep_alive_strategy: None,
+            max_failed_upgrades: None,
+            connection_timeout: None,
+            report_listen_protocols: false,
+            #[cfg(feature = "metrics")]
+            metrics_registry: None,
+            #[cfg(feature = "metrics")]
+            metrics: None,
+        }
+    }
+
+    /// Creates a new `SwarmBuilder` like [`SwarmBuilder::new`], with the
+    /// behaviour and a metrics registry created by the given closure from
+    /// the local peer ID.
+    ///
+    /// This permits behaviours to register their metrics in a registry of
+    /// their own, which is retained by the `Swarm` and can later be
+    /// obtained via [`ExpandedSwarm::metrics_registry`], e.g. to expose
+    /// the metrics via HTTP.
+    #[cfg(feature = "metrics")]
+    pub fn with_behaviour_and_metrics(
+        transport: transport::Boxed<(PeerId, StreamMuxerBox)>,
+        local_peer_id: PeerId,
+        f: impl FnOnce(&PeerId) -> (TBehaviour, prometheus::Registry)
+    ) -> Self {
+        let (behaviour, registry) = f(&local_peer_id);
+        let mut builder = SwarmBuilder::new(transport, behaviour, local_peer_id);
+        builder.metrics_registry = Some(registry);
+        builder
+    }
+
+    /// Records metrics of the `Swarm`, like the number of established
+    /// connections and failed dials, in the given registry, which is
+    /// retained by the `Swarm` and can later be obtained via
+    /// [`ExpandedSwarm::metrics_registry`].
+    ///
+    /// The events of the `NetworkBehaviour` are only known to the caller.
+    /// The metrics of the behaviours of `libp2p` are registered in the same
+    /// registry via the `libp2p::metrics::MetricsBuilder`, which records the
+    /// behaviour events passed to it.
+    #[cfg(feature = "metrics")]
+    pub fn with_metrics(mut self, registry: prometheus::Registry) -> Self {
+        match metrics::SwarmMetrics::new(&registry) {
+            Ok(metrics) => self.metrics = Some(metrics),
+            Err(e) => log::warn!("Failed to register swarm metrics: {}", e),
         }
+        self.metrics_registry = Some(registry);
+        self
+    }
+
+    /// Creates a new `SwarmBuilder` for the given behaviour using an
+    /// in-process [`MemoryTransport`](libp2p_core::transport::MemoryTransport)
+    /// and a freshly generated identity.
+    ///
+    /// Connections are neither encrypted nor otherwise secured. Swarms created
+    /// this way are only meant for testing, e.g. to connect multiple swarms in
+    /// a test via `/memory/<port>` addresses without allocating OS resources.
+    #[cfg(feature = "test-utils")]
+    pub fn with_memory_transport(behaviour: TBehaviour) -> Self {
+        use libp2p_core::{identity, transport::MemoryTransport, upgrade};
+
+        let local_public_key = identity::Keypair::generate_ed25519().public();
+        let local_peer_id = local_public_key.clone().into_peer_id();
+        let transport = MemoryTransport::default()
+            .upgrade(upgrade::Version::V1)
+            .authenticate(libp2p_plaintext::PlainText2Config { local_public_key })
+            .multiplex(libp2p_mplex::MplexConfig::new())
+            .boxed();
+
+        SwarmBuilder::new(transport, behaviour, local_peer_id)
     }
 
     /// Configures the `Executor` to use for spawning background tasks.
@@ -993,6 +1648,17 @@ where TBehaviour: NetworkBehaviour,
         self
     }
 
+    /// Configures the swarm to spawn background tasks on the `async-std` runtime.
+    ///
+    /// Note that this only concerns the executor. The transport, e.g. one
+    /// obtained from `libp2p::development_transport`, is configured separately.
+    #[cfg(feature = "async-std")]
+    pub fn with_async_std(self) -> Self {
+        self.executor(Box::new(|f| {
+            async_std::task::spawn(f);
+        }))
+    }
+
     /// Configures the number of events from the [`NetworkBehaviour`] in
     /// destination to the [`ProtocolsHandler`] that can be buffered before
     /// the [`Swarm`] has to wait. An individual buffer with this number of
@@ -1056,9 +1722,64 @@ where TBehaviour: NetworkBehaviour,
         self
     }
 
+    /// Configures a [`ConnectionKeepAliveStrategy`] that is consulted for
+    /// every connection that is no longer kept alive by its [`ProtocolsHandler`].
+    ///
+    /// By default, the lifetime of a connection is determined solely by
+    /// [`ProtocolsHandler::connection_keep_alive`].
+    pub fn keep_alive_strategy(mut self, s: impl ConnectionKeepAliveStrategy) -> Self {
+        self.keep_alive_strategy = Some(Arc::new(s));
+        self
+    }
+
+    /// Configures the number of consecutive outbound substream upgrades
+    /// whose protocol negotiation failed with a protocol error, e.g. because
+    /// the substream was reset, after which a connection is closed. `None`
+    /// means that connections are never closed due to failed upgrades.
+    ///
+    /// Each failure is still reported to the [`ProtocolsHandler`] via
+    /// [`ProtocolsHandler::inject_dial_upgrade_error`]. A remote not
+    /// supporting any of the requested protocols is not counted as a failure,
+    /// nor are timeouts and errors of the upgrades themselves. Any successful
+    /// outbound upgrade resets the count.
+    ///
+    /// Defaults to `None`.
+    pub fn max_failed_upgrades(mut self, max: Option<NonZeroUsize>) -> Self {
+        self.max_failed_upgrades = max;
+        self
+    }
+
+    /// Configures a deadline for the entire setup of outgoing connections.
+    ///
+    /// The deadline covers everything from dialing the address, including
+    /// e.g. DNS resolution, up to the connection being fully upgraded, i.e.
+    /// authenticated and multiplexed. If the deadline is exceeded, the
+    /// connection attempt is aborted with an error wrapping an [`io::Error`] of
+    /// kind [`io::ErrorKind::TimedOut`], reported via [`SwarmEvent::UnreachableAddr`]
+    /// or [`SwarmEvent::UnknownPeerUnreachableAddr`] and classified as transient
+    /// by [`classify_connection_error`].
+    ///
+    /// By default, only the timeouts configured on the transport apply.
+    pub fn connection_timeout(mut self, timeout: Duration) -> Self {
+        self.connection_timeout = Some(timeout);
+        self
+    }
+
+    /// Configures whether the protocols supported by the behaviour are
+    /// reported as [`SwarmEvent::ListenProtocolAdded`] and
+    /// [`SwarmEvent::ListenProtocolRemoved`] when the first listener is added
+    /// and the last listener is removed, respectively. Protocol names that are
+    /// not valid [`StreamProtocol`]s are not reported.
+    ///
+    /// Defaults to `false`.
+    pub fn report_listen_protocols(mut self, report: bool) -> Self {
+        self.report_listen_protocols = report;
+        self
+    }
+
     /// Builds a `Swarm` with the current configuration.
     pub fn build(mut self) -> Swarm<TBehaviour> {
-        let supported_protocols = self.behaviour
+        let supported_protocols: SmallVec<[Vec<u8>; 16]> = self.behaviour
             .new_handler()
             .inbound_protocol()
             .protocol_info()
@@ -1066,6 +1787,20 @@ where TBehaviour: NetworkBehaviour,
             .map(|info| info.protocol_name().to_vec())
             .collect();
 
+        let listen_protocols = if self.report_listen_protocols {
+            supported_protocols.iter()
+                .filter_map(|p| match std::str::from_utf8(p).map(StreamProtocol::try_from) {
+                    Ok(Ok(protocol)) => Some(protocol),
+                    _ => {
+                        log::debug!("Not reporting invalid listen protocol {}.", String::from_utf8_lossy(p));
+                        None
+                    }
+                })
+                .collect()
+        } else {
+            SmallVec::new()
+        };
+
         // If no executor has been explicitly configured, try to set up a thread pool.
         let network_cfg = self.network_config.or_else_with_executor(|| {
             match ThreadPoolBuilder::new()
@@ -1082,21 +1817,54 @@ where TBehaviour: NetworkBehaviour,
             }
         });
 
-        let network = Network::new(self.transport, self.local_peer_id, network_cfg);
+        let transport = match self.connection_timeout {
+            Some(timeout) => TransportTimeout::with_outgoing_timeout(self.transport, timeout)
+                .map_err(|err| match err {
+                    TransportTimeoutError::Timeout =>
+                        io::Error::new(io::ErrorKind::TimedOut, "connection setup timed out"),
+                    TransportTimeoutError::TimerError(err) => err,
+                    TransportTimeoutError::Other(err) => err,
+                })
+                .boxed(),
+            None => self.transport,
+        };
+
+        let network = Network::new(transport, self.local_peer_id, network_cfg);
 
         ExpandedSwarm {
             network,
             behaviour: self.behaviour,
             supported_protocols,
+            listen_protocols,
             listened_addrs: SmallVec::new(),
+            listeners: HashSet::new(),
+            pending_listen_protocol_events: VecDeque::new(),
             external_addrs: Addresses::default(),
+            external_addr_observations: HashMap::new(),
+            tentative_external_addrs: HashSet::new(),
+            timers: FuturesUnordered::new(),
             banned_peers: HashSet::new(),
             pending_event: None,
             substream_upgrade_protocol_override: self.substream_upgrade_protocol_override,
+            keep_alive_strategy: self.keep_alive_strategy,
+            max_failed_upgrades: self.max_failed_upgrades,
+            connections: Default::default(),
+            pending_tags: Default::default(),
+            #[cfg(feature = "metrics")]
+            metrics_registry: self.metrics_registry,
+            #[cfg(feature = "metrics")]
+            metrics: self.metrics,
         }
     }
 }
 
+/// A change of the protocols advertised on inbound connections.
+#[derive(Debug)]
+enum ListenProtocolEvent {
+    Added(StreamProtocol),
+    Removed(StreamProtocol),
+}
+
 /// The possible failures of [`ExpandedSwarm::dial`].
 #[derive(Debug)]
 pub enum DialError {
@@ -1143,6 +1911,40 @@ impl error::Error for DialError {
     }
 }
 
+/// The error returned by [`ExpandedSwarm::dial_blocking`].
+#[cfg(feature = "test-utils")]
+#[derive(Debug)]
+pub enum DialBlockingError {
+    /// The dialing attempt could not be initiated.
+    Dial(DialError),
+    /// The connection attempt failed.
+    Connection(PendingConnectionError<io::Error>),
+    /// The connection was not established in time.
+    Timeout,
+}
+
+#[cfg(feature = "test-utils")]
+impl fmt::Display for DialBlockingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DialBlockingError::Dial(err) => write!(f, "{}", err),
+            DialBlockingError::Connection(err) => write!(f, "Connection failed: {}", err),
+            DialBlockingError::Timeout => write!(f, "Connection timed out."),
+        }
+    }
+}
+
+#[cfg(feature = "test-utils")]
+impl error::Error for DialBlockingError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            DialBlockingError::Dial(err) => Some(err),
+            DialBlockingError::Connection(err) => Some(err),
+            DialBlockingError::Timeout => None,
+        }
+    }
+}
+
 /// Dummy implementation of [`NetworkBehaviour`] that doesn't do anything.
 #[derive(Clone, Default)]
 pub struct DummyBehaviour {
@@ -1185,12 +1987,15 @@ mod tests {
     use crate::test::{MockBehaviour, CallTraceBehaviour};
     use futures::{future, executor};
     use libp2p_core::{
+        Endpoint,
+        either::{EitherError, EitherOutput},
         identity,
-        upgrade,
+        upgrade::{self, InboundUpgrade, OutboundUpgrade, UpgradeInfo},
         multiaddr,
-        transport
+        transport::{self, ListenerEvent}
     };
     use libp2p_noise as noise;
+    use std::time::{Duration, Instant};
     use super::*;
 
     // Test execution state.
@@ -1201,6 +2006,15 @@ mod tests {
     }
 
     fn new_test_swarm<T, O>(handler_proto: T) -> Swarm<CallTraceBehaviour<MockBehaviour<T, O>>>
+    where
+        T: ProtocolsHandler + Clone,
+        T::OutEvent: Clone,
+        O: Send + 'static
+    {
+        new_test_swarm_builder(handler_proto).build()
+    }
+
+    fn new_test_swarm_builder<T, O>(handler_proto: T) -> SwarmBuilder<CallTraceBehaviour<MockBehaviour<T, O>>>
     where
         T: ProtocolsHandler + Clone,
         T::OutEvent: Clone,
@@ -1215,7 +2029,7 @@ mod tests {
             .multiplex(libp2p_mplex::MplexConfig::new())
             .boxed();
         let behaviour = CallTraceBehaviour::new(MockBehaviour::new(handler_proto));
-        SwarmBuilder::new(transport, behaviour, pubkey.into()).build()
+        SwarmBuilder::new(transport, behaviour, pubkey.into())
     }
 
     fn swarms_connected<TBehaviour>(
@@ -1565,4 +2379,1109 @@ mod tests {
             }
         }))
     }
+
+    /// Establishes a connection between two peers whose handlers do not
+    /// keep the connection alive, with both swarms configured with an
+    /// [`IdleTimeoutStrategy`].
+    ///
+    /// The test expects the connection to be closed, but not before the
+    /// configured idle timeout elapsed.
+    #[test]
+    fn test_idle_timeout_strategy() {
+        let handler_proto = DummyProtocolsHandler { keep_alive: KeepAlive::No };
+        let timeout = Duration::from_millis(500);
+
+        let mut swarm1 = new_test_swarm_builder::<_, ()>(handler_proto.clone())
+            .keep_alive_strategy(IdleTimeoutStrategy { timeout })
+            .build();
+        let mut swarm2 = new_test_swarm_builder::<_, ()>(handler_proto)
+            .keep_alive_strategy(IdleTimeoutStrategy { timeout })
+            .build();
+
+        let addr2: Multiaddr = multiaddr::Protocol::Memory(rand::random::<u64>()).into();
+        swarm2.listen_on(addr2.clone()).unwrap();
+        swarm1.dial_addr(addr2).unwrap();
+
+        let mut established = None;
+
+        executor::block_on(future::poll_fn(move |cx| {
+            loop {
+                let poll1 = Swarm::poll_next_event(Pin::new(&mut swarm1), cx);
+                let poll2 = Swarm::poll_next_event(Pin::new(&mut swarm2), cx);
+
+                match poll1 {
+                    Poll::Ready(SwarmEvent::ConnectionEstablished { .. }) => {
+                        established = Some(Instant::now());
+                    }
+                    Poll::Ready(SwarmEvent::ConnectionClosed { .. }) => {
+                        let established = established.expect("connection to be established first");
+                        assert!(established.elapsed() >= timeout);
+                        return Poll::Ready(())
+                    }
+                    _ => {}
+                }
+
+                if poll1.is_pending() && poll2.is_pending() {
+                    return Poll::Pending
+                }
+            }
+        }))
+    }
+
+    #[test]
+    fn test_connection_tags() {
+        let handler_proto = DummyProtocolsHandler { keep_alive: KeepAlive::Yes };
+
+        let mut swarm1 = new_test_swarm::<_, ()>(handler_proto.clone());
+        let mut swarm2 = new_test_swarm::<_, ()>(handler_proto);
+
+        let addr2: Multiaddr = multiaddr::Protocol::Memory(rand::random::<u64>()).into();
+        swarm2.listen_on(addr2.clone()).unwrap();
+        let dialed = swarm1.dial_addr_with_tags(addr2, vec!["dial"]).unwrap();
+        assert!(swarm1.tag_connection_add(dialed, "pending"));
+
+        executor::block_on(future::poll_fn(move |cx| {
+            loop {
+                let poll1 = Swarm::poll_next_event(Pin::new(&mut swarm1), cx);
+                let poll2 = Swarm::poll_next_event(Pin::new(&mut swarm2), cx);
+
+                let pending = poll1.is_pending() && poll2.is_pending();
+
+                match poll1 {
+                    Poll::Ready(SwarmEvent::ConnectionEstablished { peer_id, connection_id, tags, .. }) => {
+                        assert_eq!(connection_id, dialed);
+                        assert_eq!(tags, vec![Arc::from("dial"), Arc::from("pending")]);
+                        assert!(swarm1.tag_connection(connection_id, "first"));
+                        assert!(swarm1.tag_connection(connection_id, "bootstrap"));
+                        assert!(swarm1.tag_connection_add(connection_id, "user"));
+                        let info = swarm1.network_info();
+                        let tags = info.connection_tags(&connection_id);
+                        assert_eq!(tags, &[Arc::from("bootstrap"), Arc::from("user")]);
+                        swarm1.disconnect_peer_id(peer_id).unwrap();
+                    }
+                    Poll::Ready(SwarmEvent::ConnectionClosed { connection_id, tags, .. }) => {
+                        assert_eq!(tags, vec![Arc::from("bootstrap"), Arc::from("user")]);
+                        assert!(!swarm1.tag_connection(connection_id, "closed"));
+                        return Poll::Ready(())
+                    }
+                    _ => {}
+                }
+
+                if pending {
+                    return Poll::Pending
+                }
+            }
+        }))
+    }
+
+    /// Tests that the tags of a failed dial to an unknown peer are removed,
+    /// regardless of other dials to the same address.
+    #[test]
+    fn test_connection_tags_failed_dial() {
+        let handler_proto = DummyProtocolsHandler { keep_alive: KeepAlive::Yes };
+        let mut swarm = new_test_swarm::<_, ()>(handler_proto);
+
+        // A listener that drops every inbound connection right away.
+        let addr: Multiaddr = multiaddr::Protocol::Memory(rand::random::<u64>()).into();
+        let mut listener = transport::MemoryTransport::default().listen_on(addr.clone()).unwrap();
+
+        let tagged = swarm.dial_addr_with_tags(addr.clone(), vec!["tagged"]).unwrap();
+        swarm.dial_addr(addr.clone()).unwrap();
+        assert!(swarm.pending_tags.contains_key(&tagged));
+
+        let mut failed = 0;
+        executor::block_on(future::poll_fn(move |cx| {
+            while let Poll::Ready(Some(_)) = listener.poll_next_unpin(cx) {}
+
+            while let Poll::Ready(event) = Swarm::poll_next_event(Pin::new(&mut swarm), cx) {
+                if let SwarmEvent::UnknownPeerUnreachableAddr { address, .. } = event {
+                    assert_eq!(address, addr);
+                    failed += 1;
+                    if failed == 2 {
+                        assert!(swarm.pending_tags.is_empty());
+                        return Poll::Ready(())
+                    }
+                }
+            }
+
+            Poll::Pending
+        }))
+    }
+
+    #[test]
+    fn test_network_info_peer_infos() {
+        let handler_proto = DummyProtocolsHandler { keep_alive: KeepAlive::Yes };
+
+        let mut swarm1 = new_test_swarm::<_, ()>(handler_proto.clone());
+        let mut swarm2 = new_test_swarm::<_, ()>(handler_proto);
+
+        let addr2: Multiaddr = multiaddr::Protocol::Memory(rand::random::<u64>()).into();
+        swarm2.listen_on(addr2.clone()).unwrap();
+        swarm1.dial_addr(addr2.clone()).unwrap();
+
+        let swarm2_id = *swarm2.local_peer_id();
+
+        executor::block_on(future::poll_fn(move |cx| {
+            loop {
+                let poll1 = Swarm::poll_next_event(Pin::new(&mut swarm1), cx);
+                let poll2 = Swarm::poll_next_event(Pin::new(&mut swarm2), cx);
+
+                let pending = poll1.is_pending() && poll2.is_pending();
+
+                if let Poll::Ready(SwarmEvent::ConnectionEstablished { connection_id, .. }) = poll1 {
+                    let peer_infos = swarm1.network_info().peer_infos();
+                    assert_eq!(peer_infos.len(), 1);
+                    let peer_info = &peer_infos[0];
+                    assert_eq!(peer_info.peer_id, swarm2_id);
+                    assert_eq!(peer_info.connections.len(), 1);
+                    let connection = &peer_info.connections[0];
+                    assert_eq!(connection.id, connection_id);
+                    assert_eq!(connection.address, addr2);
+                    assert_eq!(connection.direction, Endpoint::Dialer);
+                    assert_eq!(peer_info.first_seen, connection.established_at);
+                    return Poll::Ready(())
+                }
+
+                if pending {
+                    return Poll::Pending
+                }
+            }
+        }))
+    }
+
+    #[test]
+    fn test_connected_peers() {
+        let handler_proto = DummyProtocolsHandler { keep_alive: KeepAlive::Yes };
+
+        let mut swarm1 = new_test_swarm::<_, ()>(handler_proto.clone());
+        let mut swarm2 = new_test_swarm::<_, ()>(handler_proto);
+
+        let addr2: Multiaddr = multiaddr::Protocol::Memory(rand::random::<u64>()).into();
+        swarm2.listen_on(addr2.clone()).unwrap();
+        swarm1.dial_addr(addr2).unwrap();
+
+        let swarm2_id = *swarm2.local_peer_id();
+        assert_eq!(swarm1.connected_peers().count(), 0);
+        assert_eq!(swarm1.num_connected_peers(), 0);
+
+        executor::block_on(future::poll_fn(move |cx| {
+            loop {
+                let poll1 = Swarm::poll_next_event(Pin::new(&mut swarm1), cx);
+                let poll2 = Swarm::poll_next_event(Pin::new(&mut swarm2), cx);
+
+                let pending = poll1.is_pending() && poll2.is_pending();
+
+                match poll1 {
+                    Poll::Ready(SwarmEvent::ConnectionEstablished { .. }) => {
+                        assert_eq!(swarm1.connected_peers().collect::<Vec<_>>(), vec![&swarm2_id]);
+                        assert_eq!(swarm1.num_connected_peers(), 1);
+                        assert!(swarm1.is_connected(&swarm2_id));
+                        swarm1.disconnect_peer_id(swarm2_id).unwrap();
+                    }
+                    Poll::Ready(SwarmEvent::ConnectionClosed { .. }) => {
+                        assert_eq!(swarm1.connected_peers().count(), 0);
+                        assert_eq!(swarm1.num_connected_peers(), 0);
+                        assert!(!swarm1.is_connected(&swarm2_id));
+                        return Poll::Ready(())
+                    }
+                    _ => {}
+                }
+
+                if pending {
+                    return Poll::Pending
+                }
+            }
+        }))
+    }
+
+    /// An upgrade for a single protocol that completes right after negotiation.
+    #[derive(Clone)]
+    struct TestUpgrade(&'static [u8]);
+
+    impl UpgradeInfo for TestUpgrade {
+        type Info = &'static [u8];
+        type InfoIter = std::iter::Once<Self::Info>;
+
+        fn protocol_info(&self) -> Self::InfoIter {
+            std::iter::once(self.0)
+        }
+    }
+
+    impl<C> InboundUpgrade<C> for TestUpgrade {
+        type Output = ();
+        type Error = void::Void;
+        type Future = future::Ready<Result<(), void::Void>>;
+
+        fn upgrade_inbound(self, _: C, _: Self::Info) -> Self::Future {
+            future::ready(Ok(()))
+        }
+    }
+
+    impl<C> OutboundUpgrade<C> for TestUpgrade {
+        type Output = ();
+        type Error = void::Void;
+        type Future = future::Ready<Result<(), void::Void>>;
+
+        fn upgrade_outbound(self, _: C, _: Self::Info) -> Self::Future {
+            future::ready(Ok(()))
+        }
+    }
+
+    /// A `ProtocolsHandler` that supports a single inbound protocol and
+    /// requests a number of outbound substreams for a single protocol,
+    /// reporting the outcome of each outbound upgrade.
+    #[derive(Clone)]
+    struct RequestingHandler {
+        inbound: &'static [u8],
+        outbound: &'static [u8],
+        to_request: usize,
+        outcomes: VecDeque<Result<(), ()>>,
+    }
+
+    impl RequestingHandler {
+        fn new(inbound: &'static [u8], outbound: &'static [u8], to_request: usize) -> Self {
+            RequestingHandler { inbound, outbound, to_request, outcomes: VecDeque::new() }
+        }
+    }
+
+    impl ProtocolsHandler for RequestingHandler {
+        type InEvent = void::Void;
+        type OutEvent = Result<(), ()>;
+        type Error = void::Void;
+        type InboundProtocol = TestUpgrade;
+        type OutboundProtocol = TestUpgrade;
+        type OutboundOpenInfo = ();
+        type InboundOpenInfo = ();
+
+        fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol, Self::InboundOpenInfo> {
+            SubstreamProtocol::new(TestUpgrade(self.inbound), ())
+        }
+
+        fn inject_fully_negotiated_inbound(&mut self, _: (), _: ()) {}
+
+        fn inject_fully_negotiated_outbound(&mut self, _: (), _: ()) {
+            self.outcomes.push_back(Ok(()));
+        }
+
+        fn inject_event(&mut self, v: void::Void) {
+            void::unreachable(v)
+        }
+
+        fn inject_dial_upgrade_error(&mut self, _: (), _: ProtocolsHandlerUpgrErr<void::Void>) {
+            self.outcomes.push_back(Err(()));
+        }
+
+        fn connection_keep_alive(&self) -> KeepAlive {
+            KeepAlive::Yes
+        }
+
+        fn poll(&mut self, _: &mut Context<'_>) -> Poll<
+            ProtocolsHandlerEvent<Self::OutboundProtocol, (), Self::OutEvent, void::Void>
+        > {
+            if let Some(outcome) = self.outcomes.pop_front() {
+                return Poll::Ready(ProtocolsHandlerEvent::Custom(outcome))
+            }
+            if self.to_request > 0 {
+                self.to_request -= 1;
+                return Poll::Ready(ProtocolsHandlerEvent::OutboundSubstreamRequest {
+                    protocol: SubstreamProtocol::new(TestUpgrade(self.outbound), ())
+                })
+            }
+            Poll::Pending
+        }
+    }
+
+    /// Tests that a connection is closed once the configured number of
+    /// outbound upgrades failed with a protocol error, here due to an
+    /// invalid protocol name.
+    #[test]
+    fn test_max_failed_upgrades() {
+        let mut swarm1 = new_test_swarm_builder::<_, ()>(RequestingHandler::new(b"/a", b"invalid", 10))
+            .max_failed_upgrades(NonZeroUsize::new(3))
+            .build();
+        let mut swarm2 = new_test_swarm::<_, ()>(RequestingHandler::new(b"/a", b"/a", 0));
+
+        let addr2: Multiaddr = multiaddr::Protocol::Memory(rand::random::<u64>()).into();
+        swarm2.listen_on(addr2.clone()).unwrap();
+        swarm1.dial_addr(addr2).unwrap();
+
+        executor::block_on(future::poll_fn(move |cx| {
+            loop {
+                let poll1 = Swarm::poll_next_event(Pin::new(&mut swarm1), cx);
+                let poll2 = Swarm::poll_next_event(Pin::new(&mut swarm2), cx);
+
+                let pending = poll1.is_pending() && poll2.is_pending();
+
+                if let Poll::Ready(SwarmEvent::ConnectionClosed { cause, .. }) = poll1 {
+                    assert!(matches!(
+                        cause,
+                        Some(ConnectionError::Handler(NodeHandlerWrapperError::TooManyFailedUpgrades))
+                    ));
+                    return Poll::Ready(())
+                }
+
+                if pending {
+                    return Poll::Pending
+                }
+            }
+        }))
+    }
+
+    /// Tests that the protocols negotiated in either direction are
+    /// reported once for the connection and the peer.
+    #[test]
+    fn test_network_info_negotiated_protocols() {
+        let mut swarm1 = new_test_swarm::<_, ()>(RequestingHandler::new(b"/a", b"/b", 2));
+        let mut swarm2 = new_test_swarm::<_, ()>(RequestingHandler::new(b"/b", b"/a", 1));
+
+        let addr2: Multiaddr = multiaddr::Protocol::Memory(rand::random::<u64>()).into();
+        swarm2.listen_on(addr2.clone()).unwrap();
+        swarm1.dial_addr(addr2).unwrap();
+
+        let expected = vec![StreamProtocol::new("/a"), StreamProtocol::new("/b")];
+
+        executor::block_on(future::poll_fn(move |cx| {
+            loop {
+                let poll1 = Swarm::poll_next_event(Pin::new(&mut swarm1), cx);
+                let poll2 = Swarm::poll_next_event(Pin::new(&mut swarm2), cx);
+
+                let pending = poll1.is_pending() && poll2.is_pending();
+
+                let peer_infos1 = swarm1.network_info().peer_infos();
+                let peer_infos2 = swarm2.network_info().peer_infos();
+                let done = |infos: &[PeerInfo]| infos.len() == 1 && infos[0].protocols.len() == 2;
+                if done(&peer_infos1) && done(&peer_infos2) {
+                    for peer_info in peer_infos1.iter().chain(peer_infos2.iter()) {
+                        assert_eq!(peer_info.protocols, expected);
+                        assert_eq!(peer_info.connections[0].protocols.len(), 2);
+                    }
+                    return Poll::Ready(())
+                }
+
+                if pending {
+                    return Poll::Pending
+                }
+            }
+        }))
+    }
+
+    /// Tests that a connection stays open if the remote does not support
+    /// one of the protocols of a composed handler, even with the strictest
+    /// limit on failed upgrades.
+    #[test]
+    fn test_max_failed_upgrades_ignores_unsupported_protocols() {
+        let handler = ProtocolsHandler::select(
+            RequestingHandler::new(b"/a", b"/a", 5),
+            RequestingHandler::new(b"/b", b"/b", 5),
+        );
+        let mut swarm1 = new_test_swarm_builder::<_, ()>(handler)
+            .max_failed_upgrades(NonZeroUsize::new(1))
+            .build();
+        let mut swarm2 = new_test_swarm::<_, ()>(RequestingHandler::new(b"/a", b"/a", 0));
+
+        let addr2: Multiaddr = multiaddr::Protocol::Memory(rand::random::<u64>()).into();
+        swarm2.listen_on(addr2.clone()).unwrap();
+        swarm1.dial_addr(addr2).unwrap();
+
+        executor::block_on(future::poll_fn(move |cx| {
+            loop {
+                let poll1 = Swarm::poll_next_event(Pin::new(&mut swarm1), cx);
+                let poll2 = Swarm::poll_next_event(Pin::new(&mut swarm2), cx);
+
+                let pending = poll1.is_pending() && poll2.is_pending();
+
+                if let Poll::Ready(SwarmEvent::ConnectionClosed { cause, .. }) = poll1 {
+                    panic!("Unexpected connection close: {:?}", cause);
+                }
+
+                let outcomes = &swarm1.behaviour.inject_event;
+                let supported = outcomes.iter()
+                    .filter(|(_, _, e)| matches!(e, EitherOutput::First(Ok(()))))
+                    .count();
+                let unsupported = outcomes.iter()
+                    .filter(|(_, _, e)| matches!(e, EitherOutput::Second(Err(()))))
+                    .count();
+                if supported == 5 && unsupported == 5 {
+                    assert_eq!(outcomes.len(), 10);
+                    return Poll::Ready(())
+                }
+
+                if pending {
+                    return Poll::Pending
+                }
+            }
+        }))
+    }
+
+    /// A `ProtocolsHandler` that sets timers on its first `poll` and
+    /// reports their IDs as they elapse.
+    #[derive(Clone)]
+    struct TimerHandler {
+        timers: Vec<(TimerId, Duration)>,
+        elapsed: VecDeque<TimerId>,
+    }
+
+    impl ProtocolsHandler for TimerHandler {
+        type InEvent = void::Void;
+        type OutEvent = TimerId;
+        type Error = void::Void;
+        type InboundProtocol = upgrade::DeniedUpgrade;
+        type OutboundProtocol = upgrade::DeniedUpgrade;
+        type OutboundOpenInfo = void::Void;
+        type InboundOpenInfo = ();
+
+        fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol, Self::InboundOpenInfo> {
+            SubstreamProtocol::new(upgrade::DeniedUpgrade, ())
+        }
+
+        fn inject_fully_negotiated_inbound(&mut self, v: void::Void, _: ()) {
+            void::unreachable(v)
+        }
+
+        fn inject_fully_negotiated_outbound(&mut self, v: void::Void, _: void::Void) {
+            void::unreachable(v)
+        }
+
+        fn inject_event(&mut self, v: void::Void) {
+            void::unreachable(v)
+        }
+
+        fn inject_timeout(&mut self, timer_id: TimerId) {
+            self.elapsed.push_back(timer_id)
+        }
+
+        fn inject_dial_upgrade_error(&mut self, v: void::Void, _: ProtocolsHandlerUpgrErr<void::Void>) {
+            void::unreachable(v)
+        }
+
+        fn connection_keep_alive(&self) -> KeepAlive {
+            KeepAlive::Yes
+        }
+
+        fn poll(&mut self, _: &mut Context<'_>) -> Poll<
+            ProtocolsHandlerEvent<Self::OutboundProtocol, void::Void, Self::OutEvent, void::Void>
+        > {
+            if let Some((timer_id, duration)) = self.timers.pop() {
+                return Poll::Ready(ProtocolsHandlerEvent::SetTimer { timer_id, duration })
+            }
+            match self.elapsed.pop_front() {
+                Some(timer_id) => Poll::Ready(ProtocolsHandlerEvent::Custom(timer_id)),
+                None => Poll::Pending,
+            }
+        }
+    }
+
+    /// Tests that timers set by a `ProtocolsHandler` are reported back
+    /// to it once they elapse.
+    #[test]
+    fn test_handler_set_timer() {
+        let short = TimerId::new();
+        let long = TimerId::new();
+        let handler = TimerHandler {
+            timers: vec![(long, Duration::from_millis(100)), (short, Duration::from_millis(10))],
+            elapsed: VecDeque::new(),
+        };
+        let mut swarm1 = new_test_swarm::<_, ()>(handler);
+        let mut swarm2 = new_test_swarm::<_, ()>(DummyProtocolsHandler { keep_alive: KeepAlive::Yes });
+
+        let addr2: Multiaddr = multiaddr::Protocol::Memory(rand::random::<u64>()).into();
+        swarm2.listen_on(addr2.clone()).unwrap();
+        swarm1.dial_addr(addr2).unwrap();
+
+        executor::block_on(future::poll_fn(move |cx| {
+            loop {
+                let poll1 = Swarm::poll_next_event(Pin::new(&mut swarm1), cx);
+                let poll2 = Swarm::poll_next_event(Pin::new(&mut swarm2), cx);
+
+                let pending = poll1.is_pending() && poll2.is_pending();
+
+                let elapsed = swarm1.behaviour.inject_event.iter()
+                    .map(|(_, _, timer_id)| *timer_id)
+                    .collect::<Vec<_>>();
+                if elapsed.len() == 2 {
+                    assert_eq!(elapsed, vec![short, long]);
+                    return Poll::Ready(())
+                }
+
+                if pending {
+                    return Poll::Pending
+                }
+            }
+        }))
+    }
+
+    #[test]
+    fn test_external_address_hooks() {
+        let handler_proto = DummyProtocolsHandler { keep_alive: KeepAlive::Yes };
+        let mut swarm = new_test_swarm::<_, ()>(handler_proto);
+
+        let addr: Multiaddr = "/ip4/1.2.3.4/tcp/1234".parse().unwrap();
+        swarm.add_external_address(addr.clone(), AddressScore::Infinite);
+        assert_eq!(swarm.behaviour.inject_new_external_addr, vec![addr.clone()]);
+        assert!(swarm.behaviour.inject_expired_external_addr.is_empty());
+
+        assert!(swarm.remove_external_address(&addr));
+        assert!(!swarm.remove_external_address(&addr));
+        assert_eq!(swarm.behaviour.inject_expired_external_addr, vec![addr]);
+        assert!(swarm.external_addresses().next().is_none());
+    }
+
+    #[test]
+    fn test_external_address_confidence() {
+        let handler_proto = DummyProtocolsHandler { keep_alive: KeepAlive::Yes };
+        let mut swarm = new_test_swarm::<_, ()>(handler_proto);
+
+        let addr: Multiaddr = "/ip4/1.2.3.4/tcp/1234".parse().unwrap();
+        let result = swarm.add_external_address_with_confidence(
+            addr.clone(), AddressScore::Finite(1), AddressConfidence::Low);
+        assert!(result.is_none());
+        assert!(swarm.behaviour.inject_new_external_addr.is_empty());
+        assert!(swarm.external_addresses().next().is_none());
+        assert_eq!(swarm.tentative_external_addresses().collect::<Vec<_>>(), vec![&addr]);
+
+        let result = swarm.add_external_address_with_confidence(
+            addr.clone(), AddressScore::Finite(1), AddressConfidence::Confirmed);
+        assert!(matches!(result, Some(AddAddressResult::Inserted { .. })));
+        assert_eq!(swarm.behaviour.inject_new_external_addr, vec![addr.clone()]);
+        assert!(swarm.tentative_external_addresses().next().is_none());
+
+        // A low confidence report does not demote a confirmed address.
+        swarm.add_external_address_with_confidence(
+            addr.clone(), AddressScore::Finite(1), AddressConfidence::Low);
+        assert!(swarm.tentative_external_addresses().next().is_none());
+        assert_eq!(swarm.external_addresses().count(), 1);
+
+        // An observed address is confirmed by two distinct observers.
+        let observed: Multiaddr = "/ip4/9.9.9.9/tcp/1234".parse().unwrap();
+        let observer = PeerId::random();
+        swarm.add_observed_address(observed.clone(), AddressScore::Finite(1), Some(observer));
+        swarm.add_observed_address(observed.clone(), AddressScore::Finite(1), Some(observer));
+        swarm.add_observed_address(observed.clone(), AddressScore::Finite(1), None);
+        assert!(swarm.tentative_external_addresses().any(|a| a == &observed));
+        assert_eq!(swarm.behaviour.inject_new_external_addr, vec![addr.clone()]);
+        swarm.add_observed_address(observed.clone(), AddressScore::Finite(1), Some(PeerId::random()));
+        assert!(swarm.tentative_external_addresses().next().is_none());
+        assert_eq!(swarm.behaviour.inject_new_external_addr, vec![addr.clone(), observed.clone()]);
+        assert_eq!(swarm.external_addresses_with_scores().len(), 1);
+
+        // Tentative addresses are removed without being reported as expired.
+        let other: Multiaddr = "/ip4/5.6.7.8/tcp/1234".parse().unwrap();
+        swarm.add_external_address_with_confidence(
+            other.clone(), AddressScore::Finite(1), AddressConfidence::Low);
+        assert!(swarm.remove_external_address(&other));
+        assert!(swarm.tentative_external_addresses().next().is_none());
+        assert!(swarm.behaviour.inject_expired_external_addr.is_empty());
+    }
+
+    #[test]
+    fn test_stream_combinators() {
+        let mut swarm = new_test_swarm::<_, ()>(DummyProtocolsHandler::default());
+
+        for _ in 0..10 {
+            let addr: Multiaddr = multiaddr::Protocol::Memory(rand::random::<u64>()).into();
+            swarm.listen_on(addr).unwrap();
+        }
+
+        let events = executor::block_on(swarm.by_ref().take(10).collect::<Vec<_>>());
+
+        assert_eq!(events.len(), 10);
+        assert!(events.iter().all(|e| matches!(e, SwarmEvent::NewListenAddr { .. })));
+    }
+
+    #[cfg(feature = "async-std")]
+    #[test]
+    fn test_async_std_executor() {
+        let handler_proto = DummyProtocolsHandler { keep_alive: KeepAlive::Yes };
+
+        let mut swarm1 = new_test_swarm_builder::<_, ()>(handler_proto.clone()).with_async_std().build();
+        let mut swarm2 = new_test_swarm_builder::<_, ()>(handler_proto).with_async_std().build();
+
+        let addr2: Multiaddr = multiaddr::Protocol::Memory(rand::random::<u64>()).into();
+        swarm2.listen_on(addr2.clone()).unwrap();
+        swarm1.dial_addr(addr2).unwrap();
+
+        async_std::task::block_on(async move {
+            loop {
+                futures::select! {
+                    event = swarm1.select_next_some() => {
+                        if let SwarmEvent::ConnectionEstablished { .. } = event {
+                            break
+                        }
+                    }
+                    _ = swarm2.select_next_some() => {}
+                }
+            }
+        })
+    }
+
+    /// Tests that a connection whose upgrade never completes is aborted
+    /// after the configured connection timeout.
+    #[test]
+    fn test_connection_timeout() {
+        let transport = transport::MemoryTransport::default()
+            .and_then(|_, _| future::pending::<Result<(PeerId, StreamMuxerBox), io::Error>>())
+            .boxed();
+        let behaviour = CallTraceBehaviour::new(MockBehaviour::<_, ()>::new(
+            DummyProtocolsHandler { keep_alive: KeepAlive::Yes }
+        ));
+        let mut swarm = SwarmBuilder::new(transport, behaviour, PeerId::random())
+            .connection_timeout(Duration::from_millis(100))
+            .build();
+
+        let addr: Multiaddr = multiaddr::Protocol::Memory(rand::random::<u64>()).into();
+        swarm.listen_on(addr.clone()).unwrap();
+        swarm.dial_addr(addr.clone()).unwrap();
+
+        let started = Instant::now();
+        executor::block_on(future::poll_fn(move |cx| {
+            loop {
+                match Swarm::poll_next_event(Pin::new(&mut swarm), cx) {
+                    Poll::Ready(SwarmEvent::UnknownPeerUnreachableAddr { address, error, retry_guidance }) => {
+                        assert_eq!(address, addr);
+                        let class = classify_connection_error(&error);
+                        assert!(class.is_transient());
+                        assert_eq!(retry_guidance, class.retry_guidance());
+                        return Poll::Ready(())
+                    }
+                    Poll::Ready(_) => {}
+                    Poll::Pending => return Poll::Pending,
+                }
+            }
+        }));
+        assert!(started.elapsed() >= Duration::from_millis(100));
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_behaviour_and_metrics() {
+        let id_keys = identity::Keypair::generate_ed25519();
+        let local_peer_id = id_keys.public().into_peer_id();
+        let noise_keys = noise::Keypair::<noise::X25519Spec>::new().into_authentic(&id_keys).unwrap();
+        let transport = transport::MemoryTransport
+            .upgrade(upgrade::Version::V1)
+            .authenticate(noise::NoiseConfig::xx(noise_keys).into_authenticated())
+            .multiplex(libp2p_mplex::MplexConfig::new())
+            .boxed();
+
+        let mut swarm = SwarmBuilder::with_behaviour_and_metrics(transport, local_peer_id, |peer_id| {
+            let registry = prometheus::Registry::new();
+            let counter = prometheus::IntCounter::new("behaviour_events", "Events").unwrap();
+            registry.register(Box::new(counter)).unwrap();
+            let handler_proto = DummyProtocolsHandler { keep_alive: KeepAlive::Yes };
+            let mut behaviour = MockBehaviour::<_, ()>::new(handler_proto);
+            behaviour.addresses.insert(*peer_id, Vec::new());
+            (CallTraceBehaviour::new(behaviour), registry)
+        }).build();
+
+        assert!(swarm.behaviour.inner().addresses.contains_key(&local_peer_id));
+        let families = swarm.metrics_registry().unwrap().gather();
+        assert_eq!(families.len(), 1);
+        assert_eq!(families[0].get_name(), "behaviour_events");
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_swarm_metrics() {
+        let handler_proto = DummyProtocolsHandler { keep_alive: KeepAlive::Yes };
+
+        let mut swarm1 = new_test_swarm_builder::<_, ()>(handler_proto.clone())
+            .with_metrics(prometheus::Registry::new())
+            .build();
+        let mut swarm2 = new_test_swarm::<_, ()>(handler_proto);
+
+        let addr2: Multiaddr = multiaddr::Protocol::Memory(rand::random::<u64>()).into();
+        swarm2.listen_on(addr2.clone()).unwrap();
+        swarm1.dial_addr(addr2).unwrap();
+
+        executor::block_on(future::poll_fn(|cx| {
+            loop {
+                let poll1 = Swarm::poll_next_event(Pin::new(&mut swarm1), cx);
+                let poll2 = Swarm::poll_next_event(Pin::new(&mut swarm2), cx);
+
+                if let Poll::Ready(SwarmEvent::ConnectionEstablished { .. }) = poll1 {
+                    return Poll::Ready(())
+                }
+
+                if poll1.is_pending() && poll2.is_pending() {
+                    return Poll::Pending
+                }
+            }
+        }));
+
+        let families = swarm1.metrics_registry().unwrap().gather();
+        let established = families.iter()
+            .find(|f| f.get_name() == "libp2p_swarm_connections_established_total")
+            .unwrap();
+        assert_eq!(established.get_metric()[0].get_counter().get_value() as u64, 1);
+    }
+
+    #[cfg(feature = "test-utils")]
+    #[test]
+    fn test_memory_transport() {
+        let handler_proto = DummyProtocolsHandler { keep_alive: KeepAlive::Yes };
+
+        let mut swarm1 = SwarmBuilder::with_memory_transport(
+            CallTraceBehaviour::new(MockBehaviour::<_, ()>::new(handler_proto.clone()))
+        ).build();
+        let mut swarm2 = SwarmBuilder::with_memory_transport(
+            CallTraceBehaviour::new(MockBehaviour::<_, ()>::new(handler_proto))
+        ).build();
+        let swarm2_id = *swarm2.local_peer_id();
+
+        let addr2: Multiaddr = multiaddr::Protocol::Memory(rand::random::<u64>()).into();
+        swarm2.listen_on(addr2.clone()).unwrap();
+        swarm1.dial_addr(addr2).unwrap();
+
+        executor::block_on(future::poll_fn(move |cx| {
+            loop {
+                let poll1 = Swarm::poll_next_event(Pin::new(&mut swarm1), cx);
+                let poll2 = Swarm::poll_next_event(Pin::new(&mut swarm2), cx);
+
+                if let Poll::Ready(SwarmEvent::ConnectionEstablished { peer_id, .. }) = poll1 {
+                    assert_eq!(peer_id, swarm2_id);
+                    return Poll::Ready(())
+                }
+
+                if poll1.is_pending() && poll2.is_pending() {
+                    return Poll::Pending
+                }
+            }
+        }))
+    }
+
+    #[cfg(feature = "test-utils")]
+    #[test]
+    fn test_dial_blocking_unreachable() {
+        let rt = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+        let mut swarm = SwarmBuilder::with_memory_transport(DummyBehaviour::default()).build();
+
+        // Nobody listens on the address.
+        let addr: Multiaddr = multiaddr::Protocol::Memory(rand::random::<u64>()).into();
+        match swarm.dial_blocking(addr, &rt) {
+            Err(DialBlockingError::Connection(_)) => {},
+            r => panic!("Unexpected result: {:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_spawn_task() {
+        use futures::channel::mpsc;
+
+        /// Spawns a task on the first `poll` and reports the value the
+        /// task sends back.
+        struct SpawningBehaviour {
+            task: Option<future::BoxFuture<'static, ()>>,
+            results: mpsc::UnboundedReceiver<u32>,
+        }
+
+        impl NetworkBehaviour for SpawningBehaviour {
+            type ProtocolsHandler = DummyProtocolsHandler;
+            type OutEvent = u32;
+
+            fn new_handler(&mut self) -> Self::ProtocolsHandler {
+                DummyProtocolsHandler::default()
+            }
+
+            fn addresses_of_peer(&mut self, _: &PeerId) -> Vec<Multiaddr> {
+                Vec::new()
+            }
+
+            fn inject_connected(&mut self, _: &PeerId) {}
+
+            fn inject_disconnected(&mut self, _: &PeerId) {}
+
+            fn inject_event(&mut self, _: PeerId, _: ConnectionId, event: void::Void) {
+                void::unreachable(event)
+            }
+
+            fn poll(&mut self, cx: &mut Context<'_>, _: &mut impl PollParameters)
+                -> Poll<NetworkBehaviourAction<void::Void, u32>>
+            {
+                if let Some(task) = self.task.take() {
+                    return Poll::Ready(NetworkBehaviourAction::SpawnTask(task))
+                }
+                match self.results.poll_next_unpin(cx) {
+                    Poll::Ready(Some(n)) => Poll::Ready(NetworkBehaviourAction::GenerateEvent(n)),
+                    Poll::Ready(None) | Poll::Pending => Poll::Pending,
+                }
+            }
+        }
+
+        let (tx, results) = mpsc::unbounded();
+        let task = async move {
+            let n = future::ready(42).await;
+            tx.unbounded_send(n).unwrap();
+        }.boxed();
+
+        let id_keys = identity::Keypair::generate_ed25519();
+        let pubkey = id_keys.public();
+        let noise_keys = noise::Keypair::<noise::X25519Spec>::new().into_authentic(&id_keys).unwrap();
+        let transport = transport::MemoryTransport::default()
+            .upgrade(upgrade::Version::V1)
+            .authenticate(noise::NoiseConfig::xx(noise_keys).into_authenticated())
+            .multiplex(libp2p_mplex::MplexConfig::new())
+            .boxed();
+        let behaviour = SpawningBehaviour { task: Some(task), results };
+        let mut swarm = SwarmBuilder::new(transport, behaviour, pubkey.into()).build();
+
+        executor::block_on(async {
+            match swarm.select_next_some().await {
+                SwarmEvent::Behaviour(n) => assert_eq!(n, 42),
+                e => panic!("Unexpected event: {:?}", e),
+            }
+        })
+    }
+
+    #[cfg(feature = "test-utils")]
+    #[test]
+    fn test_set_timer() {
+        /// Sets two timers on the first `poll` and reports their IDs as they
+        /// elapse.
+        struct TimerBehaviour {
+            timers: Vec<(TimerId, Duration)>,
+            elapsed: VecDeque<TimerId>,
+        }
+
+        impl NetworkBehaviour for TimerBehaviour {
+            type ProtocolsHandler = DummyProtocolsHandler;
+            type OutEvent = TimerId;
+
+            fn new_handler(&mut self) -> Self::ProtocolsHandler {
+                DummyProtocolsHandler::default()
+            }
+
+            fn addresses_of_peer(&mut self, _: &PeerId) -> Vec<Multiaddr> {
+                Vec::new()
+            }
+
+            fn inject_connected(&mut self, _: &PeerId) {}
+
+            fn inject_disconnected(&mut self, _: &PeerId) {}
+
+            fn inject_event(&mut self, _: PeerId, _: ConnectionId, event: void::Void) {
+                void::unreachable(event)
+            }
+
+            fn inject_timeout(&mut self, timer_id: TimerId) {
+                self.elapsed.push_back(timer_id)
+            }
+
+            fn poll(&mut self, _: &mut Context<'_>, _: &mut impl PollParameters)
+                -> Poll<NetworkBehaviourAction<void::Void, TimerId>>
+            {
+                if let Some((timer_id, duration)) = self.timers.pop() {
+                    return Poll::Ready(NetworkBehaviourAction::SetTimer { timer_id, duration })
+                }
+                match self.elapsed.pop_front() {
+                    Some(timer_id) => Poll::Ready(NetworkBehaviourAction::GenerateEvent(timer_id)),
+                    None => Poll::Pending,
+                }
+            }
+        }
+
+        let short = TimerId::new();
+        let long = TimerId::new();
+        assert_ne!(short, long);
+
+        let behaviour = TimerBehaviour {
+            timers: vec![(long, Duration::from_millis(100)), (short, Duration::from_millis(10))],
+            elapsed: VecDeque::new(),
+        };
+        let mut swarm = SwarmBuilder::with_memory_transport(behaviour).build();
+
+        executor::block_on(async {
+            for expected in [short, long].iter() {
+                match swarm.select_next_some().await {
+                    SwarmEvent::Behaviour(timer_id) => assert_eq!(&timer_id, expected),
+                    e => panic!("Unexpected event: {:?}", e),
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn listen_protocol_events() {
+        let handler_proto = DummyProtocolsHandler { keep_alive: KeepAlive::Yes };
+        let mut swarm = new_test_swarm::<_, ()>(handler_proto);
+        swarm.listen_protocols = SmallVec::from_vec(vec![StreamProtocol::new("/a/1"), StreamProtocol::new("/b/1")]);
+
+        let id1 = swarm.listen_on(multiaddr::Protocol::Memory(rand::random::<u64>()).into()).unwrap();
+        let id2 = swarm.listen_on(multiaddr::Protocol::Memory(rand::random::<u64>()).into()).unwrap();
+        swarm.remove_listener(id1).unwrap();
+        swarm.remove_listener(id2).unwrap();
+        assert!(swarm.remove_listener(id2).is_err());
+
+        let events = executor::block_on(async {
+            let mut events = Vec::new();
+            while events.len() < 4 {
+                match swarm.select_next_some().await {
+                    SwarmEvent::ListenProtocolAdded { protocol } => events.push((true, protocol)),
+                    SwarmEvent::ListenProtocolRemoved { protocol } => events.push((false, protocol)),
+                    _ => {}
+                }
+            }
+            events
+        });
+        assert_eq!(events, vec![
+            (true, StreamProtocol::new("/a/1")),
+            (true, StreamProtocol::new("/b/1")),
+            (false, StreamProtocol::new("/a/1")),
+            (false, StreamProtocol::new("/b/1")),
+        ]);
+    }
+
+    #[test]
+    fn report_listen_protocols() {
+        let swarm = new_test_swarm_builder::<_, ()>(RequestingHandler::new(b"/a/1", b"/b/1", 0)).build();
+        assert!(swarm.listen_protocols.is_empty());
+
+        let swarm = new_test_swarm_builder::<_, ()>(RequestingHandler::new(b"/a/1", b"/b/1", 0))
+            .report_listen_protocols(true)
+            .build();
+        assert_eq!(swarm.listen_protocols.to_vec(), vec![StreamProtocol::new("/a/1")]);
+
+        // Invalid protocol names are not reported.
+        let swarm = new_test_swarm_builder::<_, ()>(RequestingHandler::new(b"a/1", b"/b/1", 0))
+            .report_listen_protocols(true)
+            .build();
+        assert!(swarm.listen_protocols.is_empty());
+    }
+
+    #[test]
+    fn listen_on_all_interfaces_fails_without_supported_addresses() {
+        let handler_proto = DummyProtocolsHandler { keep_alive: KeepAlive::Yes };
+        let mut swarm = new_test_swarm::<_, ()>(handler_proto);
+
+        // The memory transport supports neither TCP nor QUIC.
+        match swarm.listen_on_all_interfaces(ListenPorts { tcp: 4001, quic: 4001 }) {
+            Err(TransportError::MultiaddrNotSupported(addr)) =>
+                assert_eq!(addr, "/ip4/0.0.0.0/tcp/4001".parse().unwrap()),
+            res => panic!("Unexpected result: {:?}", res),
+        }
+        assert!(swarm.listeners.is_empty());
+        assert!(swarm.pending_listen_protocol_events.is_empty());
+    }
+
+    /// A transport failing to listen on QUIC addresses with an I/O error.
+    #[derive(Clone)]
+    struct FailingQuicTransport;
+
+    impl Transport for FailingQuicTransport {
+        type Output = (PeerId, StreamMuxerBox);
+        type Error = io::Error;
+        type Listener = stream::Pending<Result<ListenerEvent<Self::ListenerUpgrade, io::Error>, io::Error>>;
+        type ListenerUpgrade = future::Pending<Result<Self::Output, io::Error>>;
+        type Dial = future::Pending<Result<Self::Output, io::Error>>;
+
+        fn listen_on(self, addr: Multiaddr) -> Result<Self::Listener, TransportError<io::Error>> {
+            if addr.iter().any(|p| p == Protocol::Quic) {
+                Err(TransportError::Other(io::Error::new(io::ErrorKind::Other, "QUIC failure")))
+            } else {
+                Err(TransportError::MultiaddrNotSupported(addr))
+            }
+        }
+
+        fn dial(self, addr: Multiaddr) -> Result<Self::Dial, TransportError<io::Error>> {
+            Err(TransportError::MultiaddrNotSupported(addr))
+        }
+
+        fn address_translation(&self, _: &Multiaddr, _: &Multiaddr) -> Option<Multiaddr> {
+            None
+        }
+    }
+
+    fn new_tcp_test_swarm(fail_quic: bool) -> Swarm<CallTraceBehaviour<MockBehaviour<DummyProtocolsHandler, ()>>> {
+        let id_keys = identity::Keypair::generate_ed25519();
+        let pubkey = id_keys.public();
+        let noise_keys = noise::Keypair::<noise::X25519Spec>::new().into_authentic(&id_keys).unwrap();
+        let tcp = libp2p_tcp::TcpConfig::new()
+            .upgrade(upgrade::Version::V1)
+            .authenticate(noise::NoiseConfig::xx(noise_keys).into_authenticated())
+            .multiplex(libp2p_mplex::MplexConfig::new())
+            .boxed();
+        let transport = if fail_quic {
+            tcp.or_transport(FailingQuicTransport)
+                .map(|output, _| match output {
+                    EitherOutput::First(o) | EitherOutput::Second(o) => o,
+                })
+                .map_err(|err| match err {
+                    EitherError::A(e) | EitherError::B(e) => e,
+                })
+                .boxed()
+        } else {
+            tcp
+        };
+        let handler_proto = DummyProtocolsHandler { keep_alive: KeepAlive::Yes };
+        let behaviour = CallTraceBehaviour::new(MockBehaviour::new(handler_proto));
+        SwarmBuilder::new(transport, behaviour, pubkey.into()).build()
+    }
+
+    #[test]
+    fn listen_on_all_interfaces_skips_unsupported_addresses() {
+        let mut swarm = new_tcp_test_swarm(false);
+
+        // The TCP transport does not support QUIC.
+        let ids = swarm.listen_on_all_interfaces(ListenPorts { tcp: 0, quic: 0 }).unwrap();
+        assert_eq!(ids.len(), 1);
+        assert_eq!(swarm.listeners.iter().collect::<Vec<_>>(), vec![&ids[0]]);
+    }
+
+    #[test]
+    fn listen_on_all_interfaces_removes_listeners_on_failure() {
+        let mut swarm = new_tcp_test_swarm(true);
+
+        // Listening on TCP succeeds before listening on QUIC fails.
+        match swarm.listen_on_all_interfaces(ListenPorts { tcp: 0, quic: 0 }) {
+            Err(TransportError::Other(err)) => assert_eq!(err.to_string(), "QUIC failure"),
+            res => panic!("Unexpected result: {:?}", res),
+        }
+        assert!(swarm.listeners.is_empty());
+        assert_eq!(swarm.network.listen_addrs().count(), 0);
+    }
+
+    #[test]
+    fn dial_with_peer_id_mismatch() {
+        let handler_proto = DummyProtocolsHandler { keep_alive: KeepAlive::Yes };
+        let mut swarm1 = new_test_swarm::<_, ()>(handler_proto.clone());
+        let mut swarm2 = new_test_swarm::<_, ()>(handler_proto);
+        let swarm2_id = *swarm2.local_peer_id();
+
+        let addr2: Multiaddr = multiaddr::Protocol::Memory(rand::random::<u64>()).into();
+        swarm2.listen_on(addr2.clone()).unwrap();
+
+        // The listener has a different identity than the one requested.
+        let expected_id = PeerId::random();
+        swarm1.dial_with_peer_id(expected_id, addr2.clone()).unwrap();
+
+        executor::block_on(future::poll_fn(move |cx| {
+            loop {
+                let poll1 = Swarm::poll_next_event(Pin::new(&mut swarm1), cx);
+                let poll2 = Swarm::poll_next_event(Pin::new(&mut swarm2), cx);
+
+                match poll1 {
+                    Poll::Ready(SwarmEvent::UnreachableAddr {
+                        peer_id,
+                        address,
+                        error: PendingConnectionError::PeerIdMismatch { expected, actual },
+                        ..
+                    }) => {
+                        assert_eq!(peer_id, expected_id);
+                        assert_eq!(address, addr2.clone().with(multiaddr::Protocol::P2p(expected_id.into())));
+                        assert_eq!(expected, expected_id);
+                        assert_eq!(actual, swarm2_id);
+                        assert_eq!(swarm1.network_info().num_peers(), 0);
+                        return Poll::Ready(())
+                    }
+                    Poll::Ready(SwarmEvent::ConnectionEstablished { .. }) => {
+                        panic!("Unexpected connection to a different peer.")
+                    }
+                    _ => {}
+                }
+
+                if poll1.is_pending() && poll2.is_pending() {
+                    return Poll::Pending
+                }
+            }
+        }))
+    }
 }
@@ -0,0 +1,84 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Metrics of the [`Swarm`](crate::Swarm), see [`SwarmBuilder::with_metrics`](crate::SwarmBuilder::with_metrics).
+
+use crate::SwarmEvent;
+use prometheus::{IntCounter, IntGauge, Registry};
+
+/// The metrics recorded from the events of a `Swarm`.
+#[derive(Clone)]
+pub(crate) struct SwarmMetrics {
+    connections_established: IntCounter,
+    connections_closed: IntCounter,
+    incoming_connection_errors: IntCounter,
+    dial_failures: IntCounter,
+    listen_addresses: IntGauge,
+}
+
+impl SwarmMetrics {
+    /// Creates the metrics and registers them with the given registry.
+    pub(crate) fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let metrics = SwarmMetrics {
+            connections_established: IntCounter::new(
+                "libp2p_swarm_connections_established_total",
+                "Number of connections established.",
+            )?,
+            connections_closed: IntCounter::new(
+                "libp2p_swarm_connections_closed_total",
+                "Number of established connections closed.",
+            )?,
+            incoming_connection_errors: IntCounter::new(
+                "libp2p_swarm_incoming_connection_errors_total",
+                "Number of incoming connections that failed to be established.",
+            )?,
+            dial_failures: IntCounter::new(
+                "libp2p_swarm_dial_failures_total",
+                "Number of addresses that failed to be dialed.",
+            )?,
+            listen_addresses: IntGauge::new(
+                "libp2p_swarm_listen_addresses",
+                "Number of addresses the local node is listening on.",
+            )?,
+        };
+        registry.register(Box::new(metrics.connections_established.clone()))?;
+        registry.register(Box::new(metrics.connections_closed.clone()))?;
+        registry.register(Box::new(metrics.incoming_connection_errors.clone()))?;
+        registry.register(Box::new(metrics.dial_failures.clone()))?;
+        registry.register(Box::new(metrics.listen_addresses.clone()))?;
+        Ok(metrics)
+    }
+
+    /// Records an event emitted by the `Swarm`.
+    pub(crate) fn record<TBvEv, THandleErr>(&self, event: &SwarmEvent<TBvEv, THandleErr>) {
+        match event {
+            SwarmEvent::ConnectionEstablished { .. } => self.connections_established.inc(),
+            SwarmEvent::ConnectionClosed { .. } => self.connections_closed.inc(),
+            SwarmEvent::IncomingConnectionError { .. } => self.incoming_connection_errors.inc(),
+            SwarmEvent::UnreachableAddr { .. }
+            | SwarmEvent::UnknownPeerUnreachableAddr { .. } => self.dial_failures.inc(),
+            SwarmEvent::NewListenAddr { .. } => self.listen_addresses.inc(),
+            SwarmEvent::ExpiredListenAddr { .. } => self.listen_addresses.dec(),
+            SwarmEvent::ListenerClosed { addresses, .. } =>
+                self.listen_addresses.sub(addresses.len() as i64),
+            _ => {}
+        }
+    }
+}
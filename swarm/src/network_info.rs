@@ -0,0 +1,158 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use libp2p_core::{ConnectedPoint, Endpoint, Multiaddr, PeerId, StreamProtocol};
+use libp2p_core::connection::{ConnectionCounters, ConnectionId};
+use libp2p_core::network;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use wasm_timer::Instant;
+
+/// Information about the network obtained by [`ExpandedSwarm::network_info()`](crate::ExpandedSwarm::network_info).
+#[derive(Clone, Debug)]
+pub struct NetworkInfo {
+    /// Information about the underlying [`Network`](libp2p_core::Network).
+    inner: network::NetworkInfo,
+    /// Information about all established connections.
+    connections: HashMap<ConnectionId, ConnectionInfo>,
+}
+
+impl NetworkInfo {
+    pub(crate) fn new<'a>(
+        inner: network::NetworkInfo,
+        connections: impl IntoIterator<Item = (&'a ConnectionId, &'a EstablishedConnectionMeta)>,
+    ) -> Self {
+        let now = Instant::now();
+        let connections = connections.into_iter()
+            .map(|(id, meta)| (*id, meta.to_info(*id, now)))
+            .collect();
+        NetworkInfo { inner, connections }
+    }
+
+    /// The number of connected peers, i.e. peers with whom at least
+    /// one established connection exists.
+    pub fn num_peers(&self) -> usize {
+        self.inner.num_peers()
+    }
+
+    /// Gets counters for ongoing network connections.
+    pub fn connection_counters(&self) -> &ConnectionCounters {
+        self.inner.connection_counters()
+    }
+
+    /// Gets the tags of the established connection with the given ID.
+    ///
+    /// Returns an empty slice if the connection has no tags or does not exist.
+    pub fn connection_tags(&self, connection_id: &ConnectionId) -> &[Arc<str>] {
+        self.connections.get(connection_id).map_or(&[], |info| info.tags.as_slice())
+    }
+
+    /// Gets information about every connected peer and its established connections.
+    ///
+    /// The peers are ordered by the time they were first seen, the
+    /// connections of a peer by the time they were established.
+    pub fn peer_infos(&self) -> Vec<PeerInfo> {
+        let mut peers: HashMap<PeerId, PeerInfo> = HashMap::new();
+
+        for info in self.connections.values() {
+            let peer = peers.entry(info.peer_id).or_insert_with(|| PeerInfo {
+                peer_id: info.peer_id,
+                connections: Vec::new(),
+                protocols: Vec::new(),
+                first_seen: info.established_at,
+            });
+            peer.first_seen = peer.first_seen.min(info.established_at);
+            peer.protocols.extend(info.protocols.iter().cloned());
+            peer.connections.push(info.clone());
+        }
+
+        let mut peers = peers.into_values().map(|mut peer| {
+            peer.connections.sort_by_key(|c| (c.established_at, c.id));
+            peer.protocols.sort();
+            peer.protocols.dedup();
+            peer
+        }).collect::<Vec<_>>();
+        peers.sort_by_key(|p| p.first_seen);
+        peers
+    }
+}
+
+/// Information about a connected peer, obtained by [`NetworkInfo::peer_infos`].
+#[derive(Clone, Debug)]
+pub struct PeerInfo {
+    /// Identity of the peer.
+    pub peer_id: PeerId,
+    /// The established connections to the peer.
+    pub connections: Vec<ConnectionInfo>,
+    /// The protocols negotiated with the peer on any of the established
+    /// connections, in lexicographic order.
+    pub protocols: Vec<StreamProtocol>,
+    /// When the earliest of the established connections to the peer was established.
+    pub first_seen: Instant,
+}
+
+/// Information about an established connection, obtained by [`NetworkInfo::peer_infos`].
+#[derive(Clone, Debug)]
+pub struct ConnectionInfo {
+    /// Identifier of the connection.
+    pub id: ConnectionId,
+    /// Identity of the remote peer.
+    pub peer_id: PeerId,
+    /// Address of the remote peer.
+    pub address: Multiaddr,
+    /// The local role on the connection, i.e. whether the connection is outbound
+    /// ([`Endpoint::Dialer`]) or inbound ([`Endpoint::Listener`]).
+    pub direction: Endpoint,
+    /// When the connection was established.
+    pub established_at: Instant,
+    /// For how long the connection has been established.
+    pub established_for: Duration,
+    /// Tags of the connection. See [`ExpandedSwarm::tag_connection`](crate::ExpandedSwarm::tag_connection).
+    pub tags: Vec<Arc<str>>,
+    /// The protocols negotiated on substreams of the connection so far,
+    /// in the order they were first negotiated.
+    pub protocols: Vec<StreamProtocol>,
+}
+
+/// The information the swarm keeps about each established connection.
+#[derive(Debug)]
+pub(crate) struct EstablishedConnectionMeta {
+    pub(crate) peer_id: PeerId,
+    pub(crate) endpoint: ConnectedPoint,
+    pub(crate) established_at: Instant,
+    pub(crate) tags: Vec<Arc<str>>,
+    pub(crate) protocols: Vec<StreamProtocol>,
+}
+
+impl EstablishedConnectionMeta {
+    fn to_info(&self, id: ConnectionId, now: Instant) -> ConnectionInfo {
+        ConnectionInfo {
+            id,
+            peer_id: self.peer_id,
+            address: self.endpoint.get_remote_address().clone(),
+            direction: self.endpoint.to_endpoint(),
+            established_at: self.established_at,
+            established_for: now.duration_since(self.established_at),
+            tags: self.tags.clone(),
+            protocols: self.protocols.clone(),
+        }
+    }
+}
@@ -51,6 +51,7 @@ pub use crate::upgrade::{
     UpgradeInfoSend,
 };
 
+use crate::behaviour::TimerId;
 use libp2p_core::{
     ConnectedPoint,
     Multiaddr,
@@ -63,7 +64,7 @@ use wasm_timer::Instant;
 pub use dummy::DummyProtocolsHandler;
 pub use map_in::MapInEvent;
 pub use map_out::MapOutEvent;
-pub use node_handler::{NodeHandlerWrapper, NodeHandlerWrapperBuilder, NodeHandlerWrapperError};
+pub use node_handler::{NodeHandlerWrapper, NodeHandlerWrapperBuilder, NodeHandlerWrapperError, NodeHandlerWrapperEvent};
 pub use one_shot::{OneShotHandler, OneShotHandlerConfig};
 pub use select::{IntoProtocolsHandlerSelect, ProtocolsHandlerSelect};
 
@@ -147,6 +148,13 @@ pub trait ProtocolsHandler: Send + 'static {
     /// Notifies the handler of a change in the address of the remote.
     fn inject_address_change(&mut self, _new_address: &Multiaddr) {}
 
+    /// Indicates to the handler that a timer set via
+    /// [`ProtocolsHandlerEvent::SetTimer`] has elapsed.
+    ///
+    /// Handlers composed of others pass every timeout on to all of them,
+    /// each ignoring the timers it did not set itself.
+    fn inject_timeout(&mut self, _timer_id: TimerId) {}
+
     /// Indicates to the handler that upgrading an outbound substream to the given protocol has failed.
     fn inject_dial_upgrade_error(
         &mut self,
@@ -321,6 +329,17 @@ pub enum ProtocolsHandlerEvent<TConnectionUpgrade, TOutboundOpenInfo, TCustom, T
     /// Close the connection for the given reason.
     Close(TErr),
 
+    /// Call [`ProtocolsHandler::inject_timeout`] with the given timer ID once
+    /// the given duration has elapsed, e.g. to clean up idle substreams.
+    ///
+    /// The timer is dropped together with the connection.
+    SetTimer {
+        /// The ID of the timer, see [`TimerId::new`].
+        timer_id: TimerId,
+        /// The duration after which the timer elapses.
+        duration: Duration,
+    },
+
     /// Other event.
     Custom(TCustom),
 }
@@ -346,6 +365,8 @@ impl<TConnectionUpgrade, TOutboundOpenInfo, TCustom, TErr>
             }
             ProtocolsHandlerEvent::Custom(val) => ProtocolsHandlerEvent::Custom(val),
             ProtocolsHandlerEvent::Close(val) => ProtocolsHandlerEvent::Close(val),
+            ProtocolsHandlerEvent::SetTimer { timer_id, duration } =>
+                ProtocolsHandlerEvent::SetTimer { timer_id, duration },
         }
     }
 
@@ -366,6 +387,8 @@ impl<TConnectionUpgrade, TOutboundOpenInfo, TCustom, TErr>
             }
             ProtocolsHandlerEvent::Custom(val) => ProtocolsHandlerEvent::Custom(val),
             ProtocolsHandlerEvent::Close(val) => ProtocolsHandlerEvent::Close(val),
+            ProtocolsHandlerEvent::SetTimer { timer_id, duration } =>
+                ProtocolsHandlerEvent::SetTimer { timer_id, duration },
         }
     }
 
@@ -383,6 +406,8 @@ impl<TConnectionUpgrade, TOutboundOpenInfo, TCustom, TErr>
             }
             ProtocolsHandlerEvent::Custom(val) => ProtocolsHandlerEvent::Custom(map(val)),
             ProtocolsHandlerEvent::Close(val) => ProtocolsHandlerEvent::Close(val),
+            ProtocolsHandlerEvent::SetTimer { timer_id, duration } =>
+                ProtocolsHandlerEvent::SetTimer { timer_id, duration },
         }
     }
 
@@ -400,6 +425,8 @@ impl<TConnectionUpgrade, TOutboundOpenInfo, TCustom, TErr>
             }
             ProtocolsHandlerEvent::Custom(val) => ProtocolsHandlerEvent::Custom(val),
             ProtocolsHandlerEvent::Close(val) => ProtocolsHandlerEvent::Close(map(val)),
+            ProtocolsHandlerEvent::SetTimer { timer_id, duration } =>
+                ProtocolsHandlerEvent::SetTimer { timer_id, duration },
         }
     }
 }
@@ -18,6 +18,7 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
+use crate::TimerId;
 use crate::upgrade::{InboundUpgradeSend, OutboundUpgradeSend};
 use crate::protocols_handler::{
     KeepAlive,
@@ -88,6 +89,10 @@ where
         self.inner.inject_address_change(addr)
     }
 
+    fn inject_timeout(&mut self, timer_id: TimerId) {
+        self.inner.inject_timeout(timer_id)
+    }
+
     fn inject_dial_upgrade_error(&mut self, info: Self::OutboundOpenInfo, error: ProtocolsHandlerUpgrErr<<Self::OutboundProtocol as OutboundUpgradeSend>::Error>) {
         self.inner.inject_dial_upgrade_error(info, error)
     }
@@ -110,6 +115,8 @@ where
             match ev {
                 ProtocolsHandlerEvent::Custom(ev) => ProtocolsHandlerEvent::Custom((self.map)(ev)),
                 ProtocolsHandlerEvent::Close(err) => ProtocolsHandlerEvent::Close(err),
+                ProtocolsHandlerEvent::SetTimer { timer_id, duration } =>
+                    ProtocolsHandlerEvent::SetTimer { timer_id, duration },
                 ProtocolsHandlerEvent::OutboundSubstreamRequest { protocol } => {
                     ProtocolsHandlerEvent::OutboundSubstreamRequest { protocol }
                 }
@@ -21,7 +21,7 @@
 //! A [`ProtocolsHandler`] implementation that combines multiple other `ProtocolsHandler`s
 //! indexed by some key.
 
-use crate::NegotiatedSubstream;
+use crate::{NegotiatedSubstream, TimerId};
 use crate::protocols_handler::{
     KeepAlive,
     IntoProtocolsHandler,
@@ -160,6 +160,12 @@ where
         }
     }
 
+    fn inject_timeout(&mut self, timer_id: TimerId) {
+        for h in self.handlers.values_mut() {
+            h.inject_timeout(timer_id)
+        }
+    }
+
     fn inject_dial_upgrade_error (
         &mut self,
         (key, arg): Self::OutboundOpenInfo,
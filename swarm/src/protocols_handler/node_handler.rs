@@ -18,6 +18,8 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
+use crate::TimerId;
+use crate::keep_alive::ConnectionKeepAliveStrategy;
 use crate::upgrade::SendWrapper;
 use crate::protocols_handler::{
     KeepAlive,
@@ -27,11 +29,13 @@ use crate::protocols_handler::{
     ProtocolsHandlerUpgrErr
 };
 
-use futures::prelude::*;
+use futures::{future::BoxFuture, prelude::*};
 use futures::stream::FuturesUnordered;
 use libp2p_core::{
+    Endpoint,
     Multiaddr,
     Connected,
+    PeerId,
     connection::{
         ConnectionHandler,
         ConnectionHandlerEvent,
@@ -40,9 +44,17 @@ use libp2p_core::{
         SubstreamEndpoint,
     },
     muxing::StreamMuxerBox,
-    upgrade::{self, InboundUpgradeApply, OutboundUpgradeApply, UpgradeError}
+    upgrade::{
+        self,
+        InboundUpgradeApplyWithProtocol,
+        NegotiationError,
+        OutboundUpgradeApplyWithProtocol,
+        ProtocolName,
+        StreamProtocol,
+        UpgradeError
+    }
 };
-use std::{error, fmt, pin::Pin, task::Context, task::Poll, time::Duration};
+use std::{cmp, convert::TryFrom, error, fmt, num::NonZeroUsize, pin::Pin, str, sync::Arc, task::Context, task::Poll, time::Duration};
 use wasm_timer::{Delay, Instant};
 
 /// Prototype for a `NodeHandlerWrapper`.
@@ -51,6 +63,11 @@ pub struct NodeHandlerWrapperBuilder<TIntoProtoHandler> {
     handler: TIntoProtoHandler,
     /// The substream upgrade protocol override, if any.
     substream_upgrade_protocol_override: Option<upgrade::Version>,
+    /// The swarm-wide keep-alive strategy, if any.
+    keep_alive_strategy: Option<Arc<dyn ConnectionKeepAliveStrategy>>,
+    /// The number of consecutive failed outbound upgrades after which
+    /// the connection is closed, if any.
+    max_failed_upgrades: Option<NonZeroUsize>,
 }
 
 impl<TIntoProtoHandler> NodeHandlerWrapperBuilder<TIntoProtoHandler>
@@ -62,6 +79,8 @@ where
         NodeHandlerWrapperBuilder {
             handler,
             substream_upgrade_protocol_override: None,
+            keep_alive_strategy: None,
+            max_failed_upgrades: None,
         }
     }
 
@@ -72,6 +91,22 @@ where
         self.substream_upgrade_protocol_override = version;
         self
     }
+
+    pub(crate) fn with_keep_alive_strategy(
+        mut self,
+        strategy: Option<Arc<dyn ConnectionKeepAliveStrategy>>
+    ) -> Self {
+        self.keep_alive_strategy = strategy;
+        self
+    }
+
+    pub(crate) fn with_max_failed_upgrades(
+        mut self,
+        max: Option<NonZeroUsize>
+    ) -> Self {
+        self.max_failed_upgrades = max;
+        self
+    }
 }
 
 impl<TIntoProtoHandler, TProtoHandler> IntoConnectionHandler
@@ -91,6 +126,15 @@ where
             unique_dial_upgrade_id: 0,
             shutdown: Shutdown::None,
             substream_upgrade_protocol_override: self.substream_upgrade_protocol_override,
+            keep_alive_strategy: self.keep_alive_strategy,
+            max_failed_upgrades: self.max_failed_upgrades,
+            failed_upgrades: 0,
+            peer_id: connected.peer_id,
+            role: connected.endpoint.to_endpoint(),
+            idle_since: Instant::now(),
+            timers: FuturesUnordered::new(),
+            negotiated_protocols: Vec::new(),
+            reported_protocols: 0,
         }
     }
 }
@@ -107,12 +151,12 @@ where
     /// Futures that upgrade incoming substreams.
     negotiating_in: FuturesUnordered<SubstreamUpgrade<
         TProtoHandler::InboundOpenInfo,
-        InboundUpgradeApply<Substream<StreamMuxerBox>, SendWrapper<TProtoHandler::InboundProtocol>>,
+        InboundUpgradeApplyWithProtocol<Substream<StreamMuxerBox>, SendWrapper<TProtoHandler::InboundProtocol>>,
     >>,
     /// Futures that upgrade outgoing substreams.
     negotiating_out: FuturesUnordered<SubstreamUpgrade<
         TProtoHandler::OutboundOpenInfo,
-        OutboundUpgradeApply<Substream<StreamMuxerBox>, SendWrapper<TProtoHandler::OutboundProtocol>>,
+        OutboundUpgradeApplyWithProtocol<Substream<StreamMuxerBox>, SendWrapper<TProtoHandler::OutboundProtocol>>,
     >>,
     /// For each outbound substream request, how to upgrade it. The first element of the tuple
     /// is the unique identifier (see `unique_dial_upgrade_id`).
@@ -123,6 +167,62 @@ where
     shutdown: Shutdown,
     /// The substream upgrade protocol override, if any.
     substream_upgrade_protocol_override: Option<upgrade::Version>,
+    /// The swarm-wide keep-alive strategy, if any.
+    keep_alive_strategy: Option<Arc<dyn ConnectionKeepAliveStrategy>>,
+    /// The number of consecutive failed outbound upgrades after which
+    /// the connection is closed, if any.
+    max_failed_upgrades: Option<NonZeroUsize>,
+    /// The number of consecutive outbound upgrades so far whose protocol
+    /// negotiation failed with a protocol error.
+    failed_upgrades: usize,
+    /// The remote peer of the connection.
+    peer_id: PeerId,
+    /// The local role on the connection.
+    role: Endpoint,
+    /// The instant since which the handler no longer requires
+    /// the connection to be kept alive.
+    idle_since: Instant,
+    /// Timers set via [`ProtocolsHandlerEvent::SetTimer`].
+    timers: FuturesUnordered<BoxFuture<'static, TimerId>>,
+    /// The distinct protocols negotiated on substreams of the connection so far.
+    negotiated_protocols: Vec<StreamProtocol>,
+    /// The number of `negotiated_protocols` already reported via
+    /// [`NodeHandlerWrapperEvent::ProtocolNegotiated`].
+    reported_protocols: usize,
+}
+
+impl<TProtoHandler> NodeHandlerWrapper<TProtoHandler>
+where
+    TProtoHandler: ProtocolsHandler,
+{
+    /// Determines until when the connection should be kept alive, taking
+    /// into account both the handler and the keep-alive strategy, if any.
+    fn connection_keep_alive(&mut self) -> KeepAlive {
+        let keep_alive = self.handler.connection_keep_alive();
+        if keep_alive.is_yes() {
+            self.idle_since = Instant::now();
+            return keep_alive
+        }
+        match &self.keep_alive_strategy {
+            Some(strategy) => cmp::max(
+                keep_alive,
+                strategy.keep_alive(&self.peer_id, self.idle_since, self.role)
+            ),
+            None => keep_alive,
+        }
+    }
+
+    /// Records a protocol negotiated on a substream, to be reported
+    /// if it has not been negotiated on the connection before.
+    fn record_protocol(&mut self, name: &[u8]) {
+        if self.negotiated_protocols.iter().any(|p| p.protocol_name() == name) {
+            return
+        }
+        match str::from_utf8(name).ok().and_then(|n| StreamProtocol::try_from(n).ok()) {
+            Some(protocol) => self.negotiated_protocols.push(protocol),
+            None => log::debug!("Not reporting invalid protocol name {:?}.", name),
+        }
+    }
 }
 
 struct SubstreamUpgrade<UserData, Upgrade> {
@@ -171,7 +271,8 @@ where
 ///
 /// A shutdown is planned anew based on the the return value of
 /// [`ProtocolsHandler::connection_keep_alive`] of the underlying handler
-/// after every invocation of [`ProtocolsHandler::poll`].
+/// after every invocation of [`ProtocolsHandler::poll`], possibly extended
+/// by the configured [`ConnectionKeepAliveStrategy`].
 ///
 /// A planned shutdown is always postponed for as long as there are ingoing
 /// or outgoing substreams being negotiated, i.e. it is a graceful, "idle"
@@ -185,6 +286,16 @@ enum Shutdown {
     Later(Delay, Instant)
 }
 
+/// Event produced by the `NodeHandlerWrapper`.
+#[derive(Debug)]
+pub enum NodeHandlerWrapperEvent<TEvent> {
+    /// An event produced by the underlying handler.
+    Handler(TEvent),
+    /// A protocol has been negotiated on a substream of the connection
+    /// for the first time.
+    ProtocolNegotiated(StreamProtocol),
+}
+
 /// Error generated by the `NodeHandlerWrapper`.
 #[derive(Debug)]
 pub enum NodeHandlerWrapperError<TErr> {
@@ -192,6 +303,9 @@ pub enum NodeHandlerWrapperError<TErr> {
     Handler(TErr),
     /// The connection keep-alive timeout expired.
     KeepAliveTimeout,
+    /// The configured maximum number of consecutive outbound substream
+    /// upgrades with failed protocol negotiation has been reached.
+    TooManyFailedUpgrades,
 }
 
 impl<TErr> From<TErr> for NodeHandlerWrapperError<TErr> {
@@ -209,6 +323,8 @@ where
             NodeHandlerWrapperError::Handler(err) => write!(f, "{}", err),
             NodeHandlerWrapperError::KeepAliveTimeout =>
                 write!(f, "Connection closed due to expired keep-alive timeout."),
+            NodeHandlerWrapperError::TooManyFailedUpgrades =>
+                write!(f, "Connection closed due to too many failed substream upgrades."),
         }
     }
 }
@@ -221,6 +337,7 @@ where
         match self {
             NodeHandlerWrapperError::Handler(err) => Some(err),
             NodeHandlerWrapperError::KeepAliveTimeout => None,
+            NodeHandlerWrapperError::TooManyFailedUpgrades => None,
         }
     }
 }
@@ -230,7 +347,7 @@ where
     TProtoHandler: ProtocolsHandler,
 {
     type InEvent = TProtoHandler::InEvent;
-    type OutEvent = TProtoHandler::OutEvent;
+    type OutEvent = NodeHandlerWrapperEvent<TProtoHandler::OutEvent>;
     type Error = NodeHandlerWrapperError<TProtoHandler::Error>;
     type Substream = Substream<StreamMuxerBox>;
     // The first element of the tuple is the unique upgrade identifier
@@ -247,7 +364,7 @@ where
                 let protocol = self.handler.listen_protocol();
                 let timeout = *protocol.timeout();
                 let (upgrade, user_data) = protocol.into_upgrade();
-                let upgrade = upgrade::apply_inbound(substream, SendWrapper(upgrade));
+                let upgrade = upgrade::apply_inbound_with_protocol(substream, SendWrapper(upgrade));
                 let timeout = Delay::new(timeout);
                 self.negotiating_in.push(SubstreamUpgrade {
                     user_data: Some(user_data),
@@ -276,7 +393,7 @@ where
                         version = v;
                     }
                 }
-                let upgrade = upgrade::apply_outbound(substream, upgrade, version);
+                let upgrade = upgrade::apply_outbound_with_protocol(substream, upgrade, version);
                 let timeout = Delay::new(timeout);
                 self.negotiating_out.push(SubstreamUpgrade {
                     user_data: Some(user_data),
@@ -300,25 +417,64 @@ where
     > {
         while let Poll::Ready(Some((user_data, res))) = self.negotiating_in.poll_next_unpin(cx) {
             match res {
-                Ok(upgrade) => self.handler.inject_fully_negotiated_inbound(upgrade, user_data),
+                Ok((protocol, upgrade)) => {
+                    self.record_protocol(protocol.protocol_name());
+                    self.handler.inject_fully_negotiated_inbound(upgrade, user_data)
+                }
                 Err(err) => self.handler.inject_listen_upgrade_error(user_data, err),
             }
         }
 
         while let Poll::Ready(Some((user_data, res))) = self.negotiating_out.poll_next_unpin(cx) {
             match res {
-                Ok(upgrade) => self.handler.inject_fully_negotiated_outbound(upgrade, user_data),
-                Err(err) => self.handler.inject_dial_upgrade_error(user_data, err),
+                Ok((protocol, upgrade)) => {
+                    self.failed_upgrades = 0;
+                    self.record_protocol(protocol.protocol_name());
+                    self.handler.inject_fully_negotiated_outbound(upgrade, user_data)
+                }
+                Err(err) => {
+                    // A remote not supporting the requested protocol answers
+                    // cleanly, which says nothing about the connection itself.
+                    if let ProtocolsHandlerUpgrErr::Upgrade(
+                        UpgradeError::Select(NegotiationError::ProtocolError(_))
+                    ) = err {
+                        self.failed_upgrades += 1;
+                    }
+                    self.handler.inject_dial_upgrade_error(user_data, err)
+                }
             }
         }
 
+        while let Poll::Ready(Some(timer_id)) = self.timers.poll_next_unpin(cx) {
+            self.handler.inject_timeout(timer_id);
+        }
+
+        // A connection on which the protocol negotiation for outbound substreams
+        // repeatedly breaks down occupies a connection slot without providing any
+        // working streams. Timeouts and errors of the upgrades themselves are
+        // left to the handler.
+        if let Some(max) = self.max_failed_upgrades {
+            if self.failed_upgrades >= max.get() {
+                return Poll::Ready(Err(NodeHandlerWrapperError::TooManyFailedUpgrades))
+            }
+        }
+
+        if let Some(protocol) = self.negotiated_protocols.get(self.reported_protocols) {
+            self.reported_protocols += 1;
+            return Poll::Ready(Ok(ConnectionHandlerEvent::Custom(
+                NodeHandlerWrapperEvent::ProtocolNegotiated(protocol.clone())
+            )))
+        }
+
         // Poll the handler at the end so that we see the consequences of the method
         // calls on `self.handler`.
         let poll_result = self.handler.poll(cx);
 
-        // Ask the handler whether it wants the connection (and the handler itself)
-        // to be kept alive, which determines the planned shutdown, if any.
-        match (&mut self.shutdown, self.handler.connection_keep_alive()) {
+        // Ask the handler (and the keep-alive strategy, if any) whether it wants the
+        // connection (and the handler itself) to be kept alive, which determines the
+        // planned shutdown, if any.
+        let keep_alive = self.connection_keep_alive();
+        match (&mut self.shutdown, keep_alive) {
             (Shutdown::Later(timer, deadline), KeepAlive::Until(t)) =>
                 if *deadline != t {
                     *deadline = t;
@@ -331,7 +487,7 @@ where
 
         match poll_result {
             Poll::Ready(ProtocolsHandlerEvent::Custom(event)) => {
-                return Poll::Ready(Ok(ConnectionHandlerEvent::Custom(event)));
+                return Poll::Ready(Ok(ConnectionHandlerEvent::Custom(NodeHandlerWrapperEvent::Handler(event))));
             }
             Poll::Ready(ProtocolsHandlerEvent::OutboundSubstreamRequest { protocol }) => {
                 let id = self.unique_dial_upgrade_id;
@@ -344,6 +500,11 @@ where
                 ));
             }
             Poll::Ready(ProtocolsHandlerEvent::Close(err)) => return Poll::Ready(Err(err.into())),
+            Poll::Ready(ProtocolsHandlerEvent::SetTimer { timer_id, duration }) => {
+                self.timers.push(Delay::new(duration).map(move |_| timer_id).boxed());
+                // The handler may have more to report.
+                cx.waker().wake_by_ref();
+            }
             Poll::Pending => (),
         };
 
@@ -18,6 +18,7 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
+use crate::TimerId;
 use crate::upgrade::{SendWrapper, InboundUpgradeSend, OutboundUpgradeSend};
 use crate::protocols_handler::{
     KeepAlive,
@@ -151,6 +152,11 @@ where
         self.proto2.inject_address_change(new_address)
     }
 
+    fn inject_timeout(&mut self, timer_id: TimerId) {
+        self.proto1.inject_timeout(timer_id);
+        self.proto2.inject_timeout(timer_id)
+    }
+
     fn inject_dial_upgrade_error(&mut self, info: Self::OutboundOpenInfo, error: ProtocolsHandlerUpgrErr<<Self::OutboundProtocol as OutboundUpgradeSend>::Error>) {
         match (info, error) {
             (EitherOutput::First(info), ProtocolsHandlerUpgrErr::Timer) => {
@@ -244,6 +250,9 @@ where
             Poll::Ready(ProtocolsHandlerEvent::Close(event)) => {
                 return Poll::Ready(ProtocolsHandlerEvent::Close(EitherError::A(event)));
             },
+            Poll::Ready(ProtocolsHandlerEvent::SetTimer { timer_id, duration }) => {
+                return Poll::Ready(ProtocolsHandlerEvent::SetTimer { timer_id, duration });
+            },
             Poll::Ready(ProtocolsHandlerEvent::OutboundSubstreamRequest { protocol }) => {
                 return Poll::Ready(ProtocolsHandlerEvent::OutboundSubstreamRequest {
                     protocol: protocol
@@ -261,6 +270,9 @@ where
             Poll::Ready(ProtocolsHandlerEvent::Close(event)) => {
                 return Poll::Ready(ProtocolsHandlerEvent::Close(EitherError::B(event)));
             },
+            Poll::Ready(ProtocolsHandlerEvent::SetTimer { timer_id, duration }) => {
+                return Poll::Ready(ProtocolsHandlerEvent::SetTimer { timer_id, duration });
+            },
             Poll::Ready(ProtocolsHandlerEvent::OutboundSubstreamRequest { protocol }) => {
                 return Poll::Ready(ProtocolsHandlerEvent::OutboundSubstreamRequest {
                     protocol: protocol
@@ -18,10 +18,11 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
-use libp2p_core::Multiaddr;
+use libp2p_core::{Multiaddr, PeerId};
 use smallvec::SmallVec;
-use std::{collections::VecDeque, cmp::Ordering, num::NonZeroUsize};
+use std::{collections::{HashSet, VecDeque}, cmp::Ordering, num::NonZeroUsize};
 use std::ops::{Add, Sub};
+use wasm_timer::Instant;
 
 /// A ranked collection of [`Multiaddr`] values.
 ///
@@ -68,6 +69,57 @@ pub struct AddressRecord {
     pub score: AddressScore,
 }
 
+/// The confidence in an external address of the local node, see
+/// [`ExpandedSwarm::add_external_address_with_confidence`](crate::ExpandedSwarm::add_external_address_with_confidence).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AddressConfidence {
+    /// The address is tentative, e.g. a candidate that is yet to be verified.
+    /// It is not reported as an external address until it is confirmed.
+    Low,
+    /// The address is verified, e.g. by multiple observations of remote peers.
+    Confirmed,
+}
+
+/// How well an external address of the local node is confirmed by
+/// observations of remote peers.
+///
+/// See [`ExpandedSwarm::external_addresses_with_scores`](crate::ExpandedSwarm::external_addresses_with_scores).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExternalAddressScore {
+    /// The number of distinct remote peers that reported the address.
+    pub confirmed_by: usize,
+    /// The point in time the address was last reported.
+    pub last_observed: Instant,
+}
+
+/// The observations of an external address made by remote peers.
+#[derive(Debug, Clone)]
+pub(crate) struct Observations {
+    observers: HashSet<PeerId>,
+    last_observed: Instant,
+}
+
+impl Observations {
+    pub(crate) fn new() -> Self {
+        Observations { observers: HashSet::new(), last_observed: Instant::now() }
+    }
+
+    /// Records an observation, optionally made by a known remote peer.
+    pub(crate) fn observe(&mut self, observer: Option<PeerId>) {
+        if let Some(peer) = observer {
+            self.observers.insert(peer);
+        }
+        self.last_observed = Instant::now();
+    }
+
+    pub(crate) fn score(&self) -> ExternalAddressScore {
+        ExternalAddressScore {
+            confirmed_by: self.observers.len(),
+            last_observed: self.last_observed,
+        }
+    }
+}
+
 /// A report tracked for a finitely scored address.
 #[derive(Debug, Clone)]
 struct Report {
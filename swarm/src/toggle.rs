@@ -18,7 +18,7 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
-use crate::{NetworkBehaviour, NetworkBehaviourAction, NetworkBehaviourEventProcess, PollParameters};
+use crate::{AddressMeta, NetworkBehaviour, NetworkBehaviourAction, NetworkBehaviourEventProcess, PollParameters, TimerId};
 use crate::upgrade::{SendWrapper, InboundUpgradeSend, OutboundUpgradeSend};
 use crate::protocols_handler::{
     KeepAlive,
@@ -86,6 +86,10 @@ where
         self.inner.as_mut().map(|b| b.addresses_of_peer(peer_id)).unwrap_or_else(Vec::new)
     }
 
+    fn addresses_of_peer_with_metadata(&mut self, peer_id: &PeerId) -> Vec<(Multiaddr, AddressMeta)> {
+        self.inner.as_mut().map(|b| b.addresses_of_peer_with_metadata(peer_id)).unwrap_or_default()
+    }
+
     fn inject_connected(&mut self, peer_id: &PeerId) {
         if let Some(inner) = self.inner.as_mut() {
             inner.inject_connected(peer_id)
@@ -169,6 +173,12 @@ where
         }
     }
 
+    fn inject_timeout(&mut self, timer_id: TimerId) {
+        if let Some(inner) = self.inner.as_mut() {
+            inner.inject_timeout(timer_id)
+        }
+    }
+
     fn inject_listener_error(&mut self, id: ListenerId, err: &(dyn std::error::Error + 'static)) {
         if let Some(inner) = self.inner.as_mut() {
             inner.inject_listener_error(id, err)
@@ -295,6 +305,12 @@ where
         }
     }
 
+    fn inject_timeout(&mut self, timer_id: TimerId) {
+        if let Some(inner) = self.inner.as_mut() {
+            inner.inject_timeout(timer_id)
+        }
+    }
+
     fn inject_dial_upgrade_error(&mut self, info: Self::OutboundOpenInfo, err: ProtocolsHandlerUpgrErr<<Self::OutboundProtocol as OutboundUpgradeSend>::Error>) {
         self.inner.as_mut().expect("Can't receive an outbound substream if disabled; QED")
             .inject_dial_upgrade_error(info, err)
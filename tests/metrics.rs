@@ -0,0 +1,135 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+#![cfg(all(feature = "metrics", feature = "identify", feature = "ping"))]
+
+use futures::prelude::*;
+use libp2p::core::{identity, transport::MemoryTransport, upgrade, Multiaddr, Transport};
+use libp2p::identify::{Identify, IdentifyConfig, IdentifyEvent};
+use libp2p::metrics::{Metrics, MetricsBuilder, Recorder, Registry};
+use libp2p::ping::{Ping, PingConfig, PingEvent, PingSuccess};
+use libp2p::plaintext::PlainText2Config;
+use libp2p::swarm::{Swarm, SwarmBuilder, SwarmEvent};
+use libp2p::NetworkBehaviour;
+use std::time::Duration;
+
+#[derive(NetworkBehaviour)]
+#[behaviour(out_event = "Event", event_process = false)]
+struct Behaviour {
+    identify: Identify,
+    ping: Ping,
+}
+
+#[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
+enum Event {
+    Identify(IdentifyEvent),
+    Ping(PingEvent),
+}
+
+impl From<IdentifyEvent> for Event {
+    fn from(event: IdentifyEvent) -> Self {
+        Event::Identify(event)
+    }
+}
+
+impl From<PingEvent> for Event {
+    fn from(event: PingEvent) -> Self {
+        Event::Ping(event)
+    }
+}
+
+impl Recorder<Event> for Metrics {
+    fn record(&self, event: &Event) {
+        match event {
+            Event::Identify(event) => self.record(event),
+            Event::Ping(event) => self.record(event),
+        }
+    }
+}
+
+fn build_swarm(registry: Option<Registry>) -> Swarm<Behaviour> {
+    let local_key = identity::Keypair::generate_ed25519();
+    let local_public_key = local_key.public();
+    let local_peer_id = local_public_key.clone().into_peer_id();
+    let transport = MemoryTransport
+        .upgrade(upgrade::Version::V1)
+        .authenticate(PlainText2Config { local_public_key: local_public_key.clone() })
+        .multiplex(libp2p::yamux::YamuxConfig::default())
+        .boxed();
+    let behaviour = Behaviour {
+        identify: Identify::new(IdentifyConfig::new("test".to_string(), local_public_key)),
+        ping: Ping::new(PingConfig::new().with_interval(Duration::from_millis(10)).with_keep_alive(true)),
+    };
+    let builder = SwarmBuilder::new(transport, behaviour, local_peer_id);
+    match registry {
+        Some(registry) => builder.with_metrics(registry).build(),
+        None => builder.build(),
+    }
+}
+
+fn counter_value(registry: &Registry, name: &str) -> f64 {
+    registry.gather().iter()
+        .find(|family| family.get_name() == name)
+        .map(|family| family.get_metric().iter().map(|m| m.get_counter().get_value()).sum())
+        .unwrap_or(0.0)
+}
+
+fn histogram_count(registry: &Registry, name: &str) -> u64 {
+    registry.gather().iter()
+        .find(|family| family.get_name() == name)
+        .map(|family| family.get_metric().iter().map(|m| m.get_histogram().get_sample_count()).sum())
+        .unwrap_or(0)
+}
+
+#[test]
+fn records_swarm_and_behaviour_metrics() {
+    let registry = Registry::new();
+    let metrics = MetricsBuilder::new(&registry).identify().ping().build().unwrap();
+
+    let mut swarm1 = build_swarm(Some(registry.clone()));
+    let mut swarm2 = build_swarm(None);
+
+    let addr: Multiaddr = "/memory/1130001".parse().unwrap();
+    swarm2.listen_on(addr.clone()).unwrap();
+    swarm1.dial_addr(addr).unwrap();
+
+    async_std::task::spawn(swarm2.for_each(|_| future::ready(())));
+
+    async_std::task::block_on(async {
+        let mut pings = 0;
+        let mut identified = false;
+        while pings < 3 || !identified {
+            if let SwarmEvent::Behaviour(event) = swarm1.select_next_some().await {
+                metrics.record(&event);
+                match event {
+                    Event::Ping(PingEvent { result: Ok(PingSuccess::Ping { .. }), .. }) => pings += 1,
+                    Event::Identify(IdentifyEvent::Received { .. }) => identified = true,
+                    _ => {}
+                }
+            }
+        }
+    });
+
+    let registry = swarm1.metrics_registry().unwrap();
+    assert_eq!(counter_value(registry, "libp2p_swarm_connections_established_total"), 1.0);
+    assert!(counter_value(registry, "libp2p_identify_received_total") >= 1.0);
+    assert!(histogram_count(registry, "libp2p_ping_rtt_seconds") >= 1);
+}
@@ -61,7 +61,7 @@ use libp2p_core::{
     transport::{TransportError, ListenerEvent}
 };
 use smallvec::SmallVec;
-use std::{convert::TryFrom, error, fmt, iter, net::IpAddr, str};
+use std::{convert::{Infallible, TryFrom}, error, fmt, iter, net::IpAddr, str};
 #[cfg(any(feature = "async-std", feature = "tokio"))]
 use std::io;
 #[cfg(any(feature = "async-std", feature = "tokio"))]
@@ -69,6 +69,7 @@ use trust_dns_resolver::system_conf;
 use trust_dns_resolver::{
     AsyncResolver,
     ConnectionProvider,
+    proto::rr::rdata::TXT,
     proto::xfer::dns_handle::DnsHandle,
 };
 #[cfg(feature = "tokio")]
@@ -159,6 +160,45 @@ impl<T> TokioDnsConfig<T> {
     }
 }
 
+impl<T, C, P> GenDnsConfig<T, C, P>
+where
+    C: DnsHandle<Error = ResolveError>,
+    P: ConnectionProvider<Conn = C>,
+{
+    /// Resolves all DNS components of the given address, without dialing,
+    /// returning the fully resolved addresses.
+    ///
+    /// The same limits on the number of DNS lookups and TXT records apply
+    /// as when dialing. Since no inner transport is involved, the transport
+    /// may be a placeholder, e.g. `DnsConfig::system(())`.
+    pub fn resolve(&self, addr: Multiaddr)
+        -> impl Future<Output = Result<Vec<Multiaddr>, DnsErr<Infallible>>>
+    {
+        let mut resolution = Resolution::new(self.resolver.clone(), addr);
+        async move {
+            let mut last_err = None;
+            let mut resolved = Vec::new();
+
+            while let Some(result) = resolution.next().await {
+                match result {
+                    Ok(addr) => if !resolved.contains(&addr) {
+                        resolved.push(addr);
+                    }
+                    Err(e) => last_err = Some(e),
+                }
+            }
+
+            if resolved.is_empty() {
+                Err(last_err.unwrap_or_else(||
+                    DnsErr::ResolveError(
+                        ResolveErrorKind::Message("No matching records found.").into())))
+            } else {
+                Ok(resolved)
+            }
+        }
+    }
+}
+
 impl<T, C, P> fmt::Debug for GenDnsConfig<T, C, P>
 where
     C: DnsHandle<Error = ResolveError>,
@@ -209,105 +249,56 @@ where
         // Asynchronlously resolve all DNS names in the address before proceeding
         // with dialing on the underlying transport.
         Ok(async move {
-            let resolver = self.resolver;
+            let mut resolution = Resolution::new(self.resolver, addr);
             let inner = self.inner;
 
             let mut last_err = None;
-            let mut dns_lookups = 0;
             let mut dial_attempts = 0;
-            // We optimise for the common case of a single DNS component
-            // in the address that is resolved with a single lookup.
-            let mut unresolved = SmallVec::<[Multiaddr; 1]>::new();
-            unresolved.push(addr.clone());
 
             // Resolve (i.e. replace) all DNS protocol components, initiating
             // dialing attempts as soon as there is another fully resolved
             // address.
-            while let Some(addr) = unresolved.pop() {
-                if let Some((i, name)) = addr.iter().enumerate().find(|(_, p)| match p {
-                    Protocol::Dns(_) |
-                    Protocol::Dns4(_) |
-                    Protocol::Dns6(_) |
-                    Protocol::Dnsaddr(_) => true,
-                    _ => false
-                }) {
-                    if dns_lookups == MAX_DNS_LOOKUPS {
-                        log::debug!("Too many DNS lookups. Dropping unresolved {}.", addr);
-                        last_err = Some(DnsErr::TooManyLookups);
-                        // There may still be fully resolved addresses in `unresolved`,
-                        // so keep going until `unresolved` is empty.
-                        continue
-                    }
-                    dns_lookups += 1;
-                    match resolve(&name, &resolver).await {
-                        Err(e) => {
-                            if unresolved.is_empty() {
-                                return Err(e)
-                            }
-                            // If there are still unresolved addresses, there is
-                            // a chance of success, but we track the last error.
-                            last_err = Some(e);
-                        }
-                        Ok(Resolved::One(ip)) => {
-                            log::trace!("Resolved {} -> {}", name, ip);
-                            let addr = addr.replace(i, |_| Some(ip)).expect("`i` is a valid index");
-                            unresolved.push(addr);
-                        }
-                        Ok(Resolved::Many(ips)) => {
-                            for ip in ips {
-                                log::trace!("Resolved {} -> {}", name, ip);
-                                let addr = addr.replace(i, |_| Some(ip)).expect("`i` is a valid index");
-                                unresolved.push(addr);
-                            }
-                        }
-                        Ok(Resolved::Addrs(addrs)) => {
-                            let suffix = addr.iter().skip(i + 1).collect::<Multiaddr>();
-                            let prefix = addr.iter().take(i).collect::<Multiaddr>();
-                            let mut n = 0;
-                            for a in addrs {
-                                if a.ends_with(&suffix) {
-                                    if n < MAX_TXT_RECORDS {
-                                        n += 1;
-                                        log::trace!("Resolved {} -> {}", name, a);
-                                        let addr = prefix.iter().chain(a.iter()).collect::<Multiaddr>();
-                                        unresolved.push(addr);
-                                    } else {
-                                        log::debug!("Too many TXT records. Dropping resolved {}.", a);
-                                    }
-                                }
-                            }
+            while let Some(result) = resolution.next().await {
+                match result {
+                    Err(e) => {
+                        if resolution.is_exhausted() {
+                            return Err(e)
                         }
+                        // If there are still unresolved addresses, there is
+                        // a chance of success, but we track the last error.
+                        last_err = Some(e);
                     }
-                } else {
-                    // We have a fully resolved address, so try to dial it.
-                    log::debug!("Dialing {}", addr);
-
-                    let transport = inner.clone();
-                    let result = match transport.dial(addr) {
-                        Ok(out) => {
-                            // We only count attempts that the inner transport
-                            // actually accepted, i.e. for which it produced
-                            // a dialing future.
-                            dial_attempts += 1;
-                            out.await.map_err(DnsErr::Transport)
-                        }
-                        Err(TransportError::MultiaddrNotSupported(a)) =>
-                            Err(DnsErr::MultiaddrNotSupported(a)),
-                        Err(TransportError::Other(err)) => Err(DnsErr::Transport(err))
-                    };
-
-                    match result {
-                        Ok(out) => return Ok(out),
-                        Err(err) => {
-                            log::debug!("Dial error: {:?}.", err);
-                            if unresolved.is_empty() {
-                                return Err(err)
+                    Ok(addr) => {
+                        // We have a fully resolved address, so try to dial it.
+                        log::debug!("Dialing {}", addr);
+
+                        let transport = inner.clone();
+                        let result = match transport.dial(addr) {
+                            Ok(out) => {
+                                // We only count attempts that the inner transport
+                                // actually accepted, i.e. for which it produced
+                                // a dialing future.
+                                dial_attempts += 1;
+                                out.await.map_err(DnsErr::Transport)
                             }
-                            if dial_attempts == MAX_DIAL_ATTEMPTS {
-                                log::debug!("Aborting dialing after {} attempts.", MAX_DIAL_ATTEMPTS);
-                                return Err(err)
+                            Err(TransportError::MultiaddrNotSupported(a)) =>
+                                Err(DnsErr::MultiaddrNotSupported(a)),
+                            Err(TransportError::Other(err)) => Err(DnsErr::Transport(err))
+                        };
+
+                        match result {
+                            Ok(out) => return Ok(out),
+                            Err(err) => {
+                                log::debug!("Dial error: {:?}.", err);
+                                if resolution.is_exhausted() {
+                                    return Err(err)
+                                }
+                                if dial_attempts == MAX_DIAL_ATTEMPTS {
+                                    log::debug!("Aborting dialing after {} attempts.", MAX_DIAL_ATTEMPTS);
+                                    return Err(err)
+                                }
+                                last_err = Some(err);
                             }
-                            last_err = Some(err);
                         }
                     }
                 }
@@ -372,6 +363,103 @@ where TErr: error::Error + 'static
     }
 }
 
+/// The resolution of all DNS components of an address, which may yield
+/// multiple alternative fully resolved addresses.
+///
+/// Shared by [`GenDnsConfig::resolve`] and dialing, such that both are
+/// subject to the same limit of [`MAX_DNS_LOOKUPS`].
+struct Resolution<C, P>
+where
+    C: DnsHandle<Error = ResolveError>,
+    P: ConnectionProvider<Conn = C>,
+{
+    resolver: AsyncResolver<C, P>,
+    /// The addresses that may still contain DNS components. We optimise for
+    /// the common case of a single DNS component in the address that is
+    /// resolved with a single lookup.
+    unresolved: SmallVec<[Multiaddr; 1]>,
+    dns_lookups: usize,
+}
+
+impl<C, P> Resolution<C, P>
+where
+    C: DnsHandle<Error = ResolveError>,
+    P: ConnectionProvider<Conn = C>,
+{
+    fn new(resolver: AsyncResolver<C, P>, addr: Multiaddr) -> Self {
+        let mut unresolved = SmallVec::new();
+        unresolved.push(addr);
+        Resolution { resolver, unresolved, dns_lookups: 0 }
+    }
+
+    /// Whether no further addresses can be obtained from [`Resolution::next`].
+    fn is_exhausted(&self) -> bool {
+        self.unresolved.is_empty()
+    }
+
+    /// Returns the next fully resolved address, or the error of a failed
+    /// DNS lookup. Resolution may continue after an error, as long as the
+    /// resolution is not exhausted.
+    async fn next<E: Send>(&mut self) -> Option<Result<Multiaddr, DnsErr<E>>> {
+        while let Some(addr) = self.unresolved.pop() {
+            if let Some((i, name)) = addr.iter().enumerate().find(|(_, p)| is_dns(p)) {
+                if self.dns_lookups == MAX_DNS_LOOKUPS {
+                    log::debug!("Too many DNS lookups. Dropping unresolved {}.", addr);
+                    return Some(Err(DnsErr::TooManyLookups))
+                }
+                self.dns_lookups += 1;
+                match resolve(&name, &self.resolver).await {
+                    Err(e) => return Some(Err(e)),
+                    Ok(resolved) => self.unresolved.extend(substitute(&addr, i, &name, resolved)),
+                }
+            } else {
+                return Some(Ok(addr))
+            }
+        }
+        None
+    }
+}
+
+/// Whether the given protocol component is a DNS name that needs resolving.
+fn is_dns(proto: &Protocol<'_>) -> bool {
+    matches!(proto, Protocol::Dns(_) | Protocol::Dns4(_) | Protocol::Dns6(_) | Protocol::Dnsaddr(_))
+}
+
+/// Returns the addresses obtained by substituting the DNS protocol component
+/// `name` at index `i` of `addr` with the outcome of its resolution.
+fn substitute(addr: &Multiaddr, i: usize, name: &Protocol<'_>, resolved: Resolved<'_>) -> Vec<Multiaddr> {
+    match resolved {
+        Resolved::One(ip) => {
+            log::trace!("Resolved {} -> {}", name, ip);
+            vec![addr.replace(i, |_| Some(ip)).expect("`i` is a valid index")]
+        }
+        Resolved::Many(ips) => {
+            ips.into_iter().map(|ip| {
+                log::trace!("Resolved {} -> {}", name, ip);
+                addr.replace(i, |_| Some(ip)).expect("`i` is a valid index")
+            }).collect()
+        }
+        Resolved::Addrs(addrs) => {
+            let suffix = addr.iter().skip(i + 1).collect::<Multiaddr>();
+            let prefix = addr.iter().take(i).collect::<Multiaddr>();
+            let mut n = 0;
+            let mut result = Vec::new();
+            for a in addrs {
+                if a.ends_with(&suffix) {
+                    if n < MAX_TXT_RECORDS {
+                        n += 1;
+                        log::trace!("Resolved {} -> {}", name, a);
+                        result.push(prefix.iter().chain(a.iter()).collect::<Multiaddr>());
+                    } else {
+                        log::debug!("Too many TXT records. Dropping resolved {}.", a);
+                    }
+                }
+            }
+            result
+        }
+    }
+}
+
 /// The successful outcome of [`resolve`] for a given [`Protocol`].
 enum Resolved<'a> {
     /// The given `Protocol` has been resolved to a single `Protocol`,
@@ -464,15 +552,13 @@ where
                 Ok(txts) => {
                     let mut addrs = Vec::new();
                     for txt in txts {
-                        if let Some(chars) = txt.txt_data().first() {
-                            match parse_dnsaddr_txt(chars) {
-                                Err(e) => {
-                                    // Skip over seemingly invalid entries.
-                                    log::debug!("Invalid TXT record: {:?}", e);
-                                }
-                                Ok(a) => {
-                                    addrs.push(a);
-                                }
+                        match parse_dnsaddr_txt_record(&txt) {
+                            Err(e) => {
+                                // Skip over seemingly invalid entries.
+                                log::debug!("Invalid TXT record: {:?}", e);
+                            }
+                            Ok(a) => {
+                                addrs.push(a);
                             }
                         }
                     }
@@ -485,8 +571,20 @@ where
     }
 }
 
-/// Parses a `<character-string>` of a `dnsaddr` TXT record.
-fn parse_dnsaddr_txt(txt: &[u8]) -> io::Result<Multiaddr> {
+/// Parses a `dnsaddr` TXT record.
+///
+/// A TXT record may consist of multiple `<character-string>`s, e.g. if the
+/// value exceeds the limit of 255 bytes per string, which are concatenated
+/// before parsing.
+fn parse_dnsaddr_txt_record(txt: &TXT) -> io::Result<Multiaddr> {
+    parse_dnsaddr_txt(&txt.txt_data().concat())
+}
+
+/// Parses the (concatenated) `<character-string>`s of a `dnsaddr` TXT record.
+///
+/// Exposed for fuzzing only, see `fuzz/` in the repository root.
+#[doc(hidden)]
+pub fn parse_dnsaddr_txt(txt: &[u8]) -> io::Result<Multiaddr> {
     let s = str::from_utf8(txt).map_err(invalid_data)?;
     match s.strip_prefix("dnsaddr=") {
         None => Err(invalid_data("Missing `dnsaddr=` prefix.")),
@@ -620,6 +718,19 @@ mod tests {
                 Err(e) => panic!("Unexpected error: {:?}", e),
                 Ok(_) => panic!("Unexpected success."),
             }
+
+            // Resolution without dialing yields only IP addresses.
+            let addrs = transport
+                .resolve("/dnsaddr/bootstrap.libp2p.io".parse().unwrap())
+                .await
+                .unwrap();
+            assert!(!addrs.is_empty());
+            for a in addrs {
+                match a.iter().next() {
+                    Some(Protocol::Ip4(_)) | Some(Protocol::Ip6(_)) => {},
+                    _ => panic!("Unexpected address: {}", a),
+                }
+            }
         }
 
         #[cfg(feature = "async-std")]
@@ -647,4 +758,23 @@ mod tests {
             rt.block_on(run(TokioDnsConfig::custom(CustomTransport, config, opts).unwrap()));
         }
     }
+
+    #[test]
+    fn dnsaddr_txt_record_fragments() {
+        let addr: Multiaddr = "/ip4/1.2.3.4/tcp/1234/p2p/QmcgpsyWgH8Y8ajJz1Cu72KnS5uo2Aa2LpzU7kinSupNKC"
+            .parse().unwrap();
+
+        let txt = TXT::new(vec![format!("dnsaddr={}", addr)]);
+        assert_eq!(parse_dnsaddr_txt_record(&txt).unwrap(), addr);
+
+        // A value split across multiple `<character-string>`s.
+        let value = format!("dnsaddr={}", addr);
+        let (a, b) = value.split_at(20);
+        let txt = TXT::new(vec![a.to_string(), b.to_string()]);
+        assert_eq!(txt.txt_data().len(), 2);
+        assert_eq!(parse_dnsaddr_txt_record(&txt).unwrap(), addr);
+
+        let txt = TXT::new(vec![]);
+        assert!(parse_dnsaddr_txt_record(&txt).is_err());
+    }
 }
@@ -0,0 +1,94 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Regression tests for inputs of the fuzz targets in `fuzz/`.
+//!
+//! Each input must be rejected with an error rather than cause a panic.
+
+use libp2p_core::{Multiaddr, multiaddr::Protocol};
+use libp2p_dns::parse_dnsaddr_txt;
+use std::convert::TryFrom;
+
+#[test]
+fn dnsaddr_txt() {
+    let inputs: &[&[u8]] = &[
+        b"",
+        b"dnsaddr",
+        b"dnsaddr=/",
+        b"dnsaddr=//",
+        b"dnsaddr=/ip4",
+        b"dnsaddr=/ip4/256.0.0.1",
+        b"dnsaddr=/tcp/65536",
+        b"dnsaddr=/p2p/notapeerid",
+        b"dnsaddr=/onion3/aaaa:1",
+        b"dnsaddr=/unknown/1",
+        b"dnsaddr=\xff\xfe",
+        b"\xffdnsaddr=/ip4/1.2.3.4",
+        b"/ip4/1.2.3.4/tcp/1",
+    ];
+
+    for input in inputs {
+        assert!(parse_dnsaddr_txt(input).is_err(), "{:?}", input);
+    }
+
+    assert_eq!(
+        parse_dnsaddr_txt(b"dnsaddr=/ip4/1.2.3.4/tcp/1").unwrap(),
+        "/ip4/1.2.3.4/tcp/1".parse::<Multiaddr>().unwrap()
+    );
+}
+
+#[test]
+fn multiaddr_from_str() {
+    let inputs = [
+        "ip4/1.2.3.4",
+        "/ip6/::1/tcp",
+        "/ip6zone//ip6/::1",
+        "/memory/18446744073709551616",
+        "/onion/aaaaaaaaaaaaaaaa:99999999999",
+        "/onion3/aaaa",
+        "/garlic64/",
+        "/p2p/QmYyQSo1c1Ym7orWxLYvCrM2EmxFTANf8wXmmE7DWjhx5",
+    ];
+
+    for input in inputs.iter() {
+        assert!(Multiaddr::try_from(*input).is_err(), "{:?}", input);
+    }
+}
+
+#[test]
+fn multiaddr_from_bytes() {
+    let inputs: &[&[u8]] = &[
+        // Unknown protocol code.
+        &[0xff, 0xff, 0xff, 0xff, 0x0f],
+        // Truncated `ip4` address.
+        &[0x04, 127, 0, 0],
+        // `tcp` without port.
+        &[0x06],
+        // Length prefix exceeding the input.
+        &[0x35, 0xff, 0x01, b'a'],
+        // Invalid UTF-8 in `dns4`.
+        &[0x36, 0x02, 0xff, 0xfe],
+    ];
+
+    for input in inputs {
+        assert!(Multiaddr::try_from(input.to_vec()).is_err(), "{:?}", input);
+        assert!(Protocol::from_bytes(input).is_err(), "{:?}", input);
+    }
+}
@@ -25,6 +25,7 @@ pub mod handshake;
 
 use bytes::Bytes;
 use framed::{MAX_FRAME_LEN, NoiseFramed};
+use handshake::NoiseHandshakeMetrics;
 use futures::ready;
 use futures::prelude::*;
 use log::trace;
@@ -39,12 +40,14 @@ pub struct NoiseOutput<T> {
     recv_offset: usize,
     send_buffer: Vec<u8>,
     send_offset: usize,
+    handshake_metrics: Option<NoiseHandshakeMetrics>,
 }
 
 impl<T> fmt::Debug for NoiseOutput<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("NoiseOutput")
             .field("io", &self.io)
+            .field("handshake_metrics", &self.handshake_metrics)
             .finish()
     }
 }
@@ -57,8 +60,15 @@ impl<T> NoiseOutput<T> {
             recv_offset: 0,
             send_buffer: Vec::new(),
             send_offset: 0,
+            handshake_metrics: None,
         }
     }
+
+    /// Returns the metrics of the handshake that established this session,
+    /// if available.
+    pub fn handshake_metrics(&self) -> Option<&NoiseHandshakeMetrics> {
+        self.handshake_metrics.as_ref()
+    }
 }
 
 impl<T: AsyncRead + Unpin> AsyncRead for NoiseOutput<T> {
@@ -53,6 +53,10 @@ pub struct NoiseFramed<T, S> {
     read_buffer: Vec<u8>,
     write_buffer: Vec<u8>,
     decrypt_buffer: BytesMut,
+    /// The number of bytes of all frames sent so far, including length prefixes.
+    bytes_sent: usize,
+    /// The number of bytes of all frames received so far, including length prefixes.
+    bytes_received: usize,
 }
 
 impl<T, S> fmt::Debug for NoiseFramed<T, S> {
@@ -75,9 +79,21 @@ impl<T> NoiseFramed<T, snow::HandshakeState> {
             read_buffer: Vec::new(),
             write_buffer: Vec::new(),
             decrypt_buffer: BytesMut::new(),
+            bytes_sent: 0,
+            bytes_received: 0,
         }
     }
 
+    /// The number of bytes sent so far, including frame length prefixes.
+    pub fn bytes_sent(&self) -> usize {
+        self.bytes_sent
+    }
+
+    /// The number of bytes received so far, including frame length prefixes.
+    pub fn bytes_received(&self) -> usize {
+        self.bytes_received
+    }
+
     /// Converts the `NoiseFramed` into a `NoiseOutput` encrypted data stream
     /// once the handshake is complete, including the static DH [`PublicKey`]
     /// of the remote, if received.
@@ -108,6 +124,8 @@ impl<T> NoiseFramed<T, snow::HandshakeState> {
                     read_buffer: self.read_buffer,
                     write_buffer: self.write_buffer,
                     decrypt_buffer: self.decrypt_buffer,
+                    bytes_sent: self.bytes_sent,
+                    bytes_received: self.bytes_received,
                 };
                 Ok((dh_remote_pubkey, NoiseOutput::new(io)))
             }
@@ -188,6 +206,7 @@ where
                         }
                     };
                     trace!("read: frame len = {}", n);
+                    this.bytes_received = this.bytes_received.saturating_add(2 + usize::from(n));
                     if n == 0 {
                         trace!("read: empty frame");
                         this.read_state = ReadState::Ready;
@@ -318,6 +337,7 @@ where
             Ok(n) => {
                 trace!("write: cipher text len = {} bytes", n);
                 this.write_buffer.truncate(n);
+                this.bytes_sent = this.bytes_sent.saturating_add(2 + n);
                 this.write_state = WriteState::WriteLen {
                     len: n,
                     buf: u16::to_be_bytes(n as u16),
@@ -33,7 +33,26 @@ use libp2p_core::identity;
 use futures::prelude::*;
 use futures::task;
 use prost::Message;
-use std::{io, pin::Pin, task::Context};
+use std::{io, pin::Pin, task::Context, time::Duration};
+use wasm_timer::Instant;
+
+#[cfg(feature = "metrics")]
+lazy_static::lazy_static! {
+    /// The durations of all completed handshakes, registered with the
+    /// default `prometheus` registry.
+    static ref HANDSHAKE_DURATION: prometheus::Histogram = {
+        let opts = prometheus::HistogramOpts::new(
+            "libp2p_noise_handshake_duration_seconds",
+            "Duration of completed Noise handshakes in seconds.",
+        );
+        let histogram = prometheus::Histogram::with_opts(opts)
+            .expect("The histogram options are valid.");
+        if let Err(e) = prometheus::register(Box::new(histogram.clone())) {
+            log::warn!("Failed to register Noise handshake metrics: {}", e);
+        }
+        histogram
+    };
+}
 
 /// The identity of the remote established during a handshake.
 pub enum RemoteIdentity<C> {
@@ -90,18 +109,61 @@ pub enum IdentityExchange {
     None { remote: identity::PublicKey }
 }
 
+/// Metrics of a completed Noise handshake.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoiseHandshakeMetrics {
+    /// The time from the start to the completion of the handshake.
+    pub duration: Duration,
+    /// The number of bytes sent, including frame length prefixes.
+    pub bytes_sent: usize,
+    /// The number of bytes received, including frame length prefixes.
+    pub bytes_received: usize,
+    /// The number of handshake messages sent and received, i.e. 2 for
+    /// a single roundtrip and 3 for a 1.5-roundtrip handshake pattern.
+    pub messages: usize,
+    /// The name of the handshake pattern, e.g. `"XX"`, or the empty
+    /// string if it is unknown. See [`Handshake::with_pattern`].
+    pub pattern: &'static str,
+}
+
 /// A future performing a Noise handshake pattern.
 pub struct Handshake<T, C>(
     Pin<Box<dyn Future<
         Output = Result<(RemoteIdentity<C>, NoiseOutput<T>), NoiseError>,
-    > + Send>>
+    > + Send>>,
+    &'static str,
 );
 
+impl<T, C> Handshake<T, C> {
+    fn new<F>(future: F) -> Self
+    where
+        F: Future<Output = Result<(RemoteIdentity<C>, NoiseOutput<T>), NoiseError>> + Send + 'static
+    {
+        Handshake(Box::pin(future), "")
+    }
+
+    /// Sets the name of the handshake pattern reported in the
+    /// [`NoiseHandshakeMetrics`] of the resulting [`NoiseOutput`].
+    ///
+    /// The handshakes performed by a [`NoiseConfig`](crate::NoiseConfig)
+    /// set the name of their handshake pattern.
+    pub fn with_pattern(mut self, pattern: &'static str) -> Self {
+        self.1 = pattern;
+        self
+    }
+}
+
 impl<T, C> Future for Handshake<T, C> {
     type Output = Result<(RemoteIdentity<C>, NoiseOutput<T>), NoiseError>;
 
     fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> task::Poll<Self::Output> {
-        Pin::new(&mut self.0).poll(ctx)
+        let pattern = self.1;
+        Pin::new(&mut self.0).poll(ctx).map_ok(|(remote, mut io)| {
+            if let Some(metrics) = io.handshake_metrics.as_mut() {
+                metrics.pattern = pattern;
+            }
+            (remote, io)
+        })
     }
 }
 
@@ -133,12 +195,12 @@ where
     T: AsyncWrite + AsyncRead + Send + Unpin + 'static,
     C: Protocol<C> + AsRef<[u8]>
 {
-    Handshake(Box::pin(async move {
+    Handshake::new(async move {
         let mut state = State::new(io, session, identity, identity_x, legacy)?;
         send_identity(&mut state).await?;
         recv_identity(&mut state).await?;
         state.finish()
-    }))
+    })
 }
 
 /// Creates an authenticated Noise handshake for the responder of a
@@ -168,12 +230,12 @@ where
     T: AsyncWrite + AsyncRead + Send + Unpin + 'static,
     C: Protocol<C> + AsRef<[u8]>
 {
-    Handshake(Box::pin(async move {
+    Handshake::new(async move {
         let mut state = State::new(io, session, identity, identity_x, legacy)?;
         recv_identity(&mut state).await?;
         send_identity(&mut state).await?;
         state.finish()
-    }))
+    })
 }
 
 /// Creates an authenticated Noise handshake for the initiator of a
@@ -205,13 +267,13 @@ where
     T: AsyncWrite + AsyncRead + Unpin + Send + 'static,
     C: Protocol<C> + AsRef<[u8]>
 {
-    Handshake(Box::pin(async move {
+    Handshake::new(async move {
         let mut state = State::new(io, session, identity, identity_x, legacy)?;
         send_empty(&mut state).await?;
         recv_identity(&mut state).await?;
         send_identity(&mut state).await?;
         state.finish()
-    }))
+    })
 }
 
 /// Creates an authenticated Noise handshake for the responder of a
@@ -243,13 +305,13 @@ where
     T: AsyncWrite + AsyncRead + Unpin + Send + 'static,
     C: Protocol<C> + AsRef<[u8]>
 {
-    Handshake(Box::pin(async move {
+    Handshake::new(async move {
         let mut state = State::new(io, session, identity, identity_x, legacy)?;
         recv_empty(&mut state).await?;
         send_identity(&mut state).await?;
         recv_identity(&mut state).await?;
         state.finish()
-    }))
+    })
 }
 
 //////////////////////////////////////////////////////////////////////////////
@@ -270,6 +332,10 @@ struct State<T> {
     send_identity: bool,
     /// Legacy configuration parameters.
     legacy: LegacyConfig,
+    /// When the handshake started.
+    started: Instant,
+    /// The number of handshake messages sent and received so far.
+    messages: usize,
 }
 
 impl<T> State<T> {
@@ -299,6 +365,8 @@ impl<T> State<T> {
                 id_remote_pubkey,
                 send_identity,
                 legacy,
+                started: Instant::now(),
+                messages: 0,
             }
         )
     }
@@ -312,7 +380,14 @@ impl<T> State<T>
     where
         C: Protocol<C> + AsRef<[u8]>
     {
-        let (pubkey, io) = self.io.into_transport()?;
+        let metrics = NoiseHandshakeMetrics {
+            duration: self.started.elapsed(),
+            bytes_sent: self.io.bytes_sent(),
+            bytes_received: self.io.bytes_received(),
+            messages: self.messages,
+            pattern: "",
+        };
+        let (pubkey, mut io) = self.io.into_transport()?;
         let remote = match (self.id_remote_pubkey, pubkey) {
             (_, None) => RemoteIdentity::Unknown,
             (None, Some(dh_pk)) => RemoteIdentity::StaticDhKey(dh_pk),
@@ -324,6 +399,9 @@ impl<T> State<T>
                 }
             }
         };
+        #[cfg(feature = "metrics")]
+        HANDSHAKE_DURATION.observe(metrics.duration.as_secs_f64());
+        io.handshake_metrics = Some(metrics);
         Ok((remote, io))
     }
 }
@@ -339,7 +417,10 @@ where
     match state.io.next().await {
         None => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "eof").into()),
         Some(Err(e)) => Err(e.into()),
-        Some(Ok(m)) => Ok(m),
+        Some(Ok(m)) => {
+            state.messages += 1;
+            Ok(m)
+        }
     }
 }
 
@@ -363,6 +444,7 @@ where
     T: AsyncWrite + Unpin
 {
     state.io.send(&Vec::new()).await?;
+    state.messages += 1;
     Ok(())
 }
 
@@ -450,6 +532,7 @@ where
 
     pb.encode(&mut msg).expect("Vec<u8> provides capacity as needed");
     state.io.send(&msg).await?;
+    state.messages += 1;
 
     Ok(())
 }
@@ -61,7 +61,7 @@ mod protocol;
 pub use error::NoiseError;
 pub use io::NoiseOutput;
 pub use io::handshake;
-pub use io::handshake::{Handshake, RemoteIdentity, IdentityExchange};
+pub use io::handshake::{Handshake, RemoteIdentity, IdentityExchange, NoiseHandshakeMetrics};
 pub use protocol::{Keypair, AuthenticKeypair, KeypairIdentity, PublicKey, SecretKey};
 pub use protocol::{Protocol, ProtocolParams, IX, IK, XX};
 pub use protocol::{x25519::X25519, x25519_spec::X25519Spec};
@@ -190,6 +190,7 @@ where
             self.dh_keys.into_identity(),
             IdentityExchange::Mutual,
             self.legacy)
+            .with_pattern("IX")
     }
 }
 
@@ -212,6 +213,7 @@ where
                                  self.dh_keys.into_identity(),
                                  IdentityExchange::Mutual,
                                  self.legacy)
+            .with_pattern("IX")
     }
 }
 
@@ -236,6 +238,7 @@ where
             self.dh_keys.into_identity(),
             IdentityExchange::Mutual,
             self.legacy)
+            .with_pattern("XX")
     }
 }
 
@@ -258,6 +261,7 @@ where
             self.dh_keys.into_identity(),
             IdentityExchange::Mutual,
             self.legacy)
+            .with_pattern("XX")
     }
 }
 
@@ -282,6 +286,7 @@ where
             self.dh_keys.into_identity(),
             IdentityExchange::Receive,
             self.legacy)
+            .with_pattern("IK")
     }
 }
 
@@ -305,6 +310,7 @@ where
             self.dh_keys.into_identity(),
             IdentityExchange::Send { remote: self.remote.1 },
             self.legacy)
+            .with_pattern("IK")
     }
 }
 
@@ -27,7 +27,7 @@ use libp2p_noise::{Keypair, X25519, X25519Spec, NoiseConfig, RemoteIdentity, Noi
 use libp2p_tcp::TcpConfig;
 use log::info;
 use quickcheck::QuickCheck;
-use std::{convert::TryInto, io, net::TcpStream};
+use std::{convert::TryInto, io, net::TcpStream, time::Duration};
 
 #[allow(dead_code)]
 fn core_upgrade_compat() {
@@ -176,6 +176,72 @@ fn ik_xx() {
     QuickCheck::new().max_tests(30).quickcheck(prop as fn(Vec<Message>) -> bool)
 }
 
+#[test]
+fn handshake_metrics() {
+    let _ = env_logger::try_init();
+    let server_id = identity::Keypair::generate_ed25519();
+    let client_id = identity::Keypair::generate_ed25519();
+
+    let server_dh = Keypair::<X25519Spec>::new().into_authentic(&server_id).unwrap();
+    let server_transport = TcpConfig::new()
+        .and_then(move |output, endpoint| {
+            upgrade::apply(output, NoiseConfig::xx(server_dh), endpoint, upgrade::Version::V1)
+        });
+
+    let client_dh = Keypair::<X25519Spec>::new().into_authentic(&client_id).unwrap();
+    let client_transport = TcpConfig::new()
+        .and_then(move |output, endpoint| {
+            upgrade::apply(output, NoiseConfig::xx(client_dh), endpoint, upgrade::Version::V1)
+        });
+
+    futures::executor::block_on(async {
+        let mut server = server_transport
+            .listen_on("/ip4/127.0.0.1/tcp/0".parse().unwrap())
+            .unwrap();
+
+        let server_address = server.try_next()
+            .await
+            .expect("some event")
+            .expect("no error")
+            .into_new_address()
+            .expect("listen address");
+
+        let client_fut = async {
+            client_transport.dial(server_address.clone())
+                .unwrap()
+                .await
+                .map(|(_, session)| session)
+                .expect("no error")
+        };
+
+        let server_fut = async {
+            server.try_next()
+                .await
+                .expect("some event")
+                .map(ListenerEvent::into_upgrade)
+                .expect("no error")
+                .map(|client| client.0)
+                .expect("listener upgrade")
+                .await
+                .map(|(_, session)| session)
+                .expect("no error")
+        };
+
+        let (server_session, client_session) = future::join(server_fut, client_fut).await;
+        let server_metrics = server_session.handshake_metrics().expect("handshake metrics");
+        let client_metrics = client_session.handshake_metrics().expect("handshake metrics");
+
+        for metrics in &[server_metrics, client_metrics] {
+            assert!(metrics.duration > Duration::from_secs(0));
+            assert_eq!(metrics.messages, 3);
+            assert_eq!(metrics.pattern, "XX");
+        }
+        assert_eq!(server_metrics.bytes_sent, client_metrics.bytes_received);
+        assert_eq!(server_metrics.bytes_received, client_metrics.bytes_sent);
+        assert!(client_metrics.bytes_sent > 0);
+    })
+}
+
 type Output<C> = (RemoteIdentity<C>, NoiseOutput<Negotiated<Async<TcpStream>>>);
 
 fn run<T, U, I, C>(server_transport: T, client_transport: U, messages: I)
@@ -27,6 +27,9 @@
 //! transport with `libp2p-core` or `libp2p-swarm`.
 
 mod provider;
+mod proxy_protocol;
+
+pub use proxy_protocol::ProxyProtocol;
 
 #[cfg(feature = "async-io")]
 pub use provider::async_io;
@@ -43,9 +46,10 @@ pub use provider::tokio;
 pub type TokioTcpConfig = GenTcpConfig<tokio::Tcp>;
 
 use futures::{
-    future::{self, BoxFuture, Ready},
+    future::{self, BoxFuture, Either, Ready},
     prelude::*,
     ready,
+    stream::FuturesUnordered,
 };
 use futures_timer::Delay;
 use libp2p_core::{
@@ -53,7 +57,7 @@ use libp2p_core::{
     multiaddr::{Multiaddr, Protocol},
     transport::{ListenerEvent, Transport, TransportError},
 };
-use socket2::{Domain, Socket, Type};
+use socket2::{Domain, Socket, TcpKeepalive, Type};
 use std::{
     collections::HashSet,
     io,
@@ -64,7 +68,14 @@ use std::{
     time::Duration,
 };
 
-use provider::{Provider, IfEvent};
+use provider::{Provider, IfEvent, Incoming};
+
+/// How long to wait for the PROXY protocol header of an incoming connection.
+const PROXY_HEADER_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The maximum number of incoming connections whose PROXY protocol header
+/// is read concurrently. Further connections remain in the listen backlog.
+const MAX_PENDING_PROXY_HEADERS: usize = 64;
 
 /// The configuration for a TCP/IP transport capability for libp2p.
 ///
@@ -82,10 +93,60 @@ pub struct GenTcpConfig<T> {
     nodelay: Option<bool>,
     /// Size of the listen backlog for listen sockets.
     backlog: u32,
+    /// Whether to set `SO_REUSEPORT` on listen sockets.
+    reuse_port: bool,
+    /// The PROXY protocol expected on incoming connections.
+    proxy_protocol: ProxyProtocol,
     /// The configuration of port reuse when dialing.
     port_reuse: PortReuse,
 }
 
+/// Socket options for an individual outgoing connection that complement
+/// those configured on the [`GenTcpConfig`].
+///
+/// See [`GenTcpConfig::dial_with_options`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TcpOptions {
+    /// Enables `SO_KEEPALIVE`, sending keep-alive probes once the connection
+    /// has been idle for the given duration, or `None` to keep the default.
+    pub keepalive_interval: Option<Duration>,
+    /// `SO_SNDBUF` to set, or `None` to keep the default.
+    pub send_buffer_size: Option<usize>,
+    /// `SO_RCVBUF` to set, or `None` to keep the default.
+    pub recv_buffer_size: Option<usize>,
+    /// `IP_TOS` to set, or `None` to keep the default.
+    ///
+    /// Only applies to IPv4 connections.
+    pub tos: Option<u8>,
+}
+
+impl TcpOptions {
+    /// Applies the options to a socket, except for [`TcpOptions::tos`].
+    fn apply(&self, socket: &Socket) -> io::Result<()> {
+        if let Some(interval) = self.keepalive_interval {
+            socket.set_tcp_keepalive(&TcpKeepalive::new().with_time(interval))?;
+        }
+        if let Some(size) = self.send_buffer_size {
+            socket.set_send_buffer_size(size)?;
+        }
+        if let Some(size) = self.recv_buffer_size {
+            socket.set_recv_buffer_size(size)?;
+        }
+        Ok(())
+    }
+
+    /// Applies [`TcpOptions::tos`] to a socket for the given remote address.
+    #[cfg(not(any(target_os = "fuchsia", target_os = "redox", target_os = "solaris", target_os = "illumos")))]
+    fn apply_tos(&self, socket: &Socket, socket_addr: &SocketAddr) -> io::Result<()> {
+        if let Some(tos) = self.tos {
+            if socket_addr.is_ipv4() {
+                socket.set_tos(u32::from(tos))?;
+            }
+        }
+        Ok(())
+    }
+}
+
 type Port = u16;
 
 /// The configuration for port reuse of listening sockets.
@@ -174,11 +235,17 @@ where
     ///     See [`GenTcpConfig::ttl`].
     ///   * The size of the listen backlog for new listening sockets is `1024`.
     ///     See [`GenTcpConfig::listen_backlog`].
+    ///   * `SO_REUSEPORT` is not set on listening sockets.
+    ///     See [`GenTcpConfig::reuse_port`].
+    ///   * Incoming connections are not expected to be preceded by a PROXY
+    ///     protocol header. See [`GenTcpConfig::proxy_protocol`].
     pub fn new() -> Self {
         Self {
             ttl: None,
             nodelay: None,
             backlog: 1024,
+            reuse_port: false,
+            proxy_protocol: ProxyProtocol::None,
             port_reuse: PortReuse::Disabled,
             _impl: std::marker::PhantomData,
         }
@@ -196,12 +263,43 @@ where
         self
     }
 
-    /// Configures the listen backlog for new listen sockets.
+    /// Configures the listen backlog for new listen sockets, i.e. the
+    /// number of connections that the OS TCP stack queues until they
+    /// are accepted.
     pub fn listen_backlog(mut self, backlog: u32) -> Self {
         self.backlog = backlog;
         self
     }
 
+    /// Configures the `SO_REUSEPORT` option for new listen sockets on unix
+    /// systems, permitting multiple sockets, possibly of different processes,
+    /// to listen on the same address and port, e.g. for zero-downtime restarts.
+    ///
+    /// In contrast to [`GenTcpConfig::port_reuse`], this only concerns listening
+    /// and has no effect on outgoing connections.
+    pub fn reuse_port(mut self, reuse_port: bool) -> Self {
+        self.reuse_port = reuse_port;
+        self
+    }
+
+    /// Configures the PROXY protocol expected on incoming connections,
+    /// e.g. when listening behind a load balancer like HAProxy.
+    ///
+    /// With [`ProxyProtocol::V2`], the header preceding the data of each
+    /// incoming connection is read before the connection is reported by the
+    /// [`TcpListenStream`]. The source address conveyed in the header is then
+    /// reported as the remote address of the connection. Connections whose
+    /// header is invalid or not received within 10 seconds are dropped.
+    /// The headers of at most 64 connections are read concurrently, further
+    /// connections are only accepted once one of these completes.
+    ///
+    /// > **Note**: All connections on a listening socket configured this
+    /// > way must come from the proxy, as the header is not authenticated.
+    pub fn proxy_protocol(mut self, proxy_protocol: ProxyProtocol) -> Self {
+        self.proxy_protocol = proxy_protocol;
+        self
+    }
+
     /// Configures port reuse for local sockets, which implies
     /// reuse of listening ports for outgoing connections to
     /// enhance NAT traversal capabilities.
@@ -337,14 +435,21 @@ where
 
     fn do_listen(self, socket_addr: SocketAddr) -> io::Result<TcpListenStream<T>> {
         let socket = self.create_socket(&socket_addr)?;
+        #[cfg(unix)]
+        if self.reuse_port {
+            socket.set_reuse_port(true)?;
+        }
         socket.bind(&socket_addr.into())?;
         socket.listen(self.backlog as _)?;
         socket.set_nonblocking(true)?;
-        TcpListenStream::<T>::new(socket.into(), self.port_reuse)
+        TcpListenStream::<T>::new(socket.into(), self.port_reuse, self.proxy_protocol)
     }
 
-    async fn do_dial(self, socket_addr: SocketAddr) -> Result<T::Stream, io::Error> {
+    async fn do_dial(self, socket_addr: SocketAddr, options: TcpOptions) -> Result<T::Stream, io::Error> {
         let socket = self.create_socket(&socket_addr)?;
+        options.apply(&socket)?;
+        #[cfg(not(any(target_os = "fuchsia", target_os = "redox", target_os = "solaris", target_os = "illumos")))]
+        options.apply_tos(&socket, &socket_addr)?;
 
         if let Some(addr) = self.port_reuse.local_dial_addr(&socket_addr.ip()) {
             log::trace!("Binding dial socket to listen socket {}", addr);
@@ -390,16 +495,7 @@ where
     }
 
     fn dial(self, addr: Multiaddr) -> Result<Self::Dial, TransportError<Self::Error>> {
-        let socket_addr = if let Ok(socket_addr) = multiaddr_to_socketaddr(addr.clone()) {
-            if socket_addr.port() == 0 || socket_addr.ip().is_unspecified() {
-                return Err(TransportError::MultiaddrNotSupported(addr));
-            }
-            socket_addr
-        } else {
-            return Err(TransportError::MultiaddrNotSupported(addr));
-        };
-        log::debug!("dialing {}", socket_addr);
-        Ok(Box::pin(self.do_dial(socket_addr)))
+        self.dial_with_options(addr, TcpOptions::default())
     }
 
     /// When port reuse is disabled and hence ephemeral local ports are
@@ -427,6 +523,34 @@ where
     }
 }
 
+impl<T> GenTcpConfig<T>
+where
+    T: Provider + Send + 'static,
+    T::Listener: Unpin,
+    T::IfWatcher: Unpin,
+    T::Stream: Unpin,
+{
+    /// Dials the given address like [`Transport::dial`], additionally applying
+    /// the given [`TcpOptions`] to the socket of this connection only.
+    ///
+    /// This permits e.g. different `IP_TOS` values for connections of latency
+    /// sensitive protocols and for bulk data transfers.
+    pub fn dial_with_options(self, addr: Multiaddr, options: TcpOptions)
+        -> Result<<Self as Transport>::Dial, TransportError<io::Error>>
+    {
+        let socket_addr = if let Ok(socket_addr) = multiaddr_to_socketaddr(addr.clone()) {
+            if socket_addr.port() == 0 || socket_addr.ip().is_unspecified() {
+                return Err(TransportError::MultiaddrNotSupported(addr));
+            }
+            socket_addr
+        } else {
+            return Err(TransportError::MultiaddrNotSupported(addr));
+        };
+        log::debug!("dialing {}", socket_addr);
+        Ok(Box::pin(self.do_dial(socket_addr, options)))
+    }
+}
+
 type TcpListenerEvent<S> = ListenerEvent<Ready<Result<S, io::Error>>, io::Error>;
 
 enum IfWatch<TIfWatcher> {
@@ -477,6 +601,13 @@ where
     sleep_on_error: Duration,
     /// The current pause, if any.
     pause: Option<Delay>,
+    /// The PROXY protocol expected on incoming connections.
+    proxy_protocol: ProxyProtocol,
+    /// Incoming connections whose PROXY protocol header is being read.
+    pending_proxy_headers: FuturesUnordered<BoxFuture<'static, io::Result<Incoming<T::Stream>>>>,
+    /// The maximum size of `pending_proxy_headers`, beyond which no further
+    /// connections are accepted.
+    max_pending_proxy_headers: usize,
 }
 
 impl<T> TcpListenStream<T>
@@ -485,7 +616,7 @@ where
 {
     /// Constructs a `TcpListenStream` for incoming connections around
     /// the given `TcpListener`.
-    fn new(listener: TcpListener, port_reuse: PortReuse) -> io::Result<Self> {
+    fn new(listener: TcpListener, port_reuse: PortReuse, proxy_protocol: ProxyProtocol) -> io::Result<Self> {
         let listen_addr = listener.local_addr()?;
 
         let in_addr = if match &listen_addr {
@@ -514,9 +645,34 @@ where
             in_addr,
             pause: None,
             sleep_on_error: Duration::from_millis(100),
+            proxy_protocol,
+            pending_proxy_headers: FuturesUnordered::new(),
+            max_pending_proxy_headers: MAX_PENDING_PROXY_HEADERS,
         })
     }
 
+    /// Reads the PROXY protocol header of an incoming connection,
+    /// replacing the remote address with the one conveyed in the header.
+    fn read_proxy_header(mut incoming: Incoming<T::Stream>)
+        -> BoxFuture<'static, io::Result<Incoming<T::Stream>>>
+    {
+        let read = async move {
+            if let Some(remote_addr) = proxy_protocol::read_v2_header(&mut incoming.stream).await? {
+                incoming.remote_addr = remote_addr;
+            }
+            Ok(incoming)
+        };
+        future::select(read.boxed(), Delay::new(PROXY_HEADER_TIMEOUT))
+            .map(|result| match result {
+                Either::Left((result, _)) => result,
+                Either::Right(_) => Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "timed out reading PROXY protocol header",
+                )),
+            })
+            .boxed()
+    }
+
     /// Disables port reuse for any listen address of this stream.
     ///
     /// This is done when the `TcpListenStream` encounters a fatal
@@ -627,15 +783,39 @@ where
                 }
             }
 
-            // Take the pending connection from the backlog.
-            let incoming = match T::poll_accept(&mut me.listener, cx) {
-                Poll::Pending => return Poll::Pending,
-                Poll::Ready(Ok(incoming)) => incoming,
-                Poll::Ready(Err(e)) => {
-                    // These errors are non-fatal for the listener stream.
-                    log::error!("error accepting incoming connection: {}", e);
-                    me.pause = Some(Delay::new(me.sleep_on_error));
-                    return Poll::Ready(Some(Ok(ListenerEvent::Error(e))));
+            // Report connections whose PROXY protocol header has been read.
+            let incoming = match me.pending_proxy_headers.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(incoming))) => Some(incoming),
+                Poll::Ready(Some(Err(e))) => {
+                    log::debug!("Dropping incoming connection with PROXY protocol error: {}", e);
+                    continue
+                }
+                Poll::Ready(None) | Poll::Pending => None,
+            };
+
+            // Take the pending connection from the backlog, unless the headers
+            // of too many connections are being read already. Connections
+            // are accepted again once one of these completes.
+            let incoming = match incoming {
+                Some(incoming) => incoming,
+                None if me.pending_proxy_headers.len() >= me.max_pending_proxy_headers => {
+                    return Poll::Pending
+                }
+                None => match T::poll_accept(&mut me.listener, cx) {
+                    Poll::Pending => return Poll::Pending,
+                    Poll::Ready(Ok(incoming)) => match me.proxy_protocol {
+                        ProxyProtocol::None => incoming,
+                        ProxyProtocol::V2 => {
+                            me.pending_proxy_headers.push(Self::read_proxy_header(incoming));
+                            continue
+                        }
+                    },
+                    Poll::Ready(Err(e)) => {
+                        // These errors are non-fatal for the listener stream.
+                        log::error!("error accepting incoming connection: {}", e);
+                        me.pause = Some(Delay::new(me.sleep_on_error));
+                        return Poll::Ready(Some(Ok(ListenerEvent::Error(e))));
+                    }
                 }
             };
 
@@ -810,6 +990,78 @@ mod tests {
         test("/ip6/::1/tcp/0".parse().unwrap());
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn dial_with_options() {
+        use std::os::unix::io::AsRawFd;
+
+        env_logger::try_init().ok();
+
+        const TOS: u8 = 0x10;
+
+        async fn listener<T: Provider>(addr: Multiaddr, mut ready_tx: mpsc::Sender<Multiaddr>) {
+            let tcp = GenTcpConfig::<T>::new();
+            let mut listener = tcp.listen_on(addr).unwrap();
+            loop {
+                match listener.next().await.unwrap().unwrap() {
+                    ListenerEvent::NewAddress(listen_addr) => {
+                        ready_tx.send(listen_addr).await.unwrap();
+                    }
+                    ListenerEvent::Upgrade { upgrade, .. } => {
+                        upgrade.await.unwrap();
+                        return
+                    }
+                    e => panic!("Unexpected listener event: {:?}", e),
+                }
+            }
+        }
+
+        async fn dialer<T>(mut ready_rx: mpsc::Receiver<Multiaddr>)
+        where
+            T: Provider,
+            T::Stream: AsRawFd,
+        {
+            let addr = ready_rx.next().await.unwrap();
+            let options = TcpOptions {
+                keepalive_interval: Some(Duration::from_secs(30)),
+                tos: Some(TOS),
+                ..TcpOptions::default()
+            };
+            let stream = GenTcpConfig::<T>::new()
+                .dial_with_options(addr, options)
+                .unwrap()
+                .await
+                .unwrap();
+            let socket = socket2::SockRef::from(&stream);
+            assert_eq!(socket.tos().unwrap(), u32::from(TOS));
+            assert!(socket.keepalive().unwrap());
+        }
+
+        let addr: Multiaddr = "/ip4/127.0.0.1/tcp/0".parse().unwrap();
+
+        #[cfg(feature = "async-io")]
+        {
+            let (ready_tx, ready_rx) = mpsc::channel(1);
+            let listener = listener::<async_io::Tcp>(addr.clone(), ready_tx);
+            let dialer = dialer::<async_io::Tcp>(ready_rx);
+            let listener = async_std::task::spawn(listener);
+            async_std::task::block_on(dialer);
+            async_std::task::block_on(listener);
+        }
+
+        #[cfg(feature = "tokio")]
+        {
+            let (ready_tx, ready_rx) = mpsc::channel(1);
+            let listener = listener::<tokio::Tcp>(addr, ready_tx);
+            let dialer = dialer::<tokio::Tcp>(ready_rx);
+            let rt = tokio_crate::runtime::Builder::new_current_thread().enable_io().build().unwrap();
+            let tasks = tokio_crate::task::LocalSet::new();
+            let listener = tasks.spawn_local(listener);
+            tasks.block_on(&rt, dialer);
+            tasks.block_on(&rt, listener).unwrap();
+        }
+    }
+
     #[test]
     fn wildcard_expansion() {
         env_logger::try_init().ok();
@@ -986,6 +1238,163 @@ mod tests {
         test("/ip4/127.0.0.1/tcp/0".parse().unwrap());
     }
 
+    #[test]
+    #[cfg(feature = "async-io")]
+    fn listen_backlog() {
+        env_logger::try_init().ok();
+
+        const BACKLOG: usize = 16;
+
+        async_std::task::block_on(async {
+            let tcp = GenTcpConfig::<async_io::Tcp>::new().listen_backlog(BACKLOG as u32);
+            let mut listener = tcp.listen_on("/ip4/127.0.0.1/tcp/0".parse().unwrap()).unwrap();
+            let addr = match listener.next().await.unwrap().unwrap() {
+                ListenerEvent::NewAddress(addr) => multiaddr_to_socketaddr(addr).unwrap(),
+                e => panic!("Unexpected listener event: {:?}", e),
+            };
+
+            // For a listening socket, Linux reports the configured backlog
+            // in `tcpi_sacked` of `TCP_INFO`.
+            #[cfg(target_os = "linux")]
+            {
+                use std::os::unix::io::AsRawFd;
+
+                let mut info: libc::tcp_info = unsafe { std::mem::zeroed() };
+                let mut len = std::mem::size_of::<libc::tcp_info>() as libc::socklen_t;
+                let ret = unsafe {
+                    libc::getsockopt(
+                        listener.listener.as_raw_fd(),
+                        libc::IPPROTO_TCP,
+                        libc::TCP_INFO,
+                        &mut info as *mut libc::tcp_info as *mut libc::c_void,
+                        &mut len,
+                    )
+                };
+                assert_eq!(ret, 0, "{}", io::Error::last_os_error());
+                assert_eq!(info.tcpi_sacked as usize, BACKLOG);
+            }
+
+            // Connections are established by the OS TCP stack and queued
+            // without being accepted.
+            let streams = (0 .. BACKLOG)
+                .map(|_| std::net::TcpStream::connect(addr).unwrap())
+                .collect::<Vec<_>>();
+
+            let mut accepted = 0;
+            while accepted < streams.len() {
+                match listener.next().await.unwrap().unwrap() {
+                    ListenerEvent::Upgrade { upgrade, .. } => {
+                        upgrade.await.unwrap();
+                        accepted += 1;
+                    }
+                    e => panic!("Unexpected listener event: {:?}", e),
+                }
+            }
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "async-io")]
+    fn listen_proxy_protocol() {
+        use std::io::Write;
+
+        env_logger::try_init().ok();
+
+        async_std::task::block_on(async {
+            let tcp = GenTcpConfig::<async_io::Tcp>::new().proxy_protocol(ProxyProtocol::V2);
+            let mut listener = tcp.listen_on("/ip4/127.0.0.1/tcp/0".parse().unwrap()).unwrap();
+            let addr = match listener.next().await.unwrap().unwrap() {
+                ListenerEvent::NewAddress(addr) => multiaddr_to_socketaddr(addr).unwrap(),
+                e => panic!("Unexpected listener event: {:?}", e),
+            };
+
+            let client: SocketAddr = "203.0.113.7:4242".parse().unwrap();
+            let mut stream = std::net::TcpStream::connect(addr).unwrap();
+            stream.write_all(&proxy_protocol::tests::encode_v2_header(client, addr)).unwrap();
+            stream.write_all(&[1, 2, 3]).unwrap();
+
+            match listener.next().await.unwrap().unwrap() {
+                ListenerEvent::Upgrade { upgrade, remote_addr, .. } => {
+                    assert_eq!(remote_addr, ip_to_multiaddr(client.ip(), client.port()));
+                    let mut upgrade = upgrade.await.unwrap();
+                    let mut buf = [0u8; 3];
+                    upgrade.read_exact(&mut buf).await.unwrap();
+                    assert_eq!(buf, [1, 2, 3]);
+                }
+                e => panic!("Unexpected listener event: {:?}", e),
+            }
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "async-io")]
+    fn listen_proxy_protocol_max_pending_headers() {
+        use std::io::Write;
+
+        env_logger::try_init().ok();
+
+        const MAX_PENDING: usize = 2;
+
+        async_std::task::block_on(async {
+            let tcp = GenTcpConfig::<async_io::Tcp>::new().proxy_protocol(ProxyProtocol::V2);
+            let mut listener = tcp.listen_on("/ip4/127.0.0.1/tcp/0".parse().unwrap()).unwrap();
+            listener.max_pending_proxy_headers = MAX_PENDING;
+            let addr = match listener.next().await.unwrap().unwrap() {
+                ListenerEvent::NewAddress(addr) => multiaddr_to_socketaddr(addr).unwrap(),
+                e => panic!("Unexpected listener event: {:?}", e),
+            };
+
+            // Connections that never send a header occupy all slots.
+            let silent = (0 .. MAX_PENDING)
+                .map(|_| std::net::TcpStream::connect(addr).unwrap())
+                .collect::<Vec<_>>();
+            let client: SocketAddr = "203.0.113.7:4242".parse().unwrap();
+            let mut stream = std::net::TcpStream::connect(addr).unwrap();
+            stream.write_all(&proxy_protocol::tests::encode_v2_header(client, addr)).unwrap();
+
+            // The connection with a header is not accepted while the slots are occupied.
+            match future::select(listener.next(), Delay::new(Duration::from_millis(500))).await {
+                Either::Left((e, _)) => panic!("Unexpected listener event: {:?}", e),
+                Either::Right(_) => {}
+            }
+            assert_eq!(listener.pending_proxy_headers.len(), MAX_PENDING);
+
+            // Closing the silent connections frees the slots.
+            drop(silent);
+            match listener.next().await.unwrap().unwrap() {
+                ListenerEvent::Upgrade { remote_addr, .. } => {
+                    assert_eq!(remote_addr, ip_to_multiaddr(client.ip(), client.port()));
+                }
+                e => panic!("Unexpected listener event: {:?}", e),
+            }
+        });
+    }
+
+    #[test]
+    #[cfg(all(unix, feature = "async-io"))]
+    fn listen_reuse_port() {
+        env_logger::try_init().ok();
+
+        async fn listen_addr(listener: &mut TcpListenStream<async_io::Tcp>) -> Multiaddr {
+            match listener.next().await.unwrap().unwrap() {
+                ListenerEvent::NewAddress(addr) => addr,
+                e => panic!("Unexpected listener event: {:?}", e),
+            }
+        }
+
+        async_std::task::block_on(async {
+            let tcp = GenTcpConfig::<async_io::Tcp>::new().reuse_port(true);
+            let mut listener1 = tcp.clone().listen_on("/ip4/127.0.0.1/tcp/0".parse().unwrap()).unwrap();
+            let addr = listen_addr(&mut listener1).await;
+
+            // Without `SO_REUSEPORT`, the address is in use.
+            assert!(GenTcpConfig::<async_io::Tcp>::new().listen_on(addr.clone()).is_err());
+
+            let mut listener2 = tcp.listen_on(addr.clone()).unwrap();
+            assert_eq!(listen_addr(&mut listener2).await, addr);
+        });
+    }
+
     #[test]
     fn listen_port_0() {
         env_logger::try_init().ok();
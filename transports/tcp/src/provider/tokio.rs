@@ -137,6 +137,13 @@ impl Into<tokio_crate::net::TcpStream> for TcpStream {
     }
 }
 
+#[cfg(unix)]
+impl std::os::unix::io::AsRawFd for TcpStream {
+    fn as_raw_fd(&self) -> std::os::unix::io::RawFd {
+        self.0.as_raw_fd()
+    }
+}
+
 impl AsyncRead for TcpStream {
     fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context, buf: &mut [u8]) -> Poll<Result<usize, io::Error>> {
         let mut read_buf = tokio_crate::io::ReadBuf::new(buf);
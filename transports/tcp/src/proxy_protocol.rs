@@ -0,0 +1,187 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Support for version 2 of the [PROXY protocol], with which proxies and
+//! load balancers convey the addresses of the original connection.
+//!
+//! [PROXY protocol]: https://www.haproxy.org/download/2.4/doc/proxy-protocol.txt
+
+use futures::io::{AsyncRead, AsyncReadExt};
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+/// The signature at the start of every PROXY protocol v2 header.
+const SIGNATURE: [u8; 12] = *b"\r\n\r\n\0\r\nQUIT\n";
+
+/// The length of the fixed part of a PROXY protocol v2 header.
+const HEADER_LEN: usize = 16;
+
+/// The PROXY protocol expected on incoming connections.
+///
+/// See [`GenTcpConfig::proxy_protocol`](crate::GenTcpConfig::proxy_protocol).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ProxyProtocol {
+    /// Incoming connections are not preceded by a PROXY protocol header.
+    None,
+    /// Incoming connections are preceded by a PROXY protocol v2 header.
+    V2,
+}
+
+/// Reads a PROXY protocol v2 header from the given stream, leaving the
+/// stream positioned at the first byte following the header.
+///
+/// Returns the source address of the original connection, or `None`
+/// if the header does not convey one, e.g. for health checks of the
+/// proxy itself (`LOCAL` command) or non-IP address families.
+pub(crate) async fn read_v2_header<S>(stream: &mut S) -> io::Result<Option<SocketAddr>>
+where
+    S: AsyncRead + Unpin,
+{
+    let mut header = [0; HEADER_LEN];
+    stream.read_exact(&mut header).await?;
+
+    if header[.. 12] != SIGNATURE {
+        return Err(invalid_data("invalid PROXY protocol signature"))
+    }
+    if header[12] >> 4 != 2 {
+        return Err(invalid_data("unsupported PROXY protocol version"))
+    }
+
+    let mut body = vec![0; usize::from(u16::from_be_bytes([header[14], header[15]]))];
+    stream.read_exact(&mut body).await?;
+
+    match header[12] & 0x0f {
+        // LOCAL
+        0x0 => return Ok(None),
+        // PROXY
+        0x1 => {}
+        _ => return Err(invalid_data("unsupported PROXY protocol command")),
+    }
+
+    match header[13] {
+        // TCP over IPv4
+        0x11 => {
+            if body.len() < 12 {
+                return Err(invalid_data("truncated PROXY protocol IPv4 addresses"))
+            }
+            let mut ip = [0; 4];
+            ip.copy_from_slice(&body[.. 4]);
+            let port = u16::from_be_bytes([body[8], body[9]]);
+            Ok(Some(SocketAddr::new(IpAddr::V4(Ipv4Addr::from(ip)), port)))
+        }
+        // TCP over IPv6
+        0x21 => {
+            if body.len() < 36 {
+                return Err(invalid_data("truncated PROXY protocol IPv6 addresses"))
+            }
+            let mut ip = [0; 16];
+            ip.copy_from_slice(&body[.. 16]);
+            let port = u16::from_be_bytes([body[32], body[33]]);
+            Ok(Some(SocketAddr::new(IpAddr::V6(Ipv6Addr::from(ip)), port)))
+        }
+        // Unspecified, UDP or unix sockets.
+        _ => Ok(None),
+    }
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use futures::{executor::block_on, io::Cursor};
+
+    /// Encodes a PROXY protocol v2 header with the `PROXY` command
+    /// for the given source and destination addresses.
+    pub(crate) fn encode_v2_header(src: SocketAddr, dst: SocketAddr) -> Vec<u8> {
+        let mut header = SIGNATURE.to_vec();
+        header.push(0x21);
+        let mut body = Vec::new();
+        match (src, dst) {
+            (SocketAddr::V4(src), SocketAddr::V4(dst)) => {
+                header.push(0x11);
+                body.extend_from_slice(&src.ip().octets());
+                body.extend_from_slice(&dst.ip().octets());
+            }
+            (SocketAddr::V6(src), SocketAddr::V6(dst)) => {
+                header.push(0x21);
+                body.extend_from_slice(&src.ip().octets());
+                body.extend_from_slice(&dst.ip().octets());
+            }
+            _ => panic!("Mismatching address families"),
+        }
+        body.extend_from_slice(&src.port().to_be_bytes());
+        body.extend_from_slice(&dst.port().to_be_bytes());
+        header.extend_from_slice(&(body.len() as u16).to_be_bytes());
+        header.extend_from_slice(&body);
+        header
+    }
+
+    #[test]
+    fn read_ipv4_and_ipv6_headers() {
+        for (src, dst) in &[
+            ("203.0.113.7:4242", "192.0.2.1:4001"),
+            ("[2001:db8::7]:4242", "[2001:db8::1]:4001"),
+        ] {
+            let src: SocketAddr = src.parse().unwrap();
+            let mut bytes = encode_v2_header(src, dst.parse().unwrap());
+            bytes.extend_from_slice(b"payload");
+
+            let mut stream = Cursor::new(bytes);
+            assert_eq!(block_on(read_v2_header(&mut stream)).unwrap(), Some(src));
+
+            let mut rest = Vec::new();
+            block_on(stream.read_to_end(&mut rest)).unwrap();
+            assert_eq!(rest, b"payload");
+        }
+    }
+
+    #[test]
+    fn read_local_header() {
+        // A `LOCAL` header with a TLV that must be skipped.
+        let mut bytes = SIGNATURE.to_vec();
+        bytes.extend_from_slice(&[0x20, 0x00, 0x00, 0x04, 0x04, 0x00, 0x01, 0xff]);
+        bytes.extend_from_slice(b"payload");
+
+        let mut stream = Cursor::new(bytes);
+        assert_eq!(block_on(read_v2_header(&mut stream)).unwrap(), None);
+
+        let mut rest = Vec::new();
+        block_on(stream.read_to_end(&mut rest)).unwrap();
+        assert_eq!(rest, b"payload");
+    }
+
+    #[test]
+    fn reject_invalid_headers() {
+        let mut no_signature = vec![0; HEADER_LEN];
+        no_signature[12] = 0x21;
+        let mut wrong_version = SIGNATURE.to_vec();
+        wrong_version.extend_from_slice(&[0x11, 0x11, 0x00, 0x00]);
+        let mut truncated = SIGNATURE.to_vec();
+        truncated.extend_from_slice(&[0x21, 0x11, 0x00, 0x04, 1, 2, 3, 4]);
+
+        for bytes in &[no_signature, wrong_version, truncated, SIGNATURE.to_vec()] {
+            let err = block_on(read_v2_header(&mut Cursor::new(bytes))).unwrap_err();
+            assert!(matches!(err.kind(), io::ErrorKind::InvalidData | io::ErrorKind::UnexpectedEof));
+        }
+    }
+}
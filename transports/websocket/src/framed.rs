@@ -22,6 +22,7 @@ use futures_rustls::{webpki, client, server};
 use crate::{error::Error, tls};
 use either::Either;
 use futures::{future::BoxFuture, prelude::*, ready, stream::BoxStream};
+use futures_timer::Delay;
 use libp2p_core::{
     Transport,
     either::EitherOutput,
@@ -30,12 +31,15 @@ use libp2p_core::{
 };
 use log::{debug, trace};
 use soketto::{connection, extension::deflate::Deflate, handshake};
-use std::{convert::TryInto, fmt, io, mem, pin::Pin, task::Context, task::Poll};
+use std::{convert::TryInto, fmt, io, mem, pin::Pin, task::Context, task::Poll, time::Duration};
 use url::Url;
 
 /// Max. number of payload bytes of a single frame.
 const MAX_DATA_SIZE: usize = 256 * 1024 * 1024;
 
+/// Default time to wait for a PONG after sending a keep-alive PING.
+const PONG_TIMEOUT: Duration = Duration::from_secs(20);
+
 /// A Websocket transport whose output type is a [`Stream`] and [`Sink`] of
 /// frame payloads which does not implement [`AsyncRead`] or
 /// [`AsyncWrite`]. See [`crate::WsConfig`] if you require the latter.
@@ -45,7 +49,9 @@ pub struct WsConfig<T> {
     max_data_size: usize,
     tls_config: tls::Config,
     max_redirects: u8,
-    use_deflate: bool
+    deflate: Option<DeflateConfig>,
+    ping_interval: Option<Duration>,
+    pong_timeout: Duration
 }
 
 impl<T> WsConfig<T> {
@@ -56,7 +62,9 @@ impl<T> WsConfig<T> {
             max_data_size: MAX_DATA_SIZE,
             tls_config: tls::Config::client(),
             max_redirects: 0,
-            use_deflate: false
+            deflate: None,
+            ping_interval: None,
+            pong_timeout: PONG_TIMEOUT
         }
     }
 
@@ -89,10 +97,78 @@ impl<T> WsConfig<T> {
     }
 
     /// Should the deflate extension (RFC 7692) be used if supported?
+    ///
+    /// Enabling deflate uses the default [`DeflateConfig`].
     pub fn use_deflate(&mut self, flag: bool) -> &mut Self {
-        self.use_deflate = flag;
+        self.deflate = if flag { Some(DeflateConfig::default()) } else { None };
+        self
+    }
+
+    /// Enable the deflate extension (RFC 7692) with the given configuration.
+    pub fn set_deflate_config(&mut self, c: DeflateConfig) -> &mut Self {
+        self.deflate = Some(c);
+        self
+    }
+
+    /// Set the interval at which websocket PING frames are sent to keep
+    /// idle connections alive, e.g. when behind HTTP reverse proxies.
+    ///
+    /// `None` (the default) disables keep-alive PINGs. PINGs are only sent
+    /// while the connection is being read from.
+    pub fn set_ping_interval(&mut self, interval: Option<Duration>) -> &mut Self {
+        self.ping_interval = interval;
         self
     }
+
+    /// Set how long to wait for a PONG after a keep-alive PING before the
+    /// connection is considered dead and fails with [`io::ErrorKind::TimedOut`].
+    pub fn set_pong_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.pong_timeout = timeout;
+        self
+    }
+
+    /// The keep-alive PING interval and PONG timeout, if enabled.
+    fn keep_alive(&self) -> Option<(Duration, Duration)> {
+        self.ping_interval.map(|interval| (interval, self.pong_timeout))
+    }
+}
+
+/// Configuration of the deflate extension (RFC 7692).
+///
+/// > **Note**: Context takeover is always disabled, i.e. every message is
+/// > compressed independently of previous ones.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DeflateConfig {
+    max_window_bits: u8
+}
+
+impl DeflateConfig {
+    /// Get the configured max. LZ77 window size (in bits).
+    pub fn max_window_bits(&self) -> u8 {
+        self.max_window_bits
+    }
+
+    /// Set the max. LZ77 window size (in bits) to use for compression.
+    ///
+    /// Smaller windows reduce the memory used per connection at the cost
+    /// of a lower compression ratio. The value is offered to the remote
+    /// when dialing and bounds the window of both endpoints. Listeners
+    /// accept whatever window size the dialer offers.
+    ///
+    /// # Panics
+    ///
+    /// If `bits` is not within `9 ..= 15`.
+    pub fn set_max_window_bits(&mut self, bits: u8) -> &mut Self {
+        assert!((9 ..= 15).contains(&bits), "max. window bits must be within 9 ..= 15");
+        self.max_window_bits = bits;
+        self
+    }
+}
+
+impl Default for DeflateConfig {
+    fn default() -> Self {
+        DeflateConfig { max_window_bits: 15 }
+    }
 }
 
 type TlsOrPlain<T> = EitherOutput<EitherOutput<client::TlsStream<T>, server::TlsStream<T>>, T>;
@@ -130,9 +206,10 @@ where
             }
         };
 
+        let keep_alive = self.keep_alive();
         let tls_config = self.tls_config;
         let max_size = self.max_data_size;
-        let use_deflate = self.use_deflate;
+        let use_deflate = self.deflate.is_some();
         let transport = self.transport.listen_on(inner_addr).map_err(|e| e.map(Error::Transport))?;
         let listen = transport
             .map_err(Error::Transport)
@@ -214,7 +291,7 @@ where
                             let mut builder = server.into_builder();
                             builder.set_max_message_size(max_size);
                             builder.set_max_frame_size(max_size);
-                            Connection::new(builder)
+                            Connection::new(builder, keep_alive)
                         };
 
                         Ok(conn)
@@ -273,6 +350,7 @@ where
 {
     /// Attempts to dial the given address and perform a websocket handshake.
     async fn dial_once(self, addr: WsAddress) -> Result<Either<String, Connection<T::Output>>, Error<T::Error>> {
+        let keep_alive = self.keep_alive();
         trace!("Dialing websocket address: {:?}", addr);
 
         let dial = self.transport.dial(addr.tcp_addr)
@@ -305,8 +383,11 @@ where
 
         let mut client = handshake::Client::new(stream, &addr.host_port, addr.path.as_ref());
 
-        if self.use_deflate {
-            client.add_extension(Box::new(Deflate::new(connection::Mode::Client)));
+        if let Some(config) = self.deflate {
+            let mut deflate = Deflate::new(connection::Mode::Client);
+            deflate.set_max_client_window_bits(config.max_window_bits);
+            deflate.set_max_server_window_bits(config.max_window_bits);
+            client.add_extension(Box::new(deflate));
         }
 
         match client.handshake().map_err(|e| Error::Handshake(Box::new(e))).await? {
@@ -320,7 +401,7 @@ where
             }
             handshake::ServerResponse::Accepted { .. } => {
                 trace!("websocket handshake with {} successful", addr.host_port);
-                Ok(Either::Right(Connection::new(client.into_builder())))
+                Ok(Either::Right(Connection::new(client.into_builder(), keep_alive)))
             }
         }
     }
@@ -444,9 +525,48 @@ fn location_to_multiaddr<T>(location: &str) -> Result<Multiaddr, Error<T>> {
 pub struct Connection<T> {
     receiver: BoxStream<'static, Result<IncomingData, connection::Error>>,
     sender: Pin<Box<dyn Sink<OutgoingData, Error = connection::Error> + Send>>,
+    keep_alive: Option<KeepAlive>,
     _marker: std::marker::PhantomData<T>
 }
 
+/// Keep-alive PING state of a [`Connection`].
+struct KeepAlive {
+    interval: Duration,
+    pong_timeout: Duration,
+    timer: Delay,
+    state: KeepAliveState
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum KeepAliveState {
+    /// Waiting for the next PING to be due.
+    Idle,
+    /// A PING is due and waiting for the sender to become ready.
+    Ping,
+    /// A PING has been enqueued and is being flushed.
+    Flush,
+    /// A PING has been sent and we are waiting for the PONG.
+    Pong
+}
+
+impl KeepAlive {
+    fn new(interval: Duration, pong_timeout: Duration) -> Self {
+        KeepAlive {
+            interval,
+            pong_timeout,
+            timer: Delay::new(interval),
+            state: KeepAliveState::Idle
+        }
+    }
+
+    fn on_pong(&mut self) {
+        if self.state == KeepAliveState::Pong {
+            self.state = KeepAliveState::Idle;
+            self.timer.reset(self.interval)
+        }
+    }
+}
+
 /// Data received over the websocket connection.
 #[derive(Debug, Clone)]
 pub enum IncomingData {
@@ -516,7 +636,7 @@ impl<T> Connection<T>
 where
     T: AsyncRead + AsyncWrite + Send + Unpin + 'static
 {
-    fn new(builder: connection::Builder<TlsOrPlain<T>>) -> Self {
+    fn new(builder: connection::Builder<TlsOrPlain<T>>, keep_alive: Option<(Duration, Duration)>) -> Self {
         let (sender, receiver) = builder.finish();
         let sink = quicksink::make_sink(sender, |mut sender, action| async move {
             match action {
@@ -558,6 +678,7 @@ where
         Connection {
             receiver: stream.boxed(),
             sender: Box::pin(sink),
+            keep_alive: keep_alive.map(|(interval, timeout)| KeepAlive::new(interval, timeout)),
             _marker: std::marker::PhantomData
         }
     }
@@ -576,6 +697,54 @@ where
     pub fn send_pong(&mut self, data: Vec<u8>) -> sink::Send<'_, Self, OutgoingData> {
         self.send(OutgoingData::Pong(data))
     }
+
+    /// Drive the keep-alive PINGs, if enabled.
+    ///
+    /// Returns an error if the remote failed to answer a PING in time.
+    fn poll_keep_alive(&mut self, cx: &mut Context<'_>) -> io::Result<()> {
+        if let Some(mut keep_alive) = self.keep_alive.take() {
+            let result = self.drive_keep_alive(&mut keep_alive, cx);
+            self.keep_alive = Some(keep_alive);
+            result
+        } else {
+            Ok(())
+        }
+    }
+
+    fn drive_keep_alive(&mut self, keep_alive: &mut KeepAlive, cx: &mut Context<'_>) -> io::Result<()> {
+        loop {
+            match keep_alive.state {
+                KeepAliveState::Idle => {
+                    if keep_alive.timer.poll_unpin(cx).is_pending() {
+                        return Ok(())
+                    }
+                    keep_alive.state = KeepAliveState::Ping
+                }
+                KeepAliveState::Ping => {
+                    if self.poll_ready_unpin(cx)?.is_pending() {
+                        return Ok(())
+                    }
+                    self.start_send_unpin(OutgoingData::Ping(Vec::new()))?;
+                    keep_alive.state = KeepAliveState::Flush
+                }
+                KeepAliveState::Flush => {
+                    if self.poll_flush_unpin(cx)?.is_pending() {
+                        return Ok(())
+                    }
+                    trace!("sent keep-alive ping");
+                    keep_alive.state = KeepAliveState::Pong;
+                    keep_alive.timer.reset(keep_alive.pong_timeout)
+                }
+                KeepAliveState::Pong => {
+                    if keep_alive.timer.poll_unpin(cx).is_pending() {
+                        return Ok(())
+                    }
+                    debug!("no pong received within {:?}", keep_alive.pong_timeout);
+                    return Err(io::Error::new(io::ErrorKind::TimedOut, "websocket pong timeout"))
+                }
+            }
+        }
+    }
 }
 
 impl<T> Stream for Connection<T>
@@ -585,7 +754,15 @@ where
     type Item = io::Result<IncomingData>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Err(e) = self.poll_keep_alive(cx) {
+            return Poll::Ready(Some(Err(e)))
+        }
         let item = ready!(self.receiver.poll_next_unpin(cx));
+        if let Some(Ok(IncomingData::Pong(_))) = &item {
+            if let Some(keep_alive) = self.keep_alive.as_mut() {
+                keep_alive.on_pong()
+            }
+        }
         let item = item.map(|result| {
             result.map_err(|e| io::Error::new(io::ErrorKind::Other, e))
         });
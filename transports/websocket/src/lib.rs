@@ -34,7 +34,7 @@ use libp2p_core::{
     transport::{map::{MapFuture, MapStream}, ListenerEvent, TransportError}
 };
 use rw_stream_sink::RwStreamSink;
-use std::{io, pin::Pin, task::{Context, Poll}};
+use std::{io, pin::Pin, task::{Context, Poll}, time::Duration};
 
 /// A Websocket transport.
 #[derive(Debug, Clone)]
@@ -88,6 +88,28 @@ impl<T> WsConfig<T> {
         self.transport.use_deflate(flag);
         self
     }
+
+    /// Enable the deflate extension (RFC 7692) with the given configuration.
+    pub fn set_deflate_config(&mut self, c: framed::DeflateConfig) -> &mut Self {
+        self.transport.set_deflate_config(c);
+        self
+    }
+
+    /// Set the interval at which websocket PING frames are sent to keep
+    /// idle connections alive, e.g. when behind HTTP reverse proxies.
+    ///
+    /// `None` (the default) disables keep-alive PINGs.
+    pub fn set_ping_interval(&mut self, interval: Option<Duration>) -> &mut Self {
+        self.transport.set_ping_interval(interval);
+        self
+    }
+
+    /// Set how long to wait for a PONG after a keep-alive PING before the
+    /// connection is considered dead.
+    pub fn set_pong_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.transport.set_pong_timeout(timeout);
+        self
+    }
 }
 
 impl<T> From<framed::WsConfig<T>> for WsConfig<T> {
@@ -197,7 +219,9 @@ mod tests {
     use libp2p_core::{Multiaddr, PeerId, Transport, multiaddr::Protocol};
     use libp2p_tcp as tcp;
     use futures::prelude::*;
-    use super::WsConfig;
+    use std::{io, pin::Pin, sync::{Arc, atomic::{AtomicUsize, Ordering}}, task::{Context, Poll}};
+    use std::time::Duration;
+    use super::{WsConfig, framed::{self, DeflateConfig}};
 
     #[test]
     fn dialer_connects_to_listener_ipv4() {
@@ -241,4 +265,149 @@ mod tests {
         let (a, b) = futures::join!(inbound, outbound);
         a.and(b).unwrap();
     }
+
+    #[test]
+    fn deflate_reduces_bytes_on_wire() {
+        let plain = futures::executor::block_on(bytes_on_wire(None));
+        let mut config = DeflateConfig::default();
+        config.set_max_window_bits(10);
+        let deflated = futures::executor::block_on(bytes_on_wire(Some(config)));
+        assert!(plain > 100 * 1024);
+        assert!(deflated < plain / 10, "{} >= {} / 10", deflated, plain);
+    }
+
+    /// Sends a 100 KB repetitive payload from dialer to listener and returns
+    /// the number of bytes the dialer wrote to the underlying TCP stream.
+    async fn bytes_on_wire(deflate: Option<DeflateConfig>) -> usize {
+        let written = Arc::new(AtomicUsize::new(0));
+        let counter = written.clone();
+        let tcp = tcp::TcpConfig::new()
+            .map(move |stream, _| Counting { inner: stream, written: counter.clone() });
+        let mut ws_config = WsConfig::new(tcp);
+        if let Some(c) = deflate {
+            ws_config.set_deflate_config(c);
+        }
+
+        let mut listener = ws_config.clone()
+            .listen_on("/ip4/127.0.0.1/tcp/0/ws".parse().unwrap())
+            .expect("listener");
+
+        let addr = listener.try_next().await
+            .expect("some event")
+            .expect("no error")
+            .into_new_address()
+            .expect("listen address");
+
+        let payload = b"libp2p websocket ".iter().cycle().take(100 * 1024).copied().collect::<Vec<_>>();
+
+        let inbound = async move {
+            let (conn, _addr) = listener.try_filter_map(|e| future::ready(Ok(e.into_upgrade())))
+                .try_next()
+                .await
+                .unwrap()
+                .unwrap();
+            let mut conn = conn.await.unwrap();
+            let mut buf = vec![0; 100 * 1024];
+            conn.read_exact(&mut buf).await.unwrap();
+            buf
+        };
+
+        let outbound = async {
+            let mut conn = ws_config.dial(addr).unwrap().await.unwrap();
+            conn.write_all(&payload).await.unwrap();
+            conn.flush().await.unwrap();
+            conn
+        };
+
+        let (received, _conn) = futures::join!(inbound, outbound);
+        assert_eq!(received, payload);
+        written.load(Ordering::SeqCst)
+    }
+
+    #[test]
+    fn keep_alive_ping_is_answered() {
+        futures::executor::block_on(async {
+            let (mut listener_conn, mut dialer_conn) = connect_keep_alive().await;
+            let mut buf = [0; 1];
+            // The listener answers PINGs while reading.
+            let read = listener_conn.read(&mut buf);
+            let pong = dialer_conn.next();
+            match future::select(read, pong).await {
+                future::Either::Right((Some(Ok(item)), _)) => assert!(item.is_pong()),
+                _ => panic!("expected a pong")
+            }
+        })
+    }
+
+    #[test]
+    fn keep_alive_pong_timeout() {
+        futures::executor::block_on(async {
+            // The listener never reads and thus never answers PINGs.
+            let (_listener_conn, mut dialer_conn) = connect_keep_alive().await;
+            match dialer_conn.next().await {
+                Some(Err(e)) => assert_eq!(e.kind(), io::ErrorKind::TimedOut),
+                _ => panic!("expected a pong timeout")
+            }
+        })
+    }
+
+    /// Connects a dialer sending keep-alive PINGs to a listener.
+    async fn connect_keep_alive() -> (
+        <WsConfig<tcp::TcpConfig> as Transport>::Output,
+        framed::Connection<<tcp::TcpConfig as Transport>::Output>
+    ) {
+        let mut listener = WsConfig::new(tcp::TcpConfig::new())
+            .listen_on("/ip4/127.0.0.1/tcp/0/ws".parse().unwrap())
+            .expect("listener");
+
+        let addr = listener.try_next().await
+            .expect("some event")
+            .expect("no error")
+            .into_new_address()
+            .expect("listen address");
+
+        let inbound = async move {
+            let (conn, _addr) = listener.try_filter_map(|e| future::ready(Ok(e.into_upgrade())))
+                .try_next()
+                .await
+                .unwrap()
+                .unwrap();
+            conn.await.unwrap()
+        };
+
+        let mut dialer = framed::WsConfig::new(tcp::TcpConfig::new());
+        dialer.set_ping_interval(Some(Duration::from_millis(50)))
+            .set_pong_timeout(Duration::from_millis(100));
+        let outbound = async move { dialer.dial(addr).unwrap().await.unwrap() };
+
+        futures::join!(inbound, outbound)
+    }
+
+    /// A stream counting the number of bytes written to it.
+    struct Counting<T> {
+        inner: T,
+        written: Arc<AtomicUsize>
+    }
+
+    impl<T: AsyncRead + Unpin> AsyncRead for Counting<T> {
+        fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+            Pin::new(&mut self.inner).poll_read(cx, buf)
+        }
+    }
+
+    impl<T: AsyncWrite + Unpin> AsyncWrite for Counting<T> {
+        fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+            let n = futures::ready!(Pin::new(&mut self.inner).poll_write(cx, buf))?;
+            self.written.fetch_add(n, Ordering::SeqCst);
+            Poll::Ready(Ok(n))
+        }
+
+        fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.inner).poll_flush(cx)
+        }
+
+        fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.inner).poll_close(cx)
+        }
+    }
 }